@@ -0,0 +1,326 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::accredited_investor::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::accredited_investor::ContractError;
+
+use super::state::{IDENTITY_ADDRESS, OWNER_ROLES_ADDRESS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:accredited_investor";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate accredited investor contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles and identity address
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    IDENTITY_ADDRESS.save(deps.storage, &msg.identity_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the accredited investor contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions
+    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+
+    match msg {
+        ExecuteMsg::SetAccreditationWindow {
+            token_address,
+            window,
+        } => execute::set_accreditation_window(deps, token_address, window),
+        ExecuteMsg::RemoveAccreditationWindow { token_address } => {
+            execute::remove_accreditation_window(deps, token_address)
+        }
+    }
+}
+
+pub mod execute {
+    use crate::modules::accredited_investor::{
+        msg::AccreditationWindow, state::ACCREDITATION_WINDOWS,
+    };
+
+    use super::*;
+    use cosmwasm_std::{to_json_binary, Addr, QueryRequest, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Restrict receiving the token to accredited investors until the end of the window
+    pub fn set_accreditation_window(
+        deps: DepsMut,
+        token_address: Addr,
+        window: AccreditationWindow,
+    ) -> Result<Response, ContractError> {
+        ACCREDITATION_WINDOWS.save(deps.storage, token_address.clone(), &window)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_accreditation_window")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("restricted_until", window.restricted_until.to_string())
+            .add_attribute(
+                "accreditation_topic",
+                window.accreditation_topic.to_string(),
+            ))
+    }
+
+    /// Remove the accreditation window of a token
+    pub fn remove_accreditation_window(
+        deps: DepsMut,
+        token_address: Addr,
+    ) -> Result<Response, ContractError> {
+        ACCREDITATION_WINDOWS.remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_accreditation_window")
+            .add_attribute("token_address", token_address.to_string()))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to,
+            amount,
+            decimals: _,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            env,
+            token_address,
+            from,
+            to,
+            amount,
+        )?),
+        QueryMsg::GetAccreditationWindow { token_address } => {
+            to_json_binary(&query::get_accreditation_window(deps, token_address)?)
+        }
+    }
+}
+
+pub mod query {
+    use crate::modules::accredited_investor::{
+        msg::{AccreditationWindow, IdentityQueryMsg},
+        state::ACCREDITATION_WINDOWS,
+    };
+
+    use super::*;
+    use cosmwasm_std::{Addr, Uint128};
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        _from: Option<Addr>,
+        to: Option<Addr>,
+        _amount: Option<Uint128>,
+    ) -> StdResult<bool> {
+        let Some(window) = ACCREDITATION_WINDOWS.may_load(deps.storage, token_address)? else {
+            return Ok(true);
+        };
+        // Burns have no receiver to check
+        let Some(to) = to else {
+            return Ok(true);
+        };
+        // The restriction lifts at `restricted_until`
+        if env.block.time.seconds() >= window.restricted_until.u64() {
+            return Ok(true);
+        }
+
+        let identity_address = IDENTITY_ADDRESS.load(deps.storage)?;
+        deps.querier.query_wasm_smart(
+            identity_address,
+            &IdentityQueryMsg::VerifyClaim {
+                claim_id: window.accreditation_topic,
+                user_addr: to,
+                namespace: None,
+            },
+        )
+    }
+
+    /// Get the accreditation window configured for a token, if any
+    pub fn get_accreditation_window(
+        deps: Deps,
+        token_address: Addr,
+    ) -> StdResult<Option<AccreditationWindow>> {
+        ACCREDITATION_WINDOWS.may_load(deps.storage, token_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::accredited_investor::msg::{AccreditationWindow, IdentityQueryMsg};
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, OwnedDeps, SystemResult, Timestamp, Uint128, Uint64,
+        WasmQuery,
+    };
+    use roles::owner_roles::msg::OwnerRole;
+
+    const ACCREDITATION_TOPIC: u128 = 7;
+
+    // Mock the owner roles contract and an identity contract where only "accredited"
+    // holds a valid accreditation claim
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) -> Uint64 {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+            identity_address: Addr::unchecked("identity_contract"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "owner_roles_contract" => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            WasmQuery::Smart { msg, .. } => {
+                let IdentityQueryMsg::VerifyClaim {
+                    claim_id,
+                    user_addr,
+                    ..
+                } = from_json(msg).unwrap();
+                let verified = claim_id == Uint128::new(ACCREDITATION_TOPIC)
+                    && user_addr.as_str() == "accredited";
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&verified).unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+
+        // Restricted for the next 100 seconds
+        let restricted_until = Uint64::new(mock_env().block.time.seconds() + 100);
+        let msg = ExecuteMsg::SetAccreditationWindow {
+            token_address: Addr::unchecked("token_address"),
+            window: AccreditationWindow {
+                restricted_until,
+                accreditation_topic: Uint128::new(ACCREDITATION_TOPIC),
+            },
+        };
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        restricted_until
+    }
+
+    fn check(deps: Deps, now: u64, to: &str) -> bool {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(now);
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("issuer")),
+            to: Some(Addr::unchecked(to)),
+            amount: Some(Uint128::new(100)),
+            decimals: None,
+        };
+        from_json(query(deps, env, msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn restriction_lifts_at_end_of_window() {
+        let mut deps = mock_dependencies();
+        let restricted_until = setup_contract(&mut deps).u64();
+
+        // Only accredited investors may receive the token until the window ends
+        assert!(check(deps.as_ref(), restricted_until - 1, "accredited"));
+        assert!(!check(deps.as_ref(), restricted_until - 1, "retail"));
+
+        // From then on, anyone may
+        for now in [restricted_until, restricted_until + 1] {
+            assert!(check(deps.as_ref(), now, "accredited"));
+            assert!(check(deps.as_ref(), now, "retail"));
+        }
+    }
+
+    #[test]
+    fn remove_accreditation_window() {
+        let mut deps = mock_dependencies();
+        let restricted_until = setup_contract(&mut deps).u64();
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let msg = ExecuteMsg::RemoveAccreditationWindow {
+            token_address: Addr::unchecked("token_address"),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        assert!(check(deps.as_ref(), restricted_until - 1, "retail"));
+        let window: Option<AccreditationWindow> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetAccreditationWindow {
+                    token_address: Addr::unchecked("token_address"),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(window.is_none());
+    }
+}
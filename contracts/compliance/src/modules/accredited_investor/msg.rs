@@ -0,0 +1,57 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128, Uint64};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+    pub identity_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetAccreditationWindow {
+        token_address: Addr,
+        window: AccreditationWindow,
+    },
+    RemoveAccreditationWindow {
+        token_address: Addr,
+    },
+}
+
+// Extends the shared compliance module query with accreditation specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    #[returns(Option<AccreditationWindow>)]
+    GetAccreditationWindow { token_address: Addr },
+}
+
+// Initial lockup of an offering, e.g. under Reg D, during which tokens may only be
+// received by accredited investors
+#[cw_serde]
+pub struct AccreditationWindow {
+    // Unix seconds from which anyone may receive the token again
+    pub restricted_until: Uint64,
+    // Claim topic attesting that the holder is an accredited investor
+    pub accreditation_topic: Uint128,
+}
+
+// Subset of the identity contract's queries the module relies on
+#[cw_serde]
+pub enum IdentityQueryMsg {
+    VerifyClaim {
+        claim_id: Uint128,
+        user_addr: Addr,
+        namespace: Option<String>,
+    },
+}
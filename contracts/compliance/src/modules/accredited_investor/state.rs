@@ -0,0 +1,9 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+use super::msg::AccreditationWindow;
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+// Identity contract holding the receivers' accreditation claims
+pub const IDENTITY_ADDRESS: Item<Addr> = Item::new("identity_addr");
+pub const ACCREDITATION_WINDOWS: Map<Addr, AccreditationWindow> = Map::new("accreditation_windows");
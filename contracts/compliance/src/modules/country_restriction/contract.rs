@@ -79,11 +79,18 @@ pub fn execute(
             country_code,
             active,
         } => execute::update_country_restriction(deps, token_address, country_code, active),
+        ExecuteMsg::SetContractHoldersExempt {
+            token_address,
+            exempt,
+        } => execute::set_contract_holders_exempt(deps, token_address, exempt),
     }
 }
 
 pub mod execute {
-    use crate::modules::country_restriction::{msg::RestrictedCountry, state::RESTRICTED_COUNTRY};
+    use crate::modules::country_restriction::{
+        msg::RestrictedCountry,
+        state::{CONTRACT_HOLDERS_EXEMPT, RESTRICTED_COUNTRY},
+    };
 
     use super::*;
     use cosmwasm_std::{to_json_binary, Addr, QueryRequest, WasmQuery};
@@ -105,7 +112,6 @@ pub mod execute {
     }
 
     /// Add country restriction for a token
-
     pub fn add_country_restriction(
         deps: DepsMut,
         token_address: Addr,
@@ -127,7 +133,6 @@ pub mod execute {
     }
 
     /// Remove country restriction for a token
-
     pub fn remove_country_restriction(
         deps: DepsMut,
         token_address: Addr,
@@ -142,7 +147,6 @@ pub mod execute {
     }
 
     /// Update the country restriction active status for a token
-
     pub fn update_country_restriction(
         deps: DepsMut,
         token_address: Addr,
@@ -165,6 +169,20 @@ pub mod execute {
             .add_attribute("country_code", country_code.to_string())
             .add_attribute("is_active", active.to_string()))
     }
+
+    /// Set whether identities held by contracts skip the token's restricted countries
+    pub fn set_contract_holders_exempt(
+        deps: DepsMut,
+        token_address: Addr,
+        exempt: bool,
+    ) -> Result<Response, ContractError> {
+        CONTRACT_HOLDERS_EXEMPT.save(deps.storage, token_address.clone(), &exempt)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_contract_holders_exempt")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("exempt", exempt.to_string()))
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -175,6 +193,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             from,
             to,
             amount,
+            decimals: _,
         } => to_json_binary(&query::check_compliance(
             deps,
             token_address,
@@ -186,20 +205,25 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
 }
 
 pub mod query {
-    use crate::modules::country_restriction::{msg::RestrictedCountry, state::RESTRICTED_COUNTRY};
+    use crate::modules::country_restriction::{
+        msg::{IdentityQueryMsg, RestrictedCountry},
+        state::{CONTRACT_HOLDERS_EXEMPT, RESTRICTED_COUNTRY},
+    };
 
     use super::*;
     use cosmwasm_std::{Addr, Uint128};
 
-    /// Check compliance for a token transfer
+    /// Check compliance for a token transfer. Fails when the sender or receiver has an
+    /// identity registered in one of the token's active restricted countries, unless the
+    /// identity is held by a contract and the token exempts contract holders.
     pub fn check_compliance(
         deps: Deps,
         token_address: Addr,
-        _from: Option<Addr>,
-        _to: Option<Addr>,
+        from: Option<Addr>,
+        to: Option<Addr>,
         _amount: Option<Uint128>,
     ) -> StdResult<bool> {
-        let _idenitry_address = IDENTITY_ADDRESS.load(deps.storage)?;
+        let identity_address = IDENTITY_ADDRESS.load(deps.storage)?;
 
         // Get all active restricted countries for the token
         let restricted_countries: Vec<RestrictedCountry> = RESTRICTED_COUNTRY
@@ -214,25 +238,38 @@ pub mod query {
                 })
             })
             .collect();
+        if restricted_countries.is_empty() {
+            return Ok(true);
+        }
+        let contract_holders_exempt = CONTRACT_HOLDERS_EXEMPT
+            .may_load(deps.storage, token_address)?
+            .unwrap_or_default();
 
         // Check if sender or receiver is in a restricted country
-        for _restricted_country in restricted_countries {
-            // let msg = QueryMsg::GetCountryCode {
-            //     from: from.clone(),
-            //     to: to.clone(),
-            // };
-
-            // let query = QueryRequest::Wasm(WasmQuery::Smart {
-            //     contract_addr: idenitry_address.to_string(),
-            //     msg: to_json_binary(&msg)?,
-            // });
-            // let (from_country_code, to_country_code): (String, String) =
-            //     deps.querier.query(&query)?;
-            // if from_country_code == restricted_country.country_code
-            //     || to_country_code == restricted_country.country_code
-            // {
-            //     return Ok(false);
-            // }
+        for user_addr in [from, to].into_iter().flatten() {
+            if contract_holders_exempt {
+                let is_contract_holder: Option<bool> = deps.querier.query_wasm_smart(
+                    &identity_address,
+                    &IdentityQueryMsg::IsContractHolder {
+                        owner: user_addr.to_string(),
+                    },
+                )?;
+                if is_contract_holder == Some(true) {
+                    continue;
+                }
+            }
+            let country: Option<String> = deps.querier.query_wasm_smart(
+                &identity_address,
+                &IdentityQueryMsg::GetCountry {
+                    owner: user_addr.to_string(),
+                },
+            )?;
+            if restricted_countries
+                .iter()
+                .any(|restricted| Some(&restricted.country_code) == country.as_ref())
+            {
+                return Ok(false);
+            }
         }
 
         Ok(true)
@@ -241,6 +278,7 @@ pub mod query {
 
 #[cfg(test)]
 mod tests {
+    use crate::modules::country_restriction::msg::IdentityQueryMsg;
     use crate::modules::country_restriction::state::RESTRICTED_COUNTRY;
 
     use super::*;
@@ -291,6 +329,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -340,6 +379,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -396,6 +436,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -436,4 +477,58 @@ mod tests {
             .unwrap();
         assert!(!restriction.active);
     }
+
+    #[test]
+    fn contract_holders_exempt_from_restricted_countries() {
+        let mut deps = mock_dependencies();
+        let (owner_roles_address, identity_address) = setup_contract(deps.as_mut());
+
+        // Both holders reside in the restricted country, only the treasury is a contract
+        deps.querier.update_wasm(move |query| match query {
+            cosmwasm_std::WasmQuery::Smart { contract_addr, msg } => {
+                if *contract_addr == owner_roles_address.to_string() {
+                    return SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()));
+                }
+                assert_eq!(*contract_addr, identity_address.to_string());
+                let res = match from_json(msg).unwrap() {
+                    IdentityQueryMsg::GetCountry { .. } => to_json_binary(&Some("US")),
+                    IdentityQueryMsg::IsContractHolder { owner } => {
+                        to_json_binary(&Some(owner == "treasury"))
+                    }
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let token_address = Addr::unchecked("token_address");
+        let msg = ExecuteMsg::AddCountryRestriction {
+            token_address: token_address.clone(),
+            country_code: "US".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let check = |deps: Deps, to: &str| {
+            query::check_compliance(
+                deps,
+                token_address.clone(),
+                None,
+                Some(Addr::unchecked(to)),
+                None,
+            )
+            .unwrap()
+        };
+        assert!(!check(deps.as_ref(), "treasury"));
+        assert!(!check(deps.as_ref(), "alice"));
+
+        // Once exempt, the contract-held identity passes while the wallet is still blocked
+        let msg = ExecuteMsg::SetContractHoldersExempt {
+            token_address: token_address.clone(),
+            exempt: true,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(check(deps.as_ref(), "treasury"));
+        assert!(!check(deps.as_ref(), "alice"));
+    }
 }
@@ -22,6 +22,19 @@ pub enum ExecuteMsg {
         country_code: String,
         active: bool,
     },
+    // Lets identities held by contracts, e.g. a DAO treasury, through the token's
+    // restricted countries
+    SetContractHoldersExempt {
+        token_address: Addr,
+        exempt: bool,
+    },
+}
+
+// Subset of the identity storage's queries the module relies on
+#[cw_serde]
+pub enum IdentityQueryMsg {
+    GetCountry { owner: String },
+    IsContractHolder { owner: String },
 }
 
 #[cw_serde]
@@ -7,3 +7,5 @@ pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_addre
 pub const IDENTITY_ADDRESS: Item<Addr> = Item::new("identity_addr");
 pub const RESTRICTED_COUNTRY: Map<(Addr, String), RestrictedCountry> =
     Map::new("token_compliance_modules");
+// Token address -> whether contract-held identities skip the restricted countries
+pub const CONTRACT_HOLDERS_EXEMPT: Map<Addr, bool> = Map::new("contract_holders_exempt");
@@ -0,0 +1,464 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::lockup::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::lockup::ContractError;
+
+use super::state::OWNER_ROLES_ADDRESS;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:lockup";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate lockup contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles address
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the lockup contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `env` - The environment info
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions
+    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+
+    match msg {
+        ExecuteMsg::SetLockup {
+            token_address,
+            user,
+            amount,
+            unlock_time,
+        } => execute::set_lockup(deps, env, token_address, user, amount, unlock_time),
+        ExecuteMsg::RemoveLockups {
+            token_address,
+            user,
+        } => execute::remove_lockups(deps, token_address, user),
+    }
+}
+
+pub mod execute {
+    use crate::modules::lockup::state::{Lockup, LOCKUPS};
+
+    use super::*;
+    use cosmwasm_std::{Addr, QueryRequest, Uint128, Uint64, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Lock part of a user's balance of a token until `unlock_time`, dropping lockups
+    /// that were already released
+    pub fn set_lockup(
+        deps: DepsMut,
+        env: Env,
+        token_address: Addr,
+        user: Addr,
+        amount: Uint128,
+        unlock_time: Uint64,
+    ) -> Result<Response, ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidLockup {
+                reason: "amount must be positive".to_string(),
+            });
+        }
+        let now = env.block.time.seconds();
+        if unlock_time.u64() <= now {
+            return Err(ContractError::InvalidLockup {
+                reason: "unlock_time must be in the future".to_string(),
+            });
+        }
+
+        let key = (token_address.clone(), user.clone());
+        let mut lockups: Vec<Lockup> = LOCKUPS
+            .may_load(deps.storage, key.clone())?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|lockup| super::is_locked(lockup, now))
+            .collect();
+        lockups.push(Lockup {
+            amount,
+            unlock_time,
+        });
+        LOCKUPS.save(deps.storage, key, &lockups)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_lockup")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("user", user.to_string())
+            .add_attribute("amount", amount.to_string())
+            .add_attribute("unlock_time", unlock_time.to_string()))
+    }
+
+    /// Release every lockup of a user
+    pub fn remove_lockups(
+        deps: DepsMut,
+        token_address: Addr,
+        user: Addr,
+    ) -> Result<Response, ContractError> {
+        LOCKUPS.remove(deps.storage, (token_address.clone(), user.clone()));
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_lockups")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("user", user.to_string()))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to: _,
+            amount,
+            decimals: _,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            env,
+            token_address,
+            from,
+            amount,
+        )?),
+        QueryMsg::CanTransfer {
+            token_address,
+            from,
+            amount,
+        } => to_json_binary(&query::can_transfer(
+            deps,
+            env,
+            token_address,
+            from,
+            amount,
+        )?),
+        QueryMsg::LockedBalance {
+            token_address,
+            user,
+        } => to_json_binary(&query::locked_balance(deps, env, token_address, user)?),
+        QueryMsg::GetLockups {
+            token_address,
+            user,
+        } => to_json_binary(&query::get_lockups(deps, env, token_address, user)?),
+    }
+}
+
+pub mod query {
+    use crate::modules::lockup::{
+        msg::CanTransferResponse,
+        state::{Lockup, LOCKUPS},
+    };
+
+    use super::*;
+    use cosmwasm_std::{Addr, Uint128};
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        from: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> StdResult<bool> {
+        let amount = amount.unwrap_or_default();
+        Ok(lockup_violation(deps, &env, &token_address, from, amount)?.is_none())
+    }
+
+    /// Check whether `from` has `amount` of unlocked balance to transfer, with the reason
+    /// if not
+    pub fn can_transfer(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        from: Option<Addr>,
+        amount: Uint128,
+    ) -> StdResult<CanTransferResponse> {
+        let violation = lockup_violation(deps, &env, &token_address, from, amount)?;
+        Ok(CanTransferResponse {
+            can_transfer: violation.is_none(),
+            reason: violation.map(|e| e.to_string()),
+        })
+    }
+
+    /// Get the part of a user's balance still locked at the current block time
+    pub fn locked_balance(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        user: Addr,
+    ) -> StdResult<Uint128> {
+        Ok(get_lockups(deps, env, token_address, user)?
+            .iter()
+            .map(|lockup| lockup.amount)
+            .sum())
+    }
+
+    /// Get the lockups of a user that have not been released yet
+    pub fn get_lockups(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        user: Addr,
+    ) -> StdResult<Vec<Lockup>> {
+        let now = env.block.time.seconds();
+        Ok(LOCKUPS
+            .may_load(deps.storage, (token_address, user))?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|lockup| super::is_locked(lockup, now))
+            .collect())
+    }
+
+    fn lockup_violation(
+        deps: Deps,
+        env: &Env,
+        token_address: &Addr,
+        from: Option<Addr>,
+        amount: Uint128,
+    ) -> StdResult<Option<ContractError>> {
+        // Mints spend no one's balance
+        let Some(from) = from else {
+            return Ok(None);
+        };
+        let locked = locked_balance(deps, env.clone(), token_address.clone(), from.clone())?;
+        if locked.is_zero() {
+            return Ok(None);
+        }
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            token_address,
+            &Cw20QueryMsg::Balance {
+                address: from.to_string(),
+            },
+        )?;
+        let spendable = balance.balance.saturating_sub(locked);
+        if amount <= spendable {
+            Ok(None)
+        } else {
+            Ok(Some(ContractError::TokensLocked { locked, spendable }))
+        }
+    }
+}
+
+// Whether a lockup still holds at `now`, in seconds
+fn is_locked(lockup: &super::state::Lockup, now: u64) -> bool {
+    lockup.unlock_time.u64() > now
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::lockup::{msg::CanTransferResponse, state::Lockup};
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, OwnedDeps, SystemResult, Uint128, Uint64, WasmQuery,
+    };
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+    use roles::owner_roles::msg::OwnerRole;
+
+    // Mock the owner roles contract and a token where every holder has `balance`
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, balance: u128) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "owner_roles_contract" => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                Cw20QueryMsg::Balance { .. } => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&BalanceResponse {
+                        balance: Uint128::new(balance),
+                    })
+                    .unwrap(),
+                )),
+                _ => panic!("Unexpected token query"),
+            },
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn lock(deps: DepsMut, amount: u128, unlock_time: u64) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::SetLockup {
+            token_address: Addr::unchecked("token_address"),
+            user: Addr::unchecked("alice"),
+            amount: Uint128::new(amount),
+            unlock_time: Uint64::new(unlock_time),
+        };
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        execute(deps, mock_env(), info, msg)
+    }
+
+    fn check(deps: Deps, env: Env, amount: u128) -> bool {
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("alice")),
+            to: Some(Addr::unchecked("bob")),
+            amount: Some(Uint128::new(amount)),
+            decimals: None,
+        };
+        from_json(query(deps, env, msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fully_locked_balance_blocks_transfers() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps, 100);
+        let now = mock_env().block.time.seconds();
+
+        for (amount, unlock_time) in [(0, now + 1000), (100, now)] {
+            let err = lock(deps.as_mut(), amount, unlock_time).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidLockup { .. }));
+        }
+
+        assert!(check(deps.as_ref(), mock_env(), 100));
+        lock(deps.as_mut(), 100, now + 1000).unwrap();
+        assert!(!check(deps.as_ref(), mock_env(), 1));
+
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("alice")),
+            amount: Uint128::new(1),
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!res.can_transfer);
+        assert_eq!(
+            res.reason,
+            Some("TokensLocked: 100 tokens are locked, leaving 0 spendable".to_string())
+        );
+
+        // Mints are never blocked, and other holders are unaffected
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            from: None,
+            amount: Uint128::new(1000),
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.can_transfer);
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("bob")),
+            amount: Uint128::new(100),
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(res.can_transfer);
+
+        // Removing the lockups frees the whole balance
+        let msg = ExecuteMsg::RemoveLockups {
+            token_address: Addr::unchecked("token_address"),
+            user: Addr::unchecked("alice"),
+        };
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(check(deps.as_ref(), mock_env(), 100));
+    }
+
+    #[test]
+    fn lockups_release_over_time() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps, 100);
+        let now = mock_env().block.time.seconds();
+
+        lock(deps.as_mut(), 60, now + 100).unwrap();
+        lock(deps.as_mut(), 40, now + 200).unwrap();
+        assert!(!check(deps.as_ref(), mock_env(), 1));
+
+        // Once the first lockup ends, its amount is spendable and no more
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        assert!(check(deps.as_ref(), env.clone(), 60));
+        assert!(!check(deps.as_ref(), env.clone(), 61));
+        let msg = QueryMsg::LockedBalance {
+            token_address: Addr::unchecked("token_address"),
+            user: Addr::unchecked("alice"),
+        };
+        let locked: Uint128 = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(locked, Uint128::new(40));
+        let msg = QueryMsg::GetLockups {
+            token_address: Addr::unchecked("token_address"),
+            user: Addr::unchecked("alice"),
+        };
+        let lockups: Vec<Lockup> =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            lockups,
+            vec![Lockup {
+                amount: Uint128::new(40),
+                unlock_time: Uint64::new(now + 200),
+            }]
+        );
+
+        env.block.time = env.block.time.plus_seconds(100);
+        assert!(check(deps.as_ref(), env, 100));
+    }
+}
@@ -0,0 +1,17 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("InvalidLockup: {reason}")]
+    InvalidLockup { reason: String },
+
+    #[error("TokensLocked: {locked} tokens are locked, leaving {spendable} spendable")]
+    TokensLocked { locked: Uint128, spendable: Uint128 },
+}
@@ -0,0 +1,60 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128, Uint64};
+
+use crate::modules::lockup::state::Lockup;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    // Locks `amount` of the user's balance until `unlock_time`, on top of existing lockups
+    SetLockup {
+        token_address: Addr,
+        user: Addr,
+        amount: Uint128,
+        unlock_time: Uint64,
+    },
+    // Releases every lockup of the user right away
+    RemoveLockups {
+        token_address: Addr,
+        user: Addr,
+    },
+}
+
+// Extends the shared compliance module query with lockup specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        amount: Uint128,
+    },
+    // Part of the user's balance still locked at the current block time
+    #[returns(Uint128)]
+    LockedBalance { token_address: Addr, user: Addr },
+    // Lockups of the user that have not been released yet
+    #[returns(Vec<Lockup>)]
+    GetLockups { token_address: Addr, user: Addr },
+}
+
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub can_transfer: bool,
+    // Why the transfer is rejected, if it is
+    pub reason: Option<String>,
+}
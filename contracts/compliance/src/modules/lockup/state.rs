@@ -0,0 +1,14 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Uint128, Uint64};
+use cw_storage_plus::{Item, Map};
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+// (Token address, holder) -> amounts of the holder's balance that may not be spent yet
+pub const LOCKUPS: Map<(Addr, Addr), Vec<Lockup>> = Map::new("lockups");
+
+#[cw_serde]
+pub struct Lockup {
+    pub amount: Uint128,
+    /// Unix time in seconds from which the amount may be spent
+    pub unlock_time: Uint64,
+}
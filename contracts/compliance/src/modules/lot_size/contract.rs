@@ -0,0 +1,362 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::lot_size::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::lot_size::ContractError;
+
+use super::state::OWNER_ROLES_ADDRESS;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:lot_size";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate lot size contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles address
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the lot size contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions
+    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+
+    match msg {
+        ExecuteMsg::SetLotSize {
+            token_address,
+            lot_size,
+        } => execute::set_lot_size(deps, token_address, lot_size),
+        ExecuteMsg::RemoveLotSize { token_address } => {
+            execute::remove_lot_size(deps, token_address)
+        }
+        ExecuteMsg::ImportState {
+            token_address,
+            state,
+        } => execute::import_state(deps, token_address, state),
+    }
+}
+
+pub mod execute {
+    use crate::modules::lot_size::state::LOT_SIZES;
+
+    use super::*;
+    use cosmwasm_std::{from_json, to_json_binary, Addr, QueryRequest, Uint128, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Set the lot size transfers of a token must be a multiple of
+    pub fn set_lot_size(
+        deps: DepsMut,
+        token_address: Addr,
+        lot_size: Uint128,
+    ) -> Result<Response, ContractError> {
+        if lot_size.is_zero() {
+            return Err(ContractError::InvalidLotSize {});
+        }
+        LOT_SIZES.save(deps.storage, token_address.clone(), &lot_size)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_lot_size")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("lot_size", lot_size.to_string()))
+    }
+
+    /// Remove the lot size requirement for a token
+    pub fn remove_lot_size(deps: DepsMut, token_address: Addr) -> Result<Response, ContractError> {
+        LOT_SIZES.remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_lot_size")
+            .add_attribute("token_address", token_address.to_string()))
+    }
+
+    /// Restore the lot size exported by the module this one replaces
+    pub fn import_state(
+        deps: DepsMut,
+        token_address: Addr,
+        state: Binary,
+    ) -> Result<Response, ContractError> {
+        let lot_size: Option<Uint128> = from_json(&state)?;
+        match lot_size {
+            Some(lot_size) => set_lot_size(deps, token_address, lot_size),
+            None => remove_lot_size(deps, token_address),
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to,
+            amount,
+            decimals: _,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+        )?),
+        QueryMsg::CanTransfer {
+            token_address,
+            amount,
+        } => to_json_binary(&query::can_transfer(deps, token_address, amount)?),
+        QueryMsg::GetLotSize { token_address } => {
+            to_json_binary(&query::get_lot_size(deps, token_address)?)
+        }
+        QueryMsg::ExportState { token_address } => {
+            to_json_binary(&to_json_binary(&query::get_lot_size(deps, token_address)?)?)
+        }
+    }
+}
+
+pub mod query {
+    use crate::modules::lot_size::{msg::CanTransferResponse, state::LOT_SIZES};
+
+    use super::*;
+    use cosmwasm_std::{Addr, Uint128};
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        token_address: Addr,
+        _from: Option<Addr>,
+        _to: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> StdResult<bool> {
+        // Checks that don't move an amount are not restricted by lot sizes
+        let Some(amount) = amount else {
+            return Ok(true);
+        };
+        Ok(lot_size_violation(deps, &token_address, amount)?.is_none())
+    }
+
+    /// Check whether `amount` of the token can be transferred, with the reason if not
+    pub fn can_transfer(
+        deps: Deps,
+        token_address: Addr,
+        amount: Uint128,
+    ) -> StdResult<CanTransferResponse> {
+        let violation = lot_size_violation(deps, &token_address, amount)?;
+        Ok(CanTransferResponse {
+            can_transfer: violation.is_none(),
+            reason: violation.map(|e| e.to_string()),
+        })
+    }
+
+    /// Get the lot size configured for a token, if any
+    pub fn get_lot_size(deps: Deps, token_address: Addr) -> StdResult<Option<Uint128>> {
+        LOT_SIZES.may_load(deps.storage, token_address)
+    }
+
+    fn lot_size_violation(
+        deps: Deps,
+        token_address: &Addr,
+        amount: Uint128,
+    ) -> StdResult<Option<ContractError>> {
+        let Some(lot_size) = LOT_SIZES.may_load(deps.storage, token_address.clone())? else {
+            return Ok(None);
+        };
+        if (amount % lot_size).is_zero() {
+            Ok(None)
+        } else {
+            Ok(Some(ContractError::NonLotSize { amount, lot_size }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::lot_size::{msg::CanTransferResponse, state::LOT_SIZES};
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{from_json, Addr, ContractResult, OwnedDeps, SystemResult, Uint128};
+    use roles::owner_roles::msg::OwnerRole;
+
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Mock the owner roles contract query
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { msg, .. } => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn check(deps: Deps, token_address: &Addr, amount: u128) -> bool {
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: token_address.clone(),
+            from: Some(Addr::unchecked("from")),
+            to: Some(Addr::unchecked("to")),
+            amount: Some(Uint128::new(amount)),
+            decimals: None,
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn set_and_remove_lot_size() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let token_address = Addr::unchecked("token_address");
+
+        // A zero lot size is rejected
+        let msg = ExecuteMsg::SetLotSize {
+            token_address: token_address.clone(),
+            lot_size: Uint128::zero(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidLotSize {}));
+
+        let msg = ExecuteMsg::SetLotSize {
+            token_address: token_address.clone(),
+            lot_size: Uint128::new(100),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "set_lot_size"),
+                ("token_address", token_address.as_str()),
+                ("lot_size", "100"),
+            ]
+        );
+        let lot_size = LOT_SIZES
+            .load(&deps.storage, token_address.clone())
+            .unwrap();
+        assert_eq!(lot_size, Uint128::new(100));
+
+        let msg = ExecuteMsg::RemoveLotSize {
+            token_address: token_address.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res: Option<Uint128> = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetLotSize { token_address },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn check_lot_size_compliance() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let token_address = Addr::unchecked("token_address");
+        let other_token = Addr::unchecked("other_token");
+        let msg = ExecuteMsg::SetLotSize {
+            token_address: token_address.clone(),
+            lot_size: Uint128::new(100),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Whole lots, including the zero-remainder boundary, are compliant
+        assert!(check(deps.as_ref(), &token_address, 100));
+        assert!(check(deps.as_ref(), &token_address, 300));
+        assert!(check(deps.as_ref(), &token_address, 0));
+
+        // Partial lots are not
+        assert!(!check(deps.as_ref(), &token_address, 99));
+        assert!(!check(deps.as_ref(), &token_address, 101));
+
+        // Tokens without a lot size are unrestricted
+        assert!(check(deps.as_ref(), &other_token, 101));
+
+        let msg = QueryMsg::CanTransfer {
+            token_address,
+            amount: Uint128::new(150),
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!res.can_transfer);
+        assert_eq!(
+            res.reason,
+            Some("NonLotSize: 150 is not a multiple of 100".to_string())
+        );
+    }
+}
@@ -0,0 +1,17 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("InvalidLotSize")]
+    InvalidLotSize {},
+
+    #[error("NonLotSize: {amount} is not a multiple of {lot_size}")]
+    NonLotSize { amount: Uint128, lot_size: Uint128 },
+}
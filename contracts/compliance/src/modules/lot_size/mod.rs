@@ -0,0 +1,7 @@
+pub mod contract;
+pub mod error;
+pub mod msg;
+pub mod state;
+
+pub use self::error::ContractError;
+pub use self::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
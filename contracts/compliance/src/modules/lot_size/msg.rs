@@ -0,0 +1,56 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary, Uint128};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetLotSize {
+        token_address: Addr,
+        lot_size: Uint128,
+    },
+    RemoveLotSize {
+        token_address: Addr,
+    },
+    // Seeds the lot size from `ExportState` of a replaced module, see `utils::ModuleStateExecuteMsg`
+    ImportState {
+        token_address: Addr,
+        state: Binary,
+    },
+}
+
+// Extends the shared compliance module query with lot size specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_address: Addr,
+        amount: Uint128,
+    },
+    #[returns(Option<Uint128>)]
+    GetLotSize { token_address: Addr },
+    // The lot size of the token as JSON, see `utils::ModuleStateQueryMsg`
+    #[returns(Binary)]
+    ExportState { token_address: Addr },
+}
+
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub can_transfer: bool,
+    // Why the transfer is rejected, if it is
+    pub reason: Option<String>,
+}
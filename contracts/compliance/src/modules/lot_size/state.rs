@@ -0,0 +1,6 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+// Token address -> lot size transfers must be a multiple of
+pub const LOT_SIZES: Map<Addr, Uint128> = Map::new("lot_sizes");
@@ -0,0 +1,524 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::max_balance::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::max_balance::ContractError;
+
+use super::state::{OWNER_ROLES_ADDRESS, REGISTRY_ADDRESS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:max_balance";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate max balance contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles and registry addresses
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    REGISTRY_ADDRESS.save(deps.storage, &msg.registry_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the max balance contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions
+    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+
+    match msg {
+        ExecuteMsg::SetMaxBalance {
+            token_address,
+            amount,
+        } => execute::set_max_balance(deps, token_address, amount),
+        ExecuteMsg::RemoveMaxBalance { token_address } => {
+            execute::remove_max_balance(deps, token_address)
+        }
+        ExecuteMsg::SetCountryMaxBalance {
+            token_address,
+            country,
+            amount,
+        } => execute::set_country_max_balance(deps, token_address, country, amount),
+    }
+}
+
+pub mod execute {
+    use crate::modules::max_balance::state::{COUNTRY_MAX_BALANCE, MAX_BALANCE};
+
+    use super::*;
+    use cosmwasm_std::{Addr, QueryRequest, Uint128, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Cap the balance any single account may hold of a token
+    pub fn set_max_balance(
+        deps: DepsMut,
+        token_address: Addr,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidMaxBalance {});
+        }
+        MAX_BALANCE.save(deps.storage, token_address.clone(), &amount)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_max_balance")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("amount", amount.to_string()))
+    }
+
+    /// Remove the token-wide balance cap, country overrides stay in place
+    pub fn remove_max_balance(
+        deps: DepsMut,
+        token_address: Addr,
+    ) -> Result<Response, ContractError> {
+        MAX_BALANCE.remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_max_balance")
+            .add_attribute("token_address", token_address.to_string()))
+    }
+
+    /// Set or remove the balance cap for residents of a country
+    pub fn set_country_max_balance(
+        deps: DepsMut,
+        token_address: Addr,
+        country: u16,
+        amount: Option<Uint128>,
+    ) -> Result<Response, ContractError> {
+        let key = (token_address.clone(), country);
+        match amount {
+            Some(amount) if amount.is_zero() => return Err(ContractError::InvalidMaxBalance {}),
+            Some(amount) => COUNTRY_MAX_BALANCE.save(deps.storage, key, &amount)?,
+            None => COUNTRY_MAX_BALANCE.remove(deps.storage, key),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_country_max_balance")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("country", country.to_string())
+            .add_attribute(
+                "amount",
+                amount.map_or_else(|| "none".to_string(), |amount| amount.to_string()),
+            ))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to,
+            amount,
+            decimals: _,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+        )?),
+        QueryMsg::CanTransfer {
+            token_address,
+            to,
+            amount,
+            to_current_balance,
+        } => to_json_binary(&query::can_transfer(
+            deps,
+            token_address,
+            to,
+            amount,
+            to_current_balance,
+        )?),
+        QueryMsg::GetMaxBalance {
+            token_address,
+            country,
+        } => to_json_binary(&query::get_max_balance(deps, token_address, country)?),
+    }
+}
+
+pub mod query {
+    use crate::modules::max_balance::{
+        msg::CanTransferResponse,
+        state::{COUNTRY_MAX_BALANCE, MAX_BALANCE},
+    };
+    use crate::registry::msg::{IdentityInfo, QueryMsg as RegistryQueryMsg};
+
+    use super::*;
+    use cosmwasm_std::{Addr, Order, Uint128};
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> StdResult<bool> {
+        // Burns and transfers to oneself leave no balance higher than before
+        let Some(to) = to else {
+            return Ok(true);
+        };
+        if from.as_ref() == Some(&to) {
+            return Ok(true);
+        }
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            &token_address,
+            &Cw20QueryMsg::Balance {
+                address: to.to_string(),
+            },
+        )?;
+        let violation = max_balance_violation(
+            deps,
+            &token_address,
+            &to,
+            amount.unwrap_or_default(),
+            balance.balance,
+        )?;
+        Ok(violation.is_none())
+    }
+
+    /// Check whether `to` may receive `amount` on top of its current balance, with the
+    /// reason if not
+    pub fn can_transfer(
+        deps: Deps,
+        token_address: Addr,
+        to: Addr,
+        amount: Uint128,
+        to_current_balance: Uint128,
+    ) -> StdResult<CanTransferResponse> {
+        let violation =
+            max_balance_violation(deps, &token_address, &to, amount, to_current_balance)?;
+        Ok(CanTransferResponse {
+            can_transfer: violation.is_none(),
+            reason: violation.map(|e| e.to_string()),
+        })
+    }
+
+    /// Get the cap for residents of `country`, falling back to the token-wide cap
+    pub fn get_max_balance(
+        deps: Deps,
+        token_address: Addr,
+        country: Option<u16>,
+    ) -> StdResult<Option<Uint128>> {
+        if let Some(country) = country {
+            let key = (token_address.clone(), country);
+            if let Some(max) = COUNTRY_MAX_BALANCE.may_load(deps.storage, key)? {
+                return Ok(Some(max));
+            }
+        }
+        MAX_BALANCE.may_load(deps.storage, token_address)
+    }
+
+    // The cap applying to `holder`, looking up its country only when the token has
+    // country overrides
+    fn max_balance_for(
+        deps: Deps,
+        token_address: &Addr,
+        holder: &Addr,
+    ) -> StdResult<Option<Uint128>> {
+        let has_overrides = COUNTRY_MAX_BALANCE
+            .prefix(token_address.clone())
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if !has_overrides {
+            return MAX_BALANCE.may_load(deps.storage, token_address.clone());
+        }
+        let registry = REGISTRY_ADDRESS.load(deps.storage)?;
+        let identity: Option<IdentityInfo> = deps.querier.query_wasm_smart(
+            registry,
+            &RegistryQueryMsg::GetIdentity {
+                user_addr: holder.clone(),
+            },
+        )?;
+        get_max_balance(
+            deps,
+            token_address.clone(),
+            identity.map(|identity| identity.country),
+        )
+    }
+
+    fn max_balance_violation(
+        deps: Deps,
+        token_address: &Addr,
+        to: &Addr,
+        amount: Uint128,
+        to_current_balance: Uint128,
+    ) -> StdResult<Option<ContractError>> {
+        let Some(max) = max_balance_for(deps, token_address, to)? else {
+            return Ok(None);
+        };
+        let balance = to_current_balance.checked_add(amount)?;
+        if balance <= max {
+            Ok(None)
+        } else {
+            Ok(Some(ContractError::MaxBalanceExceeded { balance, max }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::max_balance::msg::CanTransferResponse;
+    use crate::registry::msg::{IdentityInfo, QueryMsg as RegistryQueryMsg};
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, OwnedDeps, SystemResult, Uint128, WasmQuery,
+    };
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+    use roles::owner_roles::msg::OwnerRole;
+
+    // Mock the owner roles contract, a registry where "alice" resides in country 840 and
+    // "bob" in 276, and a token where every holder has `balance`
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, balance: u128) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+            registry_address: Addr::unchecked("registry"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "owner_roles_contract" => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "registry" => {
+                match from_json(msg).unwrap() {
+                    RegistryQueryMsg::GetIdentity { user_addr } => {
+                        let country = match user_addr.as_str() {
+                            "alice" => 840,
+                            "bob" => 276,
+                            _ => panic!("Unexpected user"),
+                        };
+                        let identity = IdentityInfo {
+                            identity: Addr::unchecked(format!("{user_addr}_identity")),
+                            country,
+                        };
+                        SystemResult::Ok(ContractResult::Ok(
+                            to_json_binary(&Some(identity)).unwrap(),
+                        ))
+                    }
+                    _ => panic!("Unexpected registry query"),
+                }
+            }
+            WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                Cw20QueryMsg::Balance { .. } => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&BalanceResponse {
+                        balance: Uint128::new(balance),
+                    })
+                    .unwrap(),
+                )),
+                _ => panic!("Unexpected token query"),
+            },
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn set(deps: DepsMut, msg: ExecuteMsg) {
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        execute(deps, mock_env(), info, msg).unwrap();
+    }
+
+    fn check(deps: Deps, to: &str, amount: u128) -> bool {
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("issuer")),
+            to: Some(Addr::unchecked(to)),
+            amount: Some(Uint128::new(amount)),
+            decimals: None,
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    fn can_transfer(deps: Deps, to: &str, amount: u128, balance: u128) -> CanTransferResponse {
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            to: Addr::unchecked(to),
+            amount: Uint128::new(amount),
+            to_current_balance: Uint128::new(balance),
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn balance_cap_is_inclusive() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps, 400);
+
+        // Without a cap anything goes
+        assert!(check(deps.as_ref(), "alice", 10_000));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let msg = ExecuteMsg::SetMaxBalance {
+            token_address: Addr::unchecked("token_address"),
+            amount: Uint128::zero(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMaxBalance {}));
+
+        set(
+            deps.as_mut(),
+            ExecuteMsg::SetMaxBalance {
+                token_address: Addr::unchecked("token_address"),
+                amount: Uint128::new(1000),
+            },
+        );
+
+        // Reaching the cap exactly is allowed, going one over is not
+        assert!(check(deps.as_ref(), "alice", 600));
+        assert!(!check(deps.as_ref(), "alice", 601));
+        assert!(can_transfer(deps.as_ref(), "alice", 1000, 0).can_transfer);
+        let res = can_transfer(deps.as_ref(), "alice", 1, 1000);
+        assert!(!res.can_transfer);
+        assert_eq!(
+            res.reason,
+            Some(
+                "MaxBalanceExceeded: the recipient would hold 1001, above the cap of 1000"
+                    .to_string()
+            )
+        );
+
+        // Burns never exceed a cap
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("alice")),
+            to: None,
+            amount: Some(Uint128::new(5000)),
+            decimals: None,
+        };
+        assert!(from_json::<bool>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn country_cap_takes_precedence() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps, 0);
+
+        set(
+            deps.as_mut(),
+            ExecuteMsg::SetMaxBalance {
+                token_address: Addr::unchecked("token_address"),
+                amount: Uint128::new(1000),
+            },
+        );
+        set(
+            deps.as_mut(),
+            ExecuteMsg::SetCountryMaxBalance {
+                token_address: Addr::unchecked("token_address"),
+                country: 840,
+                amount: Some(Uint128::new(5000)),
+            },
+        );
+
+        // Residents of 840 get the higher cap, others keep the token-wide one
+        assert!(check(deps.as_ref(), "alice", 5000));
+        assert!(!check(deps.as_ref(), "alice", 5001));
+        assert!(check(deps.as_ref(), "bob", 1000));
+        assert!(!check(deps.as_ref(), "bob", 1001));
+
+        // An override also applies without a token-wide cap, and may be lower than it
+        set(
+            deps.as_mut(),
+            ExecuteMsg::RemoveMaxBalance {
+                token_address: Addr::unchecked("token_address"),
+            },
+        );
+        set(
+            deps.as_mut(),
+            ExecuteMsg::SetCountryMaxBalance {
+                token_address: Addr::unchecked("token_address"),
+                country: 276,
+                amount: Some(Uint128::new(100)),
+            },
+        );
+        assert!(!check(deps.as_ref(), "bob", 101));
+        assert!(!can_transfer(deps.as_ref(), "alice", 1, 5000).can_transfer);
+
+        // Removing the override lifts the cap for that country
+        set(
+            deps.as_mut(),
+            ExecuteMsg::SetCountryMaxBalance {
+                token_address: Addr::unchecked("token_address"),
+                country: 276,
+                amount: None,
+            },
+        );
+        assert!(check(deps.as_ref(), "bob", 10_000));
+        let msg = QueryMsg::GetMaxBalance {
+            token_address: Addr::unchecked("token_address"),
+            country: Some(840),
+        };
+        let res: Option<Uint128> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, Some(Uint128::new(5000)));
+    }
+}
@@ -0,0 +1,17 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("InvalidMaxBalance: the cap must be positive")]
+    InvalidMaxBalance {},
+
+    #[error("MaxBalanceExceeded: the recipient would hold {balance}, above the cap of {max}")]
+    MaxBalanceExceeded { balance: Uint128, max: Uint128 },
+}
@@ -0,0 +1,62 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+    pub registry_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetMaxBalance {
+        token_address: Addr,
+        amount: Uint128,
+    },
+    RemoveMaxBalance {
+        token_address: Addr,
+    },
+    // Overrides the token-wide cap for residents of `country`, removed when `amount` is `None`
+    SetCountryMaxBalance {
+        token_address: Addr,
+        country: u16,
+        amount: Option<Uint128>,
+    },
+}
+
+// Extends the shared compliance module query with max balance specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    // Whether `to` may receive `amount` on top of `to_current_balance`
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_address: Addr,
+        to: Addr,
+        amount: Uint128,
+        to_current_balance: Uint128,
+    },
+    // The cap applying to residents of `country`, or the token-wide cap if `None`
+    #[returns(Option<Uint128>)]
+    GetMaxBalance {
+        token_address: Addr,
+        country: Option<u16>,
+    },
+}
+
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub can_transfer: bool,
+    // Why the transfer is rejected, if it is
+    pub reason: Option<String>,
+}
@@ -0,0 +1,11 @@
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::{Item, Map};
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+/// The compliance registry, queried for the recipient's country
+pub const REGISTRY_ADDRESS: Item<Addr> = Item::new("registry_address");
+// Token address -> most any single account may hold
+pub const MAX_BALANCE: Map<Addr, Uint128> = Map::new("max_balance");
+// (Token address, country) -> cap for residents of the country, taking precedence over the
+// token-wide cap
+pub const COUNTRY_MAX_BALANCE: Map<(Addr, u16), Uint128> = Map::new("country_max_balance");
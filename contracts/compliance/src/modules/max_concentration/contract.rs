@@ -0,0 +1,576 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::max_concentration::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::max_concentration::ContractError;
+
+use super::state::OWNER_ROLES_ADDRESS;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:max_concentration";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate max concentration contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles address
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the max concentration contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions
+    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+
+    match msg {
+        ExecuteMsg::SetConcentrationLimit {
+            token_address,
+            limit,
+        } => execute::set_concentration_limit(deps, token_address, limit),
+        ExecuteMsg::RemoveConcentrationLimit { token_address } => {
+            execute::remove_concentration_limit(deps, token_address)
+        }
+    }
+}
+
+pub mod execute {
+    use crate::modules::max_concentration::{msg::ConcentrationLimit, state::CONCENTRATION_LIMITS};
+
+    use super::*;
+    use cosmwasm_std::{to_json_binary, Addr, Decimal, QueryRequest, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Set the largest share of supply a single holder of the token may own
+    pub fn set_concentration_limit(
+        deps: DepsMut,
+        token_address: Addr,
+        limit: ConcentrationLimit,
+    ) -> Result<Response, ContractError> {
+        if limit.max_share.is_zero() || limit.max_share > Decimal::one() {
+            return Err(ContractError::InvalidMaxShare {});
+        }
+        CONCENTRATION_LIMITS.save(deps.storage, token_address.clone(), &limit)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_concentration_limit")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("max_share", limit.max_share.to_string())
+            .add_attribute("bootstrap_supply", limit.bootstrap_supply.to_string()))
+    }
+
+    /// Remove the concentration limit for a token
+    pub fn remove_concentration_limit(
+        deps: DepsMut,
+        token_address: Addr,
+    ) -> Result<Response, ContractError> {
+        CONCENTRATION_LIMITS.remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_concentration_limit")
+            .add_attribute("token_address", token_address.to_string()))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to,
+            amount,
+            decimals,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+            decimals,
+        )?),
+        QueryMsg::GetConcentrationLimit { token_address } => {
+            to_json_binary(&query::get_concentration_limit(deps, token_address)?)
+        }
+        QueryMsg::SimulateTransfer {
+            token_address,
+            from,
+            to,
+            amount,
+            overlay,
+            decimals,
+        } => to_json_binary(&query::simulate_transfer(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+            overlay,
+            decimals,
+        )?),
+    }
+}
+
+pub mod query {
+    use crate::modules::max_concentration::{
+        msg::{ConcentrationLimit, ConcentrationOverlay},
+        state::CONCENTRATION_LIMITS,
+    };
+
+    use super::*;
+    use cosmwasm_std::{from_json, Addr, StdError, Uint128};
+    use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+    use utils::SimulatedTransferResponse;
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        decimals: Option<u8>,
+    ) -> StdResult<bool> {
+        check_with_overlay(
+            deps,
+            &token_address,
+            from.as_ref(),
+            to.as_ref(),
+            amount,
+            decimals,
+            &ConcentrationOverlay::default(),
+        )
+    }
+
+    /// Check a transfer against the supply and balances left by earlier simulated
+    /// transfers, recording its effects when it is compliant
+    pub fn simulate_transfer(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: Option<Binary>,
+        decimals: Option<u8>,
+    ) -> StdResult<SimulatedTransferResponse> {
+        let mut overlay: ConcentrationOverlay = match overlay {
+            Some(overlay) => from_json(overlay)?,
+            None => ConcentrationOverlay::default(),
+        };
+        let compliant = check_with_overlay(
+            deps,
+            &token_address,
+            from.as_ref(),
+            to.as_ref(),
+            amount,
+            decimals,
+            &overlay,
+        )?;
+
+        // A rejected transfer would not execute, so it leaves no effects behind
+        if let (true, Some(amount)) = (compliant, amount) {
+            let mut supply = supply_of(deps, &token_address, &overlay)?;
+            match &from {
+                Some(from) => {
+                    let balance = balance_of(deps, &token_address, from, &overlay)?;
+                    set_balance(&mut overlay, from, balance.saturating_sub(amount));
+                }
+                None => supply = supply.checked_add(amount)?,
+            }
+            match &to {
+                Some(to) => {
+                    let balance = balance_of(deps, &token_address, to, &overlay)?;
+                    set_balance(&mut overlay, to, balance.checked_add(amount)?);
+                }
+                None => supply = supply.saturating_sub(amount),
+            }
+            overlay.total_supply = Some(supply);
+        }
+
+        Ok(SimulatedTransferResponse {
+            compliant,
+            overlay: to_json_binary(&overlay)?,
+        })
+    }
+
+    fn check_with_overlay(
+        deps: Deps,
+        token_address: &Addr,
+        from: Option<&Addr>,
+        to: Option<&Addr>,
+        amount: Option<Uint128>,
+        decimals: Option<u8>,
+        overlay: &ConcentrationOverlay,
+    ) -> StdResult<bool> {
+        // Burns and checks without an amount can't increase a holder's share
+        let (Some(to), Some(amount)) = (to, amount) else {
+            return Ok(true);
+        };
+        let Some(limit) = CONCENTRATION_LIMITS.may_load(deps.storage, token_address.clone())?
+        else {
+            return Ok(true);
+        };
+        ensure_decimals(deps, token_address, decimals)?;
+
+        // Mints grow the supply by the minted amount
+        let supply = supply_of(deps, token_address, overlay)?;
+        let supply = if from.is_none() {
+            supply.checked_add(amount)?
+        } else {
+            supply
+        };
+        if supply.is_zero() || supply < limit.bootstrap_supply {
+            return Ok(true);
+        }
+
+        let prospective_balance =
+            balance_of(deps, token_address, to, overlay)?.checked_add(amount)?;
+
+        // Compared in base units: a ratio of an 18-decimal token's amounts could be finer
+        // than `Decimal` resolves
+        Ok(prospective_balance <= supply.mul_floor(limit.max_share))
+    }
+
+    /// Ensure the token's amounts are in the decimals the registry has on record. Otherwise
+    /// the amount checked and the bootstrap supply would be on different scales than the
+    /// supply and balances read from the token.
+    fn ensure_decimals(deps: Deps, token_address: &Addr, decimals: Option<u8>) -> StdResult<()> {
+        let Some(decimals) = decimals else {
+            return Ok(());
+        };
+        let token_info: TokenInfoResponse = deps
+            .querier
+            .query_wasm_smart(token_address, &Cw20QueryMsg::TokenInfo {})?;
+        if token_info.decimals != decimals {
+            return Err(StdError::generic_err(format!(
+                "Token {token_address} has {} decimals, but the compliance registry expects {decimals}",
+                token_info.decimals
+            )));
+        }
+        Ok(())
+    }
+
+    fn supply_of(
+        deps: Deps,
+        token_address: &Addr,
+        overlay: &ConcentrationOverlay,
+    ) -> StdResult<Uint128> {
+        if let Some(supply) = overlay.total_supply {
+            return Ok(supply);
+        }
+        let token_info: TokenInfoResponse = deps
+            .querier
+            .query_wasm_smart(token_address, &Cw20QueryMsg::TokenInfo {})?;
+        Ok(token_info.total_supply)
+    }
+
+    fn balance_of(
+        deps: Deps,
+        token_address: &Addr,
+        holder: &Addr,
+        overlay: &ConcentrationOverlay,
+    ) -> StdResult<Uint128> {
+        if let Some((_, balance)) = overlay.balances.iter().find(|(addr, _)| addr == holder) {
+            return Ok(*balance);
+        }
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            token_address,
+            &Cw20QueryMsg::Balance {
+                address: holder.to_string(),
+            },
+        )?;
+        Ok(balance.balance)
+    }
+
+    fn set_balance(overlay: &mut ConcentrationOverlay, holder: &Addr, balance: Uint128) {
+        match overlay.balances.iter_mut().find(|(addr, _)| addr == holder) {
+            Some((_, existing)) => *existing = balance,
+            None => overlay.balances.push((holder.clone(), balance)),
+        }
+    }
+
+    /// Get the concentration limit configured for a token, if any
+    pub fn get_concentration_limit(
+        deps: Deps,
+        token_address: Addr,
+    ) -> StdResult<Option<ConcentrationLimit>> {
+        CONCENTRATION_LIMITS.may_load(deps.storage, token_address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::max_concentration::msg::ConcentrationLimit;
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, Decimal, OwnedDeps, SystemResult, Uint128, WasmQuery,
+    };
+    use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+    use roles::owner_roles::msg::OwnerRole;
+
+    // Mock the owner roles contract and a 6-decimal token with the given supply where
+    // every holder has `balance`
+    fn mock_contracts(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        total_supply: u128,
+        balance: u128,
+    ) {
+        mock_token(deps, 6, total_supply, balance)
+    }
+
+    fn mock_token(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        decimals: u8,
+        total_supply: u128,
+        balance: u128,
+    ) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "owner_roles_contract" => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            WasmQuery::Smart { msg, .. } => {
+                let res = match from_json(msg).unwrap() {
+                    Cw20QueryMsg::TokenInfo {} => to_json_binary(&TokenInfoResponse {
+                        name: "Token".to_string(),
+                        symbol: "TKN".to_string(),
+                        decimals,
+                        total_supply: Uint128::new(total_supply),
+                    }),
+                    Cw20QueryMsg::Balance { .. } => to_json_binary(&BalanceResponse {
+                        balance: Uint128::new(balance),
+                    }),
+                    _ => panic!("Unexpected token query"),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // 10% per holder, enforced once 1000 tokens exist
+        mock_contracts(deps, 0, 0);
+        let msg = ExecuteMsg::SetConcentrationLimit {
+            token_address: Addr::unchecked("token_address"),
+            limit: ConcentrationLimit {
+                max_share: Decimal::percent(10),
+                bootstrap_supply: Uint128::new(1000),
+            },
+        };
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    fn check(deps: Deps, from: Option<&str>, amount: u128) -> bool {
+        check_with_decimals(deps, None, from, amount).unwrap()
+    }
+
+    fn check_with_decimals(
+        deps: Deps,
+        decimals: Option<u8>,
+        from: Option<&str>,
+        amount: u128,
+    ) -> StdResult<bool> {
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: from.map(Addr::unchecked),
+            to: Some(Addr::unchecked("receiver")),
+            amount: Some(Uint128::new(amount)),
+            decimals,
+        };
+        from_json(query(deps, mock_env(), msg)?)
+    }
+
+    #[test]
+    fn invalid_max_share() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for max_share in [Decimal::zero(), Decimal::percent(101)] {
+            let msg = ExecuteMsg::SetConcentrationLimit {
+                token_address: Addr::unchecked("token_address"),
+                limit: ConcentrationLimit {
+                    max_share,
+                    bootstrap_supply: Uint128::zero(),
+                },
+            };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidMaxShare {}));
+        }
+    }
+
+    #[test]
+    fn concentration_boundary_as_supply_grows() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        // Receiver holds 50 of 1000: receiving 50 more reaches exactly 10%
+        mock_contracts(&mut deps, 1000, 50);
+        assert!(check(deps.as_ref(), Some("sender"), 50));
+        assert!(!check(deps.as_ref(), Some("sender"), 51));
+
+        // Once supply doubles, the same holder may receive more
+        mock_contracts(&mut deps, 2000, 50);
+        assert!(check(deps.as_ref(), Some("sender"), 150));
+        assert!(!check(deps.as_ref(), Some("sender"), 151));
+
+        // Minting counts the minted amount towards the supply: 200 of 2000
+        mock_contracts(&mut deps, 1800, 0);
+        assert!(check(deps.as_ref(), None, 200));
+        assert!(!check(deps.as_ref(), None, 201));
+    }
+
+    #[test]
+    fn bootstrap_supply_is_not_restricted() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        // The very first mint may go to a single holder
+        mock_contracts(&mut deps, 0, 0);
+        assert!(check(deps.as_ref(), None, 999));
+
+        // Near-zero supply is not enforced either
+        mock_contracts(&mut deps, 10, 10);
+        assert!(check(deps.as_ref(), Some("sender"), 5));
+
+        // From the bootstrap supply on, the limit applies
+        mock_contracts(&mut deps, 1000, 0);
+        assert!(!check(deps.as_ref(), Some("sender"), 101));
+    }
+
+    #[test]
+    fn simulated_transfers_accumulate() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+        mock_contracts(&mut deps, 1000, 0);
+
+        let simulate = |deps: Deps, amount: u128, overlay: Option<Binary>| {
+            let msg = QueryMsg::SimulateTransfer {
+                token_address: Addr::unchecked("token_address"),
+                from: Some(Addr::unchecked("sender")),
+                to: Some(Addr::unchecked("receiver")),
+                amount: Some(Uint128::new(amount)),
+                overlay,
+                decimals: None,
+            };
+            let res: utils::SimulatedTransferResponse =
+                from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        // Each transfer of 60 is fine on its own, but not both to the same holder
+        assert!(check(deps.as_ref(), Some("sender"), 60));
+        let first = simulate(deps.as_ref(), 60, None);
+        assert!(first.compliant);
+        let second = simulate(deps.as_ref(), 60, Some(first.overlay.clone()));
+        assert!(!second.compliant);
+
+        // The rejected transfer left no effects: 40 more reaches exactly 10%
+        assert_eq!(second.overlay, first.overlay);
+        let third = simulate(deps.as_ref(), 40, Some(second.overlay));
+        assert!(third.compliant);
+    }
+
+    #[test]
+    fn same_share_across_decimals() {
+        for decimals in [6u8, 18] {
+            let mut deps = mock_dependencies();
+            setup_contract(&mut deps);
+            let unit = 10u128.pow(decimals.into());
+
+            // Receiver holds 50 of 1000 tokens: 50 more reach exactly 10%
+            mock_token(&mut deps, decimals, 1000 * unit, 50 * unit);
+            let check =
+                |amount| check_with_decimals(deps.as_ref(), Some(decimals), Some("sender"), amount);
+            assert!(check(50 * unit).unwrap());
+            assert!(!check(50 * unit + 1).unwrap());
+        }
+    }
+
+    #[test]
+    fn mismatched_decimals_are_rejected() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        // The registry expects 6 decimals, but the token has 18
+        mock_token(&mut deps, 18, 1000 * 10u128.pow(18), 0);
+        let err = check_with_decimals(deps.as_ref(), Some(6), Some("sender"), 1).unwrap_err();
+        assert!(err.to_string().contains("has 18 decimals"));
+        assert!(check_with_decimals(deps.as_ref(), Some(18), Some("sender"), 1).unwrap());
+    }
+}
@@ -0,0 +1,65 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetConcentrationLimit {
+        token_address: Addr,
+        limit: ConcentrationLimit,
+    },
+    RemoveConcentrationLimit {
+        token_address: Addr,
+    },
+}
+
+// Extends the shared compliance module query with concentration specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    #[returns(Option<ConcentrationLimit>)]
+    GetConcentrationLimit { token_address: Addr },
+    // See `utils::ModuleSimulationQueryMsg`, the overlay is a `ConcentrationOverlay`
+    #[returns(utils::SimulatedTransferResponse)]
+    SimulateTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: Option<Binary>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+}
+
+// Supply and balances as changed by earlier simulated transfers. Only the holders those
+// transfers touched are recorded, everyone else is read from the token
+#[cw_serde]
+#[derive(Default)]
+pub struct ConcentrationOverlay {
+    pub total_supply: Option<Uint128>,
+    pub balances: Vec<(Addr, Uint128)>,
+}
+
+#[cw_serde]
+pub struct ConcentrationLimit {
+    // Largest share of the total supply a single holder may own, e.g. 0.1 for 10%
+    pub max_share: Decimal,
+    // Below this total supply the limit is not enforced, so initial distribution
+    // of a freshly minted token is not blocked
+    pub bootstrap_supply: Uint128,
+}
@@ -0,0 +1,7 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+use super::msg::ConcentrationLimit;
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+pub const CONCENTRATION_LIMITS: Map<Addr, ConcentrationLimit> = Map::new("concentration_limits");
@@ -0,0 +1,758 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::max_holders::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::max_holders::ContractError;
+
+use super::state::{OWNER_ROLES_ADDRESS, REGISTRY_ADDRESS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:max_holders";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate max holders contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles and registry addresses
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    REGISTRY_ADDRESS.save(deps.storage, &msg.registry_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the max holders contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions.
+    // Transfers are reported by the compliance registry instead.
+    if matches!(msg, ExecuteMsg::Transferred { .. }) {
+        if info.sender != REGISTRY_ADDRESS.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+    } else {
+        execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+    }
+
+    match msg {
+        ExecuteMsg::SetMaxHolders { token_address, max } => {
+            execute::set_max_holders(deps, token_address, max)
+        }
+        ExecuteMsg::RemoveMaxHolders { token_address } => {
+            execute::remove_max_holders(deps, token_address)
+        }
+        ExecuteMsg::SetHolderCount {
+            token_address,
+            count,
+        } => execute::set_holder_count(deps, token_address, count),
+        ExecuteMsg::ImportState {
+            token_address,
+            state,
+        } => execute::import_state(deps, token_address, state),
+        ExecuteMsg::Transferred {
+            token_address,
+            from,
+            to,
+            amount,
+        } => execute::record_transfer(deps, token_address, from, to, amount),
+    }
+}
+
+pub mod execute {
+    use crate::modules::max_holders::state::{HoldersState, HOLDER_COUNT, MAX_HOLDERS};
+
+    use super::*;
+    use cosmwasm_std::{from_json, Addr, QueryRequest, Uint128, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Cap the number of accounts that may hold a token at once
+    pub fn set_max_holders(
+        deps: DepsMut,
+        token_address: Addr,
+        max: u64,
+    ) -> Result<Response, ContractError> {
+        if max == 0 {
+            return Err(ContractError::InvalidMaxHolders {});
+        }
+        MAX_HOLDERS.save(deps.storage, token_address.clone(), &max)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_max_holders")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("max", max.to_string()))
+    }
+
+    /// Remove the holder cap of a token, holders keep being counted
+    pub fn remove_max_holders(
+        deps: DepsMut,
+        token_address: Addr,
+    ) -> Result<Response, ContractError> {
+        MAX_HOLDERS.remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_max_holders")
+            .add_attribute("token_address", token_address.to_string()))
+    }
+
+    /// Seed the number of accounts holding a token, for holders that got their balance
+    /// before the module was bound to it
+    pub fn set_holder_count(
+        deps: DepsMut,
+        token_address: Addr,
+        count: u64,
+    ) -> Result<Response, ContractError> {
+        HOLDER_COUNT.save(deps.storage, token_address.clone(), &count)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_holder_count")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("holder_count", count.to_string()))
+    }
+
+    /// Restore the cap and holder count exported by the module this one replaces
+    pub fn import_state(
+        mut deps: DepsMut,
+        token_address: Addr,
+        state: Binary,
+    ) -> Result<Response, ContractError> {
+        let state: HoldersState = from_json(&state)?;
+        match state.max_holders {
+            Some(max) => set_max_holders(deps.branch(), token_address.clone(), max)?,
+            None => remove_max_holders(deps.branch(), token_address.clone())?,
+        };
+        set_holder_count(deps, token_address, state.holder_count)
+    }
+
+    /// Count the holders a completed transfer added or removed, from the balances it left:
+    /// a recipient holding exactly the amount had no balance before, and a sender left
+    /// without a balance stops being a holder
+    pub fn record_transfer(
+        deps: DepsMut,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let response = Response::new()
+            .add_attribute("action", "record_transfer")
+            .add_attribute("token_address", token_address.to_string());
+        if amount.is_zero() || (from.is_some() && from == to) {
+            return Ok(response);
+        }
+
+        let added = match &to {
+            Some(to) => super::query::balance_of(deps.as_ref(), &token_address, to)? == amount,
+            None => false,
+        };
+        let removed = match &from {
+            Some(from) => super::query::balance_of(deps.as_ref(), &token_address, from)?.is_zero(),
+            None => false,
+        };
+        let count =
+            HOLDER_COUNT.update(deps.storage, token_address, |count| -> StdResult<u64> {
+                let count = count.unwrap_or_default() + u64::from(added);
+                Ok(count.saturating_sub(u64::from(removed)))
+            })?;
+
+        Ok(response.add_attribute("holder_count", count.to_string()))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to,
+            amount,
+            decimals: _,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+        )?),
+        QueryMsg::CanTransfer {
+            token_address,
+            from,
+            to,
+            amount,
+        } => to_json_binary(&query::can_transfer(deps, token_address, from, to, amount)?),
+        QueryMsg::GetMaxHolders { token_address } => {
+            to_json_binary(&query::get_max_holders(deps, token_address)?)
+        }
+        QueryMsg::GetHolderCount { token_address } => {
+            to_json_binary(&query::get_holder_count(deps, token_address)?)
+        }
+        QueryMsg::ExportState { token_address } => {
+            to_json_binary(&to_json_binary(&query::export_state(deps, token_address)?)?)
+        }
+        QueryMsg::SimulateTransfer {
+            token_address,
+            from,
+            to,
+            amount,
+            overlay,
+            decimals: _,
+        } => to_json_binary(&query::simulate_transfer(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+            overlay,
+        )?),
+    }
+}
+
+pub mod query {
+    use crate::modules::max_holders::{
+        msg::{CanTransferResponse, HoldersOverlay},
+        state::{HoldersState, HOLDER_COUNT, MAX_HOLDERS},
+    };
+
+    use super::*;
+    use cosmwasm_std::{from_json, Addr, Uint128};
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+    use utils::SimulatedTransferResponse;
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> StdResult<bool> {
+        let overlay = HoldersOverlay::default();
+        Ok(max_holders_violation(deps, &token_address, from, to, amount, &overlay)?.is_none())
+    }
+
+    /// Check whether the transfer keeps the token within its holder cap, with the reason
+    /// if not
+    pub fn can_transfer(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> StdResult<CanTransferResponse> {
+        let overlay = HoldersOverlay::default();
+        let violation = max_holders_violation(deps, &token_address, from, to, amount, &overlay)?;
+        Ok(CanTransferResponse {
+            can_transfer: violation.is_none(),
+            reason: violation.map(|e| e.to_string()),
+        })
+    }
+
+    /// Check a transfer against the holder count and balances left by earlier simulated
+    /// transfers, recording its effects as the transfer hook would when it is compliant
+    pub fn simulate_transfer(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: Option<Binary>,
+    ) -> StdResult<SimulatedTransferResponse> {
+        let mut overlay: HoldersOverlay = match overlay {
+            Some(overlay) => from_json(overlay)?,
+            None => HoldersOverlay::default(),
+        };
+        let compliant = max_holders_violation(
+            deps,
+            &token_address,
+            from.clone(),
+            to.clone(),
+            amount,
+            &overlay,
+        )?
+        .is_none();
+
+        // A rejected transfer would not execute, so it leaves no effects behind. Without a
+        // cap nothing reads the effects, so they are not tracked.
+        let tracked = compliant && MAX_HOLDERS.has(deps.storage, token_address.clone());
+        if let (true, Some(amount)) = (tracked, amount) {
+            if !amount.is_zero() && (from.is_none() || from != to) {
+                let mut count = holder_count_of(deps, &token_address, &overlay)?;
+                if let Some(from) = &from {
+                    let balance = simulated_balance(deps, &token_address, from, &overlay)?;
+                    let balance = balance.saturating_sub(amount);
+                    if balance.is_zero() {
+                        count = count.saturating_sub(1);
+                    }
+                    set_balance(&mut overlay, from, balance);
+                }
+                if let Some(to) = &to {
+                    let balance = simulated_balance(deps, &token_address, to, &overlay)?;
+                    if balance.is_zero() {
+                        count += 1;
+                    }
+                    set_balance(&mut overlay, to, balance.checked_add(amount)?);
+                }
+                overlay.holder_count = Some(count);
+            }
+        }
+
+        Ok(SimulatedTransferResponse {
+            compliant,
+            overlay: to_json_binary(&overlay)?,
+        })
+    }
+
+    /// Get the holder cap configured for a token, if any
+    pub fn get_max_holders(deps: Deps, token_address: Addr) -> StdResult<Option<u64>> {
+        MAX_HOLDERS.may_load(deps.storage, token_address)
+    }
+
+    /// Get the number of accounts currently holding a token
+    pub fn get_holder_count(deps: Deps, token_address: Addr) -> StdResult<u64> {
+        Ok(HOLDER_COUNT
+            .may_load(deps.storage, token_address)?
+            .unwrap_or_default())
+    }
+
+    /// Get the cap and holder count of a token, to carry them over to a replacement module
+    pub fn export_state(deps: Deps, token_address: Addr) -> StdResult<HoldersState> {
+        Ok(HoldersState {
+            max_holders: get_max_holders(deps, token_address.clone())?,
+            holder_count: get_holder_count(deps, token_address)?,
+        })
+    }
+
+    pub(super) fn balance_of(
+        deps: Deps,
+        token_address: &Addr,
+        holder: &Addr,
+    ) -> StdResult<Uint128> {
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            token_address,
+            &Cw20QueryMsg::Balance {
+                address: holder.to_string(),
+            },
+        )?;
+        Ok(balance.balance)
+    }
+
+    fn simulated_balance(
+        deps: Deps,
+        token_address: &Addr,
+        holder: &Addr,
+        overlay: &HoldersOverlay,
+    ) -> StdResult<Uint128> {
+        match overlay.balances.iter().find(|(addr, _)| addr == holder) {
+            Some((_, balance)) => Ok(*balance),
+            None => balance_of(deps, token_address, holder),
+        }
+    }
+
+    fn set_balance(overlay: &mut HoldersOverlay, holder: &Addr, balance: Uint128) {
+        match overlay.balances.iter_mut().find(|(addr, _)| addr == holder) {
+            Some((_, existing)) => *existing = balance,
+            None => overlay.balances.push((holder.clone(), balance)),
+        }
+    }
+
+    fn holder_count_of(
+        deps: Deps,
+        token_address: &Addr,
+        overlay: &HoldersOverlay,
+    ) -> StdResult<u64> {
+        match overlay.holder_count {
+            Some(count) => Ok(count),
+            None => get_holder_count(deps, token_address.clone()),
+        }
+    }
+
+    fn max_holders_violation(
+        deps: Deps,
+        token_address: &Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: &HoldersOverlay,
+    ) -> StdResult<Option<ContractError>> {
+        let Some(max) = MAX_HOLDERS.may_load(deps.storage, token_address.clone())? else {
+            return Ok(None);
+        };
+        // Burns and transfers to an existing holder add no holder
+        let Some(to) = to else {
+            return Ok(None);
+        };
+        if from.as_ref() == Some(&to)
+            || !simulated_balance(deps, token_address, &to, overlay)?.is_zero()
+        {
+            return Ok(None);
+        }
+
+        let mut count = holder_count_of(deps, token_address, overlay)?;
+        // A sender moving its whole balance frees its place for the recipient
+        if let (Some(from), Some(amount)) = (from, amount) {
+            if simulated_balance(deps, token_address, &from, overlay)? <= amount {
+                count = count.saturating_sub(1);
+            }
+        }
+        if count < max {
+            Ok(None)
+        } else {
+            Ok(Some(ContractError::HolderLimitReached { count, max }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::max_holders::msg::CanTransferResponse;
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, OwnedDeps, SystemResult, Uint128, WasmQuery,
+    };
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+    use roles::owner_roles::msg::OwnerRole;
+
+    // Mock the owner roles contract and a token where only the listed accounts hold a balance
+    fn mock_contracts(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        balances: &[(&str, u128)],
+    ) {
+        let balances: Vec<(String, u128)> = balances
+            .iter()
+            .map(|(holder, balance)| (holder.to_string(), *balance))
+            .collect();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "owner_roles_contract" => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                Cw20QueryMsg::Balance { address } => {
+                    let balance = balances
+                        .iter()
+                        .find(|(holder, _)| *holder == address)
+                        .map_or(0, |(_, balance)| *balance);
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_json_binary(&BalanceResponse {
+                            balance: Uint128::new(balance),
+                        })
+                        .unwrap(),
+                    ))
+                }
+                _ => panic!("Unexpected token query"),
+            },
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+            registry_address: Addr::unchecked("registry"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        mock_contracts(deps, &[]);
+    }
+
+    // Report a transfer the mocked balances already reflect
+    fn transferred(
+        deps: DepsMut,
+        sender: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        amount: u128,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Transferred {
+            token_address: Addr::unchecked("token_address"),
+            from: from.map(Addr::unchecked),
+            to: to.map(Addr::unchecked),
+            amount: Uint128::new(amount),
+        };
+        execute(
+            deps,
+            mock_env(),
+            message_info(&Addr::unchecked(sender), &[]),
+            msg,
+        )
+    }
+
+    fn check(deps: Deps, from: Option<&str>, to: &str, amount: u128) -> bool {
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: from.map(Addr::unchecked),
+            to: Some(Addr::unchecked(to)),
+            amount: Some(Uint128::new(amount)),
+            decimals: None,
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    fn holder_count(deps: Deps) -> u64 {
+        let msg = QueryMsg::GetHolderCount {
+            token_address: Addr::unchecked("token_address"),
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn set_and_remove_max_holders() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let token_address = Addr::unchecked("token_address");
+
+        let msg = ExecuteMsg::SetMaxHolders {
+            token_address: token_address.clone(),
+            max: 0,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMaxHolders {}));
+
+        let msg = ExecuteMsg::SetMaxHolders {
+            token_address: token_address.clone(),
+            max: 2,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::GetMaxHolders {
+            token_address: token_address.clone(),
+        };
+        let res: Option<u64> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, Some(2));
+
+        let msg = ExecuteMsg::RemoveMaxHolders {
+            token_address: token_address.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = QueryMsg::GetMaxHolders { token_address };
+        let res: Option<u64> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn new_holders_are_blocked_at_the_cap() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        // Alice held the token before the module was bound to it
+        mock_contracts(&mut deps, &[("alice", 100)]);
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for msg in [
+            ExecuteMsg::SetMaxHolders {
+                token_address: Addr::unchecked("token_address"),
+                max: 2,
+            },
+            ExecuteMsg::SetHolderCount {
+                token_address: Addr::unchecked("token_address"),
+                count: 1,
+            },
+        ] {
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        // Minting to a new holder reaches the cap
+        assert!(check(deps.as_ref(), None, "bob", 100));
+        mock_contracts(&mut deps, &[("alice", 100), ("bob", 100)]);
+        let err = transferred(deps.as_mut(), "token_address", None, Some("bob"), 100).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        transferred(deps.as_mut(), "registry", None, Some("bob"), 100).unwrap();
+        assert_eq!(holder_count(deps.as_ref()), 2);
+
+        // A third holder is rejected, while existing holders still trade among themselves
+        assert!(!check(deps.as_ref(), None, "carol", 100));
+        assert!(!check(deps.as_ref(), Some("alice"), "carol", 40));
+        assert!(check(deps.as_ref(), Some("alice"), "bob", 40));
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("alice")),
+            to: Some(Addr::unchecked("carol")),
+            amount: Some(Uint128::new(40)),
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!res.can_transfer);
+        assert_eq!(
+            res.reason,
+            Some("HolderLimitReached: the token already has 2 of at most 2 holders".to_string())
+        );
+
+        // Moving a whole balance to the new holder keeps the count within the cap
+        assert!(check(deps.as_ref(), Some("alice"), "carol", 100));
+
+        // Emptying a balance frees a place for a new holder
+        mock_contracts(&mut deps, &[("bob", 200)]);
+        transferred(deps.as_mut(), "registry", Some("alice"), Some("bob"), 100).unwrap();
+        assert_eq!(holder_count(deps.as_ref()), 1);
+        assert!(check(deps.as_ref(), Some("bob"), "carol", 50));
+    }
+
+    #[test]
+    fn simulated_transfers_count_new_holders() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        mock_contracts(&mut deps, &[("alice", 100)]);
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for msg in [
+            ExecuteMsg::SetMaxHolders {
+                token_address: Addr::unchecked("token_address"),
+                max: 2,
+            },
+            ExecuteMsg::SetHolderCount {
+                token_address: Addr::unchecked("token_address"),
+                count: 1,
+            },
+        ] {
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let simulate = |deps: Deps, from: Option<&str>, to: &str, amount: u128, overlay| {
+            let msg = QueryMsg::SimulateTransfer {
+                token_address: Addr::unchecked("token_address"),
+                from: from.map(Addr::unchecked),
+                to: Some(Addr::unchecked(to)),
+                amount: Some(Uint128::new(amount)),
+                overlay,
+                decimals: None,
+            };
+            let res: utils::SimulatedTransferResponse =
+                from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        // Bob and carol each fit under the cap on their own, but not both
+        assert!(check(deps.as_ref(), Some("alice"), "carol", 40));
+        let first = simulate(deps.as_ref(), None, "bob", 100, None);
+        assert!(first.compliant);
+        let second = simulate(
+            deps.as_ref(),
+            Some("alice"),
+            "carol",
+            40,
+            Some(first.overlay),
+        );
+        assert!(!second.compliant);
+
+        // Once alice moves her whole balance to bob, carol takes her place
+        let third = simulate(
+            deps.as_ref(),
+            Some("alice"),
+            "bob",
+            100,
+            Some(second.overlay),
+        );
+        assert!(third.compliant);
+        let fourth = simulate(deps.as_ref(), Some("bob"), "carol", 50, Some(third.overlay));
+        assert!(fourth.compliant);
+        let fifth = simulate(
+            deps.as_ref(),
+            Some("bob"),
+            "alice",
+            50,
+            Some(fourth.overlay),
+        );
+        assert!(!fifth.compliant);
+    }
+
+    #[test]
+    fn state_carries_over_to_a_replacement() {
+        let mut old = mock_dependencies();
+        setup_contract(&mut old);
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for msg in [
+            ExecuteMsg::SetMaxHolders {
+                token_address: Addr::unchecked("token_address"),
+                max: 5,
+            },
+            ExecuteMsg::SetHolderCount {
+                token_address: Addr::unchecked("token_address"),
+                count: 3,
+            },
+        ] {
+            execute(old.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let msg = QueryMsg::ExportState {
+            token_address: Addr::unchecked("token_address"),
+        };
+        let state: Binary = from_json(query(old.as_ref(), mock_env(), msg).unwrap()).unwrap();
+
+        let mut new = mock_dependencies();
+        setup_contract(&mut new);
+        let msg = ExecuteMsg::ImportState {
+            token_address: Addr::unchecked("token_address"),
+            state,
+        };
+        execute(new.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(holder_count(new.as_ref()), 3);
+        let msg = QueryMsg::GetMaxHolders {
+            token_address: Addr::unchecked("token_address"),
+        };
+        let res: Option<u64> = from_json(query(new.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, Some(5));
+    }
+}
@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("InvalidMaxHolders: the cap must be positive")]
+    InvalidMaxHolders {},
+
+    #[error("HolderLimitReached: the token already has {count} of at most {max} holders")]
+    HolderLimitReached { count: u64, max: u64 },
+}
@@ -0,0 +1,95 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary, Uint128};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+    pub registry_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetMaxHolders {
+        token_address: Addr,
+        max: u64,
+    },
+    RemoveMaxHolders {
+        token_address: Addr,
+    },
+    // Seeds the number of accounts already holding the token when the module is bound to it
+    SetHolderCount {
+        token_address: Addr,
+        count: u64,
+    },
+    // Seeds the cap and count from `ExportState` of a replaced module, see
+    // `utils::ModuleStateExecuteMsg`
+    ImportState {
+        token_address: Addr,
+        state: Binary,
+    },
+    // Counts the holders a completed transfer added or removed, see
+    // `utils::ModuleHookExecuteMsg`
+    Transferred {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    },
+}
+
+// Extends the shared compliance module query with holder cap specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    },
+    #[returns(Option<u64>)]
+    GetMaxHolders { token_address: Addr },
+    #[returns(u64)]
+    GetHolderCount { token_address: Addr },
+    // The cap and holder count of the token as JSON, see `utils::ModuleStateQueryMsg`
+    #[returns(Binary)]
+    ExportState { token_address: Addr },
+    // See `utils::ModuleSimulationQueryMsg`, the overlay is a `HoldersOverlay`
+    #[returns(utils::SimulatedTransferResponse)]
+    SimulateTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: Option<Binary>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+}
+
+// Holder count and balances as changed by earlier simulated transfers. Only the accounts
+// those transfers touched are recorded, everyone else is read from the token
+#[cw_serde]
+#[derive(Default)]
+pub struct HoldersOverlay {
+    pub holder_count: Option<u64>,
+    pub balances: Vec<(Addr, Uint128)>,
+}
+
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub can_transfer: bool,
+    // Why the transfer is rejected, if it is
+    pub reason: Option<String>,
+}
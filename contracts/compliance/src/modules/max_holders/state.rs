@@ -0,0 +1,19 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+/// The compliance registry, the only sender of transfer hooks
+pub const REGISTRY_ADDRESS: Item<Addr> = Item::new("registry_address");
+// Token address -> most accounts that may hold the token at once
+pub const MAX_HOLDERS: Map<Addr, u64> = Map::new("max_holders");
+// Token address -> accounts with a positive balance, seeded when the module is set up and
+// kept up to date by the registry's transfer hooks
+pub const HOLDER_COUNT: Map<Addr, u64> = Map::new("holder_count");
+
+/// A token's cap and holder count, as carried over to a replacement module
+#[cw_serde]
+pub struct HoldersState {
+    pub max_holders: Option<u64>,
+    pub holder_count: u64,
+}
@@ -1 +1,8 @@
+pub mod accredited_investor;
 pub mod country_restriction;
+pub mod lockup;
+pub mod lot_size;
+pub mod max_balance;
+pub mod max_concentration;
+pub mod max_holders;
+pub mod transfer_count;
@@ -0,0 +1,613 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw2::set_contract_version;
+use roles::owner_roles::msg::OwnerRole;
+
+use crate::modules::transfer_count::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::modules::transfer_count::ContractError;
+
+use super::state::{OWNER_ROLES_ADDRESS, REGISTRY_ADDRESS};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:compliance_modules:transfer_count";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Instantiate transfer count contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `_env` - The environment info (unused)
+/// * `info` - Message info
+/// * `msg` - Instantiate message containing the owner roles and registry addresses
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    REGISTRY_ADDRESS.save(deps.storage, &msg.registry_address)?;
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender))
+}
+
+/// Execute function for the transfer count contract
+///
+/// # Arguments
+///
+/// * `deps` - Mutable dependencies
+/// * `env` - The environment info
+/// * `info` - Message info
+/// * `msg` - Execute message
+///
+/// # Returns
+///
+/// * `Result<Response, ContractError>`
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // checking with the owner role contract to ensure only authorized personnel
+    // with a role of ComplianceManager are allowed to execute the functions.
+    // Transfers are reported by the compliance registry instead.
+    if matches!(msg, ExecuteMsg::Transferred { .. }) {
+        if info.sender != REGISTRY_ADDRESS.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {});
+        }
+    } else {
+        execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+    }
+
+    match msg {
+        ExecuteMsg::SetTransferLimit {
+            token_address,
+            max_transfers,
+            window_days,
+        } => execute::set_transfer_limit(deps, token_address, max_transfers, window_days),
+        ExecuteMsg::RemoveTransferLimit { token_address } => {
+            execute::remove_transfer_limit(deps, token_address)
+        }
+        ExecuteMsg::Transferred {
+            token_address,
+            from,
+            to: _,
+            amount: _,
+        } => execute::record_transfer(deps, env, token_address, from),
+    }
+}
+
+pub mod execute {
+    use crate::modules::transfer_count::state::{
+        DayCount, TransferLimit, MAX_WINDOW_DAYS, TRANSFER_COUNTS, TRANSFER_LIMITS,
+    };
+
+    use super::*;
+    use cosmwasm_std::{Addr, QueryRequest, WasmQuery};
+    use roles::owner_roles::{msg::OwnerRole, QueryMsg};
+
+    pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.storage)?;
+        let msg = QueryMsg::IsOwner { role, owner };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: owner_roles.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        let has_role: bool = deps.querier.query(&query)?;
+        if !has_role {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    /// Cap the outgoing transfers each sender of a token may make within a window of days
+    pub fn set_transfer_limit(
+        deps: DepsMut,
+        token_address: Addr,
+        max_transfers: u32,
+        window_days: u32,
+    ) -> Result<Response, ContractError> {
+        if max_transfers == 0 {
+            return Err(ContractError::InvalidTransferLimit {
+                reason: "max_transfers must be positive".to_string(),
+            });
+        }
+        if window_days == 0 || window_days > MAX_WINDOW_DAYS {
+            return Err(ContractError::InvalidTransferLimit {
+                reason: format!("window_days must be between 1 and {MAX_WINDOW_DAYS}"),
+            });
+        }
+        TRANSFER_LIMITS.save(
+            deps.storage,
+            token_address.clone(),
+            &TransferLimit {
+                max_transfers,
+                window_days,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_transfer_limit")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("max_transfers", max_transfers.to_string())
+            .add_attribute("window_days", window_days.to_string()))
+    }
+
+    /// Remove the transfer cap of a token
+    pub fn remove_transfer_limit(
+        deps: DepsMut,
+        token_address: Addr,
+    ) -> Result<Response, ContractError> {
+        TRANSFER_LIMITS.remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_transfer_limit")
+            .add_attribute("token_address", token_address.to_string()))
+    }
+
+    /// Count an outgoing transfer of `from` in today's bucket, dropping buckets that left
+    /// the window. Transfers of tokens without a limit and mints are not counted.
+    pub fn record_transfer(
+        deps: DepsMut,
+        env: Env,
+        token_address: Addr,
+        from: Option<Addr>,
+    ) -> Result<Response, ContractError> {
+        let response = Response::new()
+            .add_attribute("action", "record_transfer")
+            .add_attribute("token_address", token_address.to_string());
+        let Some(from) = from else {
+            return Ok(response);
+        };
+        let Some(limit) = TRANSFER_LIMITS.may_load(deps.storage, token_address.clone())? else {
+            return Ok(response);
+        };
+
+        let today = super::current_day(&env);
+        let key = (token_address, from.clone());
+        let mut days: Vec<DayCount> = TRANSFER_COUNTS
+            .may_load(deps.storage, key.clone())?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|bucket| super::in_window(bucket.day, today, &limit))
+            .collect();
+        match days.last_mut() {
+            Some(bucket) if bucket.day == today => bucket.count += 1,
+            _ => days.push(DayCount {
+                day: today,
+                count: 1,
+            }),
+        }
+        TRANSFER_COUNTS.save(deps.storage, key, &days)?;
+
+        Ok(response.add_attribute("from", from.to_string()))
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::CheckTokenCompliance {
+            token_address,
+            from,
+            to,
+            amount,
+            decimals: _,
+        } => to_json_binary(&query::check_compliance(
+            deps,
+            env,
+            token_address,
+            from,
+            to,
+            amount,
+        )?),
+        QueryMsg::CanTransfer {
+            token_address,
+            from,
+        } => to_json_binary(&query::can_transfer(deps, env, token_address, from)?),
+        QueryMsg::GetTransferLimit { token_address } => {
+            to_json_binary(&query::get_transfer_limit(deps, token_address)?)
+        }
+        QueryMsg::GetTransferCount {
+            token_address,
+            address,
+        } => to_json_binary(&query::get_transfer_count(
+            deps,
+            env,
+            token_address,
+            address,
+        )?),
+        QueryMsg::SimulateTransfer {
+            token_address,
+            from,
+            to: _,
+            amount: _,
+            overlay,
+            decimals: _,
+        } => to_json_binary(&query::simulate_transfer(
+            deps,
+            env,
+            token_address,
+            from,
+            overlay,
+        )?),
+    }
+}
+
+pub mod query {
+    use crate::modules::transfer_count::{
+        msg::{CanTransferResponse, TransferCountOverlay},
+        state::{TransferLimit, TRANSFER_COUNTS, TRANSFER_LIMITS},
+    };
+
+    use super::*;
+    use cosmwasm_std::{from_json, Addr, Uint128};
+    use utils::SimulatedTransferResponse;
+
+    /// Check compliance for a token transfer
+    pub fn check_compliance(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        from: Option<Addr>,
+        _to: Option<Addr>,
+        _amount: Option<Uint128>,
+    ) -> StdResult<bool> {
+        let overlay = TransferCountOverlay::default();
+        Ok(transfer_count_violation(deps, &env, &token_address, from, &overlay)?.is_none())
+    }
+
+    /// Check whether `from` can make another transfer of the token, with the reason if not
+    pub fn can_transfer(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        from: Option<Addr>,
+    ) -> StdResult<CanTransferResponse> {
+        let overlay = TransferCountOverlay::default();
+        let violation = transfer_count_violation(deps, &env, &token_address, from, &overlay)?;
+        Ok(CanTransferResponse {
+            can_transfer: violation.is_none(),
+            reason: violation.map(|e| e.to_string()),
+        })
+    }
+
+    /// Check a transfer against the counts left by earlier simulated transfers, counting
+    /// it as the transfer hook would when it is compliant
+    pub fn simulate_transfer(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        from: Option<Addr>,
+        overlay: Option<Binary>,
+    ) -> StdResult<SimulatedTransferResponse> {
+        let mut overlay: TransferCountOverlay = match overlay {
+            Some(overlay) => from_json(overlay)?,
+            None => TransferCountOverlay::default(),
+        };
+        let compliant =
+            transfer_count_violation(deps, &env, &token_address, from.clone(), &overlay)?.is_none();
+
+        // A rejected transfer would not execute, so it leaves no effects behind
+        if let (true, Some(from)) = (compliant, from) {
+            if TRANSFER_LIMITS.has(deps.storage, token_address) {
+                match overlay.counts.iter_mut().find(|(addr, _)| *addr == from) {
+                    Some((_, count)) => *count += 1,
+                    None => overlay.counts.push((from, 1)),
+                }
+            }
+        }
+
+        Ok(SimulatedTransferResponse {
+            compliant,
+            overlay: to_json_binary(&overlay)?,
+        })
+    }
+
+    /// Get the transfer cap configured for a token, if any
+    pub fn get_transfer_limit(deps: Deps, token_address: Addr) -> StdResult<Option<TransferLimit>> {
+        TRANSFER_LIMITS.may_load(deps.storage, token_address)
+    }
+
+    /// Get the outgoing transfers of `address` within the token's current window
+    pub fn get_transfer_count(
+        deps: Deps,
+        env: Env,
+        token_address: Addr,
+        address: Addr,
+    ) -> StdResult<u32> {
+        let Some(limit) = TRANSFER_LIMITS.may_load(deps.storage, token_address.clone())? else {
+            return Ok(0);
+        };
+        count_in_window(deps, &env, token_address, address, &limit)
+    }
+
+    fn count_in_window(
+        deps: Deps,
+        env: &Env,
+        token_address: Addr,
+        address: Addr,
+        limit: &TransferLimit,
+    ) -> StdResult<u32> {
+        let today = super::current_day(env);
+        Ok(TRANSFER_COUNTS
+            .may_load(deps.storage, (token_address, address))?
+            .unwrap_or_default()
+            .iter()
+            .filter(|bucket| super::in_window(bucket.day, today, limit))
+            .map(|bucket| bucket.count)
+            .sum())
+    }
+
+    fn transfer_count_violation(
+        deps: Deps,
+        env: &Env,
+        token_address: &Addr,
+        from: Option<Addr>,
+        overlay: &TransferCountOverlay,
+    ) -> StdResult<Option<ContractError>> {
+        // Mints have no sender to count against
+        let Some(from) = from else {
+            return Ok(None);
+        };
+        let Some(limit) = TRANSFER_LIMITS.may_load(deps.storage, token_address.clone())? else {
+            return Ok(None);
+        };
+        let simulated = overlay
+            .counts
+            .iter()
+            .find(|(addr, _)| *addr == from)
+            .map_or(0, |(_, count)| *count);
+        let count = count_in_window(deps, env, token_address.clone(), from, &limit)? + simulated;
+        if count < limit.max_transfers {
+            Ok(None)
+        } else {
+            Ok(Some(ContractError::TransferCountExceeded {
+                count,
+                max: limit.max_transfers,
+                window_days: limit.window_days,
+            }))
+        }
+    }
+}
+
+fn current_day(env: &Env) -> u64 {
+    env.block.time.seconds() / super::state::SECONDS_PER_DAY
+}
+
+// Whether a bucket of `day` still falls in the window ending `today`
+fn in_window(day: u64, today: u64, limit: &super::state::TransferLimit) -> bool {
+    day + u64::from(limit.window_days) > today
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::transfer_count::{
+        msg::CanTransferResponse,
+        state::{TransferLimit, SECONDS_PER_DAY},
+    };
+
+    use super::*;
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{from_json, Addr, ContractResult, OwnedDeps, SystemResult, Uint128};
+    use roles::owner_roles::msg::OwnerRole;
+
+    fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+            registry_address: Addr::unchecked("registry"),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Mock the owner roles contract query
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { msg, .. } => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
+                            panic!("Unexpected role query")
+                        }
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn transferred(
+        deps: DepsMut,
+        env: Env,
+        sender: &str,
+        from: &str,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Transferred {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked(from)),
+            to: Some(Addr::unchecked("to")),
+            amount: Uint128::new(100),
+        };
+        execute(deps, env, message_info(&Addr::unchecked(sender), &[]), msg)
+    }
+
+    fn check(deps: Deps, env: Env, from: &str) -> bool {
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked(from)),
+            to: Some(Addr::unchecked("to")),
+            amount: Some(Uint128::new(100)),
+            decimals: None,
+        };
+        from_json(query(deps, env, msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn set_and_remove_transfer_limit() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let token_address = Addr::unchecked("token_address");
+
+        for (max_transfers, window_days) in [(0, 7), (3, 0), (3, 367)] {
+            let msg = ExecuteMsg::SetTransferLimit {
+                token_address: token_address.clone(),
+                max_transfers,
+                window_days,
+            };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidTransferLimit { .. }));
+        }
+
+        let msg = ExecuteMsg::SetTransferLimit {
+            token_address: token_address.clone(),
+            max_transfers: 3,
+            window_days: 7,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::GetTransferLimit {
+            token_address: token_address.clone(),
+        };
+        let res: Option<TransferLimit> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            Some(TransferLimit {
+                max_transfers: 3,
+                window_days: 7
+            })
+        );
+
+        let msg = ExecuteMsg::RemoveTransferLimit {
+            token_address: token_address.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = QueryMsg::GetTransferLimit { token_address };
+        let res: Option<TransferLimit> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(res, None);
+    }
+
+    #[test]
+    fn transfer_count_resets_after_window() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let msg = ExecuteMsg::SetTransferLimit {
+            token_address: Addr::unchecked("token_address"),
+            max_transfers: 2,
+            window_days: 3,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // Only the registry may report transfers
+        let err = transferred(deps.as_mut(), mock_env(), "alice", "alice").unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // Spread over two days of the window, the cap is reached on the second transfer
+        let mut env = mock_env();
+        transferred(deps.as_mut(), env.clone(), "registry", "alice").unwrap();
+        assert!(check(deps.as_ref(), env.clone(), "alice"));
+        env.block.time = env.block.time.plus_seconds(SECONDS_PER_DAY);
+        transferred(deps.as_mut(), env.clone(), "registry", "alice").unwrap();
+        assert!(!check(deps.as_ref(), env.clone(), "alice"));
+
+        // Other senders and mints are unaffected
+        assert!(check(deps.as_ref(), env.clone(), "bob"));
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            from: None,
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(res.can_transfer);
+
+        let msg = QueryMsg::CanTransfer {
+            token_address: Addr::unchecked("token_address"),
+            from: Some(Addr::unchecked("alice")),
+        };
+        let res: CanTransferResponse =
+            from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(!res.can_transfer);
+        assert_eq!(
+            res.reason,
+            Some("TransferCountExceeded: 2 of 2 transfers made in the last 3 days".to_string())
+        );
+
+        // Once the first day leaves the window one transfer is available again
+        env.block.time = env.block.time.plus_seconds(2 * SECONDS_PER_DAY);
+        assert!(check(deps.as_ref(), env.clone(), "alice"));
+        transferred(deps.as_mut(), env.clone(), "registry", "alice").unwrap();
+        assert!(!check(deps.as_ref(), env.clone(), "alice"));
+
+        // After a full window without transfers the count is back to zero
+        env.block.time = env.block.time.plus_seconds(3 * SECONDS_PER_DAY);
+        let msg = QueryMsg::GetTransferCount {
+            token_address: Addr::unchecked("token_address"),
+            address: Addr::unchecked("alice"),
+        };
+        let count: u32 = from_json(query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(count, 0);
+        assert!(check(deps.as_ref(), env, "alice"));
+    }
+
+    #[test]
+    fn simulated_transfers_count_towards_the_cap() {
+        let mut deps = mock_dependencies();
+        setup_contract(&mut deps);
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let msg = ExecuteMsg::SetTransferLimit {
+            token_address: Addr::unchecked("token_address"),
+            max_transfers: 2,
+            window_days: 1,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        transferred(deps.as_mut(), mock_env(), "registry", "alice").unwrap();
+
+        let simulate = |deps: Deps, from: &str, overlay: Option<Binary>| {
+            let msg = QueryMsg::SimulateTransfer {
+                token_address: Addr::unchecked("token_address"),
+                from: Some(Addr::unchecked(from)),
+                to: Some(Addr::unchecked("to")),
+                amount: Some(Uint128::new(100)),
+                overlay,
+                decimals: None,
+            };
+            let res: utils::SimulatedTransferResponse =
+                from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            res
+        };
+
+        // Alice has one transfer left: the first simulated one uses it up
+        let first = simulate(deps.as_ref(), "alice", None);
+        assert!(first.compliant);
+        let second = simulate(deps.as_ref(), "alice", Some(first.overlay.clone()));
+        assert!(!second.compliant);
+        assert_eq!(second.overlay, first.overlay);
+
+        // Other senders keep their own count
+        let third = simulate(deps.as_ref(), "bob", Some(second.overlay));
+        assert!(third.compliant);
+        assert!(!simulate(deps.as_ref(), "alice", Some(third.overlay)).compliant);
+    }
+}
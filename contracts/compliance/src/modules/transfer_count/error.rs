@@ -0,0 +1,23 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("InvalidTransferLimit: {reason}")]
+    InvalidTransferLimit { reason: String },
+
+    #[error(
+        "TransferCountExceeded: {count} of {max} transfers made in the last {window_days} days"
+    )]
+    TransferCountExceeded {
+        count: u32,
+        max: u32,
+        window_days: u32,
+    },
+}
@@ -0,0 +1,81 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary, Uint128};
+
+use crate::modules::transfer_count::state::TransferLimit;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub owner_roles_address: Addr,
+    pub registry_address: Addr,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    SetTransferLimit {
+        token_address: Addr,
+        max_transfers: u32,
+        window_days: u32,
+    },
+    RemoveTransferLimit {
+        token_address: Addr,
+    },
+    // Counts a completed transfer, see `utils::ModuleHookExecuteMsg`
+    Transferred {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    },
+}
+
+// Extends the shared compliance module query with transfer count specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        // Set by the registry, see `utils::QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+    #[returns(CanTransferResponse)]
+    CanTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+    },
+    #[returns(Option<TransferLimit>)]
+    GetTransferLimit { token_address: Addr },
+    // Outgoing transfers of `address` within the token's current window
+    #[returns(u32)]
+    GetTransferCount { token_address: Addr, address: Addr },
+    // See `utils::ModuleSimulationQueryMsg`, the overlay is a `TransferCountOverlay`
+    #[returns(utils::SimulatedTransferResponse)]
+    SimulateTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: Option<Binary>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+}
+
+// Outgoing transfers earlier simulated transfers added to each sender's count. Only the
+// senders of those transfers are recorded
+#[cw_serde]
+#[derive(Default)]
+pub struct TransferCountOverlay {
+    pub counts: Vec<(Addr, u32)>,
+}
+
+#[cw_serde]
+pub struct CanTransferResponse {
+    pub can_transfer: bool,
+    // Why the transfer is rejected, if it is
+    pub reason: Option<String>,
+}
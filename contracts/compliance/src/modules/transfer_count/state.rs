@@ -0,0 +1,31 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+/// Length of the buckets transfers are counted in
+pub const SECONDS_PER_DAY: u64 = 86_400;
+/// Longest window a limit may span, bounding the buckets kept per sender
+pub const MAX_WINDOW_DAYS: u32 = 366;
+
+pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+/// The compliance registry, the only sender of transfer hooks
+pub const REGISTRY_ADDRESS: Item<Addr> = Item::new("registry_address");
+// Token address -> cap on outgoing transfers per sender
+pub const TRANSFER_LIMITS: Map<Addr, TransferLimit> = Map::new("transfer_limits");
+// (Token address, sender) -> outgoing transfers per day within the token's window, oldest first
+pub const TRANSFER_COUNTS: Map<(Addr, Addr), Vec<DayCount>> = Map::new("transfer_counts");
+
+#[cw_serde]
+pub struct TransferLimit {
+    /// Most outgoing transfers a sender may make within the window
+    pub max_transfers: u32,
+    /// Number of days, including the current one, the window spans
+    pub window_days: u32,
+}
+
+#[cw_serde]
+pub struct DayCount {
+    /// Days since the Unix epoch
+    pub day: u64,
+    pub count: u32,
+}
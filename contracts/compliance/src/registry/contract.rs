@@ -5,9 +5,11 @@ use cw2::set_contract_version;
 use roles::owner_roles::msg::OwnerRole;
 
 use crate::registry::error::ContractError;
-use crate::registry::msg::{ExecuteMsg, InstantiateMsg};
-use crate::registry::state::OWNER_ROLES_ADDRESS;
-use utils::QueryMsg;
+use crate::registry::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::registry::state::{
+    CLAIM_TOPICS_REGISTRY, DEFAULT_MAX_MODULES, FROZEN, IDENTITIES, MAX_MODULES,
+    OWNER_ROLES_ADDRESS, TOKEN_DECIMALS, TRUSTED_ISSUERS_REGISTRY,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:compliance";
@@ -34,6 +36,18 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    MAX_MODULES.save(
+        deps.storage,
+        &msg.max_modules.unwrap_or(DEFAULT_MAX_MODULES),
+    )?;
+    if let Some(registry) = msg.claim_topics_registry {
+        let registry = deps.api.addr_validate(registry.as_str())?;
+        CLAIM_TOPICS_REGISTRY.save(deps.storage, &registry)?;
+    }
+    if let Some(registry) = msg.trusted_issuers_registry {
+        let registry = deps.api.addr_validate(registry.as_str())?;
+        TRUSTED_ISSUERS_REGISTRY.save(deps.storage, &registry)?;
+    }
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", info.sender))
@@ -59,8 +73,19 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     // checking with the owner role contract to ensure only authorized personnel
-    // with a role of ComplianceManager are allowed to execute the functions
-    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ComplianceManager)?;
+    // with a role of ComplianceManager are allowed to execute the functions. Token
+    // contracts report their own transfers and are identified by the sender instead,
+    // and anyone may record a compliance decision.
+    if !matches!(
+        msg,
+        ExecuteMsg::Transferred { .. } | ExecuteMsg::PreCheckTransfer { .. }
+    ) {
+        execute::check_role(
+            deps.as_ref(),
+            info.sender.clone(),
+            OwnerRole::ComplianceManager,
+        )?;
+    }
 
     match msg {
         ExecuteMsg::AddComplianceModule {
@@ -77,14 +102,66 @@ pub fn execute(
             module_address,
             active,
         } => execute::update_compliance_module(deps, token_address, module_address, active),
+        ExecuteMsg::SetMaxModules { max_modules } => execute::set_max_modules(deps, max_modules),
+        ExecuteMsg::ReplaceModule {
+            token_address,
+            old,
+            new,
+            carry_state,
+        } => execute::replace_module(deps, token_address, old, new, carry_state),
+        ExecuteMsg::SetModuleFailurePolicy { policy } => {
+            execute::set_module_failure_policy(deps, policy)
+        }
+        ExecuteMsg::SetTokenDecimals {
+            token_address,
+            decimals,
+        } => execute::set_token_decimals(deps, token_address, decimals),
+        ExecuteMsg::SetModuleTransferHook {
+            token_address,
+            module_address,
+            enabled,
+        } => execute::set_module_transfer_hook(deps, token_address, module_address, enabled),
+        ExecuteMsg::RegisterIdentity {
+            user_addr,
+            identity,
+            country,
+        } => execute::register_identity(deps, user_addr, identity, country),
+        ExecuteMsg::DeleteIdentity { user_addr } => execute::delete_identity(deps, user_addr),
+        ExecuteMsg::SetCountryAllowed { country, allowed } => {
+            execute::set_country_allowed(deps, country, allowed)
+        }
+        ExecuteMsg::RegisterIdentitiesBatch { entries } => {
+            execute::register_identities_batch(deps, entries)
+        }
+        ExecuteMsg::Pause {} => execute::set_paused(deps, true),
+        ExecuteMsg::Unpause {} => execute::set_paused(deps, false),
+        ExecuteMsg::SetAddressFrozen { addr, frozen } => {
+            execute::set_address_frozen(deps, addr, frozen)
+        }
+        ExecuteMsg::Transferred { from, to, amount } => {
+            execute::transferred(deps.as_ref(), info.sender, from, to, amount)
+        }
+        ExecuteMsg::PreCheckTransfer {
+            token_address,
+            from,
+            to,
+            amount,
+        } => execute::pre_check_transfer(deps.as_ref(), token_address, from, to, amount),
     }
 }
 
 pub mod execute {
-    use crate::registry::{msg::ComplianceModule, state::TOKEN_COMPLIANCE_MODULES};
+    use crate::registry::{
+        msg::{ComplianceModule, IdentityInfo, ModuleFailurePolicy},
+        state::{
+            ALLOWED_COUNTRIES, COUNTRY_RESTRICTION, IDENTITIES, MAX_IDENTITIES_PER_BATCH,
+            MAX_TOKEN_DECIMALS, MODULE_FAILURE_POLICY, PAUSED, TOKEN_COMPLIANCE_MODULES,
+            TOKEN_DECIMALS,
+        },
+    };
 
     use super::*;
-    use cosmwasm_std::{to_json_binary, Addr, QueryRequest, WasmQuery};
+    use cosmwasm_std::{to_json_binary, Addr, Event, QueryRequest, Uint128, WasmMsg, WasmQuery};
     use roles::owner_roles::{msg::OwnerRole, QueryMsg};
 
     pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
@@ -95,7 +172,14 @@ pub mod execute {
             contract_addr: owner_roles.to_string(),
             msg: to_json_binary(&msg)?,
         });
-        let has_role: bool = deps.querier.query(&query)?;
+        let has_role: bool =
+            deps.querier
+                .query(&query)
+                .map_err(|e| ContractError::CrossContractQueryFailed {
+                    contract: owner_roles.to_string(),
+                    query: "IsOwner".to_string(),
+                    reason: e.to_string(),
+                })?;
         if !has_role {
             return Err(ContractError::Unauthorized {});
         }
@@ -103,13 +187,14 @@ pub mod execute {
     }
 
     /// Add a new compliance module for a token
-
     pub fn add_compliance_module(
         deps: DepsMut,
         token_address: Addr,
         module_address: Addr,
         module_name: String,
     ) -> Result<Response, ContractError> {
+        ensure_module_capacity(deps.as_ref(), &token_address, &module_address)?;
+
         TOKEN_COMPLIANCE_MODULES.save(
             deps.storage,
             (token_address.clone(), module_address.clone()),
@@ -117,6 +202,7 @@ pub mod execute {
                 name: module_name.clone(),
                 active: true,
                 address: module_address.clone(),
+                transfer_hook: false,
             },
         )?;
 
@@ -128,7 +214,6 @@ pub mod execute {
     }
 
     /// Remove a compliance module for a token
-
     pub fn remove_compliance_module(
         deps: DepsMut,
         token_address: Addr,
@@ -146,13 +231,16 @@ pub mod execute {
     }
 
     /// Update the active status of a compliance module
-
     pub fn update_compliance_module(
         deps: DepsMut,
         token_address: Addr,
         module_address: Addr,
         active: bool,
     ) -> Result<Response, ContractError> {
+        if active {
+            ensure_module_capacity(deps.as_ref(), &token_address, &module_address)?;
+        }
+
         TOKEN_COMPLIANCE_MODULES.update(
             deps.storage,
             (token_address.clone(), module_address.clone()),
@@ -169,6 +257,335 @@ pub mod execute {
             .add_attribute("module_address", module_address.to_string())
             .add_attribute("is_active", active.to_string()))
     }
+
+    /// Update the maximum number of active modules evaluated per token
+    pub fn set_max_modules(deps: DepsMut, max_modules: u32) -> Result<Response, ContractError> {
+        MAX_MODULES.save(deps.storage, &max_modules)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_max_modules")
+            .add_attribute("max_modules", max_modules.to_string()))
+    }
+
+    /// Update how modules whose compliance query fails are treated
+    pub fn set_module_failure_policy(
+        deps: DepsMut,
+        policy: ModuleFailurePolicy,
+    ) -> Result<Response, ContractError> {
+        MODULE_FAILURE_POLICY.save(deps.storage, &policy)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_module_failure_policy")
+            .add_attribute("policy", format!("{policy:?}")))
+    }
+
+    /// Record or forget the decimals of a token's amounts
+    pub fn set_token_decimals(
+        deps: DepsMut,
+        token_address: Addr,
+        decimals: Option<u8>,
+    ) -> Result<Response, ContractError> {
+        let response = Response::new()
+            .add_attribute("action", "set_token_decimals")
+            .add_attribute("token_address", token_address.to_string());
+        let Some(decimals) = decimals else {
+            TOKEN_DECIMALS.remove(deps.storage, token_address);
+            return Ok(response);
+        };
+        if decimals > MAX_TOKEN_DECIMALS {
+            return Err(ContractError::InvalidDecimals {
+                max: MAX_TOKEN_DECIMALS,
+            });
+        }
+        TOKEN_DECIMALS.save(deps.storage, token_address, &decimals)?;
+
+        Ok(response.add_attribute("decimals", decimals.to_string()))
+    }
+
+    /// Register the identity of a user, replacing the one registered before
+    pub fn register_identity(
+        deps: DepsMut,
+        user_addr: Addr,
+        identity: Addr,
+        country: u16,
+    ) -> Result<Response, ContractError> {
+        let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+        let identity = deps.api.addr_validate(identity.as_str())?;
+        IDENTITIES.save(
+            deps.storage,
+            &user_addr,
+            &IdentityInfo {
+                identity: identity.clone(),
+                country,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_identity")
+            .add_attribute("user_addr", user_addr)
+            .add_attribute("identity", identity)
+            .add_attribute("country", country.to_string()))
+    }
+
+    /// Register a cohort of new users. Every entry is checked before any is saved.
+    pub fn register_identities_batch(
+        deps: DepsMut,
+        entries: Vec<(Addr, Addr, u16)>,
+    ) -> Result<Response, ContractError> {
+        if entries.is_empty() || entries.len() > MAX_IDENTITIES_PER_BATCH {
+            return Err(ContractError::InvalidIdentityBatch {
+                max: MAX_IDENTITIES_PER_BATCH,
+            });
+        }
+
+        let mut validated: Vec<(Addr, IdentityInfo)> = Vec::with_capacity(entries.len());
+        for (user_addr, identity, country) in entries {
+            let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+            let identity = deps.api.addr_validate(identity.as_str())?;
+            if IDENTITIES.has(deps.storage, &user_addr)
+                || validated
+                    .iter()
+                    .any(|(registered, _)| *registered == user_addr)
+            {
+                return Err(ContractError::IdentityAlreadyRegistered {
+                    user_addr: user_addr.to_string(),
+                });
+            }
+            validated.push((user_addr, IdentityInfo { identity, country }));
+        }
+
+        let mut response = Response::new()
+            .add_attribute("action", "register_identities_batch")
+            .add_attribute("count", validated.len().to_string());
+        for (user_addr, info) in &validated {
+            IDENTITIES.save(deps.storage, user_addr, info)?;
+            response = response.add_attribute("user_addr", user_addr);
+        }
+        Ok(response)
+    }
+
+    /// Allow or disallow transfers involving residents of a country
+    pub fn set_country_allowed(
+        deps: DepsMut,
+        country: u16,
+        allowed: bool,
+    ) -> Result<Response, ContractError> {
+        COUNTRY_RESTRICTION.save(deps.storage, &true)?;
+        if allowed {
+            ALLOWED_COUNTRIES.save(deps.storage, country, &true)?;
+        } else {
+            ALLOWED_COUNTRIES.remove(deps.storage, country);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_country_allowed")
+            .add_attribute("country", country.to_string())
+            .add_attribute("allowed", allowed.to_string()))
+    }
+
+    /// Stop or resume all transfers
+    pub fn set_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+        PAUSED.save(deps.storage, &paused)?;
+
+        Ok(Response::new().add_attribute("action", if paused { "pause" } else { "unpause" }))
+    }
+
+    /// Freeze or unfreeze a user's transfers
+    pub fn set_address_frozen(
+        deps: DepsMut,
+        addr: Addr,
+        frozen: bool,
+    ) -> Result<Response, ContractError> {
+        if frozen {
+            FROZEN.save(deps.storage, &addr, &true)?;
+        } else {
+            FROZEN.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_address_frozen")
+            .add_attribute("addr", addr)
+            .add_attribute("frozen", frozen.to_string()))
+    }
+
+    /// Remove the identity registered for a user
+    pub fn delete_identity(deps: DepsMut, user_addr: Addr) -> Result<Response, ContractError> {
+        if !IDENTITIES.has(deps.storage, &user_addr) {
+            return Err(ContractError::IdentityNotFound {
+                user_addr: user_addr.to_string(),
+            });
+        }
+        IDENTITIES.remove(deps.storage, &user_addr);
+
+        Ok(Response::new()
+            .add_attribute("action", "delete_identity")
+            .add_attribute("user_addr", user_addr))
+    }
+
+    /// Enable or disable forwarding of the token's transfers to a bound module
+    pub fn set_module_transfer_hook(
+        deps: DepsMut,
+        token_address: Addr,
+        module_address: Addr,
+        enabled: bool,
+    ) -> Result<Response, ContractError> {
+        TOKEN_COMPLIANCE_MODULES.update(
+            deps.storage,
+            (token_address.clone(), module_address.clone()),
+            |module| -> Result<ComplianceModule, ContractError> {
+                let mut module = module.ok_or(ContractError::ComplianceNotFound {})?;
+                module.transfer_hook = enabled;
+                Ok(module)
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_module_transfer_hook")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("module_address", module_address.to_string())
+            .add_attribute("enabled", enabled.to_string()))
+    }
+
+    /// Forward a completed transfer of the sending token to its hooked, active modules
+    pub fn transferred(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let hook = to_json_binary(&utils::ModuleHookExecuteMsg::Transferred {
+            token_address: token_address.clone(),
+            from,
+            to,
+            amount,
+        })?;
+        let messages = TOKEN_COMPLIANCE_MODULES
+            .prefix(token_address.clone())
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .filter_map(|item| match item {
+                Ok((_, module)) if module.active && module.transfer_hook => {
+                    Some(Ok(WasmMsg::Execute {
+                        contract_addr: module.address.to_string(),
+                        msg: hook.clone(),
+                        funds: vec![],
+                    }))
+                }
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transferred")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("notified_modules", messages.len().to_string())
+            .add_messages(messages))
+    }
+
+    /// Record the compliance decision for a transfer as a `compliance_decision` event
+    pub fn pre_check_transfer(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> Result<Response, ContractError> {
+        let res = super::query::check_compliance_detailed(
+            deps,
+            token_address.clone(),
+            from.clone(),
+            to.clone(),
+            amount,
+        )?;
+        let mut decision = Event::new("compliance_decision")
+            .add_attribute("token_address", token_address)
+            .add_attribute("from", from.as_ref().map(Addr::as_str).unwrap_or_default())
+            .add_attribute("to", to.as_ref().map(Addr::as_str).unwrap_or_default())
+            .add_attribute("amount", amount.unwrap_or_default())
+            .add_attribute("verdict", if res.compliant { "allowed" } else { "blocked" });
+        if let Some(reason) = super::query::rejection_reason(&res) {
+            decision = decision.add_attribute("reason", reason);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "pre_check_transfer")
+            .add_event(decision))
+    }
+
+    /// Replace the module bound to a token, optionally carrying its state over
+    pub fn replace_module(
+        deps: DepsMut,
+        token_address: Addr,
+        old: Addr,
+        new: Addr,
+        carry_state: bool,
+    ) -> Result<Response, ContractError> {
+        let module = TOKEN_COMPLIANCE_MODULES
+            .may_load(deps.storage, (token_address.clone(), old.clone()))?
+            .ok_or(ContractError::ComplianceNotFound {})?;
+        if TOKEN_COMPLIANCE_MODULES.has(deps.storage, (token_address.clone(), new.clone())) {
+            return Err(ContractError::ComplianceAlreadyExists {});
+        }
+
+        // The import runs before the rebinding takes effect for any later transfer, and a
+        // failed import reverts the whole replacement
+        let mut response = Response::new();
+        if carry_state {
+            let state: Binary = deps.querier.query_wasm_smart(
+                old.to_string(),
+                &utils::ModuleStateQueryMsg::ExportState {
+                    token_address: token_address.clone(),
+                },
+            )?;
+            response = response.add_message(WasmMsg::Execute {
+                contract_addr: new.to_string(),
+                msg: to_json_binary(&utils::ModuleStateExecuteMsg::ImportState {
+                    token_address: token_address.clone(),
+                    state,
+                })?,
+                funds: vec![],
+            });
+        }
+
+        TOKEN_COMPLIANCE_MODULES.remove(deps.storage, (token_address.clone(), old.clone()));
+        TOKEN_COMPLIANCE_MODULES.save(
+            deps.storage,
+            (token_address.clone(), new.clone()),
+            &ComplianceModule {
+                address: new.clone(),
+                ..module
+            },
+        )?;
+
+        Ok(response
+            .add_attribute("action", "replace_module")
+            .add_attribute("token_address", token_address.to_string())
+            .add_attribute("old_module", old.to_string())
+            .add_attribute("new_module", new.to_string())
+            .add_attribute("carry_state", carry_state.to_string()))
+    }
+
+    /// Ensure activating `module_address` keeps the token within the module cap
+    fn ensure_module_capacity(
+        deps: Deps,
+        token_address: &Addr,
+        module_address: &Addr,
+    ) -> Result<(), ContractError> {
+        let max = MAX_MODULES.load(deps.storage)?;
+        let active_modules = TOKEN_COMPLIANCE_MODULES
+            .prefix(token_address.clone())
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .filter(|item| match item {
+                Ok((addr, module)) => module.active && addr != module_address,
+                Err(_) => false,
+            })
+            .count();
+        if active_modules >= max as usize {
+            return Err(ContractError::TooManyModules { max });
+        }
+        Ok(())
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -186,17 +603,60 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to,
             amount,
         )?),
+        QueryMsg::CheckTokenComplianceDetailed {
+            token_address,
+            from,
+            to,
+            amount,
+        } => to_json_binary(&query::check_compliance_detailed(
+            deps,
+            token_address,
+            from,
+            to,
+            amount,
+        )?),
+        QueryMsg::GetMaxModules {} => to_json_binary(&MAX_MODULES.load(deps.storage)?),
+        QueryMsg::GetModuleFailurePolicy {} => to_json_binary(&query::module_failure_policy(deps)?),
+        QueryMsg::GetTokenDecimals { token_address } => {
+            to_json_binary(&TOKEN_DECIMALS.may_load(deps.storage, token_address)?)
+        }
+        QueryMsg::SimulateTransfers {
+            token_address,
+            transfers,
+        } => to_json_binary(&query::simulate_transfers(deps, token_address, transfers)?),
+        QueryMsg::GetIdentity { user_addr } => {
+            to_json_binary(&IDENTITIES.may_load(deps.storage, &user_addr)?)
+        }
+        QueryMsg::IsVerified { user_addr } => to_json_binary(&query::is_verified(deps, user_addr)?),
+        QueryMsg::CanTransfer { from, to } => to_json_binary(&query::can_transfer(deps, from, to)?),
+        QueryMsg::IsPaused {} => to_json_binary(&query::is_paused(deps)?),
+        QueryMsg::IsFrozen { addr } => to_json_binary(&FROZEN.has(deps.storage, &addr)),
+        QueryMsg::ListFrozenAddresses { start_after, limit } => {
+            to_json_binary(&query::list_frozen_addresses(deps, start_after, limit)?)
+        }
     }
 }
 
 pub mod query {
-    use crate::registry::{msg::ComplianceModule, state::TOKEN_COMPLIANCE_MODULES};
+    use std::collections::HashMap;
+
+    use crate::registry::{
+        msg::{
+            ClaimTopicStatus, ClaimTopicsQueryMsg, ComplianceCheckResponse, ComplianceModule,
+            IdentityQueryMsg, ModuleFailurePolicy, PreflightVerificationResponse, TransferStep,
+        },
+        state::{
+            ALLOWED_COUNTRIES, COUNTRY_RESTRICTION, MAX_SIMULATION_STEPS,
+            MAX_TOPICS_PER_VERIFICATION, MODULE_FAILURE_POLICY, PAUSED, TOKEN_COMPLIANCE_MODULES,
+        },
+    };
 
     use super::*;
-    use cosmwasm_std::{to_json_binary, Addr, QueryRequest, Uint128, WasmQuery};
+    use cosmwasm_std::{to_json_binary, Addr, QueryRequest, StdError, Uint128, WasmQuery};
+    use utils::pagination::{paginate, MAX_LIMIT};
+    use utils::{ModuleSimulationQueryMsg, SimulatedTransferResponse};
 
     /// Check compliance for a token transfer
-
     pub fn check_compliance(
         deps: Deps,
         token_address: Addr,
@@ -204,87 +664,449 @@ pub mod query {
         to: Option<Addr>,
         amount: Option<Uint128>,
     ) -> StdResult<bool> {
-        // Get all active compliance modules for the token
-        let valid_modules: Vec<ComplianceModule> = TOKEN_COMPLIANCE_MODULES
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .filter_map(|item| {
-                item.ok().and_then(|((token_addr, _), module)| {
-                    if token_addr == token_address && module.active {
-                        Some(module)
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect();
+        check_compliance_detailed(deps, token_address, from, to, amount).map(|res| res.compliant)
+    }
+
+    /// Check compliance for a token transfer, reporting how the verdict was reached.
+    /// A transfer the registry restricts itself is rejected before any module is queried.
+    /// Modules are queried in order and evaluation stops at the first rejection.
+    /// A token with more active modules than the cap is rejected without querying any module.
+    /// A module that fails to answer is handled per the `ModuleFailurePolicy`.
+    pub fn check_compliance_detailed(
+        deps: Deps,
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    ) -> StdResult<ComplianceCheckResponse> {
+        if let Some(restriction) = registry_restriction(deps, &from, &to)? {
+            return Ok(restricted(restriction));
+        }
+        let Some(valid_modules) = active_modules(deps, &token_address)? else {
+            return Ok(exceeds_max_modules());
+        };
+
+        let policy = module_failure_policy(deps)?;
+        let decimals = TOKEN_DECIMALS.may_load(deps.storage, token_address.clone())?;
 
         // Check compliance with each module
+        let mut modules_evaluated = 0;
+        let mut skipped_modules = vec![];
         for module in valid_modules {
-            let msg = QueryMsg::CheckTokenCompliance {
-                token_address: token_address.clone(),
-                from: from.clone(),
-                to: to.clone(),
+            let transfer = Transfer {
+                token_address: &token_address,
+                from: &from,
+                to: &to,
                 amount,
+                decimals,
             };
-
-            let query = QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: module.address.to_string(),
-                msg: to_json_binary(&msg)?,
-            });
-            let is_compliant: bool = deps.querier.query(&query)?;
-            if !is_compliant {
-                return Ok(false);
+            let verdict = module_verdict(deps, &policy, &module, &transfer)?;
+            modules_evaluated += 1;
+            match verdict {
+                Some(true) => {}
+                Some(false) => {
+                    return Ok(ComplianceCheckResponse {
+                        compliant: false,
+                        modules_evaluated,
+                        failed_module: Some(module.address),
+                        exceeds_max_modules: false,
+                        skipped_modules,
+                        restriction: None,
+                    });
+                }
+                None => skipped_modules.push(module.address),
             }
         }
 
-        Ok(true)
+        Ok(ComplianceCheckResponse {
+            compliant: true,
+            modules_evaluated,
+            failed_module: None,
+            exceeds_max_modules: false,
+            skipped_modules,
+            restriction: None,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Check a sequence of transfers, carrying each module's simulated effects from one
+    /// compliant step to the next. A rejected step is reported but its effects are dropped,
+    /// as the transfer would not have executed.
+    pub fn simulate_transfers(
+        deps: Deps,
+        token_address: Addr,
+        transfers: Vec<TransferStep>,
+    ) -> StdResult<Vec<ComplianceCheckResponse>> {
+        if transfers.len() > MAX_SIMULATION_STEPS {
+            return Err(StdError::generic_err(format!(
+                "At most {MAX_SIMULATION_STEPS} transfers can be simulated at once"
+            )));
+        }
+        let valid_modules = active_modules(deps, &token_address)?;
+        let policy = module_failure_policy(deps)?;
+        let decimals = TOKEN_DECIMALS.may_load(deps.storage, token_address.clone())?;
 
-    use super::*;
-    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
-    use cosmwasm_std::{from_json, Addr, ContractResult, SystemResult, Uint128};
+        let mut overlays: HashMap<Addr, Binary> = HashMap::new();
+        let mut verdicts = Vec::with_capacity(transfers.len());
+        for step in transfers {
+            if let Some(restriction) = registry_restriction(deps, &step.from, &step.to)? {
+                verdicts.push(restricted(restriction));
+                continue;
+            }
+            let Some(valid_modules) = &valid_modules else {
+                verdicts.push(exceeds_max_modules());
+                continue;
+            };
+            let mut staged = vec![];
+            let mut verdict = ComplianceCheckResponse {
+                compliant: true,
+                modules_evaluated: 0,
+                failed_module: None,
+                exceeds_max_modules: false,
+                skipped_modules: vec![],
+                restriction: None,
+            };
+            for module in valid_modules {
+                let simulated: StdResult<SimulatedTransferResponse> =
+                    deps.querier.query_wasm_smart(
+                        module.address.to_string(),
+                        &ModuleSimulationQueryMsg::SimulateTransfer {
+                            token_address: token_address.clone(),
+                            from: step.from.clone(),
+                            to: step.to.clone(),
+                            amount: step.amount,
+                            overlay: overlays.get(&module.address).cloned(),
+                            decimals,
+                        },
+                    );
+                let is_compliant = match simulated {
+                    Ok(res) => {
+                        staged.push((module.address.clone(), res.overlay));
+                        Some(res.compliant)
+                    }
+                    // The module does not support simulation
+                    Err(_) => module_verdict(
+                        deps,
+                        &policy,
+                        module,
+                        &Transfer {
+                            token_address: &token_address,
+                            from: &step.from,
+                            to: &step.to,
+                            amount: step.amount,
+                            decimals,
+                        },
+                    )?,
+                };
+                verdict.modules_evaluated += 1;
+                match is_compliant {
+                    Some(true) => {}
+                    Some(false) => {
+                        verdict.compliant = false;
+                        verdict.failed_module = Some(module.address.clone());
+                        break;
+                    }
+                    None => verdict.skipped_modules.push(module.address.clone()),
+                }
+            }
+            if verdict.compliant {
+                overlays.extend(staged);
+            }
+            verdicts.push(verdict);
+        }
+        Ok(verdicts)
+    }
 
-    // Helper function to instantiate the contract
-    fn setup_contract(deps: DepsMut) {
-        let msg = InstantiateMsg {
-            owner_roles_address: Addr::unchecked("owner_roles"),
-        };
-        let info = message_info(&Addr::unchecked("creator"), &[]);
-        let res = instantiate(deps, mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+    /// The active compliance modules for the token, or `None` when there are more than the cap
+    fn active_modules(
+        deps: Deps,
+        token_address: &Addr,
+    ) -> StdResult<Option<Vec<ComplianceModule>>> {
+        let max = MAX_MODULES.load(deps.storage)?;
+
+        // Read at most one past the cap
+        let valid_modules: Vec<ComplianceModule> = TOKEN_COMPLIANCE_MODULES
+            .prefix(token_address.clone())
+            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .filter_map(|item| item.ok().map(|(_, module)| module))
+            .filter(|module| module.active)
+            .take(max as usize + 1)
+            .collect();
+
+        if valid_modules.len() > max as usize {
+            return Ok(None);
+        }
+        Ok(Some(valid_modules))
     }
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies();
-        setup_contract(deps.as_mut());
+    fn exceeds_max_modules() -> ComplianceCheckResponse {
+        ComplianceCheckResponse {
+            compliant: false,
+            modules_evaluated: 0,
+            failed_module: None,
+            exceeds_max_modules: true,
+            skipped_modules: vec![],
+            restriction: None,
+        }
+    }
 
-        // Check that the owner_roles_address was properly set
-        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.as_ref().storage).unwrap();
-        assert_eq!(owner_roles, Addr::unchecked("owner_roles"));
+    /// Why the check rejected the transfer, `None` for a compliant one
+    pub fn rejection_reason(res: &ComplianceCheckResponse) -> Option<String> {
+        if res.compliant {
+            return None;
+        }
+        if let Some(restriction) = &res.restriction {
+            return Some(restriction.clone());
+        }
+        if let Some(module) = &res.failed_module {
+            return Some(format!("Rejected by compliance module {module}"));
+        }
+        Some("The token has more active compliance modules than allowed".to_string())
     }
 
-    #[test]
-    fn add_compliance_module() {
-        let mut deps = mock_dependencies();
-        setup_contract(deps.as_mut());
+    fn restricted(restriction: String) -> ComplianceCheckResponse {
+        ComplianceCheckResponse {
+            compliant: false,
+            modules_evaluated: 0,
+            failed_module: None,
+            exceeds_max_modules: false,
+            skipped_modules: vec![],
+            restriction: Some(restriction),
+        }
+    }
 
-        // Mock the owner roles contract query
-        deps.querier.update_wasm(|query| match query {
-            cosmwasm_std::WasmQuery::Smart { msg, .. } => {
-                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
-                match parsed {
-                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
-                        if role == OwnerRole::ComplianceManager {
-                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
-                        } else {
+    /// Why the registry stops the transfer regardless of its modules: transfers are paused,
+    /// a party is frozen, or, once a country allow-list is configured, a party has no
+    /// identity registered in an allowed country. Mints and burns have no party on one side.
+    fn registry_restriction(
+        deps: Deps,
+        from: &Option<Addr>,
+        to: &Option<Addr>,
+    ) -> StdResult<Option<String>> {
+        if is_paused(deps)? {
+            return Ok(Some("Transfers are paused".to_string()));
+        }
+        for user_addr in [from, to].into_iter().flatten() {
+            if FROZEN.has(deps.storage, user_addr) {
+                return Ok(Some(format!("{user_addr} is frozen")));
+            }
+        }
+        if COUNTRY_RESTRICTION
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+        {
+            for user_addr in [from, to].into_iter().flatten() {
+                let Some(identity) = IDENTITIES.may_load(deps.storage, user_addr)? else {
+                    return Ok(Some(format!("{user_addr} has no identity registered")));
+                };
+                if !ALLOWED_COUNTRIES.has(deps.storage, identity.country) {
+                    return Ok(Some(format!(
+                        "{user_addr} resides in country {}, which is not allowed",
+                        identity.country
+                    )));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether the user's identity holds a claim for every required topic. The active
+    /// topics are verified together, in batches of at most
+    /// `MAX_TOPICS_PER_VERIFICATION`, so the identity asks the trusted issuers registry
+    /// about each issuer once per batch rather than once per topic. Deprecated topics
+    /// accept no new claims, so they are no longer required.
+    pub fn is_verified(deps: Deps, user_addr: Addr) -> StdResult<bool> {
+        let Some(identity) = IDENTITIES.may_load(deps.storage, &user_addr)? else {
+            return Ok(false);
+        };
+        let Some(claim_topics_registry) = CLAIM_TOPICS_REGISTRY.may_load(deps.storage)? else {
+            return Ok(true);
+        };
+        let trusted_issuers_registry = TRUSTED_ISSUERS_REGISTRY
+            .may_load(deps.storage)?
+            .map(String::from);
+
+        let mut required_topics = vec![];
+        let mut start_after = None;
+        loop {
+            let topics: Vec<Uint128> = deps.querier.query_wasm_smart(
+                &claim_topics_registry,
+                &ClaimTopicsQueryMsg::GetClaimTopics {
+                    start_after,
+                    limit: Some(MAX_LIMIT),
+                },
+            )?;
+            for topic in &topics {
+                let status: ClaimTopicStatus = deps.querier.query_wasm_smart(
+                    &claim_topics_registry,
+                    &ClaimTopicsQueryMsg::GetClaimTopicStatus { topic: *topic },
+                )?;
+                if status == ClaimTopicStatus::Active {
+                    required_topics.push(*topic);
+                }
+            }
+            if topics.len() < MAX_LIMIT as usize {
+                break;
+            }
+            start_after = topics.last().copied();
+        }
+
+        for batch in required_topics.chunks(MAX_TOPICS_PER_VERIFICATION) {
+            let response: PreflightVerificationResponse = deps.querier.query_wasm_smart(
+                &identity.identity,
+                &IdentityQueryMsg::PreflightVerification {
+                    user_addr: user_addr.clone(),
+                    required_topics: batch.to_vec(),
+                    trusted_issuers_registry: trusted_issuers_registry.clone(),
+                },
+            )?;
+            if !response.verified {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn list_frozen_addresses(
+        deps: Deps,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Addr>> {
+        Ok(
+            paginate(deps.storage, &FROZEN, start_after.as_ref(), limit)?
+                .into_iter()
+                .map(|(addr, _)| addr)
+                .collect(),
+        )
+    }
+
+    /// Whether transfers are not paused, neither party is frozen, and both parties reside
+    /// in an allowed country. A user without an identity has no known country and can't
+    /// transfer.
+    pub fn can_transfer(deps: Deps, from: Addr, to: Addr) -> StdResult<bool> {
+        if is_paused(deps)? {
+            return Ok(false);
+        }
+        for user_addr in [from, to] {
+            if FROZEN.has(deps.storage, &user_addr) {
+                return Ok(false);
+            }
+            let Some(identity) = IDENTITIES.may_load(deps.storage, &user_addr)? else {
+                return Ok(false);
+            };
+            if !ALLOWED_COUNTRIES.has(deps.storage, identity.country) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn is_paused(deps: Deps) -> StdResult<bool> {
+        Ok(PAUSED.may_load(deps.storage)?.unwrap_or_default())
+    }
+
+    pub fn module_failure_policy(deps: Deps) -> StdResult<ModuleFailurePolicy> {
+        Ok(MODULE_FAILURE_POLICY
+            .may_load(deps.storage)?
+            .unwrap_or_default())
+    }
+
+    /// A transfer as checked by the modules
+    struct Transfer<'a> {
+        token_address: &'a Addr,
+        from: &'a Option<Addr>,
+        to: &'a Option<Addr>,
+        amount: Option<Uint128>,
+        decimals: Option<u8>,
+    }
+
+    /// The module's verdict, or `None` when its query failed and the policy skips it
+    fn module_verdict(
+        deps: Deps,
+        policy: &ModuleFailurePolicy,
+        module: &ComplianceModule,
+        transfer: &Transfer,
+    ) -> StdResult<Option<bool>> {
+        match module_compliance(deps, module, transfer) {
+            Ok(compliant) => Ok(Some(compliant)),
+            Err(err) => match policy {
+                ModuleFailurePolicy::FailClosed => Err(err),
+                ModuleFailurePolicy::SkipAndFlag => Ok(None),
+            },
+        }
+    }
+
+    fn module_compliance(
+        deps: Deps,
+        module: &ComplianceModule,
+        transfer: &Transfer,
+    ) -> StdResult<bool> {
+        let msg = utils::QueryMsg::CheckTokenCompliance {
+            token_address: transfer.token_address.clone(),
+            from: transfer.from.clone(),
+            to: transfer.to.clone(),
+            amount: transfer.amount,
+            decimals: transfer.decimals,
+        };
+
+        let query = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: module.address.to_string(),
+            msg: to_json_binary(&msg)?,
+        });
+        deps.querier.query(&query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::registry::msg::{ComplianceCheckResponse, ModuleFailurePolicy, TransferStep};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cosmwasm_std::{
+        from_json, Addr, ContractResult, CosmosMsg, Event, SystemResult, Uint128, WasmMsg,
+    };
+
+    // Helper function to instantiate the contract
+    fn setup_contract(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles"),
+            max_modules: None,
+            claim_topics_registry: None,
+            trusted_issuers_registry: None,
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let res = instantiate(deps, mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // Check that the owner_roles_address was properly set
+        let owner_roles = OWNER_ROLES_ADDRESS.load(deps.as_ref().storage).unwrap();
+        assert_eq!(owner_roles, Addr::unchecked("owner_roles"));
+    }
+
+    #[test]
+    fn add_compliance_module() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // Mock the owner roles contract query
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { msg, .. } => {
+                let parsed: roles::owner_roles::QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    roles::owner_roles::QueryMsg::IsOwner { role, .. } => {
+                        if role == OwnerRole::ComplianceManager {
+                            SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                        } else {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -317,6 +1139,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -356,6 +1179,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -396,6 +1220,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -414,11 +1239,12 @@ mod tests {
             cosmwasm_std::WasmQuery::Smart { msg, .. } => {
                 let parsed: utils::QueryMsg = from_json(msg).unwrap();
                 match parsed {
-                    QueryMsg::CheckTokenCompliance {
+                    utils::QueryMsg::CheckTokenCompliance {
                         token_address: _,
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -437,4 +1263,1694 @@ mod tests {
         let is_compliant: bool = from_json(res).unwrap();
         assert!(is_compliant);
     }
+
+    // Mock the owner roles contract (always authorized) and the compliance modules,
+    // where modules listed in `rejecting` return a non-compliant verdict
+    fn mock_roles_and_modules(
+        deps: &mut cosmwasm_std::OwnedDeps<
+            cosmwasm_std::MemoryStorage,
+            cosmwasm_std::testing::MockApi,
+            cosmwasm_std::testing::MockQuerier,
+        >,
+        rejecting: Vec<&'static str>,
+    ) {
+        deps.querier.update_wasm(move |query| match query {
+            cosmwasm_std::WasmQuery::Smart { contract_addr, .. } => {
+                if contract_addr == "owner_roles" {
+                    return SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()));
+                }
+                let compliant = !rejecting.contains(&contract_addr.as_str());
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&compliant).unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
+    fn setup_contract_with_max_modules(deps: DepsMut, max_modules: u32) {
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles"),
+            max_modules: Some(max_modules),
+            claim_topics_registry: None,
+            trusted_issuers_registry: None,
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        instantiate(deps, mock_env(), info, msg).unwrap();
+    }
+
+    fn add_module(deps: DepsMut, module: &str) -> Result<Response, ContractError> {
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let msg = ExecuteMsg::AddComplianceModule {
+            token_address: Addr::unchecked("token"),
+            module_address: Addr::unchecked(module),
+            module_name: module.to_string(),
+        };
+        execute(deps, mock_env(), info, msg)
+    }
+
+    fn detailed_check(deps: Deps) -> ComplianceCheckResponse {
+        let msg = QueryMsg::CheckTokenComplianceDetailed {
+            token_address: Addr::unchecked("token"),
+            from: Some(Addr::unchecked("sender")),
+            to: Some(Addr::unchecked("receiver")),
+            amount: Some(Uint128::new(100)),
+        };
+        from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn adding_modules_beyond_cap_fails() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 2);
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        add_module(deps.as_mut(), "module_a").unwrap();
+        add_module(deps.as_mut(), "module_b").unwrap();
+        let err = add_module(deps.as_mut(), "module_c").unwrap_err();
+        assert!(matches!(err, ContractError::TooManyModules { max: 2 }));
+
+        // Re-adding an already bound module does not count twice
+        add_module(deps.as_mut(), "module_b").unwrap();
+
+        // Deactivating a module frees up a slot
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let msg = ExecuteMsg::UpdateComplianceModule {
+            token_address: Addr::unchecked("token"),
+            module_address: Addr::unchecked("module_a"),
+            active: false,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        add_module(deps.as_mut(), "module_c").unwrap();
+    }
+
+    #[test]
+    fn detailed_check_short_circuits_on_first_rejection() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 5);
+        mock_roles_and_modules(&mut deps, vec!["module_b"]);
+
+        add_module(deps.as_mut(), "module_a").unwrap();
+        add_module(deps.as_mut(), "module_b").unwrap();
+        add_module(deps.as_mut(), "module_c").unwrap();
+
+        let res = detailed_check(deps.as_ref());
+        assert_eq!(
+            res,
+            ComplianceCheckResponse {
+                compliant: false,
+                modules_evaluated: 2,
+                failed_module: Some(Addr::unchecked("module_b")),
+                exceeds_max_modules: false,
+                skipped_modules: vec![],
+                restriction: None,
+            }
+        );
+
+        // All modules are evaluated when none reject
+        mock_roles_and_modules(&mut deps, vec![]);
+        let res = detailed_check(deps.as_ref());
+        assert!(res.compliant);
+        assert_eq!(res.modules_evaluated, 3);
+        assert_eq!(res.failed_module, None);
+    }
+
+    #[test]
+    fn modules_exceeding_cap_fail_closed() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 3);
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        add_module(deps.as_mut(), "module_a").unwrap();
+        add_module(deps.as_mut(), "module_b").unwrap();
+        add_module(deps.as_mut(), "module_c").unwrap();
+
+        // Lower the cap below the number of bound modules
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let msg = ExecuteMsg::SetMaxModules { max_modules: 2 };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let res = detailed_check(deps.as_ref());
+        assert_eq!(
+            res,
+            ComplianceCheckResponse {
+                compliant: false,
+                modules_evaluated: 0,
+                failed_module: None,
+                exceeds_max_modules: true,
+                skipped_modules: vec![],
+                restriction: None,
+            }
+        );
+
+        // The plain check reports non-compliance instead of erroring
+        let msg = QueryMsg::CheckTokenCompliance {
+            token_address: Addr::unchecked("token"),
+            from: Some(Addr::unchecked("sender")),
+            to: Some(Addr::unchecked("receiver")),
+            amount: Some(Uint128::new(100)),
+        };
+        let is_compliant: bool = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!is_compliant);
+    }
+
+    #[test]
+    fn replace_module_carries_state() {
+        use crate::modules::lot_size;
+        use cosmwasm_std::{Empty, StdResult};
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        // Stands in for the owner roles contract, granting every role
+        fn noop(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+        fn grant_all(_: Deps, _: Env, _: roles::owner_roles::QueryMsg) -> StdResult<Binary> {
+            to_json_binary(&true)
+        }
+
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let token = app.api().addr_make("token");
+
+        let roles_code = app.store_code(Box::new(ContractWrapper::new(noop, noop, grant_all)));
+        let owner_roles = app
+            .instantiate_contract(roles_code, admin.clone(), &Empty {}, &[], "roles", None)
+            .unwrap();
+        let registry_code =
+            app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+        let registry = app
+            .instantiate_contract(
+                registry_code,
+                admin.clone(),
+                &InstantiateMsg {
+                    owner_roles_address: owner_roles.clone(),
+                    max_modules: None,
+                    claim_topics_registry: None,
+                    trusted_issuers_registry: None,
+                },
+                &[],
+                "registry",
+                None,
+            )
+            .unwrap();
+        let module_code = app.store_code(Box::new(ContractWrapper::new(
+            lot_size::contract::execute,
+            lot_size::contract::instantiate,
+            lot_size::contract::query,
+        )));
+        let mut modules = vec![];
+        for label in ["lot_size_v1", "lot_size_v2"] {
+            let module = app
+                .instantiate_contract(
+                    module_code,
+                    admin.clone(),
+                    &lot_size::InstantiateMsg {
+                        owner_roles_address: owner_roles.clone(),
+                    },
+                    &[],
+                    label,
+                    None,
+                )
+                .unwrap();
+            modules.push(module);
+        }
+        let (old, new) = (modules[0].clone(), modules[1].clone());
+
+        app.execute_contract(
+            admin.clone(),
+            old.clone(),
+            &lot_size::ExecuteMsg::SetLotSize {
+                token_address: token.clone(),
+                lot_size: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            registry.clone(),
+            &ExecuteMsg::AddComplianceModule {
+                token_address: token.clone(),
+                module_address: old.clone(),
+                module_name: "lot_size".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        app.execute_contract(
+            admin.clone(),
+            registry.clone(),
+            &ExecuteMsg::ReplaceModule {
+                token_address: token.clone(),
+                old: old.clone(),
+                new: new.clone(),
+                carry_state: true,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // The new module picked up the old one's configuration
+        let lot_size: Option<Uint128> = app
+            .wrap()
+            .query_wasm_smart(
+                new.clone(),
+                &lot_size::QueryMsg::GetLotSize {
+                    token_address: token.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(lot_size, Some(Uint128::new(100)));
+
+        // and is the one the registry now consults
+        let res: ComplianceCheckResponse = app
+            .wrap()
+            .query_wasm_smart(
+                registry.clone(),
+                &QueryMsg::CheckTokenComplianceDetailed {
+                    token_address: token.clone(),
+                    from: None,
+                    to: None,
+                    amount: Some(Uint128::new(150)),
+                },
+            )
+            .unwrap();
+        assert!(!res.compliant);
+        assert_eq!(res.failed_module, Some(new.clone()));
+
+        // The old module is no longer bound
+        let err = app
+            .execute_contract(
+                admin,
+                registry,
+                &ExecuteMsg::ReplaceModule {
+                    token_address: token,
+                    old,
+                    new,
+                    carry_state: false,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ComplianceNotFound {})
+        ));
+    }
+
+    #[test]
+    fn simulated_transfers_respect_cumulative_limits() {
+        use crate::modules::max_concentration;
+        use cosmwasm_std::{Decimal, Empty, StdResult};
+        use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        // Stands in for the owner roles contract, granting every role
+        fn noop(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+        fn grant_all(_: Deps, _: Env, _: roles::owner_roles::QueryMsg) -> StdResult<Binary> {
+            to_json_binary(&true)
+        }
+        // Stands in for a token with a supply of 1000 and no holders yet
+        fn token_query(_: Deps, _: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+            match msg {
+                Cw20QueryMsg::TokenInfo {} => to_json_binary(&TokenInfoResponse {
+                    name: "Token".to_string(),
+                    symbol: "TKN".to_string(),
+                    decimals: 6,
+                    total_supply: Uint128::new(1000),
+                }),
+                _ => to_json_binary(&BalanceResponse {
+                    balance: Uint128::zero(),
+                }),
+            }
+        }
+
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let sender = app.api().addr_make("sender");
+        let receiver = app.api().addr_make("receiver");
+
+        let roles_code = app.store_code(Box::new(ContractWrapper::new(noop, noop, grant_all)));
+        let owner_roles = app
+            .instantiate_contract(roles_code, admin.clone(), &Empty {}, &[], "roles", None)
+            .unwrap();
+        let token_code = app.store_code(Box::new(ContractWrapper::new(noop, noop, token_query)));
+        let token = app
+            .instantiate_contract(token_code, admin.clone(), &Empty {}, &[], "token", None)
+            .unwrap();
+        let registry_code =
+            app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+        let registry = app
+            .instantiate_contract(
+                registry_code,
+                admin.clone(),
+                &InstantiateMsg {
+                    owner_roles_address: owner_roles.clone(),
+                    max_modules: None,
+                    claim_topics_registry: None,
+                    trusted_issuers_registry: None,
+                },
+                &[],
+                "registry",
+                None,
+            )
+            .unwrap();
+        let module_code = app.store_code(Box::new(ContractWrapper::new(
+            max_concentration::contract::execute,
+            max_concentration::contract::instantiate,
+            max_concentration::contract::query,
+        )));
+        let module = app
+            .instantiate_contract(
+                module_code,
+                admin.clone(),
+                &max_concentration::InstantiateMsg {
+                    owner_roles_address: owner_roles.clone(),
+                },
+                &[],
+                "max_concentration",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            admin.clone(),
+            module.clone(),
+            &max_concentration::ExecuteMsg::SetConcentrationLimit {
+                token_address: token.clone(),
+                limit: max_concentration::msg::ConcentrationLimit {
+                    max_share: Decimal::percent(10),
+                    bootstrap_supply: Uint128::zero(),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            registry.clone(),
+            &ExecuteMsg::AddComplianceModule {
+                token_address: token.clone(),
+                module_address: module.clone(),
+                module_name: "max_concentration".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let step = |amount: u128| TransferStep {
+            from: Some(sender.clone()),
+            to: Some(receiver.clone()),
+            amount: Some(Uint128::new(amount)),
+        };
+
+        // Each transfer of 60 passes when checked on its own
+        let compliant: bool = app
+            .wrap()
+            .query_wasm_smart(
+                registry.clone(),
+                &QueryMsg::CheckTokenCompliance {
+                    token_address: token.clone(),
+                    from: Some(sender.clone()),
+                    to: Some(receiver.clone()),
+                    amount: Some(Uint128::new(60)),
+                },
+            )
+            .unwrap();
+        assert!(compliant);
+
+        // but the second one would take the receiver past 10% of the supply. It is
+        // dropped, so a following transfer of 40 still fits
+        let verdicts: Vec<ComplianceCheckResponse> = app
+            .wrap()
+            .query_wasm_smart(
+                registry.clone(),
+                &QueryMsg::SimulateTransfers {
+                    token_address: token.clone(),
+                    transfers: vec![step(60), step(60), step(40)],
+                },
+            )
+            .unwrap();
+        let compliant: Vec<bool> = verdicts.iter().map(|v| v.compliant).collect();
+        assert_eq!(compliant, vec![true, false, true]);
+        assert_eq!(verdicts[1].failed_module, Some(module));
+    }
+
+    #[test]
+    fn module_failure_policy() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 5);
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        add_module(deps.as_mut(), "module_a").unwrap();
+        add_module(deps.as_mut(), "module_broken").unwrap();
+        add_module(deps.as_mut(), "module_c").unwrap();
+
+        // module_broken can no longer be queried
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { contract_addr, .. } => match contract_addr.as_str() {
+                "owner_roles" | "module_a" | "module_c" => {
+                    SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                }
+                _ => SystemResult::Ok(ContractResult::Err("unknown variant".to_string())),
+            },
+            _ => panic!("Unexpected query type"),
+        });
+
+        // Fails closed by default
+        let msg = QueryMsg::CheckTokenComplianceDetailed {
+            token_address: Addr::unchecked("token"),
+            from: Some(Addr::unchecked("sender")),
+            to: Some(Addr::unchecked("receiver")),
+            amount: Some(Uint128::new(100)),
+        };
+        query(deps.as_ref(), mock_env(), msg).unwrap_err();
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let msg = ExecuteMsg::SetModuleFailurePolicy {
+            policy: ModuleFailurePolicy::SkipAndFlag,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let policy: ModuleFailurePolicy = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetModuleFailurePolicy {},
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(policy, ModuleFailurePolicy::SkipAndFlag);
+
+        // The broken module is skipped and flagged, the others still decide
+        let res = detailed_check(deps.as_ref());
+        assert_eq!(
+            res,
+            ComplianceCheckResponse {
+                compliant: true,
+                modules_evaluated: 3,
+                failed_module: None,
+                exceeds_max_modules: false,
+                skipped_modules: vec![Addr::unchecked("module_broken")],
+                restriction: None,
+            }
+        );
+
+        // Simulations apply the same policy
+        let msg = QueryMsg::SimulateTransfers {
+            token_address: Addr::unchecked("token"),
+            transfers: vec![crate::registry::msg::TransferStep {
+                from: Some(Addr::unchecked("sender")),
+                to: Some(Addr::unchecked("receiver")),
+                amount: Some(Uint128::new(100)),
+            }],
+        };
+        let verdicts: Vec<ComplianceCheckResponse> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(verdicts[0].compliant);
+        assert_eq!(
+            verdicts[0].skipped_modules,
+            vec![Addr::unchecked("module_broken")]
+        );
+    }
+
+    #[test]
+    fn failing_owner_roles_query_is_attributed() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Err("unknown variant `IsOwner`".to_string()))
+        });
+
+        let err = add_module(deps.as_mut(), "module_a").unwrap_err();
+        match err {
+            ContractError::CrossContractQueryFailed {
+                contract, query, ..
+            } => {
+                assert_eq!(contract, "owner_roles");
+                assert_eq!(query, "IsOwner");
+            }
+            err => panic!("Unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn token_decimals_are_passed_to_modules() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        mock_roles_and_modules(&mut deps, vec![]);
+        add_module(deps.as_mut(), "module").unwrap();
+
+        // The module only accepts transfers of 18-decimal amounts
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { contract_addr, msg } => {
+                if contract_addr == "owner_roles" {
+                    return SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()));
+                }
+                let utils::QueryMsg::CheckTokenCompliance { decimals, .. } =
+                    from_json(msg).unwrap();
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&(decimals == Some(18))).unwrap(),
+                ))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+        let set_decimals = |deps: DepsMut, decimals: Option<u8>| {
+            let info = message_info(&Addr::unchecked("admin"), &[]);
+            let msg = ExecuteMsg::SetTokenDecimals {
+                token_address: Addr::unchecked("token"),
+                decimals,
+            };
+            execute(deps, mock_env(), info, msg)
+        };
+        let get_decimals = |deps: Deps| -> Option<u8> {
+            let msg = QueryMsg::GetTokenDecimals {
+                token_address: Addr::unchecked("token"),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // Without decimals on record, none are passed on
+        assert!(!detailed_check(deps.as_ref()).compliant);
+
+        set_decimals(deps.as_mut(), Some(18)).unwrap();
+        assert_eq!(get_decimals(deps.as_ref()), Some(18));
+        assert!(detailed_check(deps.as_ref()).compliant);
+
+        // Decimals are bounded
+        let err = set_decimals(deps.as_mut(), Some(19)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidDecimals { max: 18 }));
+
+        set_decimals(deps.as_mut(), None).unwrap();
+        assert_eq!(get_decimals(deps.as_ref()), None);
+    }
+
+    #[test]
+    fn transfers_are_forwarded_to_hooked_modules() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 5);
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        add_module(deps.as_mut(), "module_a").unwrap();
+        add_module(deps.as_mut(), "module_b").unwrap();
+        add_module(deps.as_mut(), "module_c").unwrap();
+
+        let admin = message_info(&Addr::unchecked("admin"), &[]);
+        for module in ["module_a", "module_c"] {
+            let msg = ExecuteMsg::SetModuleTransferHook {
+                token_address: Addr::unchecked("token"),
+                module_address: Addr::unchecked(module),
+                enabled: true,
+            };
+            execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+        }
+        // Inactive modules are not notified even with the hook enabled
+        let msg = ExecuteMsg::UpdateComplianceModule {
+            token_address: Addr::unchecked("token"),
+            module_address: Addr::unchecked("module_c"),
+            active: false,
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap();
+
+        // The hook can only be set on bound modules
+        let msg = ExecuteMsg::SetModuleTransferHook {
+            token_address: Addr::unchecked("token"),
+            module_address: Addr::unchecked("unbound"),
+            enabled: true,
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin, msg).unwrap_err();
+        assert!(matches!(err, ContractError::ComplianceNotFound {}));
+
+        // The token reports its transfer without holding any role
+        deps.querier
+            .update_wasm(|_| panic!("Transferred must not query"));
+        let msg = ExecuteMsg::Transferred {
+            from: Some(Addr::unchecked("sender")),
+            to: Some(Addr::unchecked("receiver")),
+            amount: Uint128::new(100),
+        };
+        let info = message_info(&Addr::unchecked("token"), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let expected = to_json_binary(&utils::ModuleHookExecuteMsg::Transferred {
+            token_address: Addr::unchecked("token"),
+            from: Some(Addr::unchecked("sender")),
+            to: Some(Addr::unchecked("receiver")),
+            amount: Uint128::new(100),
+        })
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "module_a".to_string(),
+                msg: expected,
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn register_and_delete_identities() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let user = deps.api.addr_make("user");
+        let identity = deps.api.addr_make("identity");
+        let new_identity = deps.api.addr_make("new_identity");
+        let register = |identity: &Addr, country: u16| ExecuteMsg::RegisterIdentity {
+            user_addr: user.clone(),
+            identity: identity.clone(),
+            country,
+        };
+        let get_identity = |deps: Deps| -> Option<crate::registry::msg::IdentityInfo> {
+            let msg = QueryMsg::GetIdentity {
+                user_addr: user.clone(),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let is_verified = |deps: Deps| -> bool {
+            let msg = QueryMsg::IsVerified {
+                user_addr: user.clone(),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(get_identity(deps.as_ref()), None);
+        assert!(!is_verified(deps.as_ref()));
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            register(&identity, 840),
+        )
+        .unwrap();
+        let registered = get_identity(deps.as_ref()).unwrap();
+        assert_eq!((registered.identity, registered.country), (identity, 840));
+        assert!(is_verified(deps.as_ref()));
+
+        // Registering again replaces the identity
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            register(&new_identity, 276),
+        )
+        .unwrap();
+        let registered = get_identity(deps.as_ref()).unwrap();
+        assert_eq!(
+            (registered.identity, registered.country),
+            (new_identity, 276)
+        );
+
+        // Malformed addresses are refused
+        let msg = ExecuteMsg::RegisterIdentity {
+            user_addr: user.clone(),
+            identity: Addr::unchecked("not-an-address"),
+            country: 840,
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+
+        let msg = ExecuteMsg::DeleteIdentity {
+            user_addr: user.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(get_identity(deps.as_ref()), None);
+        assert!(!is_verified(deps.as_ref()));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::IdentityNotFound { .. }));
+    }
+
+    #[test]
+    fn is_verified_requires_a_claim_for_every_topic() {
+        use crate::registry::msg::{
+            ClaimTopicStatus, ClaimTopicsQueryMsg, IdentityQueryMsg, PreflightVerificationResponse,
+            TopicVerification,
+        };
+
+        let mut deps = mock_dependencies();
+        let claim_topics = deps.api.addr_make("claim_topics");
+        let trusted_issuers = deps.api.addr_make("trusted_issuers");
+        let full_identity = deps.api.addr_make("full_identity");
+        let partial_identity = deps.api.addr_make("partial_identity");
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles"),
+            max_modules: None,
+            claim_topics_registry: Some(claim_topics.clone()),
+            trusted_issuers_registry: Some(trusted_issuers.clone()),
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&Addr::unchecked("creator"), &[]),
+            msg,
+        )
+        .unwrap();
+
+        // The registry requires topics 1 and 2, topic 3 was deprecated. One identity holds
+        // claims for topics 1 and 2, the other only for topic 2, and neither for topic 3.
+        let (topics_addr, trusted_addr, full_addr) = (
+            claim_topics.to_string(),
+            trusted_issuers.to_string(),
+            full_identity.to_string(),
+        );
+        deps.querier.update_wasm(move |query| match query {
+            cosmwasm_std::WasmQuery::Smart { contract_addr, msg } => {
+                let res = if contract_addr == "owner_roles" {
+                    to_json_binary(&true)
+                } else if *contract_addr == topics_addr {
+                    match from_json(msg).unwrap() {
+                        ClaimTopicsQueryMsg::GetClaimTopics { .. } => {
+                            to_json_binary(&vec![Uint128::new(1), Uint128::new(2), Uint128::new(3)])
+                        }
+                        ClaimTopicsQueryMsg::GetClaimTopicStatus { topic } => {
+                            to_json_binary(&if topic == Uint128::new(3) {
+                                ClaimTopicStatus::Deprecated
+                            } else {
+                                ClaimTopicStatus::Active
+                            })
+                        }
+                    }
+                } else {
+                    // Every required topic is verified in a single query
+                    let IdentityQueryMsg::PreflightVerification {
+                        required_topics,
+                        trusted_issuers_registry,
+                        ..
+                    } = from_json(msg).unwrap()
+                    else {
+                        panic!("Expected a preflight verification");
+                    };
+                    // Issuers are vetted by the identity against the configured registry
+                    assert_eq!(trusted_issuers_registry, Some(trusted_addr.clone()));
+                    assert_eq!(required_topics, vec![Uint128::new(1), Uint128::new(2)]);
+                    let topics = required_topics
+                        .iter()
+                        .map(|topic| TopicVerification {
+                            topic: *topic,
+                            satisfied: *contract_addr == full_addr || *topic == Uint128::new(2),
+                            claim_id: None,
+                            issuer: None,
+                        })
+                        .collect::<Vec<_>>();
+                    to_json_binary(&PreflightVerificationResponse {
+                        verified: topics.iter().all(|topic| topic.satisfied),
+                        topics,
+                    })
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let full_user = deps.api.addr_make("full_user");
+        let partial_user = deps.api.addr_make("partial_user");
+        for (user_addr, identity) in [
+            (full_user.clone(), full_identity),
+            (partial_user.clone(), partial_identity),
+        ] {
+            let msg = ExecuteMsg::RegisterIdentity {
+                user_addr,
+                identity,
+                country: 840,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let is_verified = |deps: Deps, user_addr: &Addr| -> bool {
+            let msg = QueryMsg::IsVerified {
+                user_addr: user_addr.clone(),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        assert!(is_verified(deps.as_ref(), &full_user));
+        assert!(!is_verified(deps.as_ref(), &partial_user));
+        assert!(!is_verified(deps.as_ref(), &deps.api.addr_make("stranger")));
+    }
+
+    #[test]
+    fn register_identities_batch() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let identity = deps.api.addr_make("identity");
+        let entries: Vec<(Addr, Addr, u16)> = (0..50)
+            .map(|i| {
+                (
+                    deps.api.addr_make(&format!("user_{i}")),
+                    identity.clone(),
+                    840,
+                )
+            })
+            .collect();
+        let is_registered = |deps: Deps, user_addr: &Addr| -> bool {
+            let msg = QueryMsg::GetIdentity {
+                user_addr: user_addr.clone(),
+            };
+            from_json::<Option<crate::registry::msg::IdentityInfo>>(
+                query(deps, mock_env(), msg).unwrap(),
+            )
+            .unwrap()
+            .is_some()
+        };
+
+        // One malformed address, or one user already registered, fails the whole batch
+        let mut invalid = entries.clone();
+        invalid[25].1 = Addr::unchecked("not-an-address");
+        let msg = ExecuteMsg::RegisterIdentitiesBatch { entries: invalid };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+        let mut repeated = entries.clone();
+        repeated[49].0 = entries[0].0.clone();
+        let msg = ExecuteMsg::RegisterIdentitiesBatch { entries: repeated };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::IdentityAlreadyRegistered { .. }
+        ));
+        assert!(entries
+            .iter()
+            .all(|(user_addr, _, _)| !is_registered(deps.as_ref(), user_addr)));
+
+        let msg = ExecuteMsg::RegisterIdentitiesBatch {
+            entries: entries.clone(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes[1], ("count", "50"));
+        let registered: Vec<&str> = res
+            .attributes
+            .iter()
+            .filter(|attr| attr.key == "user_addr")
+            .map(|attr| attr.value.as_str())
+            .collect();
+        assert_eq!(registered.len(), 50);
+        assert!(entries
+            .iter()
+            .all(|(user_addr, _, _)| is_registered(deps.as_ref(), user_addr)));
+
+        let msg = ExecuteMsg::RegisterIdentitiesBatch {
+            entries: entries[..1].to_vec(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::IdentityAlreadyRegistered { .. }
+        ));
+    }
+
+    #[test]
+    fn can_transfer_between_allowed_countries() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let identity = deps.api.addr_make("identity");
+        let us_investor = deps.api.addr_make("us_investor");
+        let de_investor = deps.api.addr_make("de_investor");
+        let blocked_investor = deps.api.addr_make("blocked_investor");
+        let unregistered = deps.api.addr_make("unregistered");
+        let msg = ExecuteMsg::RegisterIdentitiesBatch {
+            entries: vec![
+                (us_investor.clone(), identity.clone(), 840),
+                (de_investor.clone(), identity.clone(), 276),
+                (blocked_investor.clone(), identity, 408),
+            ],
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        for country in [840, 276, 408] {
+            let msg = ExecuteMsg::SetCountryAllowed {
+                country,
+                allowed: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::SetCountryAllowed {
+            country: 408,
+            allowed: false,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let can_transfer = |deps: Deps, from: &Addr, to: &Addr| -> bool {
+            let msg = QueryMsg::CanTransfer {
+                from: from.clone(),
+                to: to.clone(),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        assert!(can_transfer(deps.as_ref(), &us_investor, &de_investor));
+        assert!(!can_transfer(
+            deps.as_ref(),
+            &us_investor,
+            &blocked_investor
+        ));
+        assert!(!can_transfer(
+            deps.as_ref(),
+            &blocked_investor,
+            &de_investor
+        ));
+        assert!(!can_transfer(deps.as_ref(), &us_investor, &unregistered));
+    }
+
+    #[test]
+    fn pause_and_freeze_block_transfers() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let identity = deps.api.addr_make("identity");
+        let alice = deps.api.addr_make("alice");
+        let bob = deps.api.addr_make("bob");
+        let carol = deps.api.addr_make("carol");
+        let msg = ExecuteMsg::RegisterIdentitiesBatch {
+            entries: vec![
+                (alice.clone(), identity.clone(), 840),
+                (bob.clone(), identity.clone(), 840),
+                (carol.clone(), identity, 840),
+            ],
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SetCountryAllowed {
+            country: 840,
+            allowed: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let can_transfer = |deps: Deps, from: &Addr, to: &Addr| -> bool {
+            let msg = QueryMsg::CanTransfer {
+                from: from.clone(),
+                to: to.clone(),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let is_paused = |deps: Deps| -> bool {
+            from_json(query(deps, mock_env(), QueryMsg::IsPaused {}).unwrap()).unwrap()
+        };
+        assert!(!is_paused(deps.as_ref()));
+        assert!(can_transfer(deps.as_ref(), &alice, &bob));
+
+        // A global pause blocks every transfer until lifted
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        assert!(is_paused(deps.as_ref()));
+        assert!(!can_transfer(deps.as_ref(), &alice, &bob));
+        assert!(!can_transfer(deps.as_ref(), &bob, &carol));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+        assert!(!is_paused(deps.as_ref()));
+        assert!(can_transfer(deps.as_ref(), &alice, &bob));
+
+        // Freezing a user blocks transfers from and to them only
+        let msg = ExecuteMsg::SetAddressFrozen {
+            addr: bob.clone(),
+            frozen: true,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::IsFrozen { addr: bob.clone() };
+        let frozen: bool = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(frozen);
+        assert!(!can_transfer(deps.as_ref(), &alice, &bob));
+        assert!(!can_transfer(deps.as_ref(), &bob, &carol));
+        assert!(can_transfer(deps.as_ref(), &alice, &carol));
+
+        let msg = ExecuteMsg::SetAddressFrozen {
+            addr: bob.clone(),
+            frozen: false,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(can_transfer(deps.as_ref(), &alice, &bob));
+    }
+
+    #[test]
+    fn list_frozen_addresses_pages_through_frozen_users() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        mock_roles_and_modules(&mut deps, vec![]);
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let mut users = ["alice", "bob", "carol", "dave", "erin"]
+            .map(|name| deps.api.addr_make(name))
+            .to_vec();
+        for addr in &users {
+            let msg = ExecuteMsg::SetAddressFrozen {
+                addr: addr.clone(),
+                frozen: true,
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        // Unfrozen users leave the list, frozen ones stay listed once when frozen again
+        for (addr, frozen) in [(users[1].clone(), false), (users[3].clone(), true)] {
+            let msg = ExecuteMsg::SetAddressFrozen { addr, frozen };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        users.remove(1);
+        users.sort();
+
+        let mut listed = vec![];
+        let mut start_after = None;
+        loop {
+            let msg = QueryMsg::ListFrozenAddresses {
+                start_after,
+                limit: Some(3),
+            };
+            let page: Vec<Addr> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert!(page.len() <= 3);
+            let Some(last) = page.last().cloned() else {
+                break;
+            };
+            listed.extend(page);
+            start_after = Some(last);
+        }
+        assert_eq!(listed, users);
+    }
+
+    #[test]
+    fn country_allow_list_fails_compliance_checks() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 5);
+        mock_roles_and_modules(&mut deps, vec![]);
+        add_module(deps.as_mut(), "module_a").unwrap();
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        let identity = deps.api.addr_make("identity");
+        let us_investor = deps.api.addr_make("us_investor");
+        let blocked_investor = deps.api.addr_make("blocked_investor");
+        let unregistered = deps.api.addr_make("unregistered");
+        let msg = ExecuteMsg::RegisterIdentitiesBatch {
+            entries: vec![
+                (us_investor.clone(), identity.clone(), 840),
+                (blocked_investor.clone(), identity, 408),
+            ],
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let check = |deps: Deps, from: Option<&Addr>, to: Option<&Addr>| -> bool {
+            let msg = QueryMsg::CheckTokenCompliance {
+                token_address: Addr::unchecked("token"),
+                from: from.cloned(),
+                to: to.cloned(),
+                amount: Some(Uint128::new(100)),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        // Without an allow-list, countries are left to the modules
+        assert!(check(
+            deps.as_ref(),
+            Some(&blocked_investor),
+            Some(&unregistered)
+        ));
+
+        let msg = ExecuteMsg::SetCountryAllowed {
+            country: 840,
+            allowed: true,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(check(deps.as_ref(), Some(&us_investor), Some(&us_investor)));
+        assert!(!check(
+            deps.as_ref(),
+            Some(&us_investor),
+            Some(&blocked_investor)
+        ));
+        assert!(!check(
+            deps.as_ref(),
+            Some(&blocked_investor),
+            Some(&us_investor)
+        ));
+        assert!(!check(
+            deps.as_ref(),
+            Some(&us_investor),
+            Some(&unregistered)
+        ));
+        assert!(!check(
+            deps.as_ref(),
+            Some(&unregistered),
+            Some(&us_investor)
+        ));
+
+        // Mints and burns only require the holder's side to be allowed
+        assert!(check(deps.as_ref(), None, Some(&us_investor)));
+        assert!(check(deps.as_ref(), Some(&us_investor), None));
+        assert!(!check(deps.as_ref(), None, Some(&unregistered)));
+        assert!(!check(deps.as_ref(), Some(&blocked_investor), None));
+
+        let msg = QueryMsg::CheckTokenComplianceDetailed {
+            token_address: Addr::unchecked("token"),
+            from: Some(us_investor),
+            to: Some(blocked_investor.clone()),
+            amount: Some(Uint128::new(100)),
+        };
+        let res: ComplianceCheckResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res.restriction,
+            Some(format!(
+                "{blocked_investor} resides in country 408, which is not allowed"
+            ))
+        );
+    }
+
+    #[test]
+    fn pre_check_records_blocked_decisions() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 5);
+        mock_roles_and_modules(&mut deps, vec![]);
+        add_module(deps.as_mut(), "module_a").unwrap();
+
+        // Anyone may record a decision, whatever its verdict
+        let pre_check = |deps: DepsMut| -> Event {
+            let msg = ExecuteMsg::PreCheckTransfer {
+                token_address: Addr::unchecked("token"),
+                from: Some(Addr::unchecked("sender")),
+                to: None,
+                amount: Some(Uint128::new(100)),
+            };
+            let info = message_info(&Addr::unchecked("anyone"), &[]);
+            let res = execute(deps, mock_env(), info, msg).unwrap();
+            res.events
+                .into_iter()
+                .find(|event| event.ty == "compliance_decision")
+                .unwrap()
+        };
+        assert_eq!(
+            pre_check(deps.as_mut()),
+            Event::new("compliance_decision")
+                .add_attribute("token_address", "token")
+                .add_attribute("from", "sender")
+                .add_attribute("to", "")
+                .add_attribute("amount", "100")
+                .add_attribute("verdict", "allowed")
+        );
+
+        mock_roles_and_modules(&mut deps, vec!["module_a"]);
+        let event = pre_check(deps.as_mut());
+        assert_eq!(event.attributes[4].value, "blocked");
+        assert_eq!(
+            event.attributes[5].value,
+            "Rejected by compliance module module_a"
+        );
+
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Pause {}).unwrap();
+        let event = pre_check(deps.as_mut());
+        assert_eq!(event.attributes[4].value, "blocked");
+        assert_eq!(event.attributes[5].value, "Transfers are paused");
+    }
+
+    #[test]
+    fn pause_and_freeze_fail_compliance_checks() {
+        let mut deps = mock_dependencies();
+        setup_contract_with_max_modules(deps.as_mut(), 5);
+        mock_roles_and_modules(&mut deps, vec![]);
+        add_module(deps.as_mut(), "module_a").unwrap();
+
+        let check = |deps: Deps, from: Option<&str>, to: Option<&str>| -> bool {
+            let msg = QueryMsg::CheckTokenCompliance {
+                token_address: Addr::unchecked("token"),
+                from: from.map(Addr::unchecked),
+                to: to.map(Addr::unchecked),
+                amount: Some(Uint128::new(100)),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let simulate = |deps: Deps| -> Vec<ComplianceCheckResponse> {
+            let msg = QueryMsg::SimulateTransfers {
+                token_address: Addr::unchecked("token"),
+                transfers: vec![
+                    TransferStep {
+                        from: Some(Addr::unchecked("sender")),
+                        to: Some(Addr::unchecked("receiver")),
+                        amount: Some(Uint128::new(100)),
+                    },
+                    TransferStep {
+                        from: None,
+                        to: Some(Addr::unchecked("sender")),
+                        amount: Some(Uint128::new(100)),
+                    },
+                ],
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        assert!(check(deps.as_ref(), Some("sender"), Some("receiver")));
+
+        // A paused registry rejects every transfer without consulting the modules
+        let info = message_info(&Addr::unchecked("admin"), &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        assert!(!check(deps.as_ref(), Some("sender"), Some("receiver")));
+        assert!(!check(deps.as_ref(), None, Some("receiver")));
+        let res = detailed_check(deps.as_ref());
+        assert_eq!(res.modules_evaluated, 0);
+        assert_eq!(res.restriction, Some("Transfers are paused".to_string()));
+        assert!(simulate(deps.as_ref()).iter().all(|res| !res.compliant));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+        assert!(check(deps.as_ref(), Some("sender"), Some("receiver")));
+
+        // A frozen user can neither send nor receive, including mints to them
+        let msg = ExecuteMsg::SetAddressFrozen {
+            addr: Addr::unchecked("sender"),
+            frozen: true,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(!check(deps.as_ref(), Some("sender"), Some("receiver")));
+        assert!(!check(deps.as_ref(), Some("receiver"), Some("sender")));
+        assert!(check(deps.as_ref(), None, Some("receiver")));
+        let res = detailed_check(deps.as_ref());
+        assert_eq!(res.restriction, Some("sender is frozen".to_string()));
+        let verdicts = simulate(deps.as_ref());
+        assert!(!verdicts[0].compliant);
+        assert!(!verdicts[1].compliant);
+    }
+
+    // Stands in for the owner roles contract, granting every role
+    fn roles_noop(
+        _: DepsMut,
+        _: Env,
+        _: MessageInfo,
+        _: cosmwasm_std::Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+    fn grant_all(_: Deps, _: Env, _: roles::owner_roles::QueryMsg) -> StdResult<Binary> {
+        to_json_binary(&true)
+    }
+
+    /// A cw20-base token checking its movements with a fresh registry, `admin` as its
+    /// minter. Returns the owner roles stand-in, the registry and the token.
+    fn setup_cw20_with_registry(
+        app: &mut cw_multi_test::App,
+        admin: &Addr,
+        balances: &[(&Addr, u128)],
+    ) -> (Addr, Addr, Addr) {
+        use cw20::{Cw20Coin, MinterResponse};
+        use cw20_base::msg::{InstantiateMsg as TokenInstantiateMsg, InstantiateTokenInfo};
+        use cw_multi_test::{ContractWrapper, Executor};
+
+        let roles_code = app.store_code(Box::new(ContractWrapper::new(
+            roles_noop, roles_noop, grant_all,
+        )));
+        let owner_roles = app
+            .instantiate_contract(
+                roles_code,
+                admin.clone(),
+                &cosmwasm_std::Empty {},
+                &[],
+                "roles",
+                None,
+            )
+            .unwrap();
+        let registry_code =
+            app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+        let registry = app
+            .instantiate_contract(
+                registry_code,
+                admin.clone(),
+                &InstantiateMsg {
+                    owner_roles_address: owner_roles.clone(),
+                    max_modules: None,
+                    claim_topics_registry: None,
+                    trusted_issuers_registry: None,
+                },
+                &[],
+                "registry",
+                None,
+            )
+            .unwrap();
+        let token_code = app.store_code(Box::new(ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        )));
+        let token = app
+            .instantiate_contract(
+                token_code,
+                admin.clone(),
+                &TokenInstantiateMsg {
+                    token_info: InstantiateTokenInfo {
+                        name: "Security Token".to_string(),
+                        symbol: "SEC".to_string(),
+                        decimals: 6,
+                        initial_balances: balances
+                            .iter()
+                            .map(|(address, amount)| Cw20Coin {
+                                address: address.to_string(),
+                                amount: Uint128::new(*amount),
+                            })
+                            .collect(),
+                        mint: Some(MinterResponse {
+                            minter: admin.to_string(),
+                            cap: None,
+                        }),
+                        marketing: None,
+                    },
+                    registeries: cw20_base::msg::Registeries {
+                        compliance_address: registry.to_string(),
+                    },
+                },
+                &[],
+                "token",
+                None,
+            )
+            .unwrap();
+        (owner_roles, registry, token)
+    }
+
+    #[test]
+    fn cw20_token_is_checked_under_its_contract_address() {
+        use crate::modules::max_concentration;
+        use cosmwasm_std::Decimal;
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let holder = app.api().addr_make("holder");
+        let receiver = app.api().addr_make("receiver");
+        let (owner_roles, registry, token) =
+            setup_cw20_with_registry(&mut app, &admin, &[(&holder, 1000)]);
+
+        // Bind a module that reads balances and the supply from the token
+        let module_code = app.store_code(Box::new(ContractWrapper::new(
+            max_concentration::contract::execute,
+            max_concentration::contract::instantiate,
+            max_concentration::contract::query,
+        )));
+        let module = app
+            .instantiate_contract(
+                module_code,
+                admin.clone(),
+                &max_concentration::InstantiateMsg {
+                    owner_roles_address: owner_roles,
+                },
+                &[],
+                "max_concentration",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            module.clone(),
+            &max_concentration::ExecuteMsg::SetConcentrationLimit {
+                token_address: token.clone(),
+                limit: max_concentration::msg::ConcentrationLimit {
+                    max_share: Decimal::percent(10),
+                    bootstrap_supply: Uint128::zero(),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin,
+            registry,
+            &ExecuteMsg::AddComplianceModule {
+                token_address: token.clone(),
+                module_address: module,
+                module_name: "max_concentration".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // The module answers for the token, up to 10% of the supply and no further
+        let transfer = |amount: u128| Cw20ExecuteMsg::Transfer {
+            recipient: receiver.to_string(),
+            amount: Uint128::new(amount),
+        };
+        app.execute_contract(holder.clone(), token.clone(), &transfer(100), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(holder, token, &transfer(1), &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Compliance check failed");
+    }
+
+    #[test]
+    fn cw20_token_reports_its_transfers_to_hooked_modules() {
+        use crate::modules::transfer_count;
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let holder = app.api().addr_make("holder");
+        let receiver = app.api().addr_make("receiver");
+        let (owner_roles, registry, token) =
+            setup_cw20_with_registry(&mut app, &admin, &[(&holder, 1000)]);
+
+        let module_code = app.store_code(Box::new(ContractWrapper::new(
+            transfer_count::contract::execute,
+            transfer_count::contract::instantiate,
+            transfer_count::contract::query,
+        )));
+        let module = app
+            .instantiate_contract(
+                module_code,
+                admin.clone(),
+                &transfer_count::InstantiateMsg {
+                    owner_roles_address: owner_roles,
+                    registry_address: registry.clone(),
+                },
+                &[],
+                "transfer_count",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            module.clone(),
+            &transfer_count::ExecuteMsg::SetTransferLimit {
+                token_address: token.clone(),
+                max_transfers: 2,
+                window_days: 1,
+            },
+            &[],
+        )
+        .unwrap();
+        for msg in [
+            ExecuteMsg::AddComplianceModule {
+                token_address: token.clone(),
+                module_address: module.clone(),
+                module_name: "transfer_count".to_string(),
+            },
+            ExecuteMsg::SetModuleTransferHook {
+                token_address: token.clone(),
+                module_address: module.clone(),
+                enabled: true,
+            },
+        ] {
+            app.execute_contract(admin.clone(), registry.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        // Transfers and burns count against the holder, mints have no sender
+        let transfer = Cw20ExecuteMsg::Transfer {
+            recipient: receiver.to_string(),
+            amount: Uint128::new(10),
+        };
+        app.execute_contract(holder.clone(), token.clone(), &transfer, &[])
+            .unwrap();
+        let mint = Cw20ExecuteMsg::Mint {
+            recipient: holder.to_string(),
+            amount: Uint128::new(10),
+        };
+        app.execute_contract(admin, token.clone(), &mint, &[])
+            .unwrap();
+        let burn = Cw20ExecuteMsg::Burn {
+            amount: Uint128::new(10),
+        };
+        app.execute_contract(holder.clone(), token.clone(), &burn, &[])
+            .unwrap();
+        let count: u32 = app
+            .wrap()
+            .query_wasm_smart(
+                &module,
+                &transfer_count::QueryMsg::GetTransferCount {
+                    token_address: token.clone(),
+                    address: holder.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+
+        // The cap is reached, until the window moves past today
+        let err = app
+            .execute_contract(holder.clone(), token.clone(), &transfer, &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Compliance check failed");
+        app.update_block(|block| {
+            block.time = block
+                .time
+                .plus_seconds(transfer_count::state::SECONDS_PER_DAY)
+        });
+        app.execute_contract(holder, token, &transfer, &[]).unwrap();
+    }
+
+    #[test]
+    fn cw20_token_keeps_the_holder_count_of_hooked_modules() {
+        use crate::modules::max_holders;
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, ContractWrapper, Executor};
+
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let holder = app.api().addr_make("holder");
+        let second = app.api().addr_make("second");
+        let third = app.api().addr_make("third");
+        let (owner_roles, registry, token) =
+            setup_cw20_with_registry(&mut app, &admin, &[(&holder, 1000)]);
+
+        let module_code = app.store_code(Box::new(ContractWrapper::new(
+            max_holders::contract::execute,
+            max_holders::contract::instantiate,
+            max_holders::contract::query,
+        )));
+        let module = app
+            .instantiate_contract(
+                module_code,
+                admin.clone(),
+                &max_holders::InstantiateMsg {
+                    owner_roles_address: owner_roles,
+                    registry_address: registry.clone(),
+                },
+                &[],
+                "max_holders",
+                None,
+            )
+            .unwrap();
+        // The token already has one holder when the module is bound to it
+        for msg in [
+            max_holders::ExecuteMsg::SetMaxHolders {
+                token_address: token.clone(),
+                max: 2,
+            },
+            max_holders::ExecuteMsg::SetHolderCount {
+                token_address: token.clone(),
+                count: 1,
+            },
+        ] {
+            app.execute_contract(admin.clone(), module.clone(), &msg, &[])
+                .unwrap();
+        }
+        for msg in [
+            ExecuteMsg::AddComplianceModule {
+                token_address: token.clone(),
+                module_address: module.clone(),
+                module_name: "max_holders".to_string(),
+            },
+            ExecuteMsg::SetModuleTransferHook {
+                token_address: token.clone(),
+                module_address: module.clone(),
+                enabled: true,
+            },
+        ] {
+            app.execute_contract(admin.clone(), registry.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let transfer = |recipient: &Addr, amount: u128| Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        };
+        app.execute_contract(holder.clone(), token.clone(), &transfer(&second, 100), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(holder.clone(), token.clone(), &transfer(&third, 100), &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Compliance check failed");
+
+        // Minting to a new holder is capped as well, the minter holding nothing to give up
+        let mint = Cw20ExecuteMsg::Mint {
+            recipient: third.to_string(),
+            amount: Uint128::new(100),
+        };
+        let err = app
+            .execute_contract(admin.clone(), token.clone(), &mint, &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Compliance check failed");
+
+        // Once the second holder sells out, the third one can come in
+        app.execute_contract(second, token.clone(), &transfer(&holder, 100), &[])
+            .unwrap();
+        app.execute_contract(holder, token.clone(), &transfer(&third, 100), &[])
+            .unwrap();
+        let count: u64 = app
+            .wrap()
+            .query_wasm_smart(
+                &module,
+                &max_holders::QueryMsg::GetHolderCount {
+                    token_address: token,
+                },
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn cw20_mints_under_a_country_allow_list() {
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, Executor};
+
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let investor = app.api().addr_make("investor");
+        let unregistered = app.api().addr_make("unregistered");
+        let identity = app.api().addr_make("identity");
+        let (_, registry, token) = setup_cw20_with_registry(&mut app, &admin, &[]);
+
+        // Only the investor is registered, and their country allowed
+        for msg in [
+            ExecuteMsg::RegisterIdentitiesBatch {
+                entries: vec![(investor.clone(), identity, 840)],
+            },
+            ExecuteMsg::SetCountryAllowed {
+                country: 840,
+                allowed: true,
+            },
+        ] {
+            app.execute_contract(admin.clone(), registry.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        // The minter needs no identity of its own, only the recipient is checked
+        let mint = |recipient: &Addr| Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(100),
+        };
+        app.execute_contract(admin.clone(), token.clone(), &mint(&investor), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(admin, token, &mint(&unregistered), &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Compliance check failed");
+    }
 }
@@ -9,6 +9,31 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("CrossContractQueryFailed: {query} query to {contract} failed: {reason}")]
+    CrossContractQueryFailed {
+        contract: String,
+        query: String,
+        reason: String,
+    },
+
     #[error("ComplianceNotFound")]
     ComplianceNotFound {},
+
+    #[error("ComplianceAlreadyExists")]
+    ComplianceAlreadyExists {},
+
+    #[error("TooManyModules: at most {max} active modules per token")]
+    TooManyModules { max: u32 },
+
+    #[error("InvalidDecimals: at most {max} decimals")]
+    InvalidDecimals { max: u8 },
+
+    #[error("IdentityNotFound: no identity registered for {user_addr}")]
+    IdentityNotFound { user_addr: String },
+
+    #[error("IdentityAlreadyRegistered: {user_addr} already has an identity")]
+    IdentityAlreadyRegistered { user_addr: String },
+
+    #[error("InvalidIdentityBatch: expected 1 to {max} entries")]
+    InvalidIdentityBatch { max: usize },
 }
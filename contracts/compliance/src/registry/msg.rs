@@ -1,9 +1,19 @@
-use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Addr;
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Uint128};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner_roles_address: Addr,
+    /// Maximum number of active modules evaluated per token, defaults to `DEFAULT_MAX_MODULES`
+    pub max_modules: Option<u32>,
+    /// Claim topics registry listing the topics `IsVerified` requires claims for.
+    /// Without one, a registered identity is verified.
+    #[serde(default)]
+    pub claim_topics_registry: Option<Addr>,
+    /// Trusted issuers registry the issuers of those claims must be trusted by for the
+    /// topic. Without one, claims from any issuer count.
+    #[serde(default)]
+    pub trusted_issuers_registry: Option<Addr>,
 }
 
 #[cw_serde]
@@ -23,6 +33,243 @@ pub enum ExecuteMsg {
         module_address: Addr,
         active: bool,
     },
+
+    SetMaxModules {
+        max_modules: u32,
+    },
+
+    /// Rebind a token from `old` to `new`, keeping the binding's name and status.
+    /// With `carry_state`, `new` is first seeded with the state `old` exports for the token
+    /// (see `utils::ModuleStateQueryMsg`); the registry must then be allowed to execute on `new`.
+    ReplaceModule {
+        token_address: Addr,
+        old: Addr,
+        new: Addr,
+        carry_state: bool,
+    },
+
+    /// Choose how a module whose compliance query fails is treated
+    SetModuleFailurePolicy {
+        policy: ModuleFailurePolicy,
+    },
+
+    /// Record the decimals the token's amounts are expressed in, passed on to every module
+    /// checking its transfers. `None` forgets them.
+    SetTokenDecimals {
+        token_address: Addr,
+        decimals: Option<u8>,
+    },
+
+    /// Enable or disable forwarding of the token's completed transfers to a bound module,
+    /// see `utils::ModuleHookExecuteMsg`. The registry must be allowed to execute on it.
+    SetModuleTransferHook {
+        token_address: Addr,
+        module_address: Addr,
+        enabled: bool,
+    },
+
+    /// Register `identity` as the on-chain identity of `user_addr`, residing in `country`
+    /// (ISO 3166-1 numeric). Replaces any identity already registered for the user.
+    RegisterIdentity {
+        user_addr: Addr,
+        identity: Addr,
+        country: u16,
+    },
+
+    DeleteIdentity {
+        user_addr: Addr,
+    },
+
+    /// Allow or disallow transfers involving users residing in `country`, see `CanTransfer`.
+    /// Once any country is configured, compliance checks also require every party of a
+    /// transfer to have an identity registered in an allowed country.
+    SetCountryAllowed {
+        country: u16,
+        allowed: bool,
+    },
+
+    /// Register several `(user_addr, identity, country)` at once. Nothing is registered if
+    /// any address is invalid or any user is repeated or already has an identity.
+    RegisterIdentitiesBatch {
+        entries: Vec<(Addr, Addr, u16)>,
+    },
+
+    /// Stop all transfers in an emergency: every compliance check and `CanTransfer` fails
+    Pause {},
+    Unpause {},
+
+    /// Freeze or unfreeze a single user, failing compliance checks of transfers from and to them
+    SetAddressFrozen {
+        addr: Addr,
+        frozen: bool,
+    },
+
+    /// Sent by a token contract after a transfer went through. Forwarded to its active
+    /// modules that have the transfer hook enabled; not gated by the compliance manager role.
+    Transferred {
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    },
+
+    /// Check a transfer and emit its `compliance_decision` event, with the verdict and the
+    /// reason for a rejection. Never fails on a blocked transfer, so the decision is kept on
+    /// chain even when the transfer itself would revert; not gated by the compliance manager role.
+    PreCheckTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(bool)]
+    CheckTokenCompliance {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    },
+    #[returns(ComplianceCheckResponse)]
+    CheckTokenComplianceDetailed {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+    },
+    #[returns(u32)]
+    GetMaxModules {},
+    #[returns(ModuleFailurePolicy)]
+    GetModuleFailurePolicy {},
+    #[returns(Option<u8>)]
+    GetTokenDecimals { token_address: Addr },
+    /// Check a sequence of transfers as if each compliant one executed before the next.
+    /// Modules implementing `utils::ModuleSimulationQueryMsg` see the effects of earlier
+    /// steps, other modules check each step on its own. Returns one verdict per step.
+    #[returns(Vec<ComplianceCheckResponse>)]
+    SimulateTransfers {
+        token_address: Addr,
+        transfers: Vec<TransferStep>,
+    },
+    /// The identity registered for the user, `None` if there is none
+    #[returns(Option<IdentityInfo>)]
+    GetIdentity { user_addr: Addr },
+    /// Whether the user has an identity registered that holds a valid claim, from an issuer
+    /// trusted for the topic, for every active topic of the claim topics registry
+    #[returns(bool)]
+    IsVerified { user_addr: Addr },
+    /// Whether transfers are not paused, neither user is frozen, and both users have an
+    /// identity registered in an allowed country
+    #[returns(bool)]
+    CanTransfer { from: Addr, to: Addr },
+    #[returns(bool)]
+    IsPaused {},
+    #[returns(bool)]
+    IsFrozen { addr: Addr },
+    /// A page of the frozen addresses, in ascending order. Freezing covers an address's
+    /// whole balance, so there is no frozen amount to report.
+    #[returns(Vec<Addr>)]
+    ListFrozenAddresses {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+}
+
+/// Subset of the claim topics registry's queries the registry relies on
+#[cw_serde]
+pub enum ClaimTopicsQueryMsg {
+    GetClaimTopics {
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    },
+    GetClaimTopicStatus {
+        topic: Uint128,
+    },
+}
+
+/// Mirror of the claim topics registry's topic status
+#[cw_serde]
+pub enum ClaimTopicStatus {
+    Active,
+    Deprecated,
+    NotFound,
+}
+
+/// Subset of the identity contract's queries the registry relies on
+#[cw_serde]
+pub enum IdentityQueryMsg {
+    VerifyClaim {
+        claim_id: Uint128,
+        user_addr: Addr,
+        namespace: Option<String>,
+        trusted_issuers_registry: Option<String>,
+    },
+    PreflightVerification {
+        user_addr: Addr,
+        required_topics: Vec<Uint128>,
+        trusted_issuers_registry: Option<String>,
+    },
+}
+
+/// Mirror of the identity contract's preflight verification response
+#[cw_serde]
+pub struct PreflightVerificationResponse {
+    pub verified: bool,
+    pub topics: Vec<TopicVerification>,
+}
+
+#[cw_serde]
+pub struct TopicVerification {
+    pub topic: Uint128,
+    pub satisfied: bool,
+    pub claim_id: Option<String>,
+    pub issuer: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct IdentityInfo {
+    /// The user's on_chain_id contract
+    pub identity: Addr,
+    /// ISO 3166-1 numeric code of the user's country of residence
+    pub country: u16,
+}
+
+#[cw_serde]
+pub struct TransferStep {
+    pub from: Option<Addr>,
+    pub to: Option<Addr>,
+    pub amount: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct ComplianceCheckResponse {
+    pub compliant: bool,
+    /// Number of modules queried before a verdict was reached
+    pub modules_evaluated: u32,
+    /// The module that rejected the transfer, if any
+    pub failed_module: Option<Addr>,
+    /// Set when the token has more active modules than the configured cap
+    pub exceeds_max_modules: bool,
+    /// Modules that could not be queried and were skipped under `ModuleFailurePolicy::SkipAndFlag`
+    pub skipped_modules: Vec<Addr>,
+    /// Why the registry itself rejected the transfer before consulting any module, e.g.
+    /// because transfers are paused or a party is frozen
+    #[serde(default)]
+    pub restriction: Option<String>,
+}
+
+/// How a compliance module whose query fails (e.g. after a broken migration) is treated
+#[cw_serde]
+#[derive(Default)]
+pub enum ModuleFailurePolicy {
+    /// The whole compliance check errors
+    #[default]
+    FailClosed,
+    /// The module is skipped and reported in `ComplianceCheckResponse::skipped_modules`
+    SkipAndFlag,
 }
 
 #[cw_serde]
@@ -30,4 +277,7 @@ pub struct ComplianceModule {
     pub active: bool,
     pub name: String,
     pub address: Addr,
+    /// Whether the module is notified of the token's transfers, unset on older bindings
+    #[serde(default)]
+    pub transfer_hook: bool,
 }
@@ -1,8 +1,38 @@
 use cosmwasm_std::Addr;
 use cw_storage_plus::{Item, Map};
 
-use crate::registry::msg::ComplianceModule;
+use crate::registry::msg::{ComplianceModule, IdentityInfo, ModuleFailurePolicy};
+
+pub const DEFAULT_MAX_MODULES: u32 = 10;
+/// Most decimals a token may be configured with, as for CW20 tokens
+pub const MAX_TOKEN_DECIMALS: u8 = 18;
+/// Most transfers a single `SimulateTransfers` query may contain
+pub const MAX_SIMULATION_STEPS: usize = 50;
+/// Most identities a single `RegisterIdentitiesBatch` may register
+pub const MAX_IDENTITIES_PER_BATCH: usize = 100;
+/// Most topics `is_verified` asks an identity to verify in one query, as the identity
+/// contract accepts
+pub const MAX_TOPICS_PER_VERIFICATION: usize = 100;
 
 pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_contract_address");
+/// Registries `IsVerified` consults, unset when not configured at instantiation
+pub const CLAIM_TOPICS_REGISTRY: Item<Addr> = Item::new("claim_topics_registry");
+pub const TRUSTED_ISSUERS_REGISTRY: Item<Addr> = Item::new("trusted_issuers_registry");
+pub const MAX_MODULES: Item<u32> = Item::new("max_modules");
+/// Unset on contracts instantiated before the policy existed, read as `FailClosed`
+pub const MODULE_FAILURE_POLICY: Item<ModuleFailurePolicy> = Item::new("module_failure_policy");
+/// Decimals of the token amounts passed to modules, for the tokens that have them configured
+pub const TOKEN_DECIMALS: Map<Addr, u8> = Map::new("token_decimals");
+/// User -> the identity registered for them
+pub const IDENTITIES: Map<&Addr, IdentityInfo> = Map::new("identities");
+/// Countries whose residents may transfer, only allowed countries are stored
+pub const ALLOWED_COUNTRIES: Map<u16, bool> = Map::new("allowed_countries");
+/// Set once a country allow-list is configured, from then on compliance checks require both
+/// parties to reside in an allowed country
+pub const COUNTRY_RESTRICTION: Item<bool> = Item::new("country_restriction");
+/// Whether all transfers are stopped, unset on contracts instantiated before pausing existed
+pub const PAUSED: Item<bool> = Item::new("paused");
+/// Users that may not transfer, only frozen users are stored
+pub const FROZEN: Map<&Addr, bool> = Map::new("frozen");
 pub const TOKEN_COMPLIANCE_MODULES: Map<(Addr, Addr), ComplianceModule> =
     Map::new("token_compliance_modules");
@@ -4,6 +4,7 @@ use cosmwasm_std::{
 };
 use cw20::{AllowanceResponse, Cw20ReceiveMsg, Expiration};
 
+use crate::contract::{transferred_hook, validate_compliance};
 use crate::error::ContractError;
 use crate::state::{ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, TOKEN_INFO};
 
@@ -132,7 +133,16 @@ pub fn execute_transfer_from(
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
 
-    // deduct allowance before doing anything else have enough allowance
+    // the owner's tokens move, so the owner is checked rather than the spender
+    let decision = validate_compliance(
+        deps.as_ref(),
+        &env,
+        Some(owner_addr.clone()),
+        Some(rcpt_addr.clone()),
+        Some(amount),
+    )?;
+
+    // deduct allowance before touching balances, failing without enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     BALANCES.update(
@@ -148,13 +158,18 @@ pub fn execute_transfer_from(
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
-    let res = Response::new().add_attributes(vec![
-        attr("action", "transfer_from"),
-        attr("from", owner),
-        attr("to", recipient),
-        attr("by", info.sender),
-        attr("amount", amount),
-    ]);
+    let hook = transferred_hook(deps.as_ref(), Some(owner_addr), Some(rcpt_addr), amount)?;
+
+    let res = Response::new()
+        .add_message(hook)
+        .add_attributes(vec![
+            attr("action", "transfer_from"),
+            attr("from", owner),
+            attr("to", recipient),
+            attr("by", info.sender),
+            attr("amount", amount),
+        ])
+        .add_event(decision);
     Ok(res)
 }
 
@@ -168,7 +183,15 @@ pub fn execute_burn_from(
 ) -> Result<Response, ContractError> {
     let owner_addr = deps.api.addr_validate(&owner)?;
 
-    // deduct allowance before doing anything else have enough allowance
+    let decision = validate_compliance(
+        deps.as_ref(),
+        &env,
+        Some(owner_addr.clone()),
+        None,
+        Some(amount),
+    )?;
+
+    // deduct allowance before touching balances, failing without enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     // lower balance
@@ -185,12 +208,17 @@ pub fn execute_burn_from(
         Ok(meta)
     })?;
 
-    let res = Response::new().add_attributes(vec![
-        attr("action", "burn_from"),
-        attr("from", owner),
-        attr("by", info.sender),
-        attr("amount", amount),
-    ]);
+    let hook = transferred_hook(deps.as_ref(), Some(owner_addr), None, amount)?;
+
+    let res = Response::new()
+        .add_message(hook)
+        .add_attributes(vec![
+            attr("action", "burn_from"),
+            attr("from", owner),
+            attr("by", info.sender),
+            attr("amount", amount),
+        ])
+        .add_event(decision);
     Ok(res)
 }
 
@@ -206,7 +234,15 @@ pub fn execute_send_from(
     let rcpt_addr = deps.api.addr_validate(&contract)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
 
-    // deduct allowance before doing anything else have enough allowance
+    let decision = validate_compliance(
+        deps.as_ref(),
+        &env,
+        Some(owner_addr.clone()),
+        Some(rcpt_addr.clone()),
+        Some(amount),
+    )?;
+
+    // deduct allowance before touching balances, failing without enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     // move the tokens to the contract
@@ -239,7 +275,13 @@ pub fn execute_send_from(
     }
     .into_cosmos_msg(contract)?;
 
-    let res = Response::new().add_message(msg).add_attributes(attrs);
+    let hook = transferred_hook(deps.as_ref(), Some(owner_addr), Some(rcpt_addr), amount)?;
+
+    let res = Response::new()
+        .add_message(hook)
+        .add_message(msg)
+        .add_attributes(attrs)
+        .add_event(decision);
     Ok(res)
 }
 
@@ -256,8 +298,13 @@ pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<
 mod tests {
     use super::*;
 
-    use cosmwasm_std::testing::{message_info, mock_dependencies_with_balance, mock_env, MockApi};
-    use cosmwasm_std::{coins, CosmosMsg, SubMsg, Timestamp, WasmMsg};
+    use cosmwasm_std::testing::{
+        message_info, mock_dependencies_with_balance, mock_env, MockApi, MockQuerier,
+    };
+    use cosmwasm_std::{
+        coins, from_json, to_json_binary, ContractResult, CosmosMsg, SubMsg, SystemResult,
+        Timestamp, WasmMsg, WasmQuery,
+    };
     use cw20::{Cw20Coin, TokenInfoResponse};
 
     use crate::contract::{execute, instantiate, query_balance, query_token_info};
@@ -267,6 +314,18 @@ mod tests {
         query_balance(deps, address.into()).unwrap().balance
     }
 
+    // Mock the compliance query, blocking any movement out of `frozen`
+    fn mock_compliance(querier: &mut MockQuerier, frozen: Option<Addr>) {
+        querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let utils::QueryMsg::CheckTokenCompliance { from, .. } = from_json(msg).unwrap();
+                let compliant = frozen.is_none() || from != frozen;
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&compliant).unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+    }
+
     // this will set up the instantiation for other tests
     fn do_instantiate<T: Into<String>>(
         mut deps: DepsMut,
@@ -503,6 +562,7 @@ mod tests {
     #[test]
     fn transfer_from_respects_limits() {
         let mut deps = mock_dependencies_with_balance(&[]);
+        mock_compliance(&mut deps.querier, None);
         let owner = deps.api.addr_make("addr0001").to_string();
         let spender = deps.api.addr_make("addr0002").to_string();
         let rcpt = deps.api.addr_make("addr0003").to_string();
@@ -585,6 +645,7 @@ mod tests {
     #[test]
     fn burn_from_respects_limits() {
         let mut deps = mock_dependencies_with_balance(&[]);
+        mock_compliance(&mut deps.querier, None);
         let owner = deps.api.addr_make("addr0001").to_string();
         let spender = deps.api.addr_make("addr0002").to_string();
 
@@ -663,6 +724,7 @@ mod tests {
     #[test]
     fn send_from_respects_limits() {
         let mut deps = mock_dependencies_with_balance(&[]);
+        mock_compliance(&mut deps.querier, None);
         let owner = deps.api.addr_make("addr0001").to_string();
         let spender = deps.api.addr_make("addr0002").to_string();
         let contract = deps.api.addr_make("addr0003").to_string();
@@ -694,7 +756,7 @@ mod tests {
         let env = mock_env();
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
         assert_eq!(res.attributes[0], attr("action", "send_from"));
-        assert_eq!(1, res.messages.len());
+        assert_eq!(2, res.messages.len());
 
         // we record this as sent by the one who requested, not the one who was paying
         let binary_msg = Cw20ReceiveMsg {
@@ -705,7 +767,7 @@ mod tests {
         .into_json_binary()
         .unwrap();
         assert_eq!(
-            res.messages[0],
+            res.messages[1],
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: contract.clone(),
                 msg: binary_msg,
@@ -765,6 +827,54 @@ mod tests {
         assert_eq!(err, ContractError::Expired {});
     }
 
+    #[test]
+    fn frozen_owner_cannot_spend_allowance() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = deps.api.addr_make("addr0001").to_string();
+        let spender = deps.api.addr_make("addr0002").to_string();
+        let rcpt = deps.api.addr_make("addr0003").to_string();
+        mock_compliance(&mut deps.querier, Some(Addr::unchecked(&owner)));
+
+        let start = Uint128::new(999999);
+        do_instantiate(deps.as_mut(), &owner, start);
+
+        // the allowance was granted before the owner got frozen
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(77777),
+            expires: None,
+        };
+        let info = message_info(&Addr::unchecked(owner.clone()), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // yet the spender can't move, send or burn any of the owner's tokens
+        let amount = Uint128::new(44444);
+        let msgs = [
+            ExecuteMsg::TransferFrom {
+                owner: owner.clone(),
+                recipient: rcpt.clone(),
+                amount,
+            },
+            ExecuteMsg::SendFrom {
+                owner: owner.clone(),
+                contract: rcpt.clone(),
+                amount,
+                msg: Binary::default(),
+            },
+            ExecuteMsg::BurnFrom {
+                owner: owner.clone(),
+                amount,
+            },
+        ];
+        for msg in msgs {
+            let info = message_info(&Addr::unchecked(spender.clone()), &[]);
+            let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+            assert_eq!(err, ContractError::ComplianceCheckFailed);
+        }
+        assert_eq!(get_balance(deps.as_ref(), owner), start);
+        assert_eq!(get_balance(deps.as_ref(), rcpt), Uint128::zero());
+    }
+
     #[test]
     fn no_past_expiration() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
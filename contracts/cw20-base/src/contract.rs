@@ -2,8 +2,8 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::Order::Ascending;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, QueryRequest, Response,
-    StdError, StdResult, Uint128, WasmQuery,
+    to_json_binary, Addr, Binary, Deps, DepsMut, Env, Event, MessageInfo, QueryRequest, Response,
+    StdError, StdResult, Uint128, WasmMsg, WasmQuery,
 };
 
 use cw2::{ensure_from_older_version, set_contract_version};
@@ -189,22 +189,28 @@ pub fn validate_accounts(accounts: &[Cw20Coin]) -> Result<(), ContractError> {
     }
 }
 
+/// Checks the movement with the compliance contract, returning the `compliance_decision`
+/// event to emit for monitoring. Blocked movements revert along with their event; callers
+/// that need a trace of them pre-check through the registry's `PreCheckTransfer`.
 pub fn validate_compliance(
     deps: Deps,
+    env: &Env,
     from: Option<Addr>,
     to: Option<Addr>,
     amount: Option<Uint128>,
-) -> Result<(), ContractError> {
+) -> Result<Event, ContractError> {
     use utils::QueryMsg;
     // check compliance
-    let token_info = TOKEN_INFO.load(deps.storage)?;
     let compliance_address = COMPLIANCE_ADDRESS.load(deps.storage)?;
 
     let msg = QueryMsg::CheckTokenCompliance {
-        token_address: Addr::unchecked(token_info.name),
-        from,
-        to,
+        // Modules query the token itself, so it is identified by its contract address
+        token_address: env.contract.address.clone(),
+        from: from.clone(),
+        to: to.clone(),
         amount,
+        // The registry fills in the decimals it has on record
+        decimals: None,
     };
 
     let query = QueryRequest::Wasm(WasmQuery::Smart {
@@ -215,7 +221,29 @@ pub fn validate_compliance(
     if !is_compliant {
         return Err(ContractError::ComplianceCheckFailed);
     }
-    Ok(())
+
+    Ok(Event::new("compliance_decision")
+        .add_attribute("from", from.as_ref().map(Addr::as_str).unwrap_or_default())
+        .add_attribute("to", to.as_ref().map(Addr::as_str).unwrap_or_default())
+        .add_attribute("amount", amount.unwrap_or_default())
+        .add_attribute("verdict", "allowed"))
+}
+
+/// Reports a movement that went through to the compliance registry, for the modules that
+/// track past transfers. Sent before any other message of the response, so modules see
+/// the balances the movement left.
+pub fn transferred_hook(
+    deps: Deps,
+    from: Option<Addr>,
+    to: Option<Addr>,
+    amount: Uint128,
+) -> Result<WasmMsg, ContractError> {
+    let compliance_address = COMPLIANCE_ADDRESS.load(deps.storage)?;
+    Ok(WasmMsg::Execute {
+        contract_addr: compliance_address.to_string(),
+        msg: to_json_binary(&utils::RegistryHookExecuteMsg::Transferred { from, to, amount })?,
+        funds: vec![],
+    })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -272,7 +300,7 @@ pub fn execute(
 
 pub fn execute_transfer(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -280,8 +308,9 @@ pub fn execute_transfer(
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
 
     // add compliance check
-    validate_compliance(
+    let decision = validate_compliance(
         deps.as_ref(),
+        &env,
         Some(info.sender.clone()),
         Some(rcpt_addr.clone()),
         Some(amount),
@@ -300,22 +329,37 @@ pub fn execute_transfer(
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
+    let hook = transferred_hook(
+        deps.as_ref(),
+        Some(info.sender.clone()),
+        Some(rcpt_addr),
+        amount,
+    )?;
+
     let res = Response::new()
         .add_attribute("action", "transfer")
         .add_attribute("from", info.sender)
         .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_event(decision)
+        .add_message(hook);
     Ok(res)
 }
 
 pub fn execute_burn(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     // add compliance check
-    validate_compliance(deps.as_ref(), Some(info.sender.clone()), None, Some(amount))?;
+    let decision = validate_compliance(
+        deps.as_ref(),
+        &env,
+        Some(info.sender.clone()),
+        None,
+        Some(amount),
+    )?;
 
     // lower balance
     BALANCES.update(
@@ -331,25 +375,30 @@ pub fn execute_burn(
         Ok(info)
     })?;
 
+    let hook = transferred_hook(deps.as_ref(), Some(info.sender.clone()), None, amount)?;
+
     let res = Response::new()
         .add_attribute("action", "burn")
         .add_attribute("from", info.sender)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_event(decision)
+        .add_message(hook);
     Ok(res)
 }
 
 pub fn execute_mint(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    // add compliance check
-    validate_compliance(
+    // add compliance check, new tokens come from no account
+    let decision = validate_compliance(
         deps.as_ref(),
-        Some(info.sender.clone()),
+        &env,
+        None,
         Some(rcpt_addr.clone()),
         Some(amount),
     )?;
@@ -385,16 +434,20 @@ pub fn execute_mint(
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
+    let hook = transferred_hook(deps.as_ref(), None, Some(rcpt_addr), amount)?;
+
     let res = Response::new()
         .add_attribute("action", "mint")
         .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_event(decision)
+        .add_message(hook);
     Ok(res)
 }
 
 pub fn execute_send(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     contract: String,
     amount: Uint128,
@@ -403,8 +456,9 @@ pub fn execute_send(
     let rcpt_addr = deps.api.addr_validate(&contract)?;
 
     // add compliance check
-    validate_compliance(
+    let decision = validate_compliance(
         deps.as_ref(),
+        &env,
         Some(info.sender.clone()),
         Some(rcpt_addr.clone()),
         Some(amount),
@@ -424,11 +478,20 @@ pub fn execute_send(
         |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
     )?;
 
+    let hook = transferred_hook(
+        deps.as_ref(),
+        Some(info.sender.clone()),
+        Some(rcpt_addr),
+        amount,
+    )?;
+
     let res = Response::new()
         .add_attribute("action", "send")
         .add_attribute("from", &info.sender)
         .add_attribute("to", &contract)
         .add_attribute("amount", amount)
+        .add_event(decision)
+        .add_message(hook)
         .add_message(
             Cw20ReceiveMsg {
                 sender: info.sender.into(),
@@ -984,6 +1047,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1002,7 +1066,7 @@ mod tests {
         let info = message_info(&Addr::unchecked(minter.clone()), &[]);
         let env = mock_env();
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+        assert_eq!(1, res.messages.len());
         assert_eq!(get_balance(deps.as_ref(), genesis), amount);
         assert_eq!(get_balance(deps.as_ref(), winner.clone()), prize);
 
@@ -1045,6 +1109,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1130,6 +1195,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1178,6 +1244,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1323,6 +1390,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1368,7 +1436,28 @@ mod tests {
             amount: transfer,
         };
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.messages.len(), 0);
+        // the registry is told about the transfer
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: MockApi::default().addr_make("compliance_addr").to_string(),
+                msg: to_json_binary(&utils::RegistryHookExecuteMsg::Transferred {
+                    from: Some(Addr::unchecked(&addr1)),
+                    to: Some(Addr::unchecked(&addr2)),
+                    amount: transfer,
+                })
+                .unwrap(),
+                funds: vec![],
+            })]
+        );
+        assert_eq!(
+            res.events,
+            vec![Event::new("compliance_decision")
+                .add_attribute("from", &addr1)
+                .add_attribute("to", &addr2)
+                .add_attribute("amount", transfer)
+                .add_attribute("verdict", "allowed")]
+        );
 
         let remainder = amount1.checked_sub(transfer).unwrap();
         assert_eq!(get_balance(deps.as_ref(), addr1), remainder);
@@ -1395,6 +1484,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&false).unwrap())),
                 }
             }
@@ -1432,6 +1522,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1468,7 +1559,7 @@ mod tests {
         let env = mock_env();
         let msg = ExecuteMsg::Burn { amount: burn };
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.messages.len(), 0);
+        assert_eq!(res.messages.len(), 1);
 
         let remainder = amount1.checked_sub(burn).unwrap();
         assert_eq!(get_balance(deps.as_ref(), addr1), remainder);
@@ -1498,6 +1589,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
@@ -1535,7 +1627,8 @@ mod tests {
             msg: send_msg.clone(),
         };
         let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.messages.len(), 1);
+        // the registry hook comes first, then the send message
+        assert_eq!(res.messages.len(), 2);
 
         // ensure proper send message sent
         // this is the message we want delivered to the other side
@@ -1548,7 +1641,7 @@ mod tests {
         .unwrap();
         // and this is how it must be wrapped for the vm to process it
         assert_eq!(
-            res.messages[0],
+            res.messages[1],
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: contract.clone(),
                 msg: binary_msg,
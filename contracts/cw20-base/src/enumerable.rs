@@ -288,6 +288,7 @@ mod tests {
                         from: _,
                         to: _,
                         amount: _,
+                        decimals: _,
                     } => SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())),
                 }
             }
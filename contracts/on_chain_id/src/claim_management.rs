@@ -1,10 +1,28 @@
 use crate::error::ContractError;
-use crate::state::{Claim, KeyType, CLAIMS, OWNER};
-use crate::utils::{check_key_authorization, verify_claim_signature};
-use cosmwasm_std::{Addr, Binary, DepsMut, MessageInfo, Response, Uint128};
+use crate::msg::ExecuteMsg;
+use crate::state::{
+    Claim, ClaimVersion, DuplicateClaimPolicy, KeyType, SignedExpiry, CLAIMS,
+    CLAIM_MIGRATION_APPROVALS, CLAIM_VERSIONS, CONFIG, CRL, MAX_CLAIMS_PER_BATCH,
+    MAX_CLAIM_VERSIONS, MAX_REVOCATIONS_PER_ISSUER, MAX_REVOCATIONS_PER_PUBLISH, OWNER,
+    REVOKED_CLAIMS, SIGNER_CONTRACTS, TOPIC_ALLOWED_ISSUERS, TOPIC_CLAIM_LIMITS,
+    TOPIC_HOLDER_PURPOSE, USED_CLAIM_PERMITS,
+};
+use crate::utils::{
+    check_admin, check_claim_size, check_claim_subject, check_claim_topic_open,
+    check_compliance_manager, check_data_hash, check_data_root, check_encryption_recipient,
+    check_holder_purpose, check_issuer_key, check_key_authorization, check_related_identity,
+    check_self_attestation, check_topic_allowed_issuer, claim_permit_message_hash,
+    consume_claim_nonce, expiry_update_message_hash, generate_claim_id, topic_claim_limit,
+    track_total_claims, verify_claim_signature, verify_issuer_signature,
+};
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, DepsMut, Env, Event, MessageInfo, Response, Storage, Uint128,
+    Uint64, WasmMsg,
+};
 
 pub fn execute_add_claim(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     claim: Claim,
     public_key: Binary,
@@ -13,110 +31,1001 @@ pub fn execute_add_claim(
     // Check sender is authorized
 
     // Check if the sender is authorized to add claims (must have a CLAIM_SIGNER_KEY)
-    check_key_authorization(&deps, &info.sender, KeyType::ClaimSignerKey).map_err(|e| {
-        ContractError::Unauthorized {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ClaimSignerKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
+        },
+    )?;
+
+    let claim = add_claim(deps, &env, claim, public_key, &user_addr)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_claim")
+        .add_attribute("claim_topic", claim.topic))
+}
+
+pub fn execute_add_claim_with_permit(
+    mut deps: DepsMut,
+    env: Env,
+    claim: Claim,
+    public_key: Binary,
+    user_addr: Addr,
+    issuer_permit: Binary,
+) -> Result<Response, ContractError> {
+    // The issuer, not the submitter, must hold a CLAIM_SIGNER_KEY
+    check_key_authorization(&mut deps, &env, &claim.issuer, KeyType::ClaimSignerKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Issuer lacks CLAIM_SIGNER_KEY: {}", e),
+        },
+    )?;
+
+    let encoding = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .signature_encoding;
+    let permit_hash =
+        claim_permit_message_hash(&env.contract.address, &claim, &user_addr, &encoding);
+    if USED_CLAIM_PERMITS.has(deps.storage, &permit_hash) {
+        return Err(ContractError::PermitAlreadyUsed {});
+    }
+    check_issuer_key(
+        deps.as_ref(),
+        &env,
+        &claim.issuer,
+        &claim.signature_scheme,
+        &public_key,
+    )?;
+    verify_issuer_signature(
+        &deps,
+        &claim.signature_scheme,
+        &permit_hash,
+        &issuer_permit,
+        &public_key,
+    )?;
+    USED_CLAIM_PERMITS
+        .save(deps.storage, &permit_hash, &true)
+        .map_err(|e| ContractError::SaveError {
+            entity: "used claim permits".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    let claim = add_claim(deps, &env, claim, public_key, &user_addr)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_claim_with_permit")
+        .add_attribute("claim_topic", claim.topic)
+        .add_attribute("issuer", claim.issuer))
+}
+
+// Validates a signed claim and stores it for `user_addr` under the configured duplicate policy
+fn add_claim(
+    deps: DepsMut,
+    env: &Env,
+    mut claim: Claim,
+    public_key: Binary,
+    user_addr: &Addr,
+) -> Result<Claim, ContractError> {
+    validate_claim(&deps, env, &mut claim, public_key)?;
+    check_claim_subject(&claim, user_addr)?;
+    check_self_attestation(deps.storage, &claim, user_addr)?;
+    check_holder_purpose(&deps, user_addr, &claim)?;
+    consume_claim_nonce(deps.storage, &claim)?;
+    stamp_claim(&deps, env, &mut claim)?;
+
+    let config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    push_claim(
+        deps,
+        env,
+        user_addr,
+        claim.clone(),
+        &config.duplicate_claim_policy,
+    )?;
+    Ok(claim)
+}
+
+pub fn execute_add_claims_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    claims: Vec<Claim>,
+    public_key: Binary,
+    user_addr: Addr,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ClaimSignerKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
+        },
+    )?;
+    if claims.is_empty() || claims.len() > MAX_CLAIMS_PER_BATCH {
+        return Err(ContractError::InvalidClaimBatch {
+            reason: format!("Expected 1 to {} claims", MAX_CLAIMS_PER_BATCH),
+        });
+    }
+
+    // Any rejected claim fails the transaction, undoing the claims added before it. A topic
+    // attested twice, in the batch or already on the identity, is rejected as well.
+    let mut response = Response::new()
+        .add_attribute("action", "add_claims_batch")
+        .add_attribute("claim_count", claims.len().to_string());
+    for mut claim in claims {
+        validate_claim(&deps, &env, &mut claim, public_key.clone())?;
+        check_claim_subject(&claim, &user_addr)?;
+        check_self_attestation(deps.storage, &claim, &user_addr)?;
+        check_holder_purpose(&deps, &user_addr, &claim)?;
+        consume_claim_nonce(deps.storage, &claim)?;
+        stamp_claim(&deps, &env, &mut claim)?;
+        let claim_topic = claim.topic;
+        push_claim(
+            deps.branch(),
+            &env,
+            &user_addr,
+            claim,
+            &DuplicateClaimPolicy::Reject,
+        )?;
+        response = response.add_attribute("claim_topic", claim_topic);
+    }
+
+    Ok(response)
+}
+
+pub fn execute_replace_claim(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    old_claim_id: String,
+    mut new_claim: Claim,
+    public_key: Binary,
+    user_addr: Addr,
+) -> Result<Response, ContractError> {
+    // Adding the new claim needs a CLAIM_SIGNER_KEY, removing the old one the usual rights
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ClaimSignerKey).map_err(
+        |e| ContractError::Unauthorized {
             reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
+        },
+    )?;
+
+    // The old claim is taken out first so a renewal by the same issuer doesn't collide
+    // with it. Both changes land in this transaction, or neither does if anything fails.
+    let old_claim = take_claim_by_id(&mut deps, &user_addr, &old_claim_id)?;
+    authorize_claim_removal(&mut deps, &env, &info.sender, &old_claim)?;
+
+    validate_claim(&deps, &env, &mut new_claim, public_key)?;
+    check_claim_subject(&new_claim, &user_addr)?;
+    check_self_attestation(deps.storage, &new_claim, &user_addr)?;
+    check_holder_purpose(&deps, &user_addr, &new_claim)?;
+    consume_claim_nonce(deps.storage, &new_claim)?;
+    stamp_claim(&deps, &env, &mut new_claim)?;
+    let new_claim_id = new_claim.id.clone().unwrap_or_default();
+    push_claim(
+        deps.branch(),
+        &env,
+        &user_addr,
+        new_claim.clone(),
+        &DuplicateClaimPolicy::Reject,
+    )?;
+    supersede_claim(deps.storage, &env, &user_addr, old_claim, &new_claim_id)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "replace_claim")
+        .add_attribute("old_claim_id", old_claim_id)
+        .add_attribute("new_claim_id", new_claim.id.unwrap_or_default())
+        .add_attribute("claim_topic", new_claim.topic))
+}
+
+// Stamps the issuance time and applies the default validity period, if any
+fn stamp_claim(deps: &DepsMut, env: &Env, claim: &mut Claim) -> Result<(), ContractError> {
+    let issued_at = env.block.time.seconds();
+    claim.issued_at = Some(Uint64::new(issued_at));
+    claim.signed_expiry = None;
+    if claim.valid_until.is_none() {
+        let config = CONFIG
+            .may_load(deps.storage)
+            .map_err(|e| ContractError::LoadError {
+                entity: "config".to_string(),
+                reason: e.to_string(),
+            })?
+            .unwrap_or_default();
+        if let Some(default_validity_secs) = config.default_validity_secs {
+            claim.signed_expiry = Some(SignedExpiry { valid_until: None });
+            claim.valid_until = Some(Uint64::new(issued_at.saturating_add(default_validity_secs)));
         }
-    })?;
+    }
+    Ok(())
+}
+
+pub fn execute_set_topic_allowed_issuers(
+    deps: DepsMut,
+    info: MessageInfo,
+    topic: Uint128,
+    issuers: Vec<String>,
+) -> Result<Response, ContractError> {
+    check_admin(&deps, &info.sender)?;
+
+    let issuers = issuers
+        .iter()
+        .map(|issuer| {
+            deps.api
+                .addr_validate(issuer)
+                .map_err(|e| ContractError::InvalidAddress {
+                    reason: format!("Invalid issuer address: {}", e),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if issuers.is_empty() {
+        TOPIC_ALLOWED_ISSUERS.remove(deps.storage, topic.u128());
+    } else {
+        TOPIC_ALLOWED_ISSUERS
+            .save(deps.storage, topic.u128(), &issuers)
+            .map_err(|e| ContractError::SaveError {
+                entity: "topic allowed issuers".to_string(),
+                reason: e.to_string(),
+            })?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_topic_allowed_issuers")
+        .add_attribute("topic", topic)
+        .add_attribute(
+            "issuers",
+            issuers
+                .iter()
+                .map(Addr::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+}
+
+pub fn execute_set_topic_holder_purpose(
+    deps: DepsMut,
+    info: MessageInfo,
+    topic: Uint128,
+    required_holder_purpose: Option<KeyType>,
+) -> Result<Response, ContractError> {
+    check_admin(&deps, &info.sender)?;
+
+    match &required_holder_purpose {
+        Some(key_type) => TOPIC_HOLDER_PURPOSE
+            .save(deps.storage, topic.u128(), key_type)
+            .map_err(|e| ContractError::SaveError {
+                entity: "topic holder purpose".to_string(),
+                reason: e.to_string(),
+            })?,
+        None => TOPIC_HOLDER_PURPOSE.remove(deps.storage, topic.u128()),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_topic_holder_purpose")
+        .add_attribute("topic", topic)
+        .add_attribute(
+            "required_holder_purpose",
+            required_holder_purpose.map_or("none".to_string(), |key_type| key_type.to_string()),
+        ))
+}
+
+pub fn execute_set_topic_claim_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    topic: Uint128,
+    max_claims: Option<u32>,
+) -> Result<Response, ContractError> {
+    check_admin(&deps, &info.sender)?;
+
+    match max_claims {
+        Some(max_claims) => TOPIC_CLAIM_LIMITS
+            .save(deps.storage, topic.u128(), &max_claims)
+            .map_err(|e| ContractError::SaveError {
+                entity: "topic claim limit".to_string(),
+                reason: e.to_string(),
+            })?,
+        None => TOPIC_CLAIM_LIMITS.remove(deps.storage, topic.u128()),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_topic_claim_limit")
+        .add_attribute("topic", topic)
+        .add_attribute(
+            "max_claims",
+            max_claims.map_or("default".to_string(), |max| max.to_string()),
+        ))
+}
+
+pub fn execute_set_signer_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    signer_contract: Option<String>,
+) -> Result<Response, ContractError> {
+    // Like revocations, the delegation only concerns the sender's own claims
+    let signer_contract = signer_contract
+        .map(|addr| {
+            deps.api
+                .addr_validate(&addr)
+                .map_err(|e| ContractError::InvalidAddress {
+                    reason: format!("Invalid signer contract address: {}", e),
+                })
+        })
+        .transpose()?;
+    match &signer_contract {
+        Some(signer_contract) => SIGNER_CONTRACTS
+            .save(deps.storage, &info.sender, signer_contract)
+            .map_err(|e| ContractError::SaveError {
+                entity: "signer contract".to_string(),
+                reason: e.to_string(),
+            })?,
+        None => SIGNER_CONTRACTS.remove(deps.storage, &info.sender),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_signer_contract")
+        .add_attribute("issuer", info.sender)
+        .add_attribute(
+            "signer_contract",
+            signer_contract.map_or("none".to_string(), |addr| addr.to_string()),
+        ))
+}
+
+pub fn execute_publish_revocations(
+    deps: DepsMut,
+    info: MessageInfo,
+    revoked_ids: Vec<Binary>,
+) -> Result<Response, ContractError> {
+    // Issuers only ever revoke their own claims, so publishing needs no key on this identity
+    if revoked_ids.len() > MAX_REVOCATIONS_PER_PUBLISH {
+        return Err(ContractError::InvalidRevocation {
+            reason: format!(
+                "At most {} revocations can be published at once",
+                MAX_REVOCATIONS_PER_PUBLISH
+            ),
+        });
+    }
+    if let Some(id) = revoked_ids.iter().find(|id| id.len() != 32) {
+        return Err(ContractError::InvalidRevocation {
+            reason: format!("Expected a 32 byte claim ID hash, got {} bytes", id.len()),
+        });
+    }
+
+    let mut revoked = CRL
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let mut published = 0;
+    for id in revoked_ids {
+        if !revoked.contains(&id) {
+            revoked.push(id);
+            published += 1;
+        }
+    }
+    if revoked.len() > MAX_REVOCATIONS_PER_ISSUER {
+        return Err(ContractError::InvalidRevocation {
+            reason: format!(
+                "Revocation list is limited to {} entries",
+                MAX_REVOCATIONS_PER_ISSUER
+            ),
+        });
+    }
+    CRL.save(deps.storage, &info.sender, &revoked)
+        .map_err(|e| ContractError::SaveError {
+            entity: "revocation list".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "publish_revocations")
+        .add_attribute("issuer", info.sender)
+        .add_attribute("published", published.to_string())
+        .add_attribute("total", revoked.len().to_string()))
+}
+
+pub fn execute_revoke_claim_signature(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    claim_id: String,
+    user_addr: Addr,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ClaimSignerKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
+        },
+    )?;
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+
+    // The issuer is part of the claim ID, so every claim stored under it shares one issuer
+    let issuer = CLAIMS
+        .may_load(deps.storage, &user_addr)
+        .map_err(|e| ContractError::LoadError {
+            entity: "claims".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|claim| claim.id.as_deref() == Some(claim_id.as_str()))
+        .map(|claim| claim.issuer)
+        .ok_or_else(|| ContractError::ClaimIdNotFound {
+            claim_id: claim_id.clone(),
+        })?;
+    if issuer != info.sender {
+        return Err(ContractError::Unauthorized {
+            reason: "Only the issuer of a claim can revoke it".to_string(),
+        });
+    }
+
+    REVOKED_CLAIMS
+        .save(deps.storage, &claim_id, &true)
+        .map_err(|e| ContractError::SaveError {
+            entity: "revoked claims".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_claim_signature")
+        .add_attribute("issuer", info.sender)
+        .add_attribute("user_addr", user_addr)
+        .add_attribute("claim_id", claim_id))
+}
+
+pub fn execute_approve_claim_migration(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_identity: String,
+    claim_id: String,
+) -> Result<Response, ContractError> {
+    // Claims may only be moved into this identity with the consent of its management
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks MANAGEMENT_KEY: {}", e),
+        },
+    )?;
+
+    let from_identity =
+        deps.api
+            .addr_validate(&from_identity)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid source identity address: {}", e),
+            })?;
+
+    CLAIM_MIGRATION_APPROVALS
+        .save(deps.storage, (&from_identity, &claim_id), &true)
+        .map_err(|e| ContractError::SaveError {
+            entity: "claim migration approval".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve_claim_migration")
+        .add_attribute("from_identity", from_identity)
+        .add_attribute("claim_id", claim_id))
+}
+
+pub fn execute_migrate_claim(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    claim_id: String,
+    user_addr: Addr,
+    to_identity: String,
+    public_key: Binary,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks MANAGEMENT_KEY: {}", e),
+        },
+    )?;
+
+    let to_identity =
+        deps.api
+            .addr_validate(&to_identity)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid target identity address: {}", e),
+            })?;
+    if to_identity == env.contract.address {
+        return Err(ContractError::InvalidAddress {
+            reason: "Cannot migrate a claim to the identity holding it".to_string(),
+        });
+    }
+
+    let claim = take_claim_by_id(&mut deps, &user_addr, &claim_id)?;
+
+    // The target re-verifies the claim and rejects it unless it approved the move,
+    // which reverts the removal above
+    let receive = WasmMsg::Execute {
+        contract_addr: to_identity.to_string(),
+        msg: to_json_binary(&ExecuteMsg::ReceiveClaim {
+            claim,
+            public_key,
+            user_addr,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_message(receive)
+        .add_attribute("action", "migrate_claim")
+        .add_attribute("claim_id", claim_id)
+        .add_attribute("to_identity", to_identity))
+}
+
+pub fn execute_receive_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    mut claim: Claim,
+    public_key: Binary,
+    user_addr: Addr,
+) -> Result<Response, ContractError> {
+    // Only claims whose migration was approved by a ManagementKey of this identity are accepted
+    let claim_id = claim.id.clone().unwrap_or_default();
+    let approval = (&info.sender, claim_id.as_str());
+    if !CLAIM_MIGRATION_APPROVALS.has(deps.storage, approval) {
+        return Err(ContractError::Unauthorized {
+            reason: format!(
+                "Migration of claim {} from {} was not approved",
+                claim_id, info.sender
+            ),
+        });
+    }
+    CLAIM_MIGRATION_APPROVALS.remove(deps.storage, approval);
+
+    // Check the claim against this identity's own configuration. Its nonce was consumed
+    // when it was first added, on the source identity. The signature covers the expiry
+    // the issuer signed, not one the source identity set afterwards.
+    let valid_until = claim.valid_until;
+    if let Some(signed) = &claim.signed_expiry {
+        claim.valid_until = signed.valid_until;
+    }
+    validate_claim(&deps, &env, &mut claim, public_key)?;
+    claim.valid_until = valid_until;
+    check_claim_subject(&claim, &user_addr)?;
+    check_self_attestation(deps.storage, &claim, &user_addr)?;
+    check_holder_purpose(&deps, &user_addr, &claim)?;
+    let claim_topic = claim.topic;
+    push_claim(deps, &env, &user_addr, claim, &DuplicateClaimPolicy::Reject)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "receive_claim")
+        .add_attribute("from_identity", info.sender)
+        .add_attribute("claim_id", claim_id)
+        .add_attribute("claim_topic", claim_topic))
+}
+
+pub fn execute_force_remove_claim(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    user_addr: Addr,
+    claim_id: String,
+) -> Result<Response, ContractError> {
+    // Regulatory takedowns bypass the identity's keys, so they are gated by owner_roles instead
+    check_compliance_manager(&deps, &info.sender)?;
+
+    let claim = take_claim_by_id(&mut deps, &user_addr, &claim_id)?;
+
+    let event = Event::new("forced_removal")
+        .add_attribute("actor", info.sender.to_string())
+        .add_attribute("user_addr", user_addr.to_string())
+        .add_attribute("claim_id", claim_id.clone())
+        .add_attribute("claim_topic", claim.topic)
+        .add_attribute("issuer", claim.issuer.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "force_remove_claim")
+        .add_attribute("claim_id", claim_id))
+}
+
+// Removes and returns the claim of `user_addr` with the given ID, without any authorization
+fn take_claim_by_id(
+    deps: &mut DepsMut,
+    user_addr: &Addr,
+    claim_id: &str,
+) -> Result<Claim, ContractError> {
+    let mut claims =
+        CLAIMS
+            .load(deps.storage, user_addr)
+            .map_err(|e| ContractError::LoadError {
+                entity: "claims".to_string(),
+                reason: e.to_string(),
+            })?;
+    let index = claims
+        .iter()
+        .position(|c| c.id.as_deref() == Some(claim_id))
+        .ok_or_else(|| ContractError::ClaimIdNotFound {
+            claim_id: claim_id.to_string(),
+        })?;
+    let claim = claims.remove(index);
+    CLAIMS
+        .save(deps.storage, user_addr, &claims)
+        .map_err(|e| ContractError::SaveError {
+            entity: "claims".to_string(),
+            reason: e.to_string(),
+        })?;
+    track_total_claims(deps.storage, false)?;
+
+    Ok(claim)
+}
+
+// Verifies a claim about to be stored on this identity and assigns its ID
+fn validate_claim(
+    deps: &DepsMut,
+    env: &Env,
+    claim: &mut Claim,
+    public_key: Binary,
+) -> Result<(), ContractError> {
+    // An empty namespace is the default one
+    if claim.namespace.as_deref() == Some("") {
+        claim.namespace = None;
+    }
+
+    let max_claim_data_bytes = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .max_claim_data_bytes;
+    check_claim_size(claim, max_claim_data_bytes)?;
+
+    // Data signed by hash must match that hash
+    check_data_hash(claim)?;
+    check_data_root(claim)?;
+
+    // The key must be the issuer's, or the one its signer contract authorizes
+    check_issuer_key(
+        deps.as_ref(),
+        env,
+        &claim.issuer,
+        &claim.signature_scheme,
+        &public_key,
+    )?;
 
     // Verify the issuer's signature (must be signed by a CLAIM_SIGNER_KEY)
-    verify_claim_signature(&deps, &claim, public_key).map_err(|e| {
+    verify_claim_signature(deps, claim, public_key).map_err(|e| {
         ContractError::InvalidSignature {
             reason: format!("Failed to verify claim signature: {}", e),
         }
     })?;
 
+    // Topics with curated issuers only accept claims from those issuers
+    check_topic_allowed_issuer(deps, claim)?;
+
+    // Deprecated topics accept no new claims
+    check_claim_topic_open(deps, claim)?;
+
+    // Encrypted claims must be readable by one of the identity's EncryptionKeys
+    check_encryption_recipient(deps, claim)?;
+
+    // Relationship claims must link to an existing identity
+    check_related_identity(deps, claim)?;
+
     // Generate and set the claim ID
-    // generate_claim_id(&mut claim);
+    generate_claim_id(claim);
 
+    Ok(())
+}
+
+// Appends a claim to those of `user_addr`, keeping one claim per topic, namespace and issuer.
+// A claim the issuer already made for the topic is handled according to `on_duplicate`.
+fn push_claim(
+    deps: DepsMut,
+    env: &Env,
+    user_addr: &Addr,
+    claim: Claim,
+    on_duplicate: &DuplicateClaimPolicy,
+) -> Result<(), ContractError> {
     // Load existing claims or create a new vector if none exist
     let mut claims = CLAIMS
-        .may_load(deps.storage, &user_addr)
+        .may_load(deps.storage, user_addr)
         .map_err(|e| ContractError::LoadError {
             entity: "claims".to_string(),
             reason: e.to_string(),
         })?
         .unwrap_or_default();
-    // Check if the claim already exists
-    if claims.iter().any(|c| c.topic == claim.topic) {
-        return Err(ContractError::ClaimAlreadyExists {
-            claim_topic: claim.topic,
-        });
+    // Check if the issuer already attested to this topic
+    let existing = claims.iter().position(|c| {
+        c.is_in_topic(claim.topic, claim.namespace.as_deref()) && c.issuer == claim.issuer
+    });
+    match (existing, on_duplicate) {
+        (None, _) => {
+            let max = topic_claim_limit(deps.storage, claim.topic)?;
+            let in_topic = claims
+                .iter()
+                .filter(|c| c.is_in_topic(claim.topic, claim.namespace.as_deref()))
+                .count();
+            if in_topic >= max as usize {
+                return Err(ContractError::TopicClaimLimitExceeded {
+                    topic: claim.topic,
+                    max,
+                });
+            }
+            track_total_claims(deps.storage, true)?;
+            claims.push(claim)
+        }
+        (Some(_), DuplicateClaimPolicy::Reject) => {
+            return Err(ContractError::ClaimAlreadyExists {
+                claim_topic: claim.topic,
+            })
+        }
+        (Some(index), DuplicateClaimPolicy::Overwrite) => {
+            let current_id = claim.id.clone().unwrap_or_default();
+            let previous = std::mem::replace(&mut claims[index], claim);
+            supersede_claim(deps.storage, env, user_addr, previous, &current_id)?;
+        }
+        (Some(_), DuplicateClaimPolicy::Ignore) => return Ok(()),
     }
 
-    // Add the new claim
-    claims.push(claim.clone());
-
     // Save the updated claims
+    CLAIMS
+        .save(deps.storage, user_addr, &claims)
+        .map_err(|e| ContractError::SaveError {
+            entity: "claims".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(())
+}
+
+// Adds `previous` to the history of the claim that replaced it, now stored as `current_id`.
+// The history moves along when the new version has a different ID.
+fn supersede_claim(
+    storage: &mut dyn Storage,
+    env: &Env,
+    user_addr: &Addr,
+    previous: Claim,
+    current_id: &str,
+) -> Result<(), ContractError> {
+    let previous_id = previous.id.clone().unwrap_or_default();
+    let mut versions = CLAIM_VERSIONS
+        .may_load(storage, (user_addr, &previous_id))
+        .map_err(|e| ContractError::LoadError {
+            entity: "claim versions".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    if previous_id != current_id {
+        CLAIM_VERSIONS.remove(storage, (user_addr, &previous_id));
+    }
+
+    versions.push(ClaimVersion {
+        version: versions.last().map_or(1, |version| version.version + 1),
+        claim: previous,
+        superseded_at: Some(Uint64::new(env.block.time.seconds())),
+    });
+    if versions.len() > MAX_CLAIM_VERSIONS {
+        versions.drain(..versions.len() - MAX_CLAIM_VERSIONS);
+    }
+    CLAIM_VERSIONS
+        .save(storage, (user_addr, current_id), &versions)
+        .map_err(|e| ContractError::SaveError {
+            entity: "claim versions".to_string(),
+            reason: e.to_string(),
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_set_claim_expiry(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    user_addr: Addr,
+    claim_id: String,
+    valid_until: Uint64,
+    issuer_signature: Binary,
+    public_key: Binary,
+) -> Result<Response, ContractError> {
+    // Same authorization as adding the claim in the first place
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ClaimSignerKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
+        },
+    )?;
+
+    let mut claims =
+        CLAIMS
+            .load(deps.storage, &user_addr)
+            .map_err(|e| ContractError::LoadError {
+                entity: "claims".to_string(),
+                reason: e.to_string(),
+            })?;
+    let claim = claims
+        .iter_mut()
+        .find(|c| c.id.as_deref() == Some(claim_id.as_str()))
+        .ok_or_else(|| ContractError::ClaimIdNotFound {
+            claim_id: claim_id.clone(),
+        })?;
+
+    let config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+
+    // The issuer must have authorized this exact change
+    let message_hash = expiry_update_message_hash(
+        &env.contract.address,
+        claim,
+        valid_until,
+        &config.signature_encoding,
+    );
+    check_issuer_key(
+        deps.as_ref(),
+        &env,
+        &claim.issuer,
+        &claim.signature_scheme,
+        &public_key,
+    )?;
+    verify_issuer_signature(
+        &deps,
+        &claim.signature_scheme,
+        &message_hash,
+        &issuer_signature,
+        &public_key,
+    )?;
+
+    // Extensions are capped, shortening is always allowed
+    let is_extension = claim
+        .valid_until
+        .is_none_or(|current| valid_until > current);
+    if let (true, Some(max_validity_secs)) = (is_extension, config.max_claim_validity_secs) {
+        let max_valid_until =
+            Uint64::new(env.block.time.seconds().saturating_add(max_validity_secs));
+        if valid_until > max_valid_until {
+            return Err(ContractError::ExpiryBeyondMax { max_valid_until });
+        }
+    }
+
+    let previous = claim.clone();
+    if claim.signed_expiry.is_none() {
+        claim.signed_expiry = Some(SignedExpiry {
+            valid_until: claim.valid_until,
+        });
+    }
+    claim.valid_until = Some(valid_until);
     CLAIMS
         .save(deps.storage, &user_addr, &claims)
         .map_err(|e| ContractError::SaveError {
             entity: "claims".to_string(),
             reason: e.to_string(),
         })?;
+    supersede_claim(deps.storage, &env, &user_addr, previous, &claim_id)?;
 
     Ok(Response::new()
-        .add_attribute("action", "add_claim")
-        .add_attribute("claim_topic", claim.topic))
+        .add_attribute("action", "set_claim_expiry")
+        .add_attribute("claim_id", claim_id)
+        .add_attribute("valid_until", valid_until))
 }
 
 pub fn execute_remove_claim(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     claim_topic: Uint128,
     user_addr: Addr,
 ) -> Result<Response, ContractError> {
-    // Check if the sender is authorized to remove claims (must have a CLAIM_SIGNER_KEY)
-    check_key_authorization(&deps, &info.sender, KeyType::ClaimSignerKey).map_err(|e| {
-        ContractError::Unauthorized {
-            reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
-        }
+    remove_claim(deps, &env, &info.sender, &user_addr, |c| {
+        c.is_in_topic(claim_topic, None)
+    })?
+    .ok_or(ContractError::ClaimNotFound { claim_topic })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_claim")
+        .add_attribute("claim_topic", claim_topic))
+}
+
+pub fn execute_remove_claim_by_id(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    claim_id: String,
+    user_addr: Addr,
+) -> Result<Response, ContractError> {
+    let claim = remove_claim(deps, &env, &info.sender, &user_addr, |c| {
+        c.id.as_deref() == Some(claim_id.as_str())
+    })?
+    .ok_or_else(|| ContractError::ClaimIdNotFound {
+        claim_id: claim_id.clone(),
     })?;
 
-    // Get the owner of the identity
-    let owner = OWNER
-        .load(deps.storage)
-        .map_err(|e| ContractError::LoadError {
-            entity: "owner".to_string(),
-            reason: e.to_string(),
-        })?;
+    Ok(Response::new()
+        .add_attribute("action", "remove_claim")
+        .add_attribute("claim_id", claim_id)
+        .add_attribute("claim_topic", claim.topic))
+}
 
+// Removes a claim of `user_addr` matching `matches`, returning it, or `None` when there is
+// no such claim. The sender's own claim is picked over those of other issuers.
+fn remove_claim(
+    mut deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    user_addr: &Addr,
+    matches: impl Fn(&Claim) -> bool,
+) -> Result<Option<Claim>, ContractError> {
     // Load existing claims
     let mut claims =
         CLAIMS
-            .load(deps.storage, &user_addr)
+            .load(deps.storage, user_addr)
             .map_err(|e| ContractError::LoadError {
                 entity: "claims".to_string(),
                 reason: e.to_string(),
             })?;
 
     // Find the claim and check authorization
-    if let Some(index) = claims.iter().position(|c| c.topic == claim_topic) {
-        let claim = &claims[index];
-
-        // Check if the sender is the issuer or the owner
-        if info.sender != claim.issuer && info.sender != owner {
-            return Err(ContractError::Unauthorized {
-                reason: "Only the claim issuer or the identity owner can remove a claim"
-                    .to_string(),
-            });
+    let Some(index) = claims
+        .iter()
+        .position(|c| matches(c) && c.issuer == *sender)
+        .or_else(|| claims.iter().position(&matches))
+    else {
+        return Ok(None);
+    };
+    authorize_claim_removal(&mut deps, env, sender, &claims[index])?;
+
+    // Remove the claim
+    let claim = claims.remove(index);
+
+    // Save the updated claims
+    CLAIMS
+        .save(deps.storage, user_addr, &claims)
+        .map_err(|e| ContractError::SaveError {
+            entity: "claims".to_string(),
+            reason: e.to_string(),
+        })?;
+    track_total_claims(deps.storage, false)?;
+
+    Ok(Some(claim))
+}
+
+fn authorize_claim_removal(
+    deps: &mut DepsMut,
+    env: &Env,
+    sender: &Addr,
+    claim: &Claim,
+) -> Result<(), ContractError> {
+    let config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+
+    // When enabled, issuers may retract the claims they issued without holding a key here
+    if config.allow_issuer_removal && *sender == claim.issuer {
+        return Ok(());
+    }
+
+    // Check if the sender is authorized to remove claims (must have a CLAIM_SIGNER_KEY)
+    check_key_authorization(deps, env, sender, KeyType::ClaimSignerKey).map_err(|e| {
+        ContractError::Unauthorized {
+            reason: format!("Sender lacks CLAIM_SIGNER_KEY: {}", e),
         }
+    })?;
 
-        // Remove the claim
-        claims.remove(index);
+    // Get the owner of the identity
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
 
-        // Save the updated claims
-        CLAIMS
-            .save(deps.storage, &user_addr, &claims)
-            .map_err(|e| ContractError::SaveError {
-                entity: "claims".to_string(),
-                reason: e.to_string(),
-            })?;
-    } else {
-        return Err(ContractError::ClaimNotFound { claim_topic });
+    // Check if the sender is the issuer or the owner
+    if *sender != claim.issuer && *sender != owner {
+        return Err(ContractError::Unauthorized {
+            reason: "Only the claim issuer or the identity owner can remove a claim".to_string(),
+        });
     }
 
-    Ok(Response::new()
-        .add_attribute("action", "remove_claim")
-        .add_attribute("claim_topic", claim_topic))
+    Ok(())
 }
@@ -1,17 +1,51 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128,
+    from_json, to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, Uint128, Uint64,
 };
 use cw2::set_contract_version;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
-use crate::claim_management::{execute_add_claim, execute_remove_claim};
+use crate::claim_management::{
+    execute_add_claim, execute_add_claim_with_permit, execute_add_claims_batch,
+    execute_approve_claim_migration, execute_force_remove_claim, execute_migrate_claim,
+    execute_publish_revocations, execute_receive_claim, execute_remove_claim,
+    execute_remove_claim_by_id, execute_replace_claim, execute_revoke_claim_signature,
+    execute_set_claim_expiry, execute_set_signer_contract, execute_set_topic_allowed_issuers,
+    execute_set_topic_claim_limit, execute_set_topic_holder_purpose,
+};
 use crate::error::ContractError;
-use crate::key_management::{execute_add_key, execute_remove_key};
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::state::{Claim, Key, KeyType, CLAIMS, KEYS, OWNER};
+use crate::key_management::{
+    collect_management_approval, execute_accept_ownership, execute_add_key,
+    execute_approve_migration, execute_dispatch, execute_propose_new_owner, execute_reassign_keys,
+    execute_remove_key, execute_set_admin, execute_set_key_active, execute_set_key_weight,
+    execute_set_management_threshold, execute_update_config,
+};
+use crate::msg::{
+    ClaimContentsResponse, ClaimResponse, ClaimSnapshot, ClaimStatus, ClaimsPageResponse,
+    ExecuteMsg, ExpectedSignerResponse, FullSnapshotResponse, IdentitySummaryResponse,
+    InstantiateMsg, MigrateMsg, PreflightVerificationResponse, QueryAuth, QueryMsg,
+    RequiredIssuersResponse, TopicVerification,
+};
+use crate::state::{
+    Claim, ClaimVersion, Config, Key, KeyType, MigrationRecord, UnknownTopicPolicy, ADMIN, CLAIMS,
+    CLAIM_VERSIONS, CONFIG, CRL, KEYS, MAX_CLAIMS_PER_BATCH, MAX_DISCLOSURE_PROOF_DEPTH,
+    MAX_MIGRATION_RECORDS, MAX_PROFILE_TOPICS, MIGRATIONS, MIGRATION_APPROVAL, OWNER,
+    PENDING_OWNER, REVOKED_CLAIMS, SIGNER_CONTRACTS, TOPIC_ALLOWED_ISSUERS, TOPIC_HOLDER_PURPOSE,
+    TOTAL_CLAIMS,
+};
+use crate::utils::{
+    ensure_management_weight_remains, ensure_total_keys_within, generate_claim_id,
+    grant_key_purpose, is_query_authorized, is_revoked, key_address, load_admin, merkle_root,
+    message_hash, topic_claim_limit, verify_claims_batch, with_idempotency_key,
+};
+use registery::claim_topics::msg::ClaimTopicStatus;
+use registery::claim_topics::QueryMsg as ClaimTopicsQueryMsg;
+use registery::trusted_issuers::QueryMsg as TrustedIssuersQueryMsg;
+use utils::pagination::{page_limit, paginate_list, MAX_LIMIT};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:onchainid";
@@ -38,12 +72,21 @@ pub fn instantiate(
             reason: format!("Invalid owner address: {}", e),
         })?;
 
-    // Create and save the management key for the owner
-    let key = Key {
-        key_type: KeyType::ManagementKey,
-        owner: owner.clone(),
-    };
-    KEYS.save(deps.storage, &owner, &vec![key])
+    // Create and save the management key for the owner, along with any initial keys
+    let mut keys = Vec::new();
+    grant_key_purpose(&mut keys, &owner, KeyType::ManagementKey)?;
+    for (key_owner, key_type) in msg.initial_keys {
+        let key_owner = deps.api.addr_validate(key_owner.as_str()).map_err(|e| {
+            ContractError::InvalidAddress {
+                reason: format!("Invalid key owner address '{}': {}", key_owner, e),
+            }
+        })?;
+        grant_key_purpose(&mut keys, &key_owner, key_type)?;
+    }
+    let management_threshold = msg.management_threshold.unwrap_or(1);
+    ensure_management_weight_remains(&keys, management_threshold)?;
+    ensure_total_keys_within(&keys, msg.max_total_keys)?;
+    KEYS.save(deps.storage, &owner, &keys)
         .map_err(|e| ContractError::SaveError {
             entity: "keys".to_string(),
             reason: e.to_string(),
@@ -55,59 +98,409 @@ pub fn instantiate(
             entity: "owner".to_string(),
             reason: e.to_string(),
         })?;
+    if let Some(admin) = msg.admin {
+        let admin = deps
+            .api
+            .addr_validate(&admin)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid admin address: {}", e),
+            })?;
+        ADMIN
+            .save(deps.storage, &admin)
+            .map_err(|e| ContractError::SaveError {
+                entity: "admin".to_string(),
+                reason: e.to_string(),
+            })?;
+    }
+    let claim_topics_registry = msg
+        .claim_topics_registry
+        .map(|addr| {
+            deps.api
+                .addr_validate(&addr)
+                .map_err(|e| ContractError::InvalidAddress {
+                    reason: format!("Invalid claim topics registry address: {}", e),
+                })
+        })
+        .transpose()?;
+    let owner_roles = msg
+        .owner_roles_address
+        .map(|addr| {
+            deps.api
+                .addr_validate(&addr)
+                .map_err(|e| ContractError::InvalidAddress {
+                    reason: format!("Invalid owner roles address: {}", e),
+                })
+        })
+        .transpose()?;
+
+    // Save the config
+    CONFIG
+        .save(
+            deps.storage,
+            &Config {
+                default_validity_secs: msg.default_validity_secs,
+                signature_encoding: msg.signature_encoding.unwrap_or_default(),
+                allow_issuer_removal: msg.allow_issuer_removal.unwrap_or_default(),
+                claim_topics_registry,
+                owner_roles,
+                unknown_topic_policy: msg.unknown_topic_policy.unwrap_or_default(),
+                duplicate_claim_policy: msg.duplicate_claim_policy.unwrap_or_default(),
+                max_claim_validity_secs: msg.max_claim_validity_secs,
+                management_threshold,
+                max_total_keys: msg.max_total_keys,
+                max_total_claims: msg.max_total_claims,
+                max_claim_data_bytes: msg.max_claim_data_bytes,
+                max_execution_send: msg.max_execution_send,
+                max_claims_per_topic: msg.max_claims_per_topic,
+                no_self_attest_topics: msg.no_self_attest_topics,
+            },
+        )
+        .map_err(|e| ContractError::SaveError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?;
 
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    let sender = info.sender.clone();
+    // Key management waits until enough ManagementKey weight has approved it
+    if let Some(pending) = collect_management_approval(&mut deps, &env, &sender, &msg)? {
+        return Ok(pending);
+    }
+    // Idempotency keys are bound to the message they were first sent with
+    let msg_hash = match &msg {
+        ExecuteMsg::AddKey {
+            idempotency_key: Some(_),
+            ..
+        }
+        | ExecuteMsg::AddClaim {
+            idempotency_key: Some(_),
+            ..
+        } => Some(message_hash(&msg)?),
+        _ => None,
+    };
     match msg {
         ExecuteMsg::AddKey {
             key_owner,
             key_type,
-        } => execute_add_key(deps, info, key_owner, key_type),
+            idempotency_key,
+        } => with_idempotency_key(deps, &sender, idempotency_key.zip(msg_hash), |deps| {
+            execute_add_key(deps, env, info, key_owner, key_type)
+        }),
         ExecuteMsg::RevokeKey {
             key_owner,
             key_type,
-        } => execute_remove_key(deps, info, key_owner, key_type),
+        } => execute_remove_key(deps, env, info, key_owner, key_type),
+        ExecuteMsg::ReassignKeys {
+            from_owner,
+            to_owner,
+        } => execute_reassign_keys(deps, env, info, from_owner, to_owner),
+        ExecuteMsg::SetKeyWeight { key_owner, weight } => {
+            execute_set_key_weight(deps, env, info, key_owner, weight)
+        }
+        ExecuteMsg::SetKeyActive { key_owner, active } => {
+            execute_set_key_active(deps, env, info, key_owner, active)
+        }
+        ExecuteMsg::SetManagementThreshold { threshold } => {
+            execute_set_management_threshold(deps, env, info, threshold)
+        }
+        ExecuteMsg::ProposeNewOwner { new_owner } => {
+            execute_propose_new_owner(deps, env, info, new_owner)
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, info),
+        ExecuteMsg::SetAdmin { admin } => execute_set_admin(deps, info, admin),
+        ExecuteMsg::UpdateConfig { config } => execute_update_config(deps, info, config),
+        ExecuteMsg::ApproveMigration { to_version } => {
+            execute_approve_migration(deps, info, to_version)
+        }
         ExecuteMsg::AddClaim {
             claim,
             public_key,
             user_addr,
-        } => execute_add_claim(deps, info, claim, public_key, user_addr),
+            idempotency_key,
+        } => with_idempotency_key(deps, &sender, idempotency_key.zip(msg_hash), |deps| {
+            execute_add_claim(deps, env, info, claim, public_key, user_addr)
+        }),
+        ExecuteMsg::AddClaimWithPermit {
+            claim,
+            public_key,
+            user_addr,
+            issuer_permit,
+        } => execute_add_claim_with_permit(deps, env, claim, public_key, user_addr, issuer_permit),
+        ExecuteMsg::AddClaimsBatch {
+            claims,
+            public_key,
+            user_addr,
+        } => execute_add_claims_batch(deps, env, info, claims, public_key, user_addr),
         ExecuteMsg::RemoveClaim {
             claim_topic,
             user_addr,
-        } => execute_remove_claim(deps, info, claim_topic, user_addr),
+        } => execute_remove_claim(deps, env, info, claim_topic, user_addr),
+        ExecuteMsg::RemoveClaimById {
+            claim_id,
+            user_addr,
+        } => execute_remove_claim_by_id(deps, env, info, claim_id, user_addr),
+        ExecuteMsg::ReplaceClaim {
+            old_claim_id,
+            new_claim,
+            public_key,
+            user_addr,
+        } => execute_replace_claim(
+            deps,
+            env,
+            info,
+            old_claim_id,
+            new_claim,
+            public_key,
+            user_addr,
+        ),
+        ExecuteMsg::SetClaimExpiry {
+            user_addr,
+            claim_id,
+            valid_until,
+            issuer_signature,
+            public_key,
+        } => execute_set_claim_expiry(
+            deps,
+            env,
+            info,
+            user_addr,
+            claim_id,
+            valid_until,
+            issuer_signature,
+            public_key,
+        ),
+        ExecuteMsg::MigrateClaim {
+            claim_id,
+            user_addr,
+            to_identity,
+            public_key,
+        } => execute_migrate_claim(
+            deps,
+            env,
+            info,
+            claim_id,
+            user_addr,
+            to_identity,
+            public_key,
+        ),
+        ExecuteMsg::ApproveClaimMigration {
+            from_identity,
+            claim_id,
+        } => execute_approve_claim_migration(deps, env, info, from_identity, claim_id),
+        ExecuteMsg::ForceRemoveClaim {
+            user_addr,
+            claim_id,
+        } => execute_force_remove_claim(deps, info, user_addr, claim_id),
+        ExecuteMsg::SetTopicAllowedIssuers { topic, issuers } => {
+            execute_set_topic_allowed_issuers(deps, info, topic, issuers)
+        }
+        ExecuteMsg::SetTopicHolderPurpose {
+            topic,
+            required_holder_purpose,
+        } => execute_set_topic_holder_purpose(deps, info, topic, required_holder_purpose),
+        ExecuteMsg::SetTopicClaimLimit { topic, max_claims } => {
+            execute_set_topic_claim_limit(deps, info, topic, max_claims)
+        }
+        ExecuteMsg::SetSignerContract { signer_contract } => {
+            execute_set_signer_contract(deps, info, signer_contract)
+        }
+        ExecuteMsg::PublishRevocations { revoked_ids } => {
+            execute_publish_revocations(deps, info, revoked_ids)
+        }
+        ExecuteMsg::RevokeClaimSignature {
+            claim_id,
+            user_addr,
+        } => execute_revoke_claim_signature(deps, env, info, claim_id, user_addr),
+        ExecuteMsg::ReceiveClaim {
+            claim,
+            public_key,
+            user_addr,
+        } => execute_receive_claim(deps, env, info, claim, public_key, user_addr),
+        ExecuteMsg::Execute { msgs } => execute_dispatch(deps, env, info, msgs),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetKey {
             key_owner,
             key_type,
         } => to_json_binary(&query_key(deps, key_owner, key_type)?),
-        QueryMsg::GetValidatedClaimsForUser { user_addr } => {
-            to_json_binary(&get_validated_claims_for_user(deps, user_addr)?)
+        QueryMsg::GetKeys { key_owner } => to_json_binary(&get_keys(deps, key_owner)?),
+        QueryMsg::GetKeysByType { key_type } => to_json_binary(&get_keys_by_type(deps, key_type)?),
+        QueryMsg::GetStaleKeys { older_than_secs } => {
+            to_json_binary(&get_stale_keys(deps, env, older_than_secs)?)
         }
+        QueryMsg::GetValidatedClaimsForUser {
+            user_addr,
+            exclude_expired,
+        } => to_json_binary(&get_validated_claims_for_user(
+            deps,
+            env,
+            user_addr,
+            exclude_expired.unwrap_or_default(),
+        )?),
 
         QueryMsg::VerifyClaim {
             claim_id,
             user_addr,
-        } => to_json_binary(&verify_claim(deps, claim_id, user_addr)?),
+            namespace,
+            trusted_issuers_registry,
+        } => to_json_binary(&verify_claim(
+            deps,
+            env,
+            claim_id,
+            user_addr,
+            namespace,
+            trusted_issuers_registry,
+        )?),
+        QueryMsg::PreflightVerification {
+            user_addr,
+            required_topics,
+            trusted_issuers_registry,
+        } => to_json_binary(&preflight_verification(
+            deps,
+            env,
+            user_addr,
+            trusted_issuers_registry,
+            required_topics,
+        )?),
+        QueryMsg::RequiredIssuersForProfile { user_addr, profile } => to_json_binary(
+            &required_issuers_for_profile(deps, env, user_addr, profile)?,
+        ),
+        QueryMsg::WouldAcceptClaims { claims_with_keys } => {
+            to_json_binary(&verify_claims_batch(deps, &env, &claims_with_keys)?)
+        }
+        QueryMsg::VerifyProvidedClaim {
+            claim,
+            public_key,
+            required_topic,
+        } => to_json_binary(&verify_provided_claim(
+            deps,
+            env,
+            *claim,
+            public_key,
+            required_topic,
+        )?),
+        QueryMsg::GetClaimForTopic {
+            user_addr,
+            topic,
+            namespace,
+        } => to_json_binary(&get_claim_for_topic(
+            deps, env, user_addr, topic, namespace,
+        )?),
+        QueryMsg::GetClaim {
+            user_addr,
+            claim_id,
+        } => to_json_binary(&query_claim(deps, env, user_addr, claim_id)?),
+        QueryMsg::GetRawClaim {
+            user_addr,
+            claim_id,
+        } => to_json_binary(&load_claim(deps, user_addr, claim_id)?),
+        QueryMsg::GetClaimContents {
+            user_addr,
+            claim_id,
+            auth,
+        } => to_json_binary(&get_claim_contents(deps, env, user_addr, claim_id, auth)?),
+        QueryMsg::GetClaimsExpiringWithin { user_addr, seconds } => {
+            to_json_binary(&get_claims_expiring_within(deps, env, user_addr, seconds)?)
+        }
+        QueryMsg::GetClaimsPaginated {
+            user_addr,
+            start_after,
+            limit,
+        } => to_json_binary(&get_claims_paginated(deps, user_addr, start_after, limit)?),
+        QueryMsg::GetClaimsByStatus { user_addr, status } => {
+            to_json_binary(&get_claims_by_status(deps, env, user_addr, status)?)
+        }
+        QueryMsg::GetRelationshipClaims { user_addr } => {
+            to_json_binary(&get_relationship_claims(deps, user_addr)?)
+        }
+        QueryMsg::GetClaimsByIssuer { user_addr, issuer } => {
+            to_json_binary(&get_claims_by_issuer(deps, user_addr, issuer)?)
+        }
+        QueryMsg::GetClaimIdsByTopic { user_addr, topic } => {
+            to_json_binary(&get_claim_ids_by_topic(deps, user_addr, topic)?)
+        }
+        QueryMsg::GetClaimEnvelope {
+            user_addr,
+            claim_id,
+        } => to_json_binary(&load_claim(deps, user_addr, claim_id)?.encryption),
+        QueryMsg::VerifyDisclosure {
+            user_addr,
+            claim_id,
+            leaf,
+            proof,
+        } => to_json_binary(&verify_disclosure(
+            deps, env, user_addr, claim_id, leaf, proof,
+        )?),
+        QueryMsg::GetClaimHistory {
+            user_addr,
+            claim_id,
+        } => to_json_binary(&query_claim_history(deps, user_addr, claim_id)?),
+        QueryMsg::GetTopicAllowedIssuers { topic } => to_json_binary(
+            &TOPIC_ALLOWED_ISSUERS
+                .may_load(deps.storage, topic.u128())?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::GetTopicHolderPurpose { topic } => {
+            to_json_binary(&TOPIC_HOLDER_PURPOSE.may_load(deps.storage, topic.u128())?)
+        }
+        QueryMsg::GetTopicClaimLimit { topic } => {
+            to_json_binary(&topic_claim_limit(deps.storage, topic)?)
+        }
+        QueryMsg::ExpectedSigner { claim, public_key } => {
+            to_json_binary(&expected_signer(deps, claim, public_key)?)
+        }
+        QueryMsg::GetSignerContract { issuer } => {
+            to_json_binary(&SIGNER_CONTRACTS.may_load(deps.storage, &issuer)?)
+        }
+        QueryMsg::IsRevoked { issuer, claim_id } => {
+            to_json_binary(&is_revoked(deps, &issuer, &claim_id)?)
+        }
+        QueryMsg::GetRevocations {
+            issuer,
+            start_after,
+            limit,
+        } => to_json_binary(&get_revocations(deps, issuer, start_after, limit)?),
         QueryMsg::GetOwner {} => to_json_binary(&query_owner(deps)?),
+        QueryMsg::GetPendingOwner {} => to_json_binary(&PENDING_OWNER.may_load(deps.storage)?),
+        QueryMsg::GetAdmin {} => to_json_binary(&load_admin(deps.storage)?),
+        QueryMsg::GetConfig {} => {
+            to_json_binary(&CONFIG.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::IdentitySummary {} => to_json_binary(&identity_summary(deps)?),
+        QueryMsg::FullSnapshot { user_addr } => {
+            to_json_binary(&full_snapshot(deps, env, user_addr)?)
+        }
+        QueryMsg::GetMigrationHistory {} => {
+            to_json_binary(&MIGRATIONS.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetClaimsByRemovedIssuer {
+            user_addr,
+            issuer,
+            trusted_issuers_registry,
+        } => to_json_binary(&get_claims_by_removed_issuer(
+            deps,
+            user_addr,
+            issuer,
+            trusted_issuers_registry,
+        )?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let current_version = cw2::get_contract_version(deps.storage)?;
 
     if current_version.contract != CONTRACT_NAME {
@@ -127,14 +520,152 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
         });
     }
 
+    // A separate admin must have approved upgrading to this version, once
+    if ADMIN.exists(deps.storage) {
+        if MIGRATION_APPROVAL.may_load(deps.storage)?.as_deref() != Some(CONTRACT_VERSION) {
+            return Err(ContractError::MigrationNotApproved {
+                version: CONTRACT_VERSION.to_string(),
+            });
+        }
+        MIGRATION_APPROVAL.remove(deps.storage);
+    }
+
     // Perform any necessary state migrations here
+    let legacy_keys_folded = fold_legacy_keys(deps.storage)?;
+    let (claim_ids_assigned, duplicate_claims_merged) = backfill_claim_ids(deps.storage)?;
+    // Claims stored before they were counted
+    let total_claims = CLAIMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, claims)| claims.len() as u32))
+        .sum::<StdResult<u32>>()?;
+    TOTAL_CLAIMS.save(deps.storage, &total_claims)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    // Record the upgrade, keeping only the most recent ones
+    let mut migrations = MIGRATIONS.may_load(deps.storage)?.unwrap_or_default();
+    migrations.push(MigrationRecord {
+        from_version: current_version.version.clone(),
+        to_version: CONTRACT_VERSION.to_string(),
+        block_height: env.block.height,
+    });
+    if migrations.len() > MAX_MIGRATION_RECORDS {
+        migrations.drain(..migrations.len() - MAX_MIGRATION_RECORDS);
+    }
+    MIGRATIONS.save(deps.storage, &migrations)?;
+
     Ok(Response::new()
         .add_attribute("action", "migrate")
         .add_attribute("from_version", current_version.version)
-        .add_attribute("to_version", CONTRACT_VERSION))
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("legacy_keys_folded", legacy_keys_folded.to_string())
+        .add_attribute("claim_ids_assigned", claim_ids_assigned.to_string())
+        .add_attribute(
+            "duplicate_claims_merged",
+            duplicate_claims_merged.to_string(),
+        ))
+}
+
+// A key as stored before keys could hold several purposes, one entry per purpose
+#[derive(Deserialize)]
+struct LegacyKey {
+    owner: Addr,
+    key_type: KeyType,
+    #[serde(default)]
+    last_used: Option<Uint64>,
+    #[serde(default)]
+    weight: Option<u32>,
+    #[serde(default)]
+    active: Option<bool>,
+}
+
+// Folds the per-purpose entries of keys stored before keys held several purposes into
+// one key per holder, returning how many entries were folded away. A holder's
+// ManagementKey entry decides the weight and state of the folded key, so migrating
+// can't lock management out; otherwise the key stays disabled if any entry was.
+fn fold_legacy_keys(storage: &mut dyn Storage) -> StdResult<u64> {
+    let Some(owner) = OWNER.may_load(storage)? else {
+        return Ok(0);
+    };
+    let Some(raw) = storage.get(&KEYS.key(&owner)) else {
+        return Ok(0);
+    };
+    if from_json::<Vec<Key>>(&raw).is_ok() {
+        return Ok(0);
+    }
+
+    let legacy: Vec<LegacyKey> = from_json(&raw)?;
+    let entries = legacy.len();
+    let (management, others): (Vec<_>, Vec<_>) = legacy
+        .into_iter()
+        .partition(|old| old.key_type == KeyType::ManagementKey);
+    let mut keys: Vec<Key> = Vec::new();
+    for old in others.into_iter().chain(management) {
+        let weight = old.weight.unwrap_or(1);
+        let active = old.active.unwrap_or(true);
+        match keys.iter_mut().find(|key| key.owner == old.owner) {
+            Some(key) => {
+                key.last_used = key.last_used.max(old.last_used);
+                if old.key_type == KeyType::ManagementKey {
+                    key.weight = weight;
+                    key.active = active;
+                } else {
+                    key.active &= active;
+                }
+                if !key.has_purpose(&old.key_type) {
+                    key.purposes.push(old.key_type);
+                }
+            }
+            None => keys.push(Key {
+                owner: old.owner,
+                purposes: vec![old.key_type],
+                last_used: old.last_used,
+                weight,
+                active,
+            }),
+        }
+    }
+    KEYS.save(storage, &owner, &keys)?;
+    Ok((entries - keys.len()) as u64)
+}
+
+// Assigns IDs to claims stored before claims had them. Claims whose contents are equal get
+// the same ID; of those, the one that stays valid the longest is kept.
+fn backfill_claim_ids(storage: &mut dyn Storage) -> StdResult<(u64, u64)> {
+    let users = CLAIMS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    let (mut assigned, mut merged) = (0, 0);
+    for user in users {
+        let claims = CLAIMS.load(storage, &user)?;
+        if claims.iter().all(|claim| claim.id.is_some()) {
+            continue;
+        }
+
+        let mut backfilled: Vec<Claim> = Vec::with_capacity(claims.len());
+        for mut claim in claims {
+            if claim.id.is_none() {
+                generate_claim_id(&mut claim);
+                assigned += 1;
+            }
+            match backfilled.iter().position(|kept| kept.id == claim.id) {
+                None => backfilled.push(claim),
+                Some(index) => {
+                    merged += 1;
+                    let outlives = match (claim.valid_until, backfilled[index].valid_until) {
+                        (None, Some(_)) => true,
+                        (Some(valid_until), Some(kept)) => valid_until > kept,
+                        _ => false,
+                    };
+                    if outlives {
+                        backfilled[index] = claim;
+                    }
+                }
+            }
+        }
+        CLAIMS.save(storage, &user, &backfilled)?;
+    }
+    Ok((assigned, merged))
 }
 
 fn query_key(deps: Deps, key_owner: String, key_type: String) -> StdResult<Key> {
@@ -144,15 +675,10 @@ fn query_key(deps: Deps, key_owner: String, key_type: String) -> StdResult<Key>
         .map_err(|e| StdError::generic_err(format!("Invalid key owner address: {}", e)))?;
     let key_type = KeyType::from_str(&key_type)
         .map_err(|_| StdError::generic_err(format!("Invalid key type: {}", key_type)))?;
-    let owner = OWNER
-        .load(deps.storage)
-        .map_err(|e| StdError::generic_err(format!("Failed to load owner: {}", e)))?;
-    let keys = KEYS.load(deps.storage, &owner).map_err(|e| {
-        StdError::generic_err(format!("Failed to load keys for owner {}: {}", owner, e))
-    })?;
-    keys.iter()
-        .find(|key| key.key_type == key_type && key.owner == key_owner)
-        .cloned()
+    // Keys are looked up by holder, which has at most one key (see `grant_key_purpose`)
+    load_keys(deps)?
+        .into_iter()
+        .find(|key| key.owner == key_owner && key.has_purpose(&key_type))
         .ok_or_else(|| {
             StdError::not_found(format!(
                 "Key not found for owner {} and type {:?}",
@@ -161,127 +687,955 @@ fn query_key(deps: Deps, key_owner: String, key_type: String) -> StdResult<Key>
         })
 }
 
-fn get_validated_claims_for_user(deps: Deps, user_addr: Addr) -> StdResult<Vec<Claim>> {
+fn get_keys(deps: Deps, key_owner: String) -> StdResult<Vec<Key>> {
+    let key_owner = deps
+        .api
+        .addr_validate(&key_owner)
+        .map_err(|e| StdError::generic_err(format!("Invalid key owner address: {}", e)))?;
+    Ok(load_keys(deps)?
+        .into_iter()
+        .filter(|key| key.owner == key_owner)
+        .collect())
+}
+
+fn get_keys_by_type(deps: Deps, key_type: String) -> StdResult<Vec<Key>> {
+    let key_type = KeyType::from_str(&key_type)
+        .map_err(|_| StdError::generic_err(format!("Invalid key type: {}", key_type)))?;
+    Ok(load_keys(deps)?
+        .into_iter()
+        .filter(|key| key.has_purpose(&key_type))
+        .collect())
+}
+
+fn get_stale_keys(deps: Deps, env: Env, older_than_secs: u64) -> StdResult<Vec<Key>> {
+    let cutoff = env.block.time.seconds().saturating_sub(older_than_secs);
+    Ok(load_keys(deps)?
+        .into_iter()
+        .filter(|key| {
+            key.last_used
+                .is_none_or(|last_used| last_used.u64() < cutoff)
+        })
+        .collect())
+}
+
+// Every key of the identity
+fn load_keys(deps: Deps) -> StdResult<Vec<Key>> {
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| StdError::generic_err(format!("Failed to load owner: {}", e)))?;
+    Ok(KEYS.may_load(deps.storage, &owner)?.unwrap_or_default())
+}
+
+fn get_validated_claims_for_user(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    exclude_expired: bool,
+) -> StdResult<Vec<Claim>> {
     let user_addr = deps.api.addr_validate(user_addr.as_str())?;
 
-    let claims = CLAIMS
+    let mut claims = CLAIMS
         .load(deps.storage, &user_addr)
         .map_err(|e| StdError::generic_err(format!("User has no claims {}: {}", user_addr, e)))?;
+    if exclude_expired {
+        claims.retain(|claim| !claim.is_expired(env.block.time));
+    }
+    claims.retain(|claim| {
+        claim
+            .id
+            .as_deref()
+            .is_none_or(|id| !REVOKED_CLAIMS.has(deps.storage, id))
+    });
     Ok(claims)
 }
 
-fn verify_claim(deps: Deps, claim_id: Uint128, user_addr: Addr) -> StdResult<bool> {
+fn get_claims_paginated(
+    deps: Deps,
+    user_addr: Addr,
+    start_after: Option<Uint128>,
+    limit: Option<u32>,
+) -> StdResult<ClaimsPageResponse> {
     let user_addr = deps.api.addr_validate(user_addr.as_str())?;
-    let claims = CLAIMS
-        .load(deps.storage, &user_addr)
-        .map_err(|e| StdError::generic_err(format!("User has no claims  {}: {}", user_addr, e)))?;
+    let mut claims: Vec<Claim> = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|claim| start_after.is_none_or(|start_after| claim.topic > start_after))
+        .collect();
+    claims.sort_by_key(|claim| claim.topic);
 
-    Ok(claims.iter().any(|claim| claim.topic == claim_id))
-}
+    // Cut the page before a topic that doesn't fit whole, unless it's the only one
+    let limit = page_limit(limit);
+    if claims.len() > limit {
+        let split_topic = claims[limit].topic;
+        let cut = claims[..limit]
+            .iter()
+            .position(|claim| claim.topic == split_topic)
+            .unwrap_or(limit);
+        let end = match cut {
+            0 => claims.partition_point(|claim| claim.topic <= split_topic),
+            cut => cut,
+        };
+        claims.truncate(end);
+    }
 
-fn query_owner(deps: Deps) -> StdResult<Addr> {
-    OWNER
-        .load(deps.storage)
-        .map_err(|e| StdError::generic_err(format!("Failed to load owner: {}", e)))
+    Ok(ClaimsPageResponse {
+        last_topic: claims.last().map(|claim| claim.topic),
+        claims,
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::utils::hash_claim_without_signature;
-    use cosmwasm_std::{testing::MockApi, Addr, Binary};
-    use cw_multi_test::{App, ContractWrapper, Executor};
-    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
-
-    fn instantiate_contract(app: &mut App, owner: Addr) -> Addr {
-        let code = ContractWrapper::new(execute, instantiate, query);
-        let code_id = app.store_code(Box::new(code));
+fn verify_claim(
+    deps: Deps,
+    env: Env,
+    claim_id: Uint128,
+    user_addr: Addr,
+    namespace: Option<String>,
+    trusted_issuers_registry: Option<String>,
+) -> StdResult<bool> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let namespace = namespace.filter(|namespace| !namespace.is_empty());
+    let trusted_issuers_registry = trusted_issuers_registry
+        .map(|registry| deps.api.addr_validate(&registry))
+        .transpose()?;
 
-        app.instantiate_contract(
-            code_id,
-            owner.clone(),
-            &InstantiateMsg {
-                owner: owner.to_string(),
-            },
-            &[],
-            "On-chain ID Contract",
-            Some(owner.to_string()),
-        )
-        .unwrap()
+    if namespace.is_none() {
+        if let Some(verdict) = unknown_topic_verdict(deps, claim_id)? {
+            return Ok(verdict);
+        }
     }
 
-    fn create_wallet(app: &App) -> (Addr, SecretKey, PublicKey) {
-        let secp = Secp256k1::new();
-        let secret_key = SecretKey::new(&mut rand::thread_rng());
-        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
-        let addr = app.api().addr_make(&public_key.to_string());
-        (addr.clone(), secret_key, public_key)
+    // An identity without claims is simply not verified
+    let mut claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+
+    if let Some(registry) = trusted_issuers_registry {
+        let trusted = trusted_issuers_for_topic(deps, &registry, claim_id)?;
+        claims.retain(|claim| trusted.contains(&claim.issuer));
     }
 
-    #[test]
-    fn proper_initialization() {
-        let mut app = App::default();
-        let owner = app.api().addr_make("owner");
+    Ok(select_claim(deps, &env, &claims, claim_id, namespace.as_deref())?.is_some())
+}
 
-        let contract_addr = instantiate_contract(&mut app, owner.clone());
+// Issuers `registry` trusts for `topic`. The registry only knows topic numbers, so a
+// namespaced topic is looked up by its number alone.
+fn trusted_issuers_for_topic(deps: Deps, registry: &Addr, topic: Uint128) -> StdResult<Vec<Addr>> {
+    deps.querier
+        .query_wasm_smart(
+            registry,
+            &TrustedIssuersQueryMsg::GetIssuersRankedForTopic {
+                topic,
+                claim_topics_registry: None,
+            },
+        )
+        .map_err(|e| {
+            StdError::generic_err(
+                ContractError::CrossContractQueryFailed {
+                    contract: registry.to_string(),
+                    query: "GetIssuersRankedForTopic".to_string(),
+                    reason: e.to_string(),
+                }
+                .to_string(),
+            )
+        })
+}
 
-        // Test query_owner
-        let res: Addr = app
-            .wrap()
-            .query_wasm_smart(contract_addr, &QueryMsg::GetOwner {})
-            .unwrap();
-        assert_eq!(res, owner);
-    }
+// The topics a trusted issuers registry trusts each issuer for, asked once per issuer
+struct IssuerTrust {
+    registry: Addr,
+    topics: BTreeMap<Addr, Vec<Uint128>>,
+}
 
-    #[test]
-    fn add_and_remove_key() {
-        let mut app = App::default();
-        let owner = app.api().addr_make("owner");
-        let contract_addr = instantiate_contract(&mut app, owner.clone());
+impl IssuerTrust {
+    fn new(registry: Addr) -> Self {
+        IssuerTrust {
+            registry,
+            topics: BTreeMap::new(),
+        }
+    }
 
-        let key_owner = app.api().addr_make("new_key_owner");
+    fn trusts(&mut self, deps: Deps, issuer: &Addr, topic: Uint128) -> StdResult<bool> {
+        if !self.topics.contains_key(issuer) {
+            let topics = self.query_topics(deps, issuer)?;
+            self.topics.insert(issuer.clone(), topics);
+        }
+        Ok(self.topics[issuer].contains(&topic))
+    }
 
-        // Test adding a key
-        let msg = ExecuteMsg::AddKey {
-            key_owner: key_owner.to_string(),
-            key_type: "ExecutionKey".to_string(),
+    // The registry fails for issuers it doesn't know, which are then trusted for nothing
+    fn query_topics(&self, deps: Deps, issuer: &Addr) -> StdResult<Vec<Uint128>> {
+        let query_failed = |query: &str, e: StdError| {
+            StdError::generic_err(
+                ContractError::CrossContractQueryFailed {
+                    contract: self.registry.to_string(),
+                    query: query.to_string(),
+                    reason: e.to_string(),
+                }
+                .to_string(),
+            )
         };
-        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
-            .unwrap();
-        // Test querying the added key
-        let res: Key = app
-            .wrap()
+        let err = match deps.querier.query_wasm_smart(
+            &self.registry,
+            &TrustedIssuersQueryMsg::GetIssuerClaimTopics {
+                issuer: issuer.clone(),
+            },
+        ) {
+            Ok(topics) => return Ok(topics),
+            Err(e) => e,
+        };
+        let trusted: bool = deps
+            .querier
             .query_wasm_smart(
-                contract_addr.clone(),
-                &QueryMsg::GetKey {
-                    key_owner: key_owner.to_string().clone(),
-                    key_type: "ExecutionKey".to_string(),
+                &self.registry,
+                &TrustedIssuersQueryMsg::IsTrustedIssuer {
+                    issuer: issuer.clone(),
                 },
             )
-            .unwrap();
-        assert_eq!(res.owner, Addr::unchecked(key_owner.clone()));
-        assert_eq!(res.key_type, KeyType::ExecutionKey);
+            .map_err(|e| query_failed("IsTrustedIssuer", e))?;
+        if trusted {
+            return Err(query_failed("GetIssuerClaimTopics", err));
+        }
+        Ok(vec![])
+    }
+}
 
-        // Test removing the key
-        let msg = ExecuteMsg::RevokeKey {
-            key_owner: key_owner.to_string(),
-            key_type: "ExecutionKey".to_string(),
-        };
-        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
-            .unwrap();
+// Verdict on a default namespace topic the claim topics registry doesn't know, which is
+// handled by policy. `None` when the registry knows the topic or none is configured.
+fn unknown_topic_verdict(deps: Deps, topic: Uint128) -> StdResult<Option<bool>> {
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let Some(registry) = config.claim_topics_registry else {
+        return Ok(None);
+    };
+    let status: ClaimTopicStatus = deps
+        .querier
+        .query_wasm_smart(
+            &registry,
+            &ClaimTopicsQueryMsg::GetClaimTopicStatus { topic },
+        )
+        .map_err(|e| {
+            StdError::generic_err(
+                ContractError::CrossContractQueryFailed {
+                    contract: registry.to_string(),
+                    query: "GetClaimTopicStatus".to_string(),
+                    reason: e.to_string(),
+                }
+                .to_string(),
+            )
+        })?;
+    Ok((status == ClaimTopicStatus::NotFound)
+        .then_some(config.unknown_topic_policy == UnknownTopicPolicy::Ignore))
+}
 
-        // Verify the key is removed
-        let res: Result<Key, _> = app.wrap().query_wasm_smart(
-            contract_addr,
-            &QueryMsg::GetKey {
-                key_owner: key_owner.to_string(),
-                key_type: "ExecutionKey".to_string(),
-            },
-        );
-        assert!(res.is_err());
+fn preflight_verification(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    trusted_issuers_registry: Option<String>,
+    required_topics: Vec<Uint128>,
+) -> StdResult<PreflightVerificationResponse> {
+    if required_topics.len() > MAX_CLAIMS_PER_BATCH {
+        return Err(StdError::generic_err(format!(
+            "At most {} topics can be verified at once",
+            MAX_CLAIMS_PER_BATCH
+        )));
     }
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let mut issuer_trust = trusted_issuers_registry
+        .map(|registry| deps.api.addr_validate(&registry))
+        .transpose()?
+        .map(IssuerTrust::new);
+    // Loaded once for all topics
+    let claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
 
-    #[test]
+    let mut topics = Vec::with_capacity(required_topics.len());
+    for topic in required_topics {
+        let verification = match unknown_topic_verdict(deps, topic)? {
+            Some(satisfied) => TopicVerification {
+                topic,
+                satisfied,
+                claim_id: None,
+                issuer: None,
+            },
+            None => {
+                let mut candidates = vec![];
+                for claim in claims.iter().filter(|claim| claim.is_in_topic(topic, None)) {
+                    let trusted = match &mut issuer_trust {
+                        Some(issuer_trust) => issuer_trust.trusts(deps, &claim.issuer, topic)?,
+                        None => true,
+                    };
+                    if trusted {
+                        candidates.push(claim.clone());
+                    }
+                }
+                let claim = select_claim(deps, &env, &candidates, topic, None)?;
+                TopicVerification {
+                    topic,
+                    satisfied: claim.is_some(),
+                    claim_id: claim.and_then(|claim| claim.id.clone()),
+                    issuer: claim.map(|claim| claim.issuer.clone()),
+                }
+            }
+        };
+        topics.push(verification);
+    }
+    Ok(PreflightVerificationResponse {
+        verified: topics.iter().all(|topic| topic.satisfied),
+        topics,
+    })
+}
+
+fn required_issuers_for_profile(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    mut profile: Vec<Uint128>,
+) -> StdResult<RequiredIssuersResponse> {
+    if profile.len() > MAX_PROFILE_TOPICS {
+        return Err(StdError::generic_err(format!(
+            "A profile has at most {} topics",
+            MAX_PROFILE_TOPICS
+        )));
+    }
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+    profile.sort();
+    profile.dedup();
+
+    // Unsatisfied topics, with the issuers allowed to attest to each
+    let mut open: Vec<Vec<Addr>> = Vec::new();
+    let mut uncovered_topics = Vec::new();
+    for topic in profile {
+        // No claim changes the verdict on unregistered topics
+        if let Some(satisfied) = unknown_topic_verdict(deps, topic)? {
+            if !satisfied {
+                uncovered_topics.push(topic);
+            }
+            continue;
+        }
+        if select_claim(deps, &env, &claims, topic, None)?.is_some() {
+            continue;
+        }
+        match TOPIC_ALLOWED_ISSUERS.may_load(deps.storage, topic.u128())? {
+            Some(issuers) if !issuers.is_empty() => open.push(issuers),
+            _ => uncovered_topics.push(topic),
+        }
+    }
+
+    Ok(RequiredIssuersResponse {
+        issuers: fewest_covering_issuers(&open),
+        uncovered_topics,
+    })
+}
+
+// Smallest set of issuers that together are allowed for every topic, each given by its
+// allowed issuers. Searched exactly over the subsets of topics, which `MAX_PROFILE_TOPICS`
+// keeps small.
+fn fewest_covering_issuers(topics: &[Vec<Addr>]) -> Vec<Addr> {
+    // Each issuer with the topics it may attest to, as a bit set
+    let mut issuers: Vec<(Addr, usize)> = Vec::new();
+    for (index, allowed) in topics.iter().enumerate() {
+        for issuer in allowed {
+            match issuers.iter_mut().find(|(addr, _)| addr == issuer) {
+                Some((_, covered)) => *covered |= 1 << index,
+                None => issuers.push((issuer.clone(), 1 << index)),
+            }
+        }
+    }
+    issuers.sort();
+
+    // Fewest issuers covering each set of topics, with the set before the last one added.
+    // Adding an issuer only ever grows the set, so sets are settled in increasing order.
+    let all = (1usize << topics.len()) - 1;
+    let mut fewest: Vec<Option<(usize, usize, usize)>> = vec![None; all + 1];
+    fewest[0] = Some((0, 0, 0));
+    for covered in 0..all {
+        let Some((count, _, _)) = fewest[covered] else {
+            continue;
+        };
+        for (index, (_, topics)) in issuers.iter().enumerate() {
+            let next = covered | topics;
+            if fewest[next].is_none_or(|(best, _, _)| count + 1 < best) {
+                fewest[next] = Some((count + 1, covered, index));
+            }
+        }
+    }
+
+    let mut cover = Vec::new();
+    let mut covered = all;
+    while covered != 0 {
+        let Some((_, previous, index)) = fewest[covered] else {
+            break;
+        };
+        cover.push(issuers[index].0.clone());
+        covered = previous;
+    }
+    cover.sort();
+    cover
+}
+
+// The claim relied upon for `topic` among several valid ones: the most recently issued,
+// then the lowest issuer address, so the choice doesn't depend on insertion order
+fn select_claim<'a>(
+    deps: Deps,
+    env: &Env,
+    claims: &'a [Claim],
+    topic: Uint128,
+    namespace: Option<&str>,
+) -> StdResult<Option<&'a Claim>> {
+    let mut selected: Option<&Claim> = None;
+    for claim in claims
+        .iter()
+        .filter(|claim| claim.is_in_topic(topic, namespace))
+    {
+        if invalid_reason(deps, env, claim)?.is_some() {
+            continue;
+        }
+        let preferred = match selected {
+            None => true,
+            Some(current) => claim
+                .issued_at
+                .cmp(&current.issued_at)
+                .then_with(|| current.issuer.cmp(&claim.issuer))
+                .is_gt(),
+        };
+        if preferred {
+            selected = Some(claim);
+        }
+    }
+    Ok(selected)
+}
+
+fn get_claim_for_topic(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    topic: Uint128,
+    namespace: Option<String>,
+) -> StdResult<Option<Claim>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let namespace = namespace.filter(|namespace| !namespace.is_empty());
+    let claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+    Ok(select_claim(deps, &env, &claims, topic, namespace.as_deref())?.cloned())
+}
+
+fn claim_status(deps: Deps, env: &Env, claim: &Claim) -> StdResult<ClaimStatus> {
+    if claim.is_expired(env.block.time) {
+        return Ok(ClaimStatus::Expired);
+    }
+    if let Some(id) = &claim.id {
+        if is_revoked(deps, &claim.issuer, id)? {
+            return Ok(ClaimStatus::Revoked);
+        }
+    }
+    // Topic policies cover the default namespace only
+    let allowed_issuers = match claim.namespace {
+        Some(_) => None,
+        None => TOPIC_ALLOWED_ISSUERS.may_load(deps.storage, claim.topic.u128())?,
+    };
+    if let Some(allowed_issuers) = allowed_issuers {
+        if !allowed_issuers.contains(&claim.issuer) {
+            return Ok(ClaimStatus::UntrustedIssuer);
+        }
+    }
+    Ok(ClaimStatus::Valid)
+}
+
+// Why a stored claim should not be relied upon, `None` when it is valid
+fn invalid_reason(deps: Deps, env: &Env, claim: &Claim) -> StdResult<Option<String>> {
+    let reason = match claim_status(deps, env, claim)? {
+        ClaimStatus::Valid => return Ok(None),
+        ClaimStatus::Expired => "Claim has expired",
+        ClaimStatus::Revoked => "Claim has been revoked by its issuer",
+        ClaimStatus::UntrustedIssuer => "Issuer is not allowed for this topic",
+    };
+    Ok(Some(reason.to_string()))
+}
+
+fn get_claims_by_status(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    status: ClaimStatus,
+) -> StdResult<Vec<Claim>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let mut matching = vec![];
+    for claim in CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default()
+    {
+        if claim_status(deps, &env, &claim)? == status {
+            matching.push(claim);
+        }
+    }
+    Ok(matching)
+}
+
+fn load_claim(deps: Deps, user_addr: Addr, claim_id: String) -> StdResult<Claim> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|claim| claim.id.as_deref() == Some(claim_id.as_str()))
+        .ok_or_else(|| {
+            StdError::not_found(format!(
+                "Claim {} not found for user {}",
+                claim_id, user_addr
+            ))
+        })
+}
+
+fn verify_disclosure(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    claim_id: String,
+    leaf: Binary,
+    proof: Vec<Binary>,
+) -> StdResult<bool> {
+    if proof.len() > MAX_DISCLOSURE_PROOF_DEPTH {
+        return Err(StdError::generic_err(format!(
+            "Proofs are limited to {} hashes",
+            MAX_DISCLOSURE_PROOF_DEPTH
+        )));
+    }
+    let claim = load_claim(deps, user_addr, claim_id)?;
+    let Some(data_root) = &claim.data_root else {
+        return Err(StdError::generic_err(
+            "Claim does not commit to a data root",
+        ));
+    };
+    if invalid_reason(deps, &env, &claim)?.is_some() {
+        return Ok(false);
+    }
+    Ok(merkle_root(&leaf, &proof).is_some_and(|root| root.as_slice() == data_root.as_slice()))
+}
+
+fn query_claim_history(
+    deps: Deps,
+    user_addr: Addr,
+    claim_id: String,
+) -> StdResult<Vec<ClaimVersion>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let mut versions = CLAIM_VERSIONS
+        .may_load(deps.storage, (&user_addr, &claim_id))?
+        .unwrap_or_default();
+    match load_claim(deps, user_addr, claim_id) {
+        Ok(claim) => versions.push(ClaimVersion {
+            version: versions.last().map_or(1, |version| version.version + 1),
+            claim,
+            superseded_at: None,
+        }),
+        // A removed claim keeps its history
+        Err(err) if versions.is_empty() => return Err(err),
+        Err(_) => {}
+    }
+    Ok(versions)
+}
+
+fn query_claim(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    claim_id: String,
+) -> StdResult<ClaimResponse> {
+    let claim = load_claim(deps, user_addr, claim_id)?;
+    let reason = invalid_reason(deps, &env, &claim)?;
+    Ok(ClaimResponse {
+        valid: reason.is_none(),
+        reason,
+        claim,
+    })
+}
+
+fn get_claim_contents(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    claim_id: String,
+    auth: Option<QueryAuth>,
+) -> StdResult<ClaimContentsResponse> {
+    // The signature covers the query as asked, so it can't be reused for another claim
+    let query = QueryMsg::GetClaimContents {
+        user_addr: user_addr.clone(),
+        claim_id: claim_id.clone(),
+        auth: None,
+    };
+    let mut claim = load_claim(deps, user_addr, claim_id)?;
+    let authorized = is_query_authorized(
+        deps,
+        &env,
+        &query,
+        auth.as_ref(),
+        &[KeyType::ManagementKey, KeyType::ExecutionKey],
+    )?;
+    if !authorized {
+        claim.data = Binary::default();
+        claim.uri = String::new();
+        claim.encryption = None;
+    }
+    Ok(ClaimContentsResponse {
+        claim,
+        redacted: !authorized,
+    })
+}
+
+fn get_claims_expiring_within(
+    deps: Deps,
+    env: Env,
+    user_addr: Addr,
+    seconds: u64,
+) -> StdResult<Vec<Claim>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let now = env.block.time.seconds();
+    let window_end = now.saturating_add(seconds);
+
+    let mut claims: Vec<Claim> = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|claim| {
+            claim.valid_until.is_some_and(|valid_until| {
+                valid_until.u64() > now && valid_until.u64() <= window_end
+            })
+        })
+        .collect();
+    claims.sort_by_key(|claim| claim.valid_until);
+    Ok(claims)
+}
+
+fn get_relationship_claims(deps: Deps, user_addr: Addr) -> StdResult<Vec<Claim>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+
+    let claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+    Ok(claims
+        .into_iter()
+        .filter(|claim| claim.related_identity.is_some())
+        .collect())
+}
+
+fn get_claims_by_issuer(deps: Deps, user_addr: Addr, issuer: Addr) -> StdResult<Vec<Claim>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let issuer = deps.api.addr_validate(issuer.as_str())?;
+
+    let claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+    Ok(claims
+        .into_iter()
+        .filter(|claim| claim.issuer == issuer)
+        .collect())
+}
+
+fn get_claims_by_removed_issuer(
+    deps: Deps,
+    user_addr: Addr,
+    issuer: Addr,
+    trusted_issuers_registry: String,
+) -> StdResult<Vec<Claim>> {
+    let registry = deps.api.addr_validate(&trusted_issuers_registry)?;
+    let claims = get_claims_by_issuer(deps, user_addr, issuer.clone())?;
+
+    // Each topic is looked up once, claims usually share a handful of them
+    let mut still_trusted = BTreeMap::new();
+    let mut removed = vec![];
+    for claim in claims {
+        let trusted = match still_trusted.get(&claim.topic) {
+            Some(trusted) => *trusted,
+            None => {
+                let trusted =
+                    trusted_issuers_for_topic(deps, &registry, claim.topic)?.contains(&issuer);
+                still_trusted.insert(claim.topic, trusted);
+                trusted
+            }
+        };
+        if !trusted {
+            removed.push(claim);
+        }
+    }
+    Ok(removed)
+}
+
+fn get_claim_ids_by_topic(deps: Deps, user_addr: Addr, topic: Uint128) -> StdResult<Vec<String>> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+
+    let claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+    Ok(claims
+        .into_iter()
+        .filter(|claim| claim.is_in_topic(topic, None))
+        .filter_map(|claim| claim.id)
+        .collect())
+}
+
+fn expected_signer(
+    deps: Deps,
+    claim: Box<Claim>,
+    public_key: Binary,
+) -> StdResult<ExpectedSignerResponse> {
+    let signer = key_address(deps, &claim.signature_scheme, &public_key)?;
+    Ok(ExpectedSignerResponse {
+        matches_issuer: signer == claim.issuer,
+        signer,
+    })
+}
+
+fn verify_provided_claim(
+    deps: Deps,
+    env: Env,
+    mut claim: Claim,
+    public_key: Binary,
+    required_topic: Uint128,
+) -> StdResult<bool> {
+    if !claim.is_in_topic(required_topic, None) || claim.is_expired(env.block.time) {
+        return Ok(false);
+    }
+    if claim.id.is_none() {
+        generate_claim_id(&mut claim);
+    }
+    if is_revoked(deps, &claim.issuer, claim.id.as_deref().unwrap_or_default())? {
+        return Ok(false);
+    }
+    Ok(verify_claims_batch(deps, &env, &[(claim, public_key)])?.is_empty())
+}
+
+fn get_revocations(
+    deps: Deps,
+    issuer: Addr,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Binary>> {
+    let revoked = CRL.may_load(deps.storage, &issuer)?.unwrap_or_default();
+    Ok(paginate_list(revoked, start_after, limit))
+}
+
+fn query_owner(deps: Deps) -> StdResult<Addr> {
+    OWNER
+        .load(deps.storage)
+        .map_err(|e| StdError::generic_err(format!("Failed to load owner: {}", e)))
+}
+
+fn identity_summary(deps: Deps) -> StdResult<IdentitySummaryResponse> {
+    let owner = query_owner(deps)?;
+    let key_count = KEYS
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default()
+        .iter()
+        .map(|key| key.purposes.len() as u32)
+        .sum();
+    let claim_count = TOTAL_CLAIMS.may_load(deps.storage)?.unwrap_or_default();
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    Ok(IdentitySummaryResponse {
+        owner,
+        key_count,
+        claim_count,
+        claim_topics_registry: config.claim_topics_registry,
+        owner_roles: config.owner_roles,
+    })
+}
+
+fn full_snapshot(deps: Deps, env: Env, user_addr: Addr) -> StdResult<FullSnapshotResponse> {
+    let user_addr = deps.api.addr_validate(user_addr.as_str())?;
+    let owner = query_owner(deps)?;
+    let limit = MAX_LIMIT as usize;
+
+    let mut keys = KEYS.may_load(deps.storage, &owner)?.unwrap_or_default();
+    let mut claims = CLAIMS
+        .may_load(deps.storage, &user_addr)?
+        .unwrap_or_default();
+    let complete = keys.len() <= limit && claims.len() <= limit;
+    keys.truncate(limit);
+    claims.sort_by_key(|claim| claim.topic);
+    claims.truncate(limit);
+    let claims = claims
+        .into_iter()
+        .map(|claim| {
+            Ok(ClaimSnapshot {
+                status: claim_status(deps, &env, &claim)?,
+                claim,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    Ok(FullSnapshotResponse {
+        admin: load_admin(deps.storage)?,
+        owner,
+        keys,
+        claims,
+        claim_topics_registry: config.claim_topics_registry,
+        owner_roles: config.owner_roles,
+        complete,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::ClaimRejection;
+    use crate::state::WellKnownTopic;
+    use crate::state::{EncryptionEnvelope, SignatureEncoding, SignatureScheme, SignedExpiry};
+    use crate::utils::{
+        claim_id_hash, claim_message_hash, claim_permit_message_hash, expiry_update_message_hash,
+        hash_claim_without_signature, pubkey_to_address,
+    };
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi};
+    use cosmwasm_std::{Addr, Api, Binary, CanonicalAddr, Empty, Uint64};
+    use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+    use ripemd::Ripemd160;
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+    use sha2::{Digest, Sha256};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn instantiate_contract(app: &mut App, owner: Addr) -> Addr {
+        let code = ContractWrapper::new(execute, instantiate, query);
+        let code_id = app.store_code(Box::new(code));
+
+        app.instantiate_contract(
+            code_id,
+            owner.clone(),
+            &InstantiateMsg {
+                owner: owner.to_string(),
+                ..Default::default()
+            },
+            &[],
+            "On-chain ID Contract",
+            Some(owner.to_string()),
+        )
+        .unwrap()
+    }
+
+    fn instantiate_contract_with_msg(app: &mut App, owner: Addr, msg: InstantiateMsg) -> Addr {
+        let code = ContractWrapper::new(execute, instantiate, query);
+        let code_id = app.store_code(Box::new(code));
+
+        app.instantiate_contract(
+            code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            "On-chain ID Contract",
+            Some(owner.to_string()),
+        )
+        .unwrap()
+    }
+
+    // Nonces handed to test claims, increasing across tests so each is above the issuer's last
+    static NEXT_NONCE: AtomicU64 = AtomicU64::new(1);
+
+    // Signs `claim`, issued for the usual `user_addr` test holder with a fresh nonce unless
+    // it sets its own subject and nonce
+    fn sign_claim(claim: Claim, secret_key: &SecretKey) -> Claim {
+        let claim = Claim {
+            subject: claim
+                .subject
+                .or_else(|| Some(MockApi::default().addr_make("user_addr"))),
+            nonce: claim
+                .nonce
+                .or_else(|| Some(Uint64::new(NEXT_NONCE.fetch_add(1, Ordering::Relaxed)))),
+            ..claim
+        };
+        sign_claim_as_is(claim, secret_key)
+    }
+
+    fn sign_claim_as_is(claim: Claim, secret_key: &SecretKey) -> Claim {
+        let message_hash = hash_claim_without_signature(&claim);
+        let secp = Secp256k1::new();
+        let message = Message::from_slice(&message_hash).unwrap();
+        let signature = secp.sign_ecdsa(&message, secret_key);
+        Claim {
+            signature: Binary::from(signature.serialize_compact()),
+            ..claim
+        }
+    }
+
+    fn create_wallet(app: &App) -> (Addr, SecretKey, PublicKey) {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+        let hash = Ripemd160::digest(Sha256::digest(public_key.serialize()));
+        let addr = app
+            .api()
+            .addr_humanize(&CanonicalAddr::from(hash.as_slice()))
+            .unwrap();
+        (addr, secret_key, public_key)
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+
+        // Test query_owner
+        let res: Addr = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &QueryMsg::GetOwner {})
+            .unwrap();
+        assert_eq!(res, owner);
+    }
+
+    #[test]
+    fn add_and_remove_key() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+
+        let key_owner = app.api().addr_make("new_key_owner");
+
+        // Test adding a key
+        let msg = ExecuteMsg::AddKey {
+            key_owner: key_owner.to_string(),
+            key_type: "ExecutionKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        // Test querying the added key
+        let res: Key = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKey {
+                    key_owner: key_owner.to_string().clone(),
+                    key_type: "ExecutionKey".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, Addr::unchecked(key_owner.clone()));
+        assert!(res.has_purpose(&KeyType::ExecutionKey));
+
+        // Test removing the key
+        let msg = ExecuteMsg::RevokeKey {
+            key_owner: key_owner.to_string(),
+            key_type: "ExecutionKey".to_string(),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // Verify the key is removed
+        let res: Result<Key, _> = app.wrap().query_wasm_smart(
+            contract_addr,
+            &QueryMsg::GetKey {
+                key_owner: key_owner.to_string(),
+                key_type: "ExecutionKey".to_string(),
+            },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
     fn add_and_remove_claim() {
         let mut app = App::default();
         let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
@@ -292,17 +1646,30 @@ mod tests {
         let msg = ExecuteMsg::AddKey {
             key_owner: owner_addr.to_string(),
             key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
         };
         app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
             .unwrap();
 
         // Create a claim
         let claim = Claim {
-            topic: Uint128::one(),
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
             issuer: owner_addr.clone(),
             signature: Binary::from(vec![]), // This will be filled later
             data: Binary::from(vec![4, 5, 6]),
+            data_hash: None,
             uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: Some(user_addr.clone()),
+            nonce: Some(Uint64::new(1)),
+            signed_expiry: None,
         };
 
         // Hash the claim data (excluding signature)
@@ -324,6 +1691,7 @@ mod tests {
             claim: signed_claim.clone(),
             public_key: Binary::from(owner_public_key.serialize()),
             user_addr: user_addr.clone(),
+            idempotency_key: None,
         };
         app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
             .unwrap();
@@ -334,32 +1702,93 @@ mod tests {
             .query_wasm_smart(
                 contract_addr.clone(),
                 &QueryMsg::VerifyClaim {
-                    claim_id: Uint128::one(),
+                    claim_id: WellKnownTopic::Kyc.into(),
                     user_addr: user_addr.clone(),
+                    namespace: None,
+                    trusted_issuers_registry: None,
                 },
             )
             .unwrap();
-        assert_eq!(res, true);
+        assert!(res);
 
         // Test removing the claim
         let msg = ExecuteMsg::RemoveClaim {
-            claim_topic: Uint128::one(),
+            claim_topic: WellKnownTopic::Kyc.into(),
             user_addr: user_addr.clone(),
         };
         app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
             .unwrap();
 
         // Verify the claim is removed
-        let res: StdResult<Binary> = app.wrap().query_wasm_smart(
-            contract_addr.clone(),
-            &QueryMsg::VerifyClaim {
-                claim_id: Uint128::one(),
-                user_addr: user_addr.clone(),
-            },
-        );
-        assert!(res.is_err());
-    }
-
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr: user_addr.clone(),
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(!res);
+    }
+
+    #[test]
+    fn verify_claim_for_unknown_identity() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let contract_addr = instantiate_contract(&mut app, owner);
+
+        // A never-registered address is not verified, but the query succeeds
+        let stranger = app.api().addr_make("stranger");
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr: stranger,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(!res);
+
+        // Malformed input is still an error
+        let res: StdResult<bool> = app.wrap().query_wasm_smart(
+            contract_addr,
+            &QueryMsg::VerifyClaim {
+                claim_id: WellKnownTopic::Kyc.into(),
+                user_addr: Addr::unchecked("not-an-address"),
+                namespace: None,
+                trusted_issuers_registry: None,
+            },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn well_known_topics_round_trip() {
+        let topics = [
+            (WellKnownTopic::Kyc, 1u128),
+            (WellKnownTopic::Aml, 2u128),
+            (WellKnownTopic::Accreditation, 3u128),
+        ];
+
+        for (topic, number) in topics {
+            let numeric: Uint128 = topic.into();
+            assert_eq!(numeric, Uint128::new(number));
+            assert_eq!(WellKnownTopic::try_from(numeric).unwrap(), topic);
+        }
+
+        // Arbitrary numeric topics are not well-known but stay usable as raw values
+        let err = WellKnownTopic::try_from(Uint128::new(7777)).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownClaimTopic { .. }));
+    }
+
     #[test]
     fn add_and_query_claims() {
         let mut app = App::default();
@@ -371,6 +1800,7 @@ mod tests {
         let msg = ExecuteMsg::AddKey {
             key_owner: owner_addr.to_string(),
             key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
         };
         app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
             .unwrap();
@@ -378,13 +1808,25 @@ mod tests {
         let claim_topics = vec![Uint128::one(), Uint128::new(7777), Uint128::new(88)];
 
         // Add claims one at a time
-        for topic in &claim_topics {
+        for (nonce, topic) in (1..).zip(&claim_topics) {
             let claim = Claim {
-                topic: topic.clone(),
+                id: None,
+                topic: *topic,
                 issuer: owner_addr.clone(),
                 signature: Binary::from(vec![]),
                 data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
                 uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: Some(user_addr.clone()),
+                nonce: Some(Uint64::new(nonce)),
+                signed_expiry: None,
             };
 
             let message_hash = hash_claim_without_signature(&claim);
@@ -401,13 +1843,14 @@ mod tests {
                 claim: signed_claim,
                 public_key: Binary::from(owner_public_key.serialize()),
                 user_addr: user_addr.clone(),
+                idempotency_key: None,
             };
             app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
                 .unwrap();
         }
 
         // Query and verify each claim
-        for (_, topic) in claim_topics.iter().enumerate() {
+        for topic in claim_topics.iter() {
             let res: bool = app
                 .wrap()
                 .query_wasm_smart(
@@ -415,19 +1858,33 @@ mod tests {
                     &QueryMsg::VerifyClaim {
                         claim_id: *topic,
                         user_addr: user_addr.clone(),
+                        namespace: None,
+                        trusted_issuers_registry: None,
                     },
                 )
                 .unwrap();
-            assert_eq!(res, true);
+            assert!(res);
         }
 
         // Attempt to add a duplicate claim
         let duplicate_claim = Claim {
-            topic: claim_topics[0].clone(),
+            id: None,
+            topic: claim_topics[0],
             issuer: owner_addr.clone(),
             signature: Binary::from(vec![]),
             data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
             uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
         };
         let message_hash = hash_claim_without_signature(&duplicate_claim);
         let secp = Secp256k1::new();
@@ -441,6 +1898,7 @@ mod tests {
             claim: signed_duplicate_claim,
             public_key: Binary::from(owner_public_key.serialize()),
             user_addr: user_addr.clone(),
+            idempotency_key: None,
         };
         let err = app
             .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
@@ -461,6 +1919,7 @@ mod tests {
             let msg = ExecuteMsg::AddKey {
                 key_owner: owner.to_string(),
                 key_type: key_type.to_string(),
+                idempotency_key: None,
             };
             app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
                 .unwrap();
@@ -477,13 +1936,14 @@ mod tests {
                 )
                 .unwrap();
             assert_eq!(res.owner, owner);
-            assert_eq!(res.key_type, KeyType::from_str(key_type).unwrap());
+            assert!(res.has_purpose(&KeyType::from_str(key_type).unwrap()));
         }
 
         // Attempt to add a duplicate key
         let msg = ExecuteMsg::AddKey {
             key_owner: owner.to_string(),
             key_type: "ManagementKey".to_string(),
+            idempotency_key: None,
         };
         let err = app
             .execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
@@ -491,6 +1951,150 @@ mod tests {
         assert!(err.to_string().contains("Error"));
     }
 
+    #[test]
+    fn numeric_key_purposes() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+
+        // A purpose without a name is added and queried by its number
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner.to_string(),
+            key_type: "42".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let res: Key = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKey {
+                    key_owner: owner.to_string(),
+                    key_type: "42".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(res.has_purpose(&KeyType::Custom(42)));
+
+        // The named purposes are numbered 1 to 4, so "2" is the ExecutionKey
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner.to_string(),
+            key_type: "2".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let res: Key = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetKey {
+                    key_owner: owner.to_string(),
+                    key_type: "ExecutionKey".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(res.has_purpose(&KeyType::ExecutionKey));
+        assert!(KeyType::from_str("0").is_err());
+
+        // Named purposes keep their serialization and round-trip through their names
+        for key_type in [
+            KeyType::ManagementKey,
+            KeyType::ExecutionKey,
+            KeyType::ClaimSignerKey,
+            KeyType::EncryptionKey,
+            KeyType::Custom(42),
+        ] {
+            assert_eq!(KeyType::from_str(&key_type.to_string()).unwrap(), key_type);
+        }
+        assert_eq!(
+            cosmwasm_std::to_json_string(&KeyType::ManagementKey).unwrap(),
+            "\"ManagementKey\""
+        );
+        assert_eq!(
+            cosmwasm_std::to_json_string(&KeyType::Custom(42)).unwrap(),
+            "{\"Custom\":42}"
+        );
+    }
+
+    #[test]
+    fn deactivate_key() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let agent = app.api().addr_make("agent");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(agent.clone(), KeyType::ManagementKey)],
+                ..Default::default()
+            },
+        );
+        let set_active = |key_owner: &Addr, active: bool| ExecuteMsg::SetKeyActive {
+            key_owner: key_owner.to_string(),
+            active,
+        };
+        let add_key = ExecuteMsg::AddKey {
+            key_owner: agent.to_string(),
+            key_type: "ExecutionKey".to_string(),
+            idempotency_key: None,
+        };
+
+        // A disabled key stays on the identity but authorizes nothing
+        app.execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &set_active(&agent, false),
+            &[],
+        )
+        .unwrap();
+        let key: Key = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKey {
+                    key_owner: agent.to_string(),
+                    key_type: "ManagementKey".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(!key.active);
+        let err = app
+            .execute_contract(agent.clone(), contract_addr.clone(), &add_key, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+
+        // The last active ManagementKey can't be disabled
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &set_active(&owner, false),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+
+        // Re-enabled, the key authorizes again
+        app.execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &set_active(&agent, true),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(agent, contract_addr, &add_key, &[])
+            .unwrap();
+    }
+
     #[test]
     fn add_key_to_different_wallet() {
         let mut app = App::default();
@@ -504,6 +2108,7 @@ mod tests {
         let msg = ExecuteMsg::AddKey {
             key_owner: different_wallet.to_string(),
             key_type: "ExecutionKey".to_string(),
+            idempotency_key: None,
         };
         app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
             .unwrap();
@@ -522,12 +2127,13 @@ mod tests {
 
         // Verify the key details
         assert_eq!(res.owner, different_wallet);
-        assert_eq!(res.key_type, KeyType::ExecutionKey);
+        assert!(res.has_purpose(&KeyType::ExecutionKey));
 
         // Attempt to add another key with the different wallet (should fail)
         let msg = ExecuteMsg::AddKey {
             key_owner: owner.to_string(),
             key_type: "ManagementKey".to_string(),
+            idempotency_key: None,
         };
         let err = app
             .execute_contract(different_wallet.clone(), contract_addr.clone(), &msg, &[])
@@ -538,6 +2144,7 @@ mod tests {
         let msg = ExecuteMsg::AddKey {
             key_owner: owner.to_string(),
             key_type: "EncryptionKey".to_string(),
+            idempotency_key: None,
         };
         app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
             .unwrap();
@@ -554,7 +2161,7 @@ mod tests {
             )
             .unwrap();
         assert_eq!(res.owner, different_wallet);
-        assert_eq!(res.key_type, KeyType::ExecutionKey);
+        assert!(res.has_purpose(&KeyType::ExecutionKey));
 
         let res: Key = app
             .wrap()
@@ -567,6 +2174,6671 @@ mod tests {
             )
             .unwrap();
         assert_eq!(res.owner, owner);
-        assert_eq!(res.key_type, KeyType::EncryptionKey);
+        assert!(res.has_purpose(&KeyType::EncryptionKey));
+    }
+
+    #[test]
+    fn reassign_keys_to_new_agent() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+
+        let old_agent = app.api().addr_make("old_agent");
+        let new_agent = app.api().addr_make("new_agent");
+        let key_types = vec!["ExecutionKey", "EncryptionKey"];
+
+        for key_type in &key_types {
+            let msg = ExecuteMsg::AddKey {
+                key_owner: old_agent.to_string(),
+                key_type: key_type.to_string(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        // Only a ManagementKey holder can reassign keys
+        let msg = ExecuteMsg::ReassignKeys {
+            from_owner: old_agent.to_string(),
+            to_owner: new_agent.to_string(),
+        };
+        app.execute_contract(old_agent.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        for key_type in &key_types {
+            let res: Key = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKey {
+                        key_owner: new_agent.to_string(),
+                        key_type: key_type.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(res.owner, new_agent);
+            assert!(res.has_purpose(&KeyType::from_str(key_type).unwrap()));
+
+            // The previous agent no longer holds any key
+            app.wrap()
+                .query_wasm_smart::<Key>(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKey {
+                        key_owner: old_agent.to_string(),
+                        key_type: key_type.to_string(),
+                    },
+                )
+                .unwrap_err();
+        }
+
+        // Moving the owner's Management Key away is not allowed
+        let msg = ExecuteMsg::ReassignKeys {
+            from_owner: owner.to_string(),
+            to_owner: new_agent.to_string(),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn claim_signed_with_adr36_envelope() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                signature_encoding: Some(SignatureEncoding::Adr36),
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
+            issuer: owner_addr.clone(),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: Some(user_addr.clone()),
+            nonce: Some(Uint64::new(1)),
+            signed_expiry: None,
+        };
+
+        // A raw signature is rejected when the deployment expects ADR-36
+        let msg = ExecuteMsg::AddClaim {
+            claim: sign_claim(claim.clone(), &owner_secret_key),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+
+        // Sign the claim hash wrapped in the ADR-36 sign doc, as a wallet would
+        let message_hash = claim_message_hash(&claim, &SignatureEncoding::Adr36);
+        let secp = Secp256k1::new();
+        let message = Message::from_slice(&message_hash).unwrap();
+        let signature = secp.sign_ecdsa(&message, &owner_secret_key);
+        let msg = ExecuteMsg::AddClaim {
+            claim: Claim {
+                signature: Binary::from(signature.serialize_compact()),
+                ..claim
+            },
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn claim_without_expiry_uses_default_validity() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                default_validity_secs: Some(3600),
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: Uint128::one(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // The stored claim carries the default expiry
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let issued_at = app.block_info().time.seconds();
+        assert_eq!(claims[0].issued_at, Some(Uint64::new(issued_at)));
+        assert_eq!(claims[0].valid_until, Some(Uint64::new(issued_at + 3600)));
+
+        let verify = QueryMsg::VerifyClaim {
+            claim_id: Uint128::one(),
+            user_addr: user_addr.clone(),
+            namespace: None,
+            trusted_issuers_registry: None,
+        };
+
+        // Still valid at the end of the default period
+        app.update_block(|block| block.time = block.time.plus_seconds(3600));
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &verify)
+            .unwrap();
+        assert!(res);
+
+        // Invalid once the default period has passed
+        app.update_block(|block| block.time = block.time.plus_seconds(1));
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &verify)
+            .unwrap();
+        assert!(!res);
+
+        // The expired claim is still stored, but can be left out of the user's claims
+        for (exclude_expired, expected) in [(None, 1), (Some(true), 0)] {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired,
+                    },
+                )
+                .unwrap();
+            assert_eq!(claims.len(), expected);
+        }
+    }
+
+    #[test]
+    fn claim_without_expiry_never_expires_by_default() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: Uint128::one(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(10 * 365 * 24 * 3600));
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyClaim {
+                    claim_id: Uint128::one(),
+                    user_addr,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn get_claim_reports_validity() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let valid_until = app.block_info().time.seconds() + 100;
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: Some(Uint64::new(valid_until)),
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let claim_id = claims[0].id.clone().unwrap();
+        let get_claim = QueryMsg::GetClaim {
+            user_addr: user_addr.clone(),
+            claim_id: claim_id.clone(),
+        };
+
+        // A fresh claim is reported valid
+        let res: ClaimResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &get_claim)
+            .unwrap();
+        assert!(res.valid);
+        assert_eq!(res.reason, None);
+        assert_eq!(res.claim, claims[0]);
+
+        // Once expired, the verdict and reason are reported inline
+        app.update_block(|block| block.time = block.time.plus_seconds(101));
+        let res: ClaimResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &get_claim)
+            .unwrap();
+        assert!(!res.valid);
+        assert_eq!(res.reason, Some("Claim has expired".to_string()));
+
+        // The raw variant returns the stored claim regardless of validity
+        let raw: Claim = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetRawClaim {
+                    user_addr: user_addr.clone(),
+                    claim_id,
+                },
+            )
+            .unwrap();
+        assert_eq!(raw, claims[0]);
+
+        // Unknown IDs are not found
+        let res: StdResult<Claim> = app.wrap().query_wasm_smart(
+            contract_addr,
+            &QueryMsg::GetRawClaim {
+                user_addr,
+                claim_id: "unknown".to_string(),
+            },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn claim_signed_over_data_hash() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let data = Binary::from(vec![7u8; 2048]);
+        let claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Accreditation.into(),
+            issuer: owner_addr.clone(),
+            signature: Binary::from(vec![]),
+            data: data.clone(),
+            data_hash: Some(Binary::from(Sha256::digest(&data).to_vec())),
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
+        };
+
+        // The signed message commits to the hash, not the payload itself
+        let same_hash = Claim {
+            data: Binary::from(vec![]),
+            ..claim.clone()
+        };
+        assert_eq!(
+            hash_claim_without_signature(&claim),
+            hash_claim_without_signature(&same_hash)
+        );
+
+        // Data that doesn't match the signed hash is rejected
+        let tampered = Claim {
+            data: Binary::from(vec![8u8; 2048]),
+            ..sign_claim(claim.clone(), &owner_secret_key)
+        };
+        let msg = ExecuteMsg::AddClaim {
+            claim: tampered,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidDataHash { .. })
+        ));
+
+        let msg = ExecuteMsg::AddClaim {
+            claim: sign_claim(claim, &owner_secret_key),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Accreditation.into(),
+                    user_addr,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn encrypted_claim_envelope() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let recipient = app.api().addr_make("recipient");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let envelope = EncryptionEnvelope {
+            scheme: 1,
+            recipient: recipient.clone(),
+        };
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![0xde, 0xad, 0xbe, 0xef]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: Some(envelope.clone()),
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+
+        // Rejected while the recipient holds no EncryptionKey
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(err.root_cause().to_string().contains("EncryptionKey"));
+
+        let add_key = ExecuteMsg::AddKey {
+            key_owner: recipient.to_string(),
+            key_type: "EncryptionKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &add_key, &[])
+            .unwrap();
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let res: Option<EncryptionEnvelope> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetClaimEnvelope {
+                    user_addr,
+                    claim_id: claims[0].id.clone().unwrap(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res, Some(envelope));
+    }
+
+    #[test]
+    fn issuer_removes_own_claim() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let (issuer_a, issuer_a_secret_key, issuer_a_public_key) = create_wallet(&app);
+        let (issuer_b, _, _) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                allow_issuer_removal: Some(true),
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        // Issuer A signs claims through the identity's ClaimSignerKey, then loses the key
+        let add_key = ExecuteMsg::AddKey {
+            key_owner: issuer_a.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &add_key, &[])
+            .unwrap();
+        for topic in [Uint128::one(), Uint128::new(2)] {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic,
+                    issuer: issuer_a.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &issuer_a_secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(issuer_a_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(issuer_a.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+        let revoke_key = ExecuteMsg::RevokeKey {
+            key_owner: issuer_a.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &revoke_key, &[])
+            .unwrap();
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+
+        // Another issuer cannot remove issuer A's claim
+        let msg = ExecuteMsg::RemoveClaimById {
+            claim_id: claims[0].id.clone().unwrap(),
+            user_addr: user_addr.clone(),
+        };
+        app.execute_contract(issuer_b, contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+
+        // Issuer A retracts its claims by ID and by topic
+        app.execute_contract(issuer_a.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let msg = ExecuteMsg::RemoveClaim {
+            claim_topic: Uint128::new(2),
+            user_addr: user_addr.clone(),
+        };
+        app.execute_contract(issuer_a, contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr,
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        assert!(claims.is_empty());
+    }
+
+    #[test]
+    fn remove_claim_by_topic_picks_the_senders_claim() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let (issuer, issuer_secret_key, issuer_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        // The owner attests to KYC first, then another issuer does as well. Each signs
+        // through the identity's ClaimSignerKey, which the issuer holds last.
+        for (sender, secret_key, public_key) in [
+            (&owner_addr, &owner_secret_key, &owner_public_key),
+            (&issuer, &issuer_secret_key, &issuer_public_key),
+        ] {
+            let msg = ExecuteMsg::AddKey {
+                key_owner: sender.to_string(),
+                key_type: "ClaimSignerKey".to_string(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: sender.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(sender.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            if *sender == owner_addr {
+                let msg = ExecuteMsg::RevokeKey {
+                    key_owner: sender.to_string(),
+                    key_type: "ClaimSignerKey".to_string(),
+                };
+                app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                    .unwrap();
+            }
+        }
+        let issuers = |app: &App| -> Vec<Addr> {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap();
+            claims.into_iter().map(|claim| claim.issuer).collect()
+        };
+        assert_eq!(issuers(&app), vec![owner_addr.clone(), issuer.clone()]);
+
+        // Removing by topic takes the sender's own claim, not the first one of the topic
+        let msg = ExecuteMsg::RemoveClaim {
+            claim_topic: WellKnownTopic::Kyc.into(),
+            user_addr: user_addr.clone(),
+        };
+        app.execute_contract(issuer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert_eq!(issuers(&app), vec![owner_addr.clone()]);
+
+        // With no claim of its own left, the issuer can't remove the owner's
+        let err = app
+            .execute_contract(issuer, contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        assert_eq!(issuers(&app), vec![owner_addr]);
+    }
+
+    #[test]
+    fn add_claim_with_idempotency_key() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: Some("relayer-tx-1".to_string()),
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // The replayed submission succeeds without adding the claim again
+        let res = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+        assert!(wasm
+            .attributes
+            .iter()
+            .any(|a| a.key == "idempotent_replay" && a.value == "true"));
+        assert!(wasm
+            .attributes
+            .iter()
+            .any(|a| a.key == "action" && a.value == "add_claim"));
+
+        // Reusing the key for a different message is rejected rather than replayed
+        let mut other_msg = msg.clone();
+        if let ExecuteMsg::AddClaim { user_addr, .. } = &mut other_msg {
+            *user_addr = app.api().addr_make("other_user");
+        }
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &other_msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::IdempotencyKeyMismatch { .. })
+        ));
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr,
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(claims.len(), 1);
+    }
+
+    #[test]
+    fn relationship_claims() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        // The controlled entity has its own identity contract
+        let subsidiary = app.api().addr_make("subsidiary");
+        let subsidiary_identity = instantiate_contract(&mut app, subsidiary);
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let relationship_claim = |related_identity: Addr| {
+            sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(100),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(b"controls".to_vec()),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: Some(related_identity),
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            )
+        };
+
+        // Linking to an address that is not an identity contract is rejected
+        let msg = ExecuteMsg::AddClaim {
+            claim: relationship_claim(app.api().addr_make("nobody")),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+
+        let msg = ExecuteMsg::AddClaim {
+            claim: relationship_claim(subsidiary_identity.clone()),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // A plain attribute claim is not a relationship
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetRelationshipClaims { user_addr },
+            )
+            .unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].related_identity, Some(subsidiary_identity));
+        assert_eq!(claims[0].topic, Uint128::new(100));
+    }
+
+    #[test]
+    fn migration_history() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Two upgrades from older releases at different heights
+        let mut env = mock_env();
+        for (from_version, height) in [("0.0.1", 100), ("0.0.2", 200)] {
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, from_version).unwrap();
+            env.block.height = height;
+            migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetMigrationHistory {}).unwrap();
+        let history: Vec<MigrationRecord> = cosmwasm_std::from_json(res).unwrap();
+        assert_eq!(
+            history,
+            vec![
+                MigrationRecord {
+                    from_version: "0.0.1".to_string(),
+                    to_version: CONTRACT_VERSION.to_string(),
+                    block_height: 100,
+                },
+                MigrationRecord {
+                    from_version: "0.0.2".to_string(),
+                    to_version: CONTRACT_VERSION.to_string(),
+                    block_height: 200,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn migration_requires_the_admins_approval() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let admin = deps.api.addr_make("admin");
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                admin: Some(admin.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::MigrationNotApproved { .. }));
+
+        let approve = ExecuteMsg::ApproveMigration {
+            to_version: CONTRACT_VERSION.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            approve.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&admin, &[]),
+            approve,
+        )
+        .unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        // An approval covers a single migration
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::MigrationNotApproved { .. }));
+    }
+
+    #[test]
+    fn query_claims_by_removed_issuer() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let other_issuer = app.api().addr_make("other_issuer");
+        let registry_code = app.store_code(trusted_issuers_contract());
+        let mut registry_trusting = |issuer: &Addr| {
+            app.instantiate_contract(
+                registry_code,
+                owner_addr.clone(),
+                &vec![issuer.clone()],
+                &[],
+                "Trusted Issuers",
+                None,
+            )
+            .unwrap()
+        };
+        let removed_registry = registry_trusting(&other_issuer);
+        let trusting_registry = registry_trusting(&owner_addr);
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        for topic in [Uint128::one(), Uint128::new(2)] {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic,
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let res: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetClaimsByRemovedIssuer {
+                    user_addr: user_addr.clone(),
+                    issuer: owner_addr.clone(),
+                    trusted_issuers_registry: removed_registry.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.iter().map(|claim| claim.topic).collect::<Vec<_>>(),
+            vec![Uint128::one(), Uint128::new(2)]
+        );
+
+        // Nothing to clean up while the registry still trusts the issuer
+        let res: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetClaimsByRemovedIssuer {
+                    user_addr: user_addr.clone(),
+                    issuer: owner_addr.clone(),
+                    trusted_issuers_registry: trusting_registry.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(res.is_empty());
+
+        // No claims from an issuer that never signed for this user
+        let res: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetClaimsByRemovedIssuer {
+                    user_addr,
+                    issuer: other_issuer,
+                    trusted_issuers_registry: trusting_registry.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(res.is_empty());
+    }
+
+    // Stands in for the owner roles contract, granting every role
+    fn owner_roles_contract() -> Box<dyn Contract<Empty>> {
+        fn noop(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+        fn grant_all(_: Deps, _: Env, _: serde_json::Value) -> StdResult<Binary> {
+            to_json_binary(&true)
+        }
+        Box::new(ContractWrapper::new(noop, noop, grant_all))
+    }
+
+    // Deploys a claim topics registry with `topics` registered
+    fn instantiate_claim_topics_registry(
+        app: &mut App,
+        owner: &Addr,
+        topics: &[WellKnownTopic],
+    ) -> Addr {
+        let owner_roles_code = app.store_code(owner_roles_contract());
+        let owner_roles = app
+            .instantiate_contract(
+                owner_roles_code,
+                owner.clone(),
+                &Empty {},
+                &[],
+                "Owner Roles",
+                None,
+            )
+            .unwrap();
+        let registry_code = app.store_code(Box::new(ContractWrapper::new(
+            registery::claim_topics::contract::execute,
+            registery::claim_topics::contract::instantiate,
+            registery::claim_topics::contract::query,
+        )));
+        let registry = app
+            .instantiate_contract(
+                registry_code,
+                owner.clone(),
+                &registery::claim_topics::InstantiateMsg {
+                    owner_roles_address: owner_roles,
+                    max_topics: None,
+                },
+                &[],
+                "Claim Topics Registry",
+                None,
+            )
+            .unwrap();
+        for topic in topics {
+            app.execute_contract(
+                owner.clone(),
+                registry.clone(),
+                &registery::claim_topics::ExecuteMsg::AddClaimTopic {
+                    topic: (*topic).into(),
+                    info: Default::default(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        registry
+    }
+
+    #[test]
+    fn deprecated_claim_topic() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let registry = instantiate_claim_topics_registry(
+            &mut app,
+            &owner_addr,
+            &[WellKnownTopic::Kyc, WellKnownTopic::Aml],
+        );
+
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                claim_topics_registry: Some(registry.to_string()),
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let add_claim = |app: &mut App, topic: WellKnownTopic| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+        };
+        add_claim(&mut app, WellKnownTopic::Kyc).unwrap();
+
+        app.execute_contract(
+            owner_addr.clone(),
+            registry.clone(),
+            &registery::claim_topics::ExecuteMsg::DeprecateClaimTopic {
+                topic: WellKnownTopic::Kyc.into(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // The claim issued before deprecation still verifies
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr: user_addr.clone(),
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(res);
+
+        // But no new claims can be issued under the deprecated topic
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::RemoveClaim {
+                claim_topic: WellKnownTopic::Kyc.into(),
+                user_addr: user_addr.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+        let err = add_claim(&mut app, WellKnownTopic::Kyc).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ClaimTopicDeprecated { .. })
+        ));
+
+        // Active topics are unaffected
+        add_claim(&mut app, WellKnownTopic::Aml).unwrap();
+    }
+
+    #[test]
+    fn migrate_claim_between_identities() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let source = instantiate_contract(&mut app, owner_addr.clone());
+        let target = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), source.clone(), &msg, &[])
+            .unwrap();
+
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), source.clone(), &msg, &[])
+            .unwrap();
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                source.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let claim_id = claims[0].id.clone().unwrap();
+
+        let migrate = ExecuteMsg::MigrateClaim {
+            claim_id: claim_id.clone(),
+            user_addr: user_addr.clone(),
+            to_identity: target.to_string(),
+            public_key: Binary::from(owner_public_key.serialize()),
+        };
+        let verify = QueryMsg::VerifyClaim {
+            claim_id: WellKnownTopic::Kyc.into(),
+            user_addr: user_addr.clone(),
+            namespace: None,
+            trusted_issuers_registry: None,
+        };
+
+        // Without the target's approval the move is rejected and the claim stays put
+        app.execute_contract(owner_addr.clone(), source.clone(), &migrate, &[])
+            .unwrap_err();
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(source.clone(), &verify)
+            .unwrap();
+        assert!(res);
+
+        // Only a ManagementKey of the target can approve the move
+        let stranger = app.api().addr_make("stranger");
+        let approve = ExecuteMsg::ApproveClaimMigration {
+            from_identity: source.to_string(),
+            claim_id: claim_id.clone(),
+        };
+        let err = app
+            .execute_contract(stranger, target.clone(), &approve, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        app.execute_contract(owner_addr.clone(), target.clone(), &approve, &[])
+            .unwrap();
+
+        app.execute_contract(owner_addr.clone(), source.clone(), &migrate, &[])
+            .unwrap();
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(source.clone(), &verify)
+            .unwrap();
+        assert!(!res);
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(target.clone(), &verify)
+            .unwrap();
+        assert!(res);
+        let res: ClaimResponse = app
+            .wrap()
+            .query_wasm_smart(
+                target,
+                &QueryMsg::GetClaim {
+                    user_addr,
+                    claim_id,
+                },
+            )
+            .unwrap();
+        assert!(res.valid);
+    }
+
+    #[test]
+    fn migrate_claim_with_contract_set_expiry() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let source = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                default_validity_secs: Some(3600),
+                ..Default::default()
+            },
+        );
+        let target = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let now = app.block_info().time.seconds();
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), source.clone(), &msg, &[])
+            .unwrap();
+
+        // Signed without an expiry, stamped with the source's default validity
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), source.clone(), &msg, &[])
+            .unwrap();
+        let load_claim = |app: &App, identity: &Addr| -> Claim {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    identity.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap();
+            claims[0].clone()
+        };
+        let claim = load_claim(&app, &source);
+        assert_eq!(claim.valid_until, Some(Uint64::new(now + 3600)));
+        assert_eq!(
+            claim.signed_expiry,
+            Some(SignedExpiry { valid_until: None })
+        );
+        let claim_id = claim.id.clone().unwrap();
+
+        // The issuer then shortens it
+        let valid_until = Uint64::new(now + 1_000);
+        let message_hash =
+            expiry_update_message_hash(&source, &claim, valid_until, &SignatureEncoding::Raw);
+        let signature = Secp256k1::new().sign_ecdsa(
+            &Message::from_slice(&message_hash).unwrap(),
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::SetClaimExpiry {
+            user_addr: user_addr.clone(),
+            claim_id: claim_id.clone(),
+            valid_until,
+            issuer_signature: Binary::from(signature.serialize_compact()),
+            public_key: Binary::from(owner_public_key.serialize()),
+        };
+        app.execute_contract(owner_addr.clone(), source.clone(), &msg, &[])
+            .unwrap();
+
+        // The move checks the signature against the expiry the issuer signed
+        let approve = ExecuteMsg::ApproveClaimMigration {
+            from_identity: source.to_string(),
+            claim_id: claim_id.clone(),
+        };
+        app.execute_contract(owner_addr.clone(), target.clone(), &approve, &[])
+            .unwrap();
+        let migrate = ExecuteMsg::MigrateClaim {
+            claim_id,
+            user_addr: user_addr.clone(),
+            to_identity: target.to_string(),
+            public_key: Binary::from(owner_public_key.serialize()),
+        };
+        app.execute_contract(owner_addr.clone(), source, &migrate, &[])
+            .unwrap();
+        let moved = load_claim(&app, &target);
+        assert_eq!(moved.valid_until, Some(valid_until));
+        assert_eq!(
+            moved.signed_expiry,
+            Some(SignedExpiry { valid_until: None })
+        );
+    }
+
+    #[test]
+    fn force_remove_claim() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let regulator = app.api().addr_make("regulator");
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let owner_roles_code = app.store_code(Box::new(ContractWrapper::new(
+            roles::owner_roles::contract::execute,
+            roles::owner_roles::contract::instantiate,
+            roles::owner_roles::contract::query,
+        )));
+        let owner_roles = app
+            .instantiate_contract(
+                owner_roles_code,
+                owner_addr.clone(),
+                &roles::owner_roles::msg::InstantiateMsg {
+                    owner: owner_addr.clone(),
+                },
+                &[],
+                "Owner Roles",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            owner_addr.clone(),
+            owner_roles.clone(),
+            &roles::owner_roles::ExecuteMsg::AddOwnerRole {
+                role: roles::owner_roles::msg::OwnerRole::ComplianceManager,
+                owner: regulator.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                owner_roles_address: Some(owner_roles.to_string()),
+                ..Default::default()
+            },
+        );
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Aml.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let claim_id = claims[0].id.clone().unwrap();
+
+        let force_remove = ExecuteMsg::ForceRemoveClaim {
+            user_addr: user_addr.clone(),
+            claim_id: claim_id.clone(),
+        };
+
+        // Holding keys on the identity is not enough, the role is required
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &force_remove,
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+
+        let res = app
+            .execute_contract(regulator.clone(), contract_addr.clone(), &force_remove, &[])
+            .unwrap();
+        let event = res
+            .events
+            .iter()
+            .find(|e| e.ty == "wasm-forced_removal")
+            .unwrap();
+        assert!(event
+            .attributes
+            .iter()
+            .any(|a| a.key == "actor" && a.value == regulator.as_str()));
+        assert!(event
+            .attributes
+            .iter()
+            .any(|a| a.key == "claim_id" && a.value == claim_id));
+
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Aml.into(),
+                    user_addr,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(!res);
+    }
+
+    #[test]
+    fn topic_allowed_issuers() {
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let (vendor_a, vendor_a_key, vendor_a_public_key) = create_wallet(&app);
+        let (vendor_b, vendor_b_key, vendor_b_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let alice = MockApi::default().addr_make("alice");
+        let bob = MockApi::default().addr_make("bob");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let add_kyc = |app: &mut App,
+                       issuer: &Addr,
+                       secret_key: &SecretKey,
+                       public_key: &PublicKey,
+                       user_addr: &Addr| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+        };
+        let is_verified = |app: &App, user_addr: &Addr| -> bool {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyClaim {
+                        claim_id: WellKnownTopic::Kyc.into(),
+                        user_addr: user_addr.clone(),
+                        namespace: None,
+                        trusted_issuers_registry: None,
+                    },
+                )
+                .unwrap()
+        };
+
+        // Any issuer is accepted until the topic gets a list
+        add_kyc(
+            &mut app,
+            &vendor_b,
+            &vendor_b_key,
+            &vendor_b_public_key,
+            &alice,
+        )
+        .unwrap();
+        assert!(is_verified(&app, &alice));
+
+        let msg = ExecuteMsg::SetTopicAllowedIssuers {
+            topic: WellKnownTopic::Kyc.into(),
+            issuers: vec![vendor_a.to_string()],
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let res: Vec<Addr> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetTopicAllowedIssuers {
+                    topic: WellKnownTopic::Kyc.into(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res, vec![vendor_a.clone()]);
+
+        // Existing claims from issuers off the list are ignored
+        assert!(!is_verified(&app, &alice));
+
+        // and new ones are rejected
+        let err = add_kyc(
+            &mut app,
+            &vendor_b,
+            &vendor_b_key,
+            &vendor_b_public_key,
+            &bob,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::IssuerNotAllowed { .. })
+        ));
+
+        add_kyc(
+            &mut app,
+            &vendor_a,
+            &vendor_a_key,
+            &vendor_a_public_key,
+            &bob,
+        )
+        .unwrap();
+        assert!(is_verified(&app, &bob));
+    }
+
+    #[test]
+    fn claims_expiring_within() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let now = app.block_info().time.seconds();
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // topic -> expiry
+        let expiries = [
+            (1u128, Some(now + 3_000)),
+            (2, Some(now + 1_000)),
+            (3, None),
+            (4, Some(now + 90_000)),
+            (5, Some(now + 2_000)),
+        ];
+        for (topic, valid_until) in expiries {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(topic),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: valid_until.map(Uint64::new),
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let expiring = |app: &App, seconds: u64| -> Vec<u128> {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetClaimsExpiringWithin {
+                        user_addr: user_addr.clone(),
+                        seconds,
+                    },
+                )
+                .unwrap();
+            claims.iter().map(|claim| claim.topic.u128()).collect()
+        };
+
+        // Soonest first, ignoring claims without an expiry or outside the window
+        assert_eq!(expiring(&app, 3_000), vec![2, 5, 1]);
+        assert_eq!(expiring(&app, 500), Vec::<u128>::new());
+
+        // Already expired claims are no longer reported
+        app.update_block(|block| block.time = block.time.plus_seconds(1_500));
+        assert_eq!(expiring(&app, 1_500), vec![5, 1]);
+    }
+
+    #[test]
+    fn claim_selection_is_deterministic() {
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let add_kyc = |app: &mut App, valid_until: Option<u64>| -> Addr {
+            let (issuer, secret_key, public_key) = create_wallet(app);
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: valid_until.map(Uint64::new),
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            issuer
+        };
+        let selected_issuer = |app: &App| -> Option<Addr> {
+            let claim: Option<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetClaimForTopic {
+                        user_addr: user_addr.clone(),
+                        topic: WellKnownTopic::Kyc.into(),
+                        namespace: None,
+                    },
+                )
+                .unwrap();
+            claim.map(|claim| claim.issuer)
+        };
+
+        // Claims issued in the same block: the lowest issuer address wins
+        let first = add_kyc(&mut app, None);
+        let second = add_kyc(&mut app, None);
+        assert_eq!(
+            selected_issuer(&app),
+            Some(first.clone().min(second.clone()))
+        );
+
+        // A fresher claim takes precedence, whatever its issuer
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+        let expiry = app.block_info().time.seconds() + 100;
+        let freshest = add_kyc(&mut app, Some(expiry));
+        assert_eq!(selected_issuer(&app), Some(freshest));
+
+        // Once it expires, selection falls back to the remaining valid claims
+        app.update_block(|block| block.time = block.time.plus_seconds(200));
+        assert_eq!(selected_issuer(&app), Some(first.min(second)));
+        let res: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn would_accept_claims_batch() {
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr);
+
+        let mut claims_with_keys = vec![];
+        for topic in 1..=3u128 {
+            let (issuer, secret_key, public_key) = create_wallet(&app);
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(topic),
+                    issuer,
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &secret_key,
+            );
+            claims_with_keys.push((claim, Binary::from(public_key.serialize())));
+        }
+
+        let would_accept = |app: &App, claims_with_keys: &[(Claim, Binary)]| {
+            app.wrap()
+                .query_wasm_smart::<Vec<ClaimRejection>>(
+                    contract_addr.clone(),
+                    &QueryMsg::WouldAcceptClaims {
+                        claims_with_keys: claims_with_keys.to_vec(),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(would_accept(&app, &claims_with_keys), vec![]);
+
+        // Tampering with a claim after signing invalidates only that entry
+        claims_with_keys[1].0.data = Binary::from(vec![9, 9, 9]);
+        let rejections = would_accept(&app, &claims_with_keys);
+        assert_eq!(rejections.len(), 1);
+        assert_eq!(rejections[0].index, 1);
+        assert_eq!(rejections[0].reason, "Signature verification failed");
+    }
+
+    #[test]
+    fn unknown_topic_policy() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let stranger = MockApi::default().addr_make("stranger");
+        // Accreditation is not registered
+        let registry =
+            instantiate_claim_topics_registry(&mut app, &owner_addr, &[WellKnownTopic::Kyc]);
+
+        for (policy, expected) in [
+            (UnknownTopicPolicy::Reject, false),
+            (UnknownTopicPolicy::Ignore, true),
+        ] {
+            let contract_addr = instantiate_contract_with_msg(
+                &mut app,
+                owner_addr.clone(),
+                InstantiateMsg {
+                    owner: owner_addr.to_string(),
+                    claim_topics_registry: Some(registry.to_string()),
+                    unknown_topic_policy: Some(policy),
+                    ..Default::default()
+                },
+            );
+            let msg = ExecuteMsg::AddKey {
+                key_owner: owner_addr.to_string(),
+                key_type: "ClaimSignerKey".to_string(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Accreditation.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let verify = |topic: WellKnownTopic, user_addr: &Addr| -> bool {
+                app.wrap()
+                    .query_wasm_smart(
+                        contract_addr.clone(),
+                        &QueryMsg::VerifyClaim {
+                            claim_id: topic.into(),
+                            user_addr: user_addr.clone(),
+                            namespace: None,
+                            trusted_issuers_registry: None,
+                        },
+                    )
+                    .unwrap()
+            };
+
+            // The policy decides for the unregistered topic, with or without a claim
+            assert_eq!(verify(WellKnownTopic::Accreditation, &user_addr), expected);
+            assert_eq!(verify(WellKnownTopic::Accreditation, &stranger), expected);
+            // Registered topics still require a claim
+            assert!(!verify(WellKnownTopic::Kyc, &user_addr));
+        }
+    }
+
+    #[test]
+    fn claim_namespaces() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // The same issuer attests to topic 1 in the default and in a hashed-topic namespace
+        for namespace in [None, Some("hashed".to_string())] {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::one(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let verify = |app: &App, namespace: Option<&str>| -> bool {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyClaim {
+                        claim_id: Uint128::one(),
+                        user_addr: user_addr.clone(),
+                        namespace: namespace.map(str::to_string),
+                        trusted_issuers_registry: None,
+                    },
+                )
+                .unwrap()
+        };
+        assert!(verify(&app, None));
+        assert!(verify(&app, Some("")));
+        assert!(verify(&app, Some("hashed")));
+        assert!(!verify(&app, Some("other")));
+
+        let claim: Option<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetClaimForTopic {
+                    user_addr: user_addr.clone(),
+                    topic: Uint128::one(),
+                    namespace: Some("hashed".to_string()),
+                },
+            )
+            .unwrap();
+        assert_eq!(claim.unwrap().namespace, Some("hashed".to_string()));
+
+        // Removing the topic by number only affects the default namespace
+        let msg = ExecuteMsg::RemoveClaim {
+            claim_topic: Uint128::one(),
+            user_addr: user_addr.clone(),
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert!(!verify(&app, None));
+        assert!(verify(&app, Some("hashed")));
+    }
+
+    #[test]
+    fn set_claim_expiry() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let (_, other_secret_key, _) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                max_claim_validity_secs: Some(10_000),
+                ..Default::default()
+            },
+        );
+        let now = app.block_info().time.seconds();
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: Some(Uint64::new(now + 5_000)),
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let load_claim = |app: &App| -> Claim {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap();
+            claims[0].clone()
+        };
+        let set_expiry = |app: &mut App, valid_until: u64, secret_key: &SecretKey| {
+            let claim = load_claim(app);
+            let valid_until = Uint64::new(valid_until);
+            let message_hash = expiry_update_message_hash(
+                &contract_addr,
+                &claim,
+                valid_until,
+                &SignatureEncoding::Raw,
+            );
+            let signature = Secp256k1::new()
+                .sign_ecdsa(&Message::from_slice(&message_hash).unwrap(), secret_key);
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SetClaimExpiry {
+                    user_addr: user_addr.clone(),
+                    claim_id: claim.id.unwrap(),
+                    valid_until,
+                    issuer_signature: Binary::from(signature.serialize_compact()),
+                    public_key: Binary::from(owner_public_key.serialize()),
+                },
+                &[],
+            )
+        };
+
+        // Shortening
+        set_expiry(&mut app, now + 1_000, &owner_secret_key).unwrap();
+        assert_eq!(load_claim(&app).valid_until, Some(Uint64::new(now + 1_000)));
+
+        // Extending, up to the configured maximum
+        set_expiry(&mut app, now + 8_000, &owner_secret_key).unwrap();
+        assert_eq!(load_claim(&app).valid_until, Some(Uint64::new(now + 8_000)));
+        let err = set_expiry(&mut app, now + 20_000, &owner_secret_key).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ExpiryBeyondMax { .. })
+        ));
+
+        // A change the issuer didn't sign is rejected
+        let err = set_expiry(&mut app, now + 9_000, &other_secret_key).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidIssuerSignature { .. })
+        ));
+        assert_eq!(load_claim(&app).valid_until, Some(Uint64::new(now + 8_000)));
+    }
+
+    #[test]
+    fn expected_signer() {
+        let mut deps = mock_dependencies();
+        deps.api = deps.api.with_prefix("cosmos");
+
+        // Known account from the Cosmos SDK tooling test vectors
+        let public_key =
+            Binary::from_base64("AtQaCqFnshaZQp6rIkvAPyzThvCvXSDO+9AzbxVErqJP").unwrap();
+        let expected = Addr::unchecked("cosmos1h806c7khnvmjlywdrkdgk2vrayy2mmvf9rxk2r");
+        let claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
+            issuer: expected.clone(),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
+        };
+
+        let msg = QueryMsg::ExpectedSigner {
+            claim: Box::new(claim.clone()),
+            public_key: public_key.clone(),
+        };
+        let res: ExpectedSignerResponse =
+            cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            ExpectedSignerResponse {
+                signer: expected,
+                matches_issuer: true,
+            }
+        );
+
+        // A claim naming another issuer is flagged
+        let msg = QueryMsg::ExpectedSigner {
+            claim: Box::new(Claim {
+                issuer: deps.api.addr_make("someone_else"),
+                ..claim
+            }),
+            public_key,
+        };
+        let res: ExpectedSignerResponse =
+            cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!res.matches_issuer);
+    }
+
+    #[test]
+    fn revocation_list() {
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let (issuer, issuer_key, issuer_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("alice");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: issuer.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: Some(user_addr.clone()),
+                nonce: None,
+                signed_expiry: None,
+            },
+            &issuer_key,
+        );
+        let msg = ExecuteMsg::AddClaim {
+            claim,
+            public_key: Binary::from(issuer_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let claim_id = claims[0].id.clone().unwrap();
+
+        let is_verified = |app: &App| -> bool {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyClaim {
+                        claim_id: WellKnownTopic::Kyc.into(),
+                        user_addr: user_addr.clone(),
+                        namespace: None,
+                        trusted_issuers_registry: None,
+                    },
+                )
+                .unwrap()
+        };
+        let is_revoked = |app: &App| -> bool {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::IsRevoked {
+                        issuer: issuer.clone(),
+                        claim_id: claim_id.clone(),
+                    },
+                )
+                .unwrap()
+        };
+        assert!(is_verified(&app));
+        assert!(!is_revoked(&app));
+
+        // Entries must be claim ID hashes
+        let msg = ExecuteMsg::PublishRevocations {
+            revoked_ids: vec![Binary::from(claim_id.as_bytes())],
+        };
+        let err = app
+            .execute_contract(issuer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidRevocation { .. })
+        ));
+
+        // Another issuer's list doesn't affect the claim
+        let msg = ExecuteMsg::PublishRevocations {
+            revoked_ids: vec![claim_id_hash(&claim_id)],
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert!(is_verified(&app));
+
+        // Publishing by the issuer revokes it, republishing is a no-op
+        let msg = ExecuteMsg::PublishRevocations {
+            revoked_ids: vec![claim_id_hash("other claim"), claim_id_hash(&claim_id)],
+        };
+        app.execute_contract(issuer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        app.execute_contract(issuer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert!(is_revoked(&app));
+        assert!(!is_verified(&app));
+
+        let res: ClaimResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetClaim {
+                    user_addr: user_addr.clone(),
+                    claim_id: claim_id.clone(),
+                },
+            )
+            .unwrap();
+        assert!(!res.valid);
+        assert_eq!(
+            res.reason.as_deref(),
+            Some("Claim has been revoked by its issuer")
+        );
+
+        // The list is read a page at a time
+        let page: Vec<Binary> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetRevocations {
+                    issuer: issuer.clone(),
+                    start_after: Some(0),
+                    limit: Some(10),
+                },
+            )
+            .unwrap();
+        assert_eq!(page, vec![claim_id_hash(&claim_id)]);
+    }
+
+    #[test]
+    fn instantiate_with_initial_keys() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let signer = app.api().addr_make("signer");
+
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(signer.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        for (key_owner, key_type) in [(&owner, "ManagementKey"), (&signer, "ClaimSignerKey")] {
+            let res: Key = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKey {
+                        key_owner: key_owner.to_string(),
+                        key_type: key_type.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(&res.owner, key_owner);
+        }
+
+        // Key types can't be granted twice, and the owner already holds a ManagementKey
+        let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+        for initial_keys in [
+            vec![(owner.clone(), KeyType::ManagementKey)],
+            vec![
+                (signer.clone(), KeyType::ClaimSignerKey),
+                (owner.clone(), KeyType::ClaimSignerKey),
+            ],
+        ] {
+            let err = app
+                .instantiate_contract(
+                    code_id,
+                    owner.clone(),
+                    &InstantiateMsg {
+                        owner: owner.to_string(),
+                        initial_keys,
+                        ..Default::default()
+                    },
+                    &[],
+                    "On-chain ID Contract",
+                    None,
+                )
+                .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ContractError>(),
+                Some(ContractError::KeyAlreadyExists { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn replace_claim() {
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let (issuer, issuer_key, issuer_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract(&mut app, owner_addr.clone());
+        let user_addr = MockApi::default().addr_make("alice");
+
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner_addr.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let kyc_claim = |valid_until: u64, uri: &str| {
+            sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: uri.to_string(),
+                    valid_until: Some(Uint64::new(valid_until)),
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &issuer_key,
+            )
+        };
+        let now = app.block_info().time.seconds();
+        let msg = ExecuteMsg::AddClaim {
+            claim: kyc_claim(now + 100, "https://example.com/v1"),
+            public_key: Binary::from(issuer_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        let claims = |app: &App| -> Vec<Claim> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap()
+        };
+        let is_verified = |app: &App| -> bool {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyClaim {
+                        claim_id: WellKnownTopic::Kyc.into(),
+                        user_addr: user_addr.clone(),
+                        namespace: None,
+                        trusted_issuers_registry: None,
+                    },
+                )
+                .unwrap()
+        };
+        let old = claims(&app).remove(0);
+        assert!(is_verified(&app));
+
+        // An invalid replacement leaves the old claim in place
+        let mut invalid = kyc_claim(now + 1000, "https://example.com/v2");
+        invalid.data = Binary::from(vec![4, 5, 6]);
+        let msg = ExecuteMsg::ReplaceClaim {
+            old_claim_id: old.id.clone().unwrap(),
+            new_claim: invalid,
+            public_key: Binary::from(issuer_public_key.serialize()),
+            user_addr: user_addr.clone(),
+        };
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidSignature { .. })
+        ));
+        assert_eq!(claims(&app), vec![old.clone()]);
+        assert!(is_verified(&app));
+
+        // A renewal by the same issuer takes the old claim's place
+        let msg = ExecuteMsg::ReplaceClaim {
+            old_claim_id: old.id.clone().unwrap(),
+            new_claim: kyc_claim(now + 1000, "https://example.com/v2"),
+            public_key: Binary::from(issuer_public_key.serialize()),
+            user_addr: user_addr.clone(),
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let renewed = claims(&app);
+        assert_eq!(renewed.len(), 1);
+        assert_ne!(renewed[0].id, old.id);
+        assert_eq!(renewed[0].valid_until, Some(Uint64::new(now + 1000)));
+        assert!(is_verified(&app));
+
+        // The old claim is gone, so it can't be replaced again
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ClaimIdNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn key_last_used() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let agent = app.api().addr_make("agent");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+
+        let get_key = |app: &App, key_owner: &Addr, key_type: &str| -> Key {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKey {
+                        key_owner: key_owner.to_string(),
+                        key_type: key_type.to_string(),
+                    },
+                )
+                .unwrap()
+        };
+        let stale_keys = |app: &App, older_than_secs: u64| -> Vec<KeyType> {
+            let keys: Vec<Key> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetStaleKeys { older_than_secs },
+                )
+                .unwrap();
+            keys.into_iter().flat_map(|key| key.purposes).collect()
+        };
+        assert_eq!(get_key(&app, &owner, "ManagementKey").last_used, None);
+
+        // Authorizing AddKey stamps the owner's ManagementKey
+        let added_at = app.block_info().time.seconds();
+        let msg = ExecuteMsg::AddKey {
+            key_owner: agent.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert_eq!(
+            get_key(&app, &owner, "ManagementKey").last_used,
+            Some(Uint64::new(added_at))
+        );
+        assert_eq!(get_key(&app, &agent, "ClaimSignerKey").last_used, None);
+
+        // A failed authorization doesn't count as use
+        let msg = ExecuteMsg::AddKey {
+            key_owner: agent.to_string(),
+            key_type: "ExecutionKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(agent.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert_eq!(get_key(&app, &agent, "ClaimSignerKey").last_used, None);
+
+        // Unused keys are always stale, used ones once the window has passed
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        assert_eq!(stale_keys(&app, 200), vec![KeyType::ClaimSignerKey]);
+        assert_eq!(
+            stale_keys(&app, 50),
+            vec![KeyType::ManagementKey, KeyType::ClaimSignerKey]
+        );
+    }
+
+    #[test]
+    fn duplicate_claim_policy() {
+        use crate::state::DuplicateClaimPolicy;
+
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        // Each add signs the same claim afresh, so only its nonce differs
+        let add_claim = || ExecuteMsg::AddClaim {
+            claim: sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            ),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+
+        for policy in [
+            DuplicateClaimPolicy::Reject,
+            DuplicateClaimPolicy::Overwrite,
+            DuplicateClaimPolicy::Ignore,
+        ] {
+            let contract_addr = instantiate_contract_with_msg(
+                &mut app,
+                owner_addr.clone(),
+                InstantiateMsg {
+                    owner: owner_addr.to_string(),
+                    duplicate_claim_policy: Some(policy.clone()),
+                    initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                    ..Default::default()
+                },
+            );
+            let first_added = app.block_info().time.seconds();
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &add_claim(), &[])
+                .unwrap();
+
+            // Re-add the identical claim later on
+            app.update_block(|block| block.time = block.time.plus_seconds(10));
+            let res =
+                app.execute_contract(owner_addr.clone(), contract_addr.clone(), &add_claim(), &[]);
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(claims.len(), 1);
+
+            let issued_at = match policy {
+                DuplicateClaimPolicy::Reject => {
+                    assert!(matches!(
+                        res.unwrap_err().downcast_ref::<ContractError>(),
+                        Some(ContractError::ClaimAlreadyExists { .. })
+                    ));
+                    first_added
+                }
+                DuplicateClaimPolicy::Overwrite => {
+                    res.unwrap();
+                    first_added + 10
+                }
+                DuplicateClaimPolicy::Ignore => {
+                    res.unwrap();
+                    first_added
+                }
+            };
+            assert_eq!(claims[0].issued_at, Some(Uint64::new(issued_at)));
+        }
+    }
+
+    #[test]
+    fn migrate_backfills_claim_ids() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let issuer = deps.api.addr_make("issuer");
+        let user_addr = deps.api.addr_make("user");
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let legacy_claim = |topic: WellKnownTopic, valid_until: Option<u64>| Claim {
+            id: None,
+            topic: topic.into(),
+            issuer: issuer.clone(),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: valid_until.map(Uint64::new),
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
+        };
+        // The two KYC claims only differ in expiry, so they end up with the same ID
+        CLAIMS
+            .save(
+                deps.as_mut().storage,
+                &user_addr,
+                &vec![
+                    legacy_claim(WellKnownTopic::Kyc, Some(100)),
+                    legacy_claim(WellKnownTopic::Aml, None),
+                    legacy_claim(WellKnownTopic::Kyc, Some(200)),
+                ],
+            )
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("claim_ids_assigned", "3")));
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("duplicate_claims_merged", "1")));
+
+        let mut expected = vec![
+            legacy_claim(WellKnownTopic::Kyc, Some(200)),
+            legacy_claim(WellKnownTopic::Aml, None),
+        ];
+        expected.iter_mut().for_each(generate_claim_id);
+        let claims = CLAIMS.load(deps.as_ref().storage, &user_addr).unwrap();
+        assert_eq!(claims, expected);
+
+        // Running the backfill again finds nothing to do
+        assert_eq!(backfill_claim_ids(deps.as_mut().storage).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn identity_summary() {
+        use crate::msg::IdentitySummaryResponse;
+
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let registry = instantiate_claim_topics_registry(
+            &mut app,
+            &owner_addr,
+            &[WellKnownTopic::Kyc, WellKnownTopic::Aml],
+        );
+        let owner_roles = app.api().addr_make("owner_roles");
+
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                claim_topics_registry: Some(registry.to_string()),
+                owner_roles_address: Some(owner_roles.to_string()),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        let users = [
+            MockApi::default().addr_make("user_a"),
+            MockApi::default().addr_make("user_b"),
+        ];
+        for (user_addr, topic) in users
+            .iter()
+            .flat_map(|user| [(user, WellKnownTopic::Kyc), (user, WellKnownTopic::Aml)])
+            .take(3)
+        {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let res: IdentitySummaryResponse = app
+            .wrap()
+            .query_wasm_smart(contract_addr, &QueryMsg::IdentitySummary {})
+            .unwrap();
+        assert_eq!(
+            res,
+            IdentitySummaryResponse {
+                owner: owner_addr,
+                key_count: 2,
+                claim_count: 3,
+                claim_topics_registry: Some(registry),
+                owner_roles: Some(owner_roles),
+            }
+        );
+    }
+
+    #[test]
+    fn weighted_management_keys() {
+        let mut app = App::default();
+        let ceo = app.api().addr_make("ceo");
+        let director_a = app.api().addr_make("director_a");
+        let director_b = app.api().addr_make("director_b");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            ceo.clone(),
+            InstantiateMsg {
+                owner: ceo.to_string(),
+                initial_keys: vec![
+                    (director_a.clone(), KeyType::ManagementKey),
+                    (director_b.clone(), KeyType::ManagementKey),
+                ],
+                ..Default::default()
+            },
+        );
+        let has_key = |app: &App, key_owner: &Addr, key_type: &str| {
+            app.wrap()
+                .query_wasm_smart::<Key>(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKey {
+                        key_owner: key_owner.to_string(),
+                        key_type: key_type.to_string(),
+                    },
+                )
+                .is_ok()
+        };
+
+        // The CEO key weighs 2 and sensitive operations need a weight of 2
+        for msg in [
+            ExecuteMsg::SetKeyWeight {
+                key_owner: ceo.to_string(),
+                weight: 2,
+            },
+            ExecuteMsg::SetManagementThreshold { threshold: 2 },
+        ] {
+            app.execute_contract(ceo.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        // which the CEO meets alone
+        let executor = app.api().addr_make("executor");
+        let msg = ExecuteMsg::AddKey {
+            key_owner: executor.to_string(),
+            key_type: "ExecutionKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(ceo.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert!(has_key(&app, &executor, "ExecutionKey"));
+
+        // while the directors must combine. Approving twice doesn't count twice.
+        let encryptor = app.api().addr_make("encryptor");
+        let msg = ExecuteMsg::AddKey {
+            key_owner: encryptor.to_string(),
+            key_type: "EncryptionKey".to_string(),
+            idempotency_key: None,
+        };
+        for _ in 0..2 {
+            app.execute_contract(director_a.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            assert!(!has_key(&app, &encryptor, "EncryptionKey"));
+        }
+        app.execute_contract(director_b.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert!(has_key(&app, &encryptor, "EncryptionKey"));
+
+        // The threshold can't exceed what the keys weigh together
+        let err = app
+            .execute_contract(
+                ceo.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SetManagementThreshold { threshold: 5 },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidManagementWeight { .. })
+        ));
+
+        // and keys can't be revoked or lightened below it
+        for director in [&director_a, &director_b] {
+            let msg = ExecuteMsg::RevokeKey {
+                key_owner: director.to_string(),
+                key_type: "ManagementKey".to_string(),
+            };
+            app.execute_contract(ceo.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+        let err = app
+            .execute_contract(
+                ceo.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SetKeyWeight {
+                    key_owner: ceo.to_string(),
+                    weight: 1,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+    }
+
+    #[test]
+    fn claims_by_status() {
+        use crate::msg::ClaimStatus;
+
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let (issuer, issuer_key, issuer_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("alice");
+
+        // Topic 1 stays valid, 2 expires, 3 is revoked and 4 loses its issuer's trust
+        let expires_at = app.block_info().time.seconds() + 10;
+        let mut claim_ids = vec![];
+        for topic in 1u128..=4 {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(topic),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: (topic == 2).then_some(Uint64::new(expires_at)),
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &issuer_key,
+            );
+            let msg = ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(issuer_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            };
+            app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        for claim in &claims {
+            claim_ids.push(claim.id.clone().unwrap());
+        }
+
+        app.update_block(|block| block.time = block.time.plus_seconds(20));
+        let msg = ExecuteMsg::PublishRevocations {
+            revoked_ids: vec![claim_id_hash(&claim_ids[2])],
+        };
+        app.execute_contract(issuer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let msg = ExecuteMsg::SetTopicAllowedIssuers {
+            topic: Uint128::new(4),
+            issuers: vec![owner_addr.to_string()],
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        for (status, topic) in [
+            (ClaimStatus::Valid, 1u128),
+            (ClaimStatus::Expired, 2),
+            (ClaimStatus::Revoked, 3),
+            (ClaimStatus::UntrustedIssuer, 4),
+        ] {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetClaimsByStatus {
+                        user_addr: user_addr.clone(),
+                        status,
+                    },
+                )
+                .unwrap();
+            let topics: Vec<Uint128> = claims.iter().map(|claim| claim.topic).collect();
+            assert_eq!(topics, vec![Uint128::new(topic)]);
+        }
+    }
+
+    #[test]
+    fn revocations_page_size_is_clamped() {
+        let mut deps = mock_dependencies();
+        let issuer = deps.api.addr_make("issuer");
+        let revoked: Vec<Binary> = (0..40)
+            .map(|i| claim_id_hash(&format!("claim {i}")))
+            .collect();
+        CRL.save(deps.as_mut().storage, &issuer, &revoked).unwrap();
+
+        let page = |start_after: Option<u32>, limit: Option<u32>| -> Vec<Binary> {
+            let msg = QueryMsg::GetRevocations {
+                issuer: issuer.clone(),
+                start_after,
+                limit,
+            };
+            cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+
+        assert_eq!(page(None, None), revoked[..10]);
+        assert_eq!(page(None, Some(100)), revoked[..30]);
+        assert_eq!(page(Some(29), Some(100)), revoked[30..]);
+    }
+
+    #[test]
+    fn topic_holder_purpose() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let operator = app.api().addr_make("operator");
+        let investor = app.api().addr_make("investor");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![
+                    (owner_addr.clone(), KeyType::ClaimSignerKey),
+                    (operator.clone(), KeyType::ExecutionKey),
+                ],
+                ..Default::default()
+            },
+        );
+
+        // Only ExecutionKey holders may receive topic 50 claims
+        let msg = ExecuteMsg::SetTopicHolderPurpose {
+            topic: Uint128::new(50),
+            required_holder_purpose: Some(KeyType::ExecutionKey),
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let required: Option<KeyType> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetTopicHolderPurpose {
+                    topic: Uint128::new(50),
+                },
+            )
+            .unwrap();
+        assert_eq!(required, Some(KeyType::ExecutionKey));
+
+        let add_claim = |user_addr: &Addr| ExecuteMsg::AddClaim {
+            claim: sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(50),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            ),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &add_claim(&investor),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::MissingHolderPurpose { .. })
+        ));
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &add_claim(&operator),
+            &[],
+        )
+        .unwrap();
+
+        // Lifting the restriction lets anyone receive them again
+        let msg = ExecuteMsg::SetTopicHolderPurpose {
+            topic: Uint128::new(50),
+            required_holder_purpose: None,
+        };
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr,
+            &add_claim(&investor),
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn failing_registry_query_is_attributed() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let compliance_manager = app.api().addr_make("compliance_manager");
+        // Misconfigured: no contract lives at this address
+        let owner_roles = app.api().addr_make("owner_roles");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                owner_roles_address: Some(owner_roles.to_string()),
+                ..Default::default()
+            },
+        );
+
+        let err = app
+            .execute_contract(
+                compliance_manager,
+                contract_addr,
+                &ExecuteMsg::ForceRemoveClaim {
+                    user_addr: owner,
+                    claim_id: "claim".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast_ref::<ContractError>() {
+            Some(ContractError::CrossContractQueryFailed {
+                contract, query, ..
+            }) => {
+                assert_eq!(contract, owner_roles.as_str());
+                assert_eq!(query, "IsOwner");
+            }
+            err => panic!("Unexpected error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn delegated_signer_contract() {
+        use crate::msg::{SignerContractQueryMsg, SigningKeyResponse};
+        use cw_storage_plus::Item;
+
+        // Stands in for an issuer's key management contract, authorizing the key it was last given
+        fn signer_contract() -> Box<dyn Contract<Empty>> {
+            const SIGNING_KEY: Item<SigningKeyResponse> = Item::new("signing_key");
+            fn set_key(
+                deps: DepsMut,
+                _: Env,
+                _: MessageInfo,
+                key: SigningKeyResponse,
+            ) -> StdResult<Response> {
+                SIGNING_KEY.save(deps.storage, &key)?;
+                Ok(Response::new())
+            }
+            fn signing_key(deps: Deps, _: Env, _: SignerContractQueryMsg) -> StdResult<Binary> {
+                to_json_binary(&SIGNING_KEY.load(deps.storage)?)
+            }
+            Box::new(ContractWrapper::new(set_key, set_key, signing_key))
+        }
+
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let issuer = app.api().addr_make("issuer");
+        let (_, signing_secret_key, signing_public_key) = create_wallet(&app);
+        let (_, other_secret_key, other_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let signer_code = app.store_code(signer_contract());
+        let expires_at = app.block_info().time.seconds() + 100;
+        let signer = app
+            .instantiate_contract(
+                signer_code,
+                issuer.clone(),
+                &SigningKeyResponse {
+                    public_key: Binary::from(signing_public_key.serialize()),
+                    valid_until: Some(Uint64::new(expires_at)),
+                },
+                &[],
+                "Signer",
+                None,
+            )
+            .unwrap();
+
+        let msg = ExecuteMsg::SetSignerContract {
+            signer_contract: Some(signer.to_string()),
+        };
+        app.execute_contract(issuer.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let res: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetSignerContract {
+                    issuer: issuer.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res, Some(signer));
+
+        let add_claim = |topic: WellKnownTopic, secret_key: &SecretKey, public_key: &PublicKey| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(owner_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                secret_key,
+            );
+            ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(public_key.serialize()),
+                user_addr: owner_addr.clone(),
+                idempotency_key: None,
+            }
+        };
+
+        // Signing authority resolves through the signer contract
+        let msg = add_claim(
+            WellKnownTopic::Kyc,
+            &signing_secret_key,
+            &signing_public_key,
+        );
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        // A validly signed claim under any other key is rejected
+        let msg = add_claim(WellKnownTopic::Aml, &other_secret_key, &other_public_key);
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidIssuerSignature { .. })
+        ));
+
+        // as is the authorized key once it expires
+        app.update_block(|block| block.time = block.time.plus_seconds(200));
+        let msg = add_claim(
+            WellKnownTopic::Aml,
+            &signing_secret_key,
+            &signing_public_key,
+        );
+        let err = app
+            .execute_contract(owner_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidIssuerSignature { .. })
+        ));
+    }
+
+    #[test]
+    fn total_key_and_claim_caps() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                max_total_keys: Some(3),
+                max_total_claims: Some(2),
+                ..Default::default()
+            },
+        );
+
+        // A third key fits, a fourth doesn't, whoever holds it
+        let add_key = |key_owner: &str, key_type: &str| ExecuteMsg::AddKey {
+            key_owner: MockApi::default().addr_make(key_owner).to_string(),
+            key_type: key_type.to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &add_key("executor", "ExecutionKey"),
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &add_key("encryptor", "EncryptionKey"),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::TotalKeysExceeded { max: 3 })
+        ));
+
+        // Each user holds a single claim, yet the third one exceeds the identity's total
+        let add_claim = |user: &str| ExecuteMsg::AddClaim {
+            claim: sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(1),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(MockApi::default().addr_make(user)),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            ),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: MockApi::default().addr_make(user),
+            idempotency_key: None,
+        };
+        for user in ["user_a", "user_b"] {
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &add_claim(user),
+                &[],
+            )
+            .unwrap();
+        }
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &add_claim("user_c"),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::TotalClaimsExceeded { max: 2 })
+        ));
+
+        // Removing a claim frees room for another
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::RemoveClaim {
+                claim_topic: Uint128::new(1),
+                user_addr: MockApi::default().addr_make("user_a"),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(owner_addr.clone(), contract_addr, &add_claim("user_c"), &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_provided_claim() {
+        let mut deps = mock_dependencies();
+        deps.api = deps.api.with_prefix("cosmos");
+
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let public_key =
+            Binary::from(PublicKey::from_secret_key(&Secp256k1::new(), &secret_key).serialize());
+        let issuer = pubkey_to_address(deps.as_ref(), &public_key).unwrap();
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: issuer.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &secret_key,
+        );
+        let verify = |deps: Deps, claim: &Claim, required_topic: WellKnownTopic| -> bool {
+            let msg = QueryMsg::VerifyProvidedClaim {
+                claim: Box::new(claim.clone()),
+                public_key: public_key.clone(),
+                required_topic: required_topic.into(),
+            };
+            cosmwasm_std::from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // Valid for its own topic only
+        assert!(verify(deps.as_ref(), &claim, WellKnownTopic::Kyc));
+        assert!(!verify(deps.as_ref(), &claim, WellKnownTopic::Aml));
+
+        // Tampering after signing breaks the signature
+        let tampered = Claim {
+            data: Binary::from(vec![4, 5, 6]),
+            ..claim.clone()
+        };
+        assert!(!verify(deps.as_ref(), &tampered, WellKnownTopic::Kyc));
+
+        // Signed with a key that isn't the named issuer's
+        let impostor = Claim {
+            issuer: deps.api.addr_make("someone_else"),
+            ..claim.clone()
+        };
+        assert!(!verify(deps.as_ref(), &impostor, WellKnownTopic::Kyc));
+
+        // Not from an issuer allowed for the topic
+        let trusted = deps.api.addr_make("trusted");
+        TOPIC_ALLOWED_ISSUERS
+            .save(
+                deps.as_mut().storage,
+                Uint128::from(WellKnownTopic::Kyc).u128(),
+                &vec![trusted],
+            )
+            .unwrap();
+        assert!(!verify(deps.as_ref(), &claim, WellKnownTopic::Kyc));
+    }
+
+    #[test]
+    fn add_claims_batch() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let signed = |topic: u128, subject: &Addr| {
+            sign_claim(
+                Claim {
+                    id: None,
+                    topic: Uint128::new(topic),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(subject.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            )
+        };
+        let add_batch = |app: &mut App, claims: Vec<Claim>, user_addr: &Addr| {
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaimsBatch {
+                    claims,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                },
+                &[],
+            )
+        };
+        let stored_topics = |app: &App, user_addr: &Addr| -> Vec<Uint128> {
+            app.wrap()
+                .query_wasm_smart::<Vec<Claim>>(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .map(|claims| claims.into_iter().map(|claim| claim.topic).collect())
+                .unwrap_or_default()
+        };
+
+        // Three claims in one transaction
+        let alice = MockApi::default().addr_make("alice");
+        let res = add_batch(
+            &mut app,
+            vec![signed(1, &alice), signed(2, &alice), signed(3, &alice)],
+            &alice,
+        )
+        .unwrap();
+        let added_topics: Vec<_> = res
+            .events
+            .iter()
+            .flat_map(|event| &event.attributes)
+            .filter(|attr| attr.key == "claim_topic")
+            .map(|attr| attr.value.clone())
+            .collect();
+        assert_eq!(added_topics, ["1", "2", "3"]);
+        assert_eq!(
+            stored_topics(&app, &alice),
+            [Uint128::new(1), Uint128::new(2), Uint128::new(3)]
+        );
+
+        // A single bad signature rolls back the whole batch
+        let bob = MockApi::default().addr_make("bob");
+        let tampered = Claim {
+            data: Binary::from(vec![4, 5, 6]),
+            ..signed(3, &bob)
+        };
+        add_batch(
+            &mut app,
+            vec![signed(1, &bob), signed(2, &bob), tampered],
+            &bob,
+        )
+        .unwrap_err();
+        assert!(stored_topics(&app, &bob).is_empty());
+
+        // So does a topic attested twice
+        add_batch(&mut app, vec![signed(1, &bob), signed(1, &bob)], &bob).unwrap_err();
+        assert!(stored_topics(&app, &bob).is_empty());
+    }
+
+    #[test]
+    fn claims_paginated() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("user_addr");
+
+        // 25 topics added in descending order, topic 10 also under another namespace
+        let claims = (1..=25u128)
+            .rev()
+            .map(|topic| (topic, None))
+            .chain([(10, Some("other".to_string()))])
+            .map(|(topic, namespace)| {
+                sign_claim(
+                    Claim {
+                        id: None,
+                        topic: Uint128::new(topic),
+                        issuer: owner_addr.clone(),
+                        signature: Binary::from(vec![]),
+                        data: Binary::from(vec![1, 2, 3]),
+                        data_hash: None,
+                        uri: "https://example.com".to_string(),
+                        valid_until: None,
+                        issued_at: None,
+                        encryption: None,
+                        related_identity: None,
+                        namespace,
+                        data_root: None,
+                        signature_scheme: SignatureScheme::Secp256k1,
+                        subject: None,
+                        nonce: None,
+                        signed_expiry: None,
+                    },
+                    &owner_secret_key,
+                )
+            })
+            .collect();
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::AddClaimsBatch {
+                claims,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // Paged through in topic order, 10 at a time by default, keeping topic 10 together
+        let mut start_after = None;
+        let mut pages = vec![];
+        loop {
+            let page: ClaimsPageResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetClaimsPaginated {
+                        user_addr: user_addr.clone(),
+                        start_after,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            let Some(last_topic) = page.last_topic else {
+                assert!(page.claims.is_empty());
+                break;
+            };
+            start_after = Some(last_topic);
+            pages.push(
+                page.claims
+                    .iter()
+                    .map(|claim| claim.topic.u128())
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(
+            pages,
+            vec![
+                (1..=9).collect::<Vec<_>>(),
+                [10, 10].into_iter().chain(11..=18).collect(),
+                (19..=25).collect()
+            ]
+        );
+
+        // Limits above the cap are clamped to 30, enough for all of them
+        let page: ClaimsPageResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetClaimsPaginated {
+                    user_addr,
+                    start_after: None,
+                    limit: Some(100),
+                },
+            )
+            .unwrap();
+        assert_eq!(page.claims.len(), 26);
+        assert_eq!(page.last_topic, Some(Uint128::new(25)));
+    }
+
+    #[test]
+    fn claim_contents_require_signed_query() {
+        use crate::utils::query_auth_message_hash;
+
+        let mut deps = mock_dependencies();
+        let secp = Secp256k1::new();
+        let wallet = |deps: Deps| {
+            let secret_key = SecretKey::new(&mut rand::thread_rng());
+            let public_key =
+                Binary::from(PublicKey::from_secret_key(&secp, &secret_key).serialize());
+            let addr = pubkey_to_address(deps, &public_key).unwrap();
+            (addr, secret_key, public_key)
+        };
+        let (reader, reader_key, reader_public_key) = wallet(deps.as_ref());
+        let (stranger, stranger_key, stranger_public_key) = wallet(deps.as_ref());
+
+        let owner = deps.api.addr_make("owner");
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            initial_keys: vec![(reader.clone(), KeyType::ExecutionKey)],
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&owner, &[]), msg).unwrap();
+        let user_addr = deps.api.addr_make("user_addr");
+        let claim = Claim {
+            id: Some("claim".to_string()),
+            topic: WellKnownTopic::Kyc.into(),
+            issuer: deps.api.addr_make("issuer"),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
+        };
+        CLAIMS
+            .save(deps.as_mut().storage, &user_addr, &vec![claim.clone()])
+            .unwrap();
+
+        let expires_at = Uint64::new(mock_env().block.time.seconds() + 60);
+        let contents_of = |user_addr: &Addr, claim_id: &str| QueryMsg::GetClaimContents {
+            user_addr: user_addr.clone(),
+            claim_id: claim_id.to_string(),
+            auth: None,
+        };
+        let sign = |signer: &Addr, secret_key: &SecretKey, query: &QueryMsg| {
+            let hash = query_auth_message_hash(
+                &mock_env().contract.address,
+                query,
+                signer,
+                expires_at,
+                &SignatureEncoding::Raw,
+            );
+            let message = Message::from_slice(&hash).unwrap();
+            Binary::from(secp.sign_ecdsa(&message, secret_key).serialize_compact())
+        };
+        let get_contents = |env: Env, auth: Option<QueryAuth>| -> ClaimContentsResponse {
+            let msg = QueryMsg::GetClaimContents {
+                user_addr: user_addr.clone(),
+                claim_id: "claim".to_string(),
+                auth,
+            };
+            cosmwasm_std::from_json(query(deps.as_ref(), env, msg).unwrap()).unwrap()
+        };
+        let reader_auth = QueryAuth {
+            public_key: reader_public_key.clone(),
+            signature: sign(&reader, &reader_key, &contents_of(&user_addr, "claim")),
+            expires_at,
+        };
+
+        // An ExecutionKey holder reads the full claim
+        let res = get_contents(mock_env(), Some(reader_auth.clone()));
+        assert!(!res.redacted);
+        assert_eq!(res.claim, claim);
+
+        // Anyone else gets it redacted: unsigned, signed by a stranger, signed for another
+        // claim or holder, or past its expiry
+        let redacted = Claim {
+            data: Binary::default(),
+            uri: String::new(),
+            ..claim
+        };
+        let mut expired = mock_env();
+        expired.block.time = expired.block.time.plus_seconds(61);
+        for (env, auth) in [
+            (mock_env(), None),
+            (
+                mock_env(),
+                Some(QueryAuth {
+                    public_key: stranger_public_key,
+                    signature: sign(&stranger, &stranger_key, &contents_of(&user_addr, "claim")),
+                    expires_at,
+                }),
+            ),
+            (
+                mock_env(),
+                Some(QueryAuth {
+                    signature: sign(&reader, &reader_key, &contents_of(&user_addr, "other")),
+                    ..reader_auth.clone()
+                }),
+            ),
+            (
+                mock_env(),
+                Some(QueryAuth {
+                    signature: sign(&reader, &reader_key, &contents_of(&reader, "claim")),
+                    ..reader_auth.clone()
+                }),
+            ),
+            (expired, Some(reader_auth)),
+        ] {
+            let res = get_contents(env, auth);
+            assert!(res.redacted);
+            assert_eq!(res.claim, redacted);
+        }
+    }
+
+    #[test]
+    fn enumerate_keys() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let director = deps.api.addr_make("director");
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            initial_keys: vec![
+                (owner.clone(), KeyType::ClaimSignerKey),
+                (owner.clone(), KeyType::ExecutionKey),
+                (director.clone(), KeyType::ManagementKey),
+            ],
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&owner, &[]), msg).unwrap();
+        let query_keys = |msg: QueryMsg| -> Vec<(Addr, Vec<KeyType>)> {
+            let keys: Vec<Key> =
+                cosmwasm_std::from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            keys.into_iter()
+                .map(|key| (key.owner, key.purposes))
+                .collect()
+        };
+        let owner_purposes = vec![
+            KeyType::ManagementKey,
+            KeyType::ClaimSignerKey,
+            KeyType::ExecutionKey,
+        ];
+
+        // The owner's key, with every purpose it holds
+        assert_eq!(
+            query_keys(QueryMsg::GetKeys {
+                key_owner: owner.to_string()
+            }),
+            vec![(owner.clone(), owner_purposes.clone())]
+        );
+
+        // Owners without keys have none, rather than an error
+        assert_eq!(
+            query_keys(QueryMsg::GetKeys {
+                key_owner: deps.api.addr_make("unknown").to_string()
+            }),
+            vec![]
+        );
+
+        // Every holder of a key type
+        assert_eq!(
+            query_keys(QueryMsg::GetKeysByType {
+                key_type: "ManagementKey".to_string()
+            }),
+            vec![
+                (owner, owner_purposes),
+                (director, vec![KeyType::ManagementKey])
+            ]
+        );
+    }
+
+    #[test]
+    fn preflight_verification() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let add_claim = |app: &mut App, topic: WellKnownTopic| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap();
+        };
+        let preflight = |app: &App| -> PreflightVerificationResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::PreflightVerification {
+                        user_addr: user_addr.clone(),
+                        required_topics: vec![
+                            WellKnownTopic::Kyc.into(),
+                            WellKnownTopic::Aml.into(),
+                        ],
+                        trusted_issuers_registry: None,
+                    },
+                )
+                .unwrap()
+        };
+
+        // Only KYC is satisfied, by the owner's claim
+        add_claim(&mut app, WellKnownTopic::Kyc);
+        let res = preflight(&app);
+        assert!(!res.verified);
+        let satisfied: Vec<_> = res
+            .topics
+            .iter()
+            .map(|topic| (topic.satisfied, topic.issuer.clone()))
+            .collect();
+        assert_eq!(
+            satisfied,
+            vec![(true, Some(owner_addr.clone())), (false, None)]
+        );
+        assert!(res.topics[0].claim_id.is_some());
+
+        // Both are once AML is attested
+        add_claim(&mut app, WellKnownTopic::Aml);
+        let res = preflight(&app);
+        assert!(res.verified);
+        assert!(res
+            .topics
+            .iter()
+            .all(|topic| topic.satisfied && topic.issuer == Some(owner_addr.clone())));
+    }
+
+    #[test]
+    fn preflight_asks_trusted_issuers_registry_once_per_issuer() {
+        use cosmwasm_std::{ContractResult, SystemResult};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let msg = InstantiateMsg {
+            owner: owner.to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&owner, &[]), msg).unwrap();
+
+        // Issuer A attests to topics 1 to 3 and B to topic 2, both trusted for them. C, which
+        // the registry doesn't know, attests to topic 4.
+        let user_addr = deps.api.addr_make("user_addr");
+        let issuers = ["issuer_a", "issuer_b", "issuer_c"].map(|name| deps.api.addr_make(name));
+        let claim = |issuer: &Addr, topic: u128| Claim {
+            id: Some(format!("{issuer}-{topic}")),
+            topic: Uint128::new(topic),
+            issuer: issuer.clone(),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
+        };
+        let claims = vec![
+            claim(&issuers[0], 1),
+            claim(&issuers[0], 2),
+            claim(&issuers[1], 2),
+            claim(&issuers[0], 3),
+            claim(&issuers[2], 4),
+        ];
+        CLAIMS
+            .save(deps.as_mut().storage, &user_addr, &claims)
+            .unwrap();
+
+        // Counts the registry's lookups of each issuer
+        let lookups: Rc<RefCell<BTreeMap<Addr, u32>>> = Rc::default();
+        let trusted_topics = BTreeMap::from([
+            (
+                issuers[0].clone(),
+                vec![Uint128::new(1), Uint128::new(2), Uint128::new(3)],
+            ),
+            (issuers[1].clone(), vec![Uint128::new(2)]),
+        ]);
+        let counted = lookups.clone();
+        deps.querier.update_wasm(move |query| {
+            let cosmwasm_std::WasmQuery::Smart { msg, .. } = query else {
+                panic!("Unexpected query type")
+            };
+            let res = match from_json(msg).unwrap() {
+                TrustedIssuersQueryMsg::GetIssuerClaimTopics { issuer } => {
+                    *counted.borrow_mut().entry(issuer.clone()).or_default() += 1;
+                    match trusted_topics.get(&issuer) {
+                        Some(topics) => ContractResult::Ok(to_json_binary(topics).unwrap()),
+                        None => ContractResult::Err("Issuer not found".to_string()),
+                    }
+                }
+                TrustedIssuersQueryMsg::IsTrustedIssuer { issuer } => ContractResult::Ok(
+                    to_json_binary(&trusted_topics.contains_key(&issuer)).unwrap(),
+                ),
+                _ => panic!("Unexpected registry query"),
+            };
+            SystemResult::Ok(res)
+        });
+
+        let msg = QueryMsg::PreflightVerification {
+            user_addr,
+            required_topics: (1..=4).map(Uint128::new).collect(),
+            trusted_issuers_registry: Some(deps.api.addr_make("trusted_issuers").to_string()),
+        };
+        let res: PreflightVerificationResponse =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let satisfied: Vec<_> = res.topics.iter().map(|topic| topic.satisfied).collect();
+        assert_eq!(satisfied, [true, true, true, false]);
+        assert!(!res.verified);
+        assert!(lookups.borrow().values().all(|count| *count == 1));
+        assert_eq!(lookups.borrow().len(), 3);
+    }
+
+    #[test]
+    fn key_gains_and_loses_purposes() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let agent = app.api().addr_make("agent");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+        let add_key = |key_type: &str| ExecuteMsg::AddKey {
+            key_owner: agent.to_string(),
+            key_type: key_type.to_string(),
+            idempotency_key: None,
+        };
+        let revoke_key = |key_type: &str| ExecuteMsg::RevokeKey {
+            key_owner: agent.to_string(),
+            key_type: key_type.to_string(),
+        };
+        let get_key = |app: &App, key_type: &str| -> StdResult<Key> {
+            app.wrap().query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKey {
+                    key_owner: agent.to_string(),
+                    key_type: key_type.to_string(),
+                },
+            )
+        };
+        let agent_keys = |app: &App| -> Vec<Key> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKeys {
+                        key_owner: agent.to_string(),
+                    },
+                )
+                .unwrap()
+        };
+
+        // A second purpose joins the existing key
+        for key_type in ["ClaimSignerKey", "ExecutionKey"] {
+            app.execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &add_key(key_type),
+                &[],
+            )
+            .unwrap();
+        }
+        let keys = agent_keys(&app);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(
+            keys[0].purposes,
+            vec![KeyType::ClaimSignerKey, KeyType::ExecutionKey]
+        );
+        assert_eq!(get_key(&app, "ClaimSignerKey").unwrap(), keys[0]);
+        assert_eq!(get_key(&app, "ExecutionKey").unwrap(), keys[0]);
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &add_key("ExecutionKey"),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::KeyAlreadyExists { .. })
+        ));
+
+        // Revoking one purpose keeps the key for the others
+        app.execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &revoke_key("ClaimSignerKey"),
+            &[],
+        )
+        .unwrap();
+        assert!(get_key(&app, "ClaimSignerKey").is_err());
+        assert_eq!(
+            get_key(&app, "ExecutionKey").unwrap().purposes,
+            vec![KeyType::ExecutionKey]
+        );
+
+        // Without purposes left, the key is gone
+        app.execute_contract(
+            owner.clone(),
+            contract_addr.clone(),
+            &revoke_key("ExecutionKey"),
+            &[],
+        )
+        .unwrap();
+        assert!(agent_keys(&app).is_empty());
+    }
+
+    #[test]
+    fn migrate_folds_legacy_keys() {
+        let mut deps = mock_dependencies();
+        let owner = deps.api.addr_make("owner");
+        let agent = deps.api.addr_make("agent");
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&owner, &[]),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // One entry per purpose, as stored before keys held several
+        let legacy_keys = format!(
+            r#"[
+                {{"owner":"{owner}","key_type":"ClaimSignerKey","last_used":"50","active":false}},
+                {{"owner":"{owner}","key_type":"ManagementKey","weight":2}},
+                {{"owner":"{agent}","key_type":"ExecutionKey"}},
+                {{"owner":"{agent}","key_type":"EncryptionKey","active":false}}
+            ]"#
+        );
+        deps.storage.set(&KEYS.key(&owner), legacy_keys.as_bytes());
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::attr("legacy_keys_folded", "2")));
+
+        // The ManagementKey entry decides the owner's weight and state
+        let keys = KEYS.load(deps.as_ref().storage, &owner).unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                Key {
+                    owner: owner.clone(),
+                    purposes: vec![KeyType::ClaimSignerKey, KeyType::ManagementKey],
+                    last_used: Some(Uint64::new(50)),
+                    weight: 2,
+                    active: true,
+                },
+                Key {
+                    owner: agent,
+                    purposes: vec![KeyType::ExecutionKey, KeyType::EncryptionKey],
+                    last_used: None,
+                    weight: 1,
+                    active: false,
+                },
+            ]
+        );
+
+        // Folded keys are left as they are
+        assert_eq!(fold_legacy_keys(deps.as_mut().storage).unwrap(), 0);
+    }
+
+    #[test]
+    fn required_issuers_for_profile() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let [broad, left, right] = ["broad", "left", "right"].map(|name| app.api().addr_make(name));
+        let mut allow = |topic: u128, issuers: &[&Addr]| {
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::SetTopicAllowedIssuers {
+                    topic: Uint128::new(topic),
+                    issuers: issuers.iter().map(|issuer| issuer.to_string()).collect(),
+                },
+                &[],
+            )
+            .unwrap();
+        };
+        // Taking the issuer covering the most topics first would need three issuers
+        allow(11, &[&broad, &left]);
+        allow(12, &[&broad, &left]);
+        allow(13, &[&broad, &right]);
+        allow(14, &[&broad, &right]);
+        allow(15, &[&left]);
+        allow(16, &[&right]);
+        allow(
+            Uint128::from(WellKnownTopic::Kyc).u128(),
+            &[&owner_addr, &broad],
+        );
+
+        // The user already holds a KYC claim from the owner
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let required = |profile: Vec<u128>| -> RequiredIssuersResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::RequiredIssuersForProfile {
+                        user_addr: user_addr.clone(),
+                        profile: profile.into_iter().map(Uint128::new).collect(),
+                    },
+                )
+                .unwrap()
+        };
+
+        // KYC is already covered, and topic 20 accepts any issuer
+        let mut expected = vec![left.clone(), right.clone()];
+        expected.sort();
+        assert_eq!(
+            required(vec![1, 11, 12, 13, 14, 15, 16, 20]),
+            RequiredIssuersResponse {
+                issuers: expected,
+                uncovered_topics: vec![Uint128::new(20)],
+            }
+        );
+
+        // Without the topics only one of them covers, a single issuer will do
+        assert_eq!(
+            required(vec![1, 11, 12, 13, 14]),
+            RequiredIssuersResponse {
+                issuers: vec![broad],
+                uncovered_topics: vec![],
+            }
+        );
+        assert_eq!(
+            required(vec![1]),
+            RequiredIssuersResponse {
+                issuers: vec![],
+                uncovered_topics: vec![],
+            }
+        );
+
+        // Profiles are bounded
+        let err = app
+            .wrap()
+            .query_wasm_smart::<RequiredIssuersResponse>(
+                contract_addr,
+                &QueryMsg::RequiredIssuersForProfile {
+                    user_addr,
+                    profile: (0..=MAX_PROFILE_TOPICS as u128).map(Uint128::new).collect(),
+                },
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("at most"));
+    }
+
+    #[test]
+    fn two_step_ownership_transfer() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let new_owner = app.api().addr_make("new_owner");
+        let stranger = app.api().addr_make("stranger");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+        let propose = ExecuteMsg::ProposeNewOwner {
+            new_owner: new_owner.to_string(),
+        };
+
+        // Nothing to accept yet
+        let err = app
+            .execute_contract(
+                new_owner.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AcceptOwnership {},
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::NoPendingOwner {})
+        ));
+
+        // Only ManagementKey holders may propose
+        let err = app
+            .execute_contract(stranger.clone(), contract_addr.clone(), &propose, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+
+        // The owner also signs claims
+        let msg = ExecuteMsg::AddKey {
+            key_owner: owner.to_string(),
+            key_type: "ClaimSignerKey".to_string(),
+            idempotency_key: None,
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+
+        app.execute_contract(owner.clone(), contract_addr.clone(), &propose, &[])
+            .unwrap();
+        let pending: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetPendingOwner {})
+            .unwrap();
+        assert_eq!(pending, Some(new_owner.clone()));
+
+        // Only the proposed account may accept
+        let err = app
+            .execute_contract(
+                stranger.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AcceptOwnership {},
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+
+        app.execute_contract(
+            new_owner.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::AcceptOwnership {},
+            &[],
+        )
+        .unwrap();
+        let current: String = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetOwner {})
+            .unwrap();
+        assert_eq!(current, new_owner.to_string());
+        let pending: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetPendingOwner {})
+            .unwrap();
+        assert_eq!(pending, None);
+
+        // The new owner holds a ManagementKey and manages the identity, whose keys moved
+        // along with the ownership
+        let key: Key = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKey {
+                    key_owner: new_owner.to_string(),
+                    key_type: "ManagementKey".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(key.active);
+        let keys_of_type = |app: &App, key_type: &str| -> Vec<Addr> {
+            let keys: Vec<Key> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetKeysByType {
+                        key_type: key_type.to_string(),
+                    },
+                )
+                .unwrap();
+            keys.into_iter().map(|key| key.owner).collect()
+        };
+        assert_eq!(keys_of_type(&app, "ManagementKey"), vec![new_owner.clone()]);
+
+        // Every key of the previous owner was revoked
+        assert!(keys_of_type(&app, "ClaimSignerKey").is_empty());
+        let propose = ExecuteMsg::ProposeNewOwner {
+            new_owner: owner.to_string(),
+        };
+        let err = app
+            .execute_contract(owner, contract_addr, &propose, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+    }
+
+    #[test]
+    fn claim_history_keeps_prior_versions() {
+        use crate::state::{DuplicateClaimPolicy, MAX_CLAIM_VERSIONS};
+
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                duplicate_claim_policy: Some(DuplicateClaimPolicy::Overwrite),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let add_claim = |app: &mut App, data: Vec<u8>| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(data),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap();
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(claims.len(), 1);
+            claims[0].id.clone().unwrap()
+        };
+        let history = |app: &App, claim_id: &str| {
+            app.wrap().query_wasm_smart::<Vec<ClaimVersion>>(
+                contract_addr.clone(),
+                &QueryMsg::GetClaimHistory {
+                    user_addr: user_addr.clone(),
+                    claim_id: claim_id.to_string(),
+                },
+            )
+        };
+
+        // Issue the claim, then refresh it twice
+        let first_id = add_claim(&mut app, vec![1]);
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+        add_claim(&mut app, vec![2]);
+        let refreshed_at = app.block_info().time.seconds();
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+        let current_id = add_claim(&mut app, vec![3]);
+
+        let versions = history(&app, &current_id).unwrap();
+        assert_eq!(
+            versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            versions
+                .iter()
+                .map(|v| v.claim.data.to_vec())
+                .collect::<Vec<_>>(),
+            vec![vec![1], vec![2], vec![3]]
+        );
+        assert_eq!(
+            versions[1].superseded_at,
+            Some(Uint64::new(refreshed_at + 10))
+        );
+        assert_eq!(versions[2].superseded_at, None);
+        // The history follows the claim to its current ID
+        assert!(history(&app, &first_id).is_err());
+
+        // Only the current version verifies
+        let verified: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr: user_addr.clone(),
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(verified);
+        let current: ClaimResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetClaim {
+                    user_addr: user_addr.clone(),
+                    claim_id: current_id,
+                },
+            )
+            .unwrap();
+        assert_eq!(current.claim.data, Binary::from(vec![3]));
+
+        // The chain is bounded, keeping the latest versions
+        let mut current_id = String::new();
+        for data in 4..4 + MAX_CLAIM_VERSIONS as u8 {
+            current_id = add_claim(&mut app, vec![data]);
+        }
+        let versions = history(&app, &current_id).unwrap();
+        assert_eq!(versions.len(), MAX_CLAIM_VERSIONS + 1);
+        assert_eq!(versions[0].version, 3);
+        assert_eq!(
+            versions.last().unwrap().version,
+            3 + MAX_CLAIM_VERSIONS as u32
+        );
+    }
+
+    #[test]
+    fn issuer_revokes_claim_signature() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &owner_secret_key,
+        );
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &ExecuteMsg::AddClaim {
+                claim,
+                public_key: Binary::from(owner_public_key.serialize()),
+                user_addr: user_addr.clone(),
+                idempotency_key: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let validated_claims = |app: &App| -> Vec<Claim> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetValidatedClaimsForUser {
+                        user_addr: user_addr.clone(),
+                        exclude_expired: None,
+                    },
+                )
+                .unwrap()
+        };
+        let verified = |app: &App| -> bool {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::VerifyClaim {
+                        claim_id: WellKnownTopic::Kyc.into(),
+                        user_addr: user_addr.clone(),
+                        namespace: None,
+                        trusted_issuers_registry: None,
+                    },
+                )
+                .unwrap()
+        };
+        let claims = validated_claims(&app);
+        let claim_id = claims[0].id.clone().unwrap();
+        assert!(verified(&app));
+
+        // Only the issuer can revoke
+        let stranger = app.api().addr_make("stranger");
+        let revoke = ExecuteMsg::RevokeClaimSignature {
+            claim_id: claim_id.clone(),
+            user_addr: user_addr.clone(),
+        };
+        let err = app
+            .execute_contract(stranger, contract_addr.clone(), &revoke, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::RevokeClaimSignature {
+                    claim_id: "unknown".to_string(),
+                    user_addr: user_addr.clone(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ClaimIdNotFound { .. })
+        ));
+        // The claim is looked up among the given holder's claims only
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::RevokeClaimSignature {
+                    claim_id: claim_id.clone(),
+                    user_addr: app.api().addr_make("other_user"),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ClaimIdNotFound { .. })
+        ));
+        assert!(verified(&app));
+
+        app.execute_contract(owner_addr.clone(), contract_addr.clone(), &revoke, &[])
+            .unwrap();
+        assert!(!verified(&app));
+        assert!(validated_claims(&app).is_empty());
+        let revoked: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::IsRevoked {
+                    issuer: owner_addr,
+                    claim_id,
+                },
+            )
+            .unwrap();
+        assert!(revoked);
+    }
+
+    #[test]
+    fn verify_disclosure_against_data_root() {
+        let leaf_hash = |leaf: &[u8]| -> [u8; 32] {
+            Sha256::new()
+                .chain_update([0x00])
+                .chain_update(leaf)
+                .finalize()
+                .into()
+        };
+        let node_hash = |a: [u8; 32], b: [u8; 32]| -> [u8; 32] {
+            let (lower, higher) = if a <= b { (a, b) } else { (b, a) };
+            Sha256::new()
+                .chain_update([0x01])
+                .chain_update(lower)
+                .chain_update(higher)
+                .finalize()
+                .into()
+        };
+        let leaves: Vec<&[u8]> = vec![b"name=Alice", b"country=CH", b"born=1990", b"accredited"];
+        let hashes: Vec<[u8; 32]> = leaves.iter().map(|leaf| leaf_hash(leaf)).collect();
+        let left = node_hash(hashes[0], hashes[1]);
+        let right = node_hash(hashes[2], hashes[3]);
+        let root = node_hash(left, right);
+
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
+            issuer: owner_addr.clone(),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: Some(Binary::from(root.to_vec())),
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: None,
+            nonce: None,
+            signed_expiry: None,
+        };
+        let add_claim = |claim: Claim| ExecuteMsg::AddClaim {
+            claim: sign_claim(claim, &owner_secret_key),
+            public_key: Binary::from(owner_public_key.serialize()),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+
+        // Roots must be hashes
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &add_claim(Claim {
+                    data_root: Some(Binary::from(vec![1, 2, 3])),
+                    ..claim.clone()
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidDataRoot { .. })
+        ));
+
+        // The root is signed with the rest of the claim
+        let mut tampered = sign_claim(claim.clone(), &owner_secret_key);
+        tampered.data_root = Some(Binary::from(vec![0; 32]));
+        let err = app
+            .execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim: tampered,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidSignature { .. })
+        ));
+
+        app.execute_contract(
+            owner_addr.clone(),
+            contract_addr.clone(),
+            &add_claim(claim),
+            &[],
+        )
+        .unwrap();
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let claim_id = claims[0].id.clone().unwrap();
+        let disclose = |leaf: &[u8], proof: Vec<[u8; 32]>| {
+            app.wrap().query_wasm_smart::<bool>(
+                contract_addr.clone(),
+                &QueryMsg::VerifyDisclosure {
+                    user_addr: user_addr.clone(),
+                    claim_id: claim_id.clone(),
+                    leaf: Binary::from(leaf),
+                    proof: proof
+                        .into_iter()
+                        .map(|hash| Binary::from(hash.to_vec()))
+                        .collect(),
+                },
+            )
+        };
+
+        // Every attribute can be disclosed on its own
+        assert!(disclose(leaves[0], vec![hashes[1], right]).unwrap());
+        assert!(disclose(leaves[1], vec![hashes[0], right]).unwrap());
+        assert!(disclose(leaves[2], vec![hashes[3], left]).unwrap());
+        assert!(disclose(leaves[3], vec![hashes[2], left]).unwrap());
+
+        // Invalid proofs
+        assert!(!disclose(b"country=US", vec![hashes[0], right]).unwrap());
+        assert!(!disclose(leaves[1], vec![hashes[1], right]).unwrap());
+        assert!(!disclose(leaves[1], vec![hashes[0]]).unwrap());
+        assert!(!disclose(leaves[1], vec![hashes[0], right, left]).unwrap());
+        // An inner node is not a disclosed attribute
+        assert!(!disclose(&left, vec![right]).unwrap());
+        assert!(disclose(leaves[0], vec![[0; 32]; MAX_DISCLOSURE_PROOF_DEPTH + 1]).is_err());
+    }
+
+    #[test]
+    fn claim_key_must_belong_to_issuer() {
+        let mut app = App::default();
+        let (owner_addr, _, _) = create_wallet(&app);
+        let (_, other_secret_key, other_public_key) = create_wallet(&app);
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        // Validly signed, but with a key that isn't the issuer's
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: owner_addr.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &other_secret_key,
+        );
+        let err = app
+            .execute_contract(
+                owner_addr,
+                contract_addr,
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(other_public_key.serialize()),
+                    user_addr,
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::IssuerKeyMismatch {})
+        ));
+    }
+
+    #[test]
+    fn admin_and_owner_roles_are_separate() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let admin = app.api().addr_make("admin");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                admin: Some(admin.to_string()),
+                ..Default::default()
+            },
+        );
+        let res: Addr = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetAdmin {})
+            .unwrap();
+        assert_eq!(res, admin);
+
+        // Topic policies are the admin's
+        let set_issuers = ExecuteMsg::SetTopicAllowedIssuers {
+            topic: WellKnownTopic::Kyc.into(),
+            issuers: vec![owner.to_string()],
+        };
+        let err = app
+            .execute_contract(owner.clone(), contract_addr.clone(), &set_issuers, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        app.execute_contract(admin.clone(), contract_addr.clone(), &set_issuers, &[])
+            .unwrap();
+
+        // Keys are the owner's
+        let add_key = ExecuteMsg::AddKey {
+            key_owner: admin.to_string(),
+            key_type: "ExecutionKey".to_string(),
+            idempotency_key: None,
+        };
+        let err = app
+            .execute_contract(admin.clone(), contract_addr.clone(), &add_key, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        app.execute_contract(owner.clone(), contract_addr.clone(), &add_key, &[])
+            .unwrap();
+
+        // The configuration is the admin's, except the owner's management threshold
+        let update_config = ExecuteMsg::UpdateConfig {
+            config: Config {
+                default_validity_secs: Some(60),
+                management_threshold: 5,
+                ..Default::default()
+            },
+        };
+        let err = app
+            .execute_contract(owner.clone(), contract_addr.clone(), &update_config, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        app.execute_contract(admin.clone(), contract_addr.clone(), &update_config, &[])
+            .unwrap();
+        let config: Config = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(config.default_validity_secs, Some(60));
+        assert_eq!(config.management_threshold, 1);
+
+        // Only the admin hands its role over, here back to the owner
+        let reset = ExecuteMsg::SetAdmin { admin: None };
+        let err = app
+            .execute_contract(owner.clone(), contract_addr.clone(), &reset, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+        app.execute_contract(admin.clone(), contract_addr.clone(), &reset, &[])
+            .unwrap();
+        let res: Addr = app
+            .wrap()
+            .query_wasm_smart(contract_addr.clone(), &QueryMsg::GetAdmin {})
+            .unwrap();
+        assert_eq!(res, owner);
+        app.execute_contract(owner, contract_addr.clone(), &set_issuers, &[])
+            .unwrap();
+        let err = app
+            .execute_contract(admin, contract_addr, &set_issuers, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+    }
+
+    #[test]
+    fn ed25519_signed_claim() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let user_addr = app.api().addr_make("user_addr");
+        let signing_key = ed25519_zebra::SigningKey::new(rand::thread_rng());
+        let public_key = Binary::from(
+            ed25519_zebra::VerificationKey::from(&signing_key)
+                .as_ref()
+                .to_vec(),
+        );
+        let issuer = app
+            .api()
+            .addr_humanize(&CanonicalAddr::from(&Sha256::digest(&public_key)[..20]))
+            .unwrap();
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(owner.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        let mut claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
+            issuer,
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            subject: Some(user_addr.clone()),
+            nonce: Some(Uint64::new(1)),
+            signed_expiry: None,
+        };
+        let signature = signing_key.sign(&hash_claim_without_signature(&claim));
+        claim.signature = Binary::from(<[u8; 64]>::from(signature).to_vec());
+        let add_claim = |claim: Claim| ExecuteMsg::AddClaim {
+            claim,
+            public_key: public_key.clone(),
+            user_addr: user_addr.clone(),
+            idempotency_key: None,
+        };
+
+        // Taken for a secp256k1 key, it isn't the issuer's
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &add_claim(Claim {
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    ..claim.clone()
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::IssuerKeyMismatch {})
+        ));
+
+        app.execute_contract(owner, contract_addr.clone(), &add_claim(claim), &[])
+            .unwrap();
+        let verified: bool = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::VerifyClaim {
+                    claim_id: WellKnownTopic::Kyc.into(),
+                    user_addr,
+                    namespace: None,
+                    trusted_issuers_registry: None,
+                },
+            )
+            .unwrap();
+        assert!(verified);
+    }
+
+    #[test]
+    fn full_snapshot() {
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let executor = app.api().addr_make("executor");
+        let owner_roles = app.api().addr_make("owner_roles");
+        let user_addr = MockApi::default().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner_addr.clone(),
+            InstantiateMsg {
+                owner: owner_addr.to_string(),
+                owner_roles_address: Some(owner_roles.to_string()),
+                initial_keys: vec![
+                    (owner_addr.clone(), KeyType::ClaimSignerKey),
+                    (executor.clone(), KeyType::ExecutionKey),
+                ],
+                ..Default::default()
+            },
+        );
+
+        // A lasting KYC claim and an AML claim about to expire
+        let expires_at = app.block_info().time.seconds() + 10;
+        for (topic, valid_until) in [
+            (WellKnownTopic::Kyc, None),
+            (WellKnownTopic::Aml, Some(Uint64::new(expires_at))),
+        ] {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap();
+        }
+        app.update_block(|block| block.time = block.time.plus_seconds(20));
+
+        let snapshot: FullSnapshotResponse = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::FullSnapshot {
+                    user_addr: user_addr.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(snapshot.owner, owner_addr);
+        assert_eq!(snapshot.admin, owner_addr);
+        assert_eq!(snapshot.claim_topics_registry, None);
+        assert_eq!(snapshot.owner_roles, Some(owner_roles));
+        assert!(snapshot.complete);
+
+        let stored_keys: Vec<Key> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKeys {
+                    key_owner: owner_addr.to_string(),
+                },
+            )
+            .unwrap();
+        let purposes = |holder: &Addr| {
+            snapshot
+                .keys
+                .iter()
+                .find(|key| key.owner == *holder)
+                .map(|key| key.purposes.clone())
+        };
+        assert_eq!(snapshot.keys.len(), 2);
+        assert_eq!(
+            purposes(&owner_addr),
+            Some(vec![KeyType::ManagementKey, KeyType::ClaimSignerKey])
+        );
+        assert_eq!(purposes(&executor), Some(vec![KeyType::ExecutionKey]));
+        assert!(stored_keys.iter().all(|key| snapshot.keys.contains(key)));
+
+        let stored_claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr,
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr,
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(snapshot.claims.len(), stored_claims.len());
+        for snapshot_claim in &snapshot.claims {
+            assert!(snapshot_claim.claim.id.is_some());
+            assert!(stored_claims.contains(&snapshot_claim.claim));
+            let expected_status =
+                if snapshot_claim.claim.topic == Uint128::from(WellKnownTopic::Kyc) {
+                    ClaimStatus::Valid
+                } else {
+                    ClaimStatus::Expired
+                };
+            assert_eq!(snapshot_claim.status, expected_status);
+        }
+    }
+
+    #[test]
+    fn claim_uri_and_data_bounds() {
+        use crate::state::{DEFAULT_MAX_CLAIM_DATA_BYTES, MAX_CLAIM_URI_BYTES};
+
+        let mut app = App::default();
+        let (owner_addr, owner_secret_key, owner_public_key) = create_wallet(&app);
+        let contract_for = |app: &mut App, max_claim_data_bytes: Option<u32>| {
+            instantiate_contract_with_msg(
+                app,
+                owner_addr.clone(),
+                InstantiateMsg {
+                    owner: owner_addr.to_string(),
+                    max_claim_data_bytes,
+                    initial_keys: vec![(owner_addr.clone(), KeyType::ClaimSignerKey)],
+                    ..Default::default()
+                },
+            )
+        };
+        // Each claim goes to a new user so none is a duplicate
+        let mut users = 0;
+        let mut add_claim = |app: &mut App, contract_addr: &Addr, uri: String, data_len: usize| {
+            users += 1;
+            let user_addr = MockApi::default().addr_make(&format!("user_{}", users));
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: WellKnownTopic::Kyc.into(),
+                    issuer: owner_addr.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![7; data_len]),
+                    data_hash: None,
+                    uri,
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_addr.clone()),
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                &owner_secret_key,
+            );
+            app.execute_contract(
+                owner_addr.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(owner_public_key.serialize()),
+                    user_addr,
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .map_err(|err| err.downcast::<ContractError>().unwrap())
+        };
+        let uri_of_len =
+            |scheme: &str, len: usize| format!("{}{}", scheme, "a".repeat(len - scheme.len()));
+
+        let contract_addr = contract_for(&mut app, None);
+        let max_data = DEFAULT_MAX_CLAIM_DATA_BYTES as usize;
+        for scheme in ["http://", "https://", "ipfs://"] {
+            add_claim(&mut app, &contract_addr, uri_of_len(scheme, 20), 1).unwrap();
+        }
+        add_claim(
+            &mut app,
+            &contract_addr,
+            uri_of_len("https://", MAX_CLAIM_URI_BYTES),
+            max_data,
+        )
+        .unwrap();
+        assert!(matches!(
+            add_claim(
+                &mut app,
+                &contract_addr,
+                uri_of_len("https://", MAX_CLAIM_URI_BYTES + 1),
+                1
+            ),
+            Err(ContractError::ClaimUriTooLong { size, max })
+                if size == MAX_CLAIM_URI_BYTES + 1 && max == MAX_CLAIM_URI_BYTES
+        ));
+        for uri in [
+            "ftp://example.com",
+            "example.com",
+            "HTTPS://example.com",
+            "",
+        ] {
+            assert!(matches!(
+                add_claim(&mut app, &contract_addr, uri.to_string(), 1),
+                Err(ContractError::UnsupportedClaimUriScheme { .. })
+            ));
+        }
+        assert!(matches!(
+            add_claim(
+                &mut app,
+                &contract_addr,
+                "https://example.com".to_string(),
+                max_data + 1
+            ),
+            Err(ContractError::ClaimDataTooLarge { size, max })
+                if size == max_data + 1 && max == DEFAULT_MAX_CLAIM_DATA_BYTES
+        ));
+
+        // The data limit is configurable
+        let contract_addr = contract_for(&mut app, Some(16));
+        add_claim(
+            &mut app,
+            &contract_addr,
+            "https://example.com".to_string(),
+            16,
+        )
+        .unwrap();
+        assert!(matches!(
+            add_claim(
+                &mut app,
+                &contract_addr,
+                "https://example.com".to_string(),
+                17
+            ),
+            Err(ContractError::ClaimDataTooLarge { size: 17, max: 16 })
+        ));
+    }
+
+    #[test]
+    fn execution_key_dispatches_messages() {
+        use cosmwasm_std::{BankMsg, CosmosMsg, WasmMsg};
+
+        let owner = Addr::unchecked(MockApi::default().addr_make("owner"));
+        let mut app = App::new(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &owner, cosmwasm_std::coins(1_000, "ukii"))
+                .unwrap();
+        });
+        let executor = app.api().addr_make("executor");
+        let stranger = app.api().addr_make("stranger");
+        let recipient = app.api().addr_make("recipient");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                max_execution_send: Some(Uint128::new(100)),
+                initial_keys: vec![(executor.clone(), KeyType::ExecutionKey)],
+                ..Default::default()
+            },
+        );
+        app.send_tokens(
+            owner.clone(),
+            contract_addr.clone(),
+            &cosmwasm_std::coins(1_000, "ukii"),
+        )
+        .unwrap();
+        let send = |amount: u128| ExecuteMsg::Execute {
+            msgs: vec![cosmwasm_std::BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: cosmwasm_std::coins(amount, "ukii"),
+            }
+            .into()],
+        };
+        let balance = |app: &App| app.wrap().query_balance(&recipient, "ukii").unwrap().amount;
+
+        // The identity forwards a send of its ExecutionKey holder
+        app.execute_contract(executor.clone(), contract_addr.clone(), &send(100), &[])
+            .unwrap();
+        assert_eq!(balance(&app), Uint128::new(100));
+
+        // Addresses without a key can't
+        let err = app
+            .execute_contract(stranger, contract_addr.clone(), &send(100), &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::Unauthorized { .. })
+        ));
+
+        // Sends above the limit need a ManagementKey, however they are split up,
+        // as do messages calling the identity itself or whose value can't be told
+        let funds = cosmwasm_std::coins(60, "ukii");
+        let bank_send: CosmosMsg = BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: funds.clone(),
+        }
+        .into();
+        let call = |contract_addr: &Addr, msg: &ExecuteMsg, funds| -> CosmosMsg {
+            WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_json_binary(msg).unwrap(),
+                funds,
+            }
+            .into()
+        };
+        let add_key = ExecuteMsg::AddKey {
+            key_owner: executor.to_string(),
+            key_type: "ManagementKey".to_string(),
+            idempotency_key: None,
+        };
+        for msgs in [
+            send(101),
+            ExecuteMsg::Execute {
+                msgs: vec![bank_send.clone(), bank_send.clone()],
+            },
+            ExecuteMsg::Execute {
+                msgs: vec![bank_send, call(&recipient, &add_key, funds)],
+            },
+            ExecuteMsg::Execute {
+                msgs: vec![call(&contract_addr, &add_key, vec![])],
+            },
+            ExecuteMsg::Execute {
+                msgs: vec![WasmMsg::ClearAdmin {
+                    contract_addr: recipient.to_string(),
+                }
+                .into()],
+            },
+        ] {
+            let err = app
+                .execute_contract(executor.clone(), contract_addr.clone(), &msgs, &[])
+                .unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ContractError>(),
+                Some(ContractError::Unauthorized { .. })
+            ));
+        }
+        app.execute_contract(owner, contract_addr, &send(101), &[])
+            .unwrap();
+        assert_eq!(balance(&app), Uint128::new(201));
+    }
+
+    #[test]
+    fn relayer_adds_claim_with_issuer_permit() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let relayer = app.api().addr_make("relayer");
+        let user_addr = app.api().addr_make("user_addr");
+        let (issuer, issuer_secret_key, issuer_public_key) = create_wallet(&app);
+        let (_, other_secret_key, _) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(issuer.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        let claim = sign_claim(
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: issuer.clone(),
+                signature: Binary::from(vec![]),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            },
+            &issuer_secret_key,
+        );
+        let permit_for = |user_addr: &Addr, secret_key: &SecretKey| {
+            let hash = claim_permit_message_hash(
+                &contract_addr,
+                &claim,
+                user_addr,
+                &SignatureEncoding::Raw,
+            );
+            let signature =
+                Secp256k1::new().sign_ecdsa(&Message::from_slice(&hash).unwrap(), secret_key);
+            Binary::from(signature.serialize_compact())
+        };
+        let add_with_permit = |app: &mut App, issuer_permit: Binary| {
+            app.execute_contract(
+                relayer.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaimWithPermit {
+                    claim: claim.clone(),
+                    public_key: Binary::from(issuer_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    issuer_permit,
+                },
+                &[],
+            )
+        };
+
+        // Permits signed by someone else, or for another user, are rejected
+        for permit in [
+            permit_for(&user_addr, &other_secret_key),
+            permit_for(&relayer, &issuer_secret_key),
+        ] {
+            let err = add_with_permit(&mut app, permit).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ContractError>(),
+                Some(ContractError::InvalidIssuerSignature { .. })
+            ));
+        }
+
+        // The relayer holds no key, the issuer's permit authorizes the claim
+        add_with_permit(&mut app, permit_for(&user_addr, &issuer_secret_key)).unwrap();
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].issuer, issuer);
+
+        // The permit can't be replayed once the claim is removed
+        app.execute_contract(
+            issuer,
+            contract_addr.clone(),
+            &ExecuteMsg::RemoveClaimById {
+                claim_id: claims[0].id.clone().unwrap(),
+                user_addr: user_addr.clone(),
+            },
+            &[],
+        )
+        .unwrap();
+        let err =
+            add_with_permit(&mut app, permit_for(&user_addr, &issuer_secret_key)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::PermitAlreadyUsed {})
+        ));
+    }
+
+    #[test]
+    fn claim_subject_and_nonce_prevent_replays() {
+        let mut app = App::default();
+        let (issuer, issuer_secret_key, issuer_public_key) = create_wallet(&app);
+        let user_a = app.api().addr_make("user_a");
+        let user_b = app.api().addr_make("user_b");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            issuer.clone(),
+            InstantiateMsg {
+                owner: issuer.to_string(),
+                initial_keys: vec![(issuer.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        let claim_with_nonce = |nonce: u64| {
+            sign_claim(
+                Claim {
+                    id: None,
+                    // A topic per claim, as the issuer may only attest to a topic once
+                    topic: Uint128::from(100 + nonce),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: Some(user_a.clone()),
+                    nonce: Some(Uint64::new(nonce)),
+                    signed_expiry: None,
+                },
+                &issuer_secret_key,
+            )
+        };
+        let add_claim = |app: &mut App, claim: Claim, user_addr: &Addr| {
+            app.execute_contract(
+                issuer.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(issuer_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+        };
+
+        // A claim issued for user A can't be added to user B, as is or re-targeted
+        let claim = claim_with_nonce(1);
+        let err = add_claim(&mut app, claim.clone(), &user_b).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::ClaimSubjectMismatch { .. })
+        ));
+        let retargeted = Claim {
+            subject: Some(user_b.clone()),
+            ..claim.clone()
+        };
+        let err = add_claim(&mut app, retargeted, &user_b).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::InvalidSignature { .. })
+        ));
+
+        add_claim(&mut app, claim.clone(), &user_a).unwrap();
+
+        // Resubmitting the claim, or any claim with a nonce that isn't newer, is rejected
+        let err = add_claim(&mut app, claim, &user_a).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::StaleClaimNonce {
+                nonce: 1,
+                last_nonce: 1
+            })
+        ));
+        add_claim(&mut app, claim_with_nonce(3), &user_a).unwrap();
+        let err = add_claim(&mut app, claim_with_nonce(2), &user_a).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::StaleClaimNonce {
+                nonce: 2,
+                last_nonce: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn claims_must_set_subject_and_nonce() {
+        let mut app = App::default();
+        let (issuer, issuer_secret_key, issuer_public_key) = create_wallet(&app);
+        let user_addr = app.api().addr_make("user_addr");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            issuer.clone(),
+            InstantiateMsg {
+                owner: issuer.to_string(),
+                initial_keys: vec![(issuer.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
+            issuer: issuer.clone(),
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: Some(user_addr.clone()),
+            nonce: Some(Uint64::new(7)),
+            signed_expiry: None,
+        };
+        let add_claim = |app: &mut App, claim: Claim| {
+            app.execute_contract(
+                issuer.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(issuer_public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+        };
+
+        // Moving the subject and nonce into the URI changes what was signed
+        let shifted = Claim {
+            uri: format!("{}subject{}nonce7", claim.uri, user_addr),
+            subject: None,
+            nonce: None,
+            ..claim.clone()
+        };
+        assert_ne!(
+            hash_claim_without_signature(&claim),
+            hash_claim_without_signature(&shifted)
+        );
+
+        // and claims missing either are rejected, however they were signed
+        for (field, unprotected) in [
+            (
+                "subject",
+                Claim {
+                    subject: None,
+                    ..claim.clone()
+                },
+            ),
+            (
+                "nonce",
+                Claim {
+                    nonce: None,
+                    ..claim.clone()
+                },
+            ),
+        ] {
+            let err =
+                add_claim(&mut app, sign_claim_as_is(unprotected, &issuer_secret_key)).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ContractError>(),
+                Some(ContractError::MissingReplayProtection { field: missing }) if missing == field
+            ));
+        }
+        add_claim(&mut app, sign_claim_as_is(claim, &issuer_secret_key)).unwrap();
+    }
+
+    #[test]
+    fn claim_signature_delimits_fields() {
+        let issuer = MockApi::default().addr_make("issuer");
+        let claim = Claim {
+            id: None,
+            topic: WellKnownTopic::Kyc.into(),
+            issuer,
+            signature: Binary::from(vec![]),
+            data: Binary::from(vec![1, 2, 3]),
+            data_hash: None,
+            uri: "https://example.com".to_string(),
+            valid_until: None,
+            issued_at: None,
+            encryption: None,
+            related_identity: None,
+            namespace: None,
+            data_root: None,
+            signature_scheme: SignatureScheme::Secp256k1,
+            subject: Some(MockApi::default().addr_make("user_addr")),
+            nonce: Some(Uint64::new(1)),
+            signed_expiry: None,
+        };
+        // Each field set on a claim, against the same bytes carried at the end of its URI
+        let uri_with = |suffix: &str| format!("{}{}", claim.uri, suffix);
+        let related_identity = MockApi::default().addr_make("subsidiary");
+        let shifted = [
+            (
+                Claim {
+                    valid_until: Some(Uint64::new(1_700_000_000)),
+                    ..claim.clone()
+                },
+                Claim {
+                    uri: uri_with("1700000000"),
+                    ..claim.clone()
+                },
+            ),
+            (
+                Claim {
+                    namespace: Some("eu".to_string()),
+                    ..claim.clone()
+                },
+                Claim {
+                    uri: uri_with("eu"),
+                    ..claim.clone()
+                },
+            ),
+            (
+                Claim {
+                    related_identity: Some(related_identity.clone()),
+                    ..claim.clone()
+                },
+                Claim {
+                    uri: uri_with(related_identity.as_str()),
+                    ..claim.clone()
+                },
+            ),
+        ];
+        for (set, moved_into_uri) in shifted {
+            assert_ne!(
+                hash_claim_without_signature(&set),
+                hash_claim_without_signature(&moved_into_uri)
+            );
+        }
+    }
+
+    #[test]
+    fn topic_claim_limit() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let user_addr = app.api().addr_make("user_addr");
+        let issuers: Vec<_> = (0..3).map(|_| create_wallet(&app)).collect();
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                max_claims_per_topic: Some(2),
+                initial_keys: vec![(owner.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        let add_claim = |app: &mut App, issuer: usize, topic: WellKnownTopic| {
+            let (issuer, secret_key, public_key) = &issuers[issuer];
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                secret_key,
+            );
+            app.execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+        };
+
+        // The third KYC claim is over the identity-wide limit, other topics are unaffected
+        add_claim(&mut app, 0, WellKnownTopic::Kyc).unwrap();
+        add_claim(&mut app, 1, WellKnownTopic::Kyc).unwrap();
+        let err = add_claim(&mut app, 2, WellKnownTopic::Kyc).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::TopicClaimLimitExceeded { topic, max: 2 })
+                if *topic == Uint128::from(WellKnownTopic::Kyc)
+        ));
+        add_claim(&mut app, 2, WellKnownTopic::Aml).unwrap();
+
+        // Only the admin may set a topic's own limit
+        let msg = ExecuteMsg::SetTopicClaimLimit {
+            topic: WellKnownTopic::Kyc.into(),
+            max_claims: Some(3),
+        };
+        app.execute_contract(user_addr.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap_err();
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        add_claim(&mut app, 2, WellKnownTopic::Kyc).unwrap();
+
+        let limit_of = |topic: WellKnownTopic| -> u32 {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetTopicClaimLimit {
+                        topic: topic.into(),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(limit_of(WellKnownTopic::Kyc), 3);
+        assert_eq!(limit_of(WellKnownTopic::Aml), 2);
+    }
+
+    #[test]
+    fn query_claims_by_issuer_and_topic() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let user_addr = app.api().addr_make("user_addr");
+        let (issuer_a, secret_key_a, public_key_a) = create_wallet(&app);
+        let (issuer_b, secret_key_b, public_key_b) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(owner.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+
+        let mut add_claim = |issuer: &Addr,
+                             secret_key: &SecretKey,
+                             public_key: &PublicKey,
+                             topic: WellKnownTopic| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                secret_key,
+            );
+            app.execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap();
+        };
+        add_claim(&issuer_a, &secret_key_a, &public_key_a, WellKnownTopic::Kyc);
+        add_claim(&issuer_a, &secret_key_a, &public_key_a, WellKnownTopic::Aml);
+        add_claim(&issuer_b, &secret_key_b, &public_key_b, WellKnownTopic::Kyc);
+
+        let claims: Vec<Claim> = app
+            .wrap()
+            .query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetValidatedClaimsForUser {
+                    user_addr: user_addr.clone(),
+                    exclude_expired: None,
+                },
+            )
+            .unwrap();
+        let id_of = |issuer: &Addr, topic: WellKnownTopic| {
+            let topic = Uint128::from(topic);
+            claims
+                .iter()
+                .find(|claim| claim.issuer == *issuer && claim.topic == topic)
+                .and_then(|claim| claim.id.clone())
+                .unwrap()
+        };
+
+        let by_issuer = |issuer: &Addr| -> Vec<Uint128> {
+            let claims: Vec<Claim> = app
+                .wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetClaimsByIssuer {
+                        user_addr: user_addr.clone(),
+                        issuer: issuer.clone(),
+                    },
+                )
+                .unwrap();
+            claims.iter().map(|claim| claim.topic).collect()
+        };
+        assert_eq!(
+            by_issuer(&issuer_a),
+            vec![
+                Uint128::from(WellKnownTopic::Kyc),
+                Uint128::from(WellKnownTopic::Aml)
+            ]
+        );
+        assert_eq!(
+            by_issuer(&issuer_b),
+            vec![Uint128::from(WellKnownTopic::Kyc)]
+        );
+        assert!(by_issuer(&owner).is_empty());
+
+        let ids_by_topic = |topic: WellKnownTopic| -> Vec<String> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetClaimIdsByTopic {
+                        user_addr: user_addr.clone(),
+                        topic: topic.into(),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(
+            ids_by_topic(WellKnownTopic::Kyc),
+            vec![
+                id_of(&issuer_a, WellKnownTopic::Kyc),
+                id_of(&issuer_b, WellKnownTopic::Kyc)
+            ]
+        );
+        assert_eq!(
+            ids_by_topic(WellKnownTopic::Aml),
+            vec![id_of(&issuer_a, WellKnownTopic::Aml)]
+        );
+        assert!(ids_by_topic(WellKnownTopic::Accreditation).is_empty());
+    }
+
+    #[test]
+    fn get_key_resolves_by_holder() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let other = app.api().addr_make("other");
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![
+                    (owner.clone(), KeyType::ClaimSignerKey),
+                    (other.clone(), KeyType::ExecutionKey),
+                ],
+                ..Default::default()
+            },
+        );
+        let get_key = |key_owner: &Addr, key_type: &str| -> StdResult<Key> {
+            app.wrap().query_wasm_smart(
+                contract_addr.clone(),
+                &QueryMsg::GetKey {
+                    key_owner: key_owner.to_string(),
+                    key_type: key_type.to_string(),
+                },
+            )
+        };
+
+        // The owner's own key carries all of its purposes
+        for key_type in ["ManagementKey", "ClaimSignerKey"] {
+            let key = get_key(&owner, key_type).unwrap();
+            assert_eq!(key.owner, owner);
+            assert_eq!(
+                key.purposes,
+                vec![KeyType::ManagementKey, KeyType::ClaimSignerKey]
+            );
+        }
+        // Keys the owner granted to others are found by their holder only
+        assert_eq!(get_key(&other, "ExecutionKey").unwrap().owner, other);
+        get_key(&owner, "ExecutionKey").unwrap_err();
+        get_key(&other, "ManagementKey").unwrap_err();
+    }
+
+    // Stands in for a trusted issuers registry trusting the instantiated issuers for every topic
+    fn trusted_issuers_contract() -> Box<dyn Contract<Empty>> {
+        const TRUSTED: cw_storage_plus::Item<Vec<Addr>> = cw_storage_plus::Item::new("trusted");
+        fn instantiate(
+            deps: DepsMut,
+            _: Env,
+            _: MessageInfo,
+            trusted: Vec<Addr>,
+        ) -> StdResult<Response> {
+            TRUSTED.save(deps.storage, &trusted)?;
+            Ok(Response::new())
+        }
+        fn noop(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+        fn query(deps: Deps, _: Env, msg: TrustedIssuersQueryMsg) -> StdResult<Binary> {
+            match msg {
+                TrustedIssuersQueryMsg::GetIssuersRankedForTopic { .. } => {
+                    to_json_binary(&TRUSTED.load(deps.storage)?)
+                }
+                _ => Err(StdError::generic_err("unsupported")),
+            }
+        }
+        Box::new(ContractWrapper::new(noop, instantiate, query))
+    }
+
+    #[test]
+    fn verify_claim_against_trusted_issuers_registry() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let user_addr = app.api().addr_make("user_addr");
+        let (trusted_issuer, trusted_secret_key, trusted_public_key) = create_wallet(&app);
+        let (other_issuer, other_secret_key, other_public_key) = create_wallet(&app);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(owner.clone(), KeyType::ClaimSignerKey)],
+                ..Default::default()
+            },
+        );
+        let registry_code = app.store_code(trusted_issuers_contract());
+        let registry = app
+            .instantiate_contract(
+                registry_code,
+                owner.clone(),
+                &vec![trusted_issuer.clone()],
+                &[],
+                "Trusted Issuers",
+                None,
+            )
+            .unwrap();
+
+        let mut add_claim = |issuer: &Addr,
+                             secret_key: &SecretKey,
+                             public_key: &PublicKey,
+                             topic: WellKnownTopic| {
+            let claim = sign_claim(
+                Claim {
+                    id: None,
+                    topic: topic.into(),
+                    issuer: issuer.clone(),
+                    signature: Binary::from(vec![]),
+                    data: Binary::from(vec![1, 2, 3]),
+                    data_hash: None,
+                    uri: "https://example.com".to_string(),
+                    valid_until: None,
+                    issued_at: None,
+                    encryption: None,
+                    related_identity: None,
+                    namespace: None,
+                    data_root: None,
+                    signature_scheme: SignatureScheme::Secp256k1,
+                    subject: None,
+                    nonce: None,
+                    signed_expiry: None,
+                },
+                secret_key,
+            );
+            app.execute_contract(
+                owner.clone(),
+                contract_addr.clone(),
+                &ExecuteMsg::AddClaim {
+                    claim,
+                    public_key: Binary::from(public_key.serialize()),
+                    user_addr: user_addr.clone(),
+                    idempotency_key: None,
+                },
+                &[],
+            )
+            .unwrap();
+        };
+        add_claim(
+            &trusted_issuer,
+            &trusted_secret_key,
+            &trusted_public_key,
+            WellKnownTopic::Aml,
+        );
+        add_claim(
+            &other_issuer,
+            &other_secret_key,
+            &other_public_key,
+            WellKnownTopic::Kyc,
+        );
+
+        let verify = |topic: WellKnownTopic, registry: Option<&Addr>| {
+            app.wrap().query_wasm_smart::<bool>(
+                contract_addr.clone(),
+                &QueryMsg::VerifyClaim {
+                    claim_id: topic.into(),
+                    user_addr: user_addr.clone(),
+                    namespace: None,
+                    trusted_issuers_registry: registry.map(Addr::to_string),
+                },
+            )
+        };
+
+        // Without a registry any issuer is accepted
+        assert!(verify(WellKnownTopic::Kyc, None).unwrap());
+        // With one, only claims from issuers it trusts count
+        assert!(verify(WellKnownTopic::Aml, Some(&registry)).unwrap());
+        assert!(!verify(WellKnownTopic::Kyc, Some(&registry)).unwrap());
+
+        // A registry that can't be queried is an error rather than a negative verdict
+        assert!(verify(WellKnownTopic::Aml, Some(&owner)).is_err());
+    }
+
+    #[test]
+    fn self_attestation_rejected_on_configured_topics() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let (user_addr, user_secret_key, user_public_key) = create_wallet(&app);
+        let (issuer, issuer_secret_key, issuer_public_key) = create_wallet(&app);
+        let sanctions_clearance = Uint128::new(100);
+        let contract_addr = instantiate_contract_with_msg(
+            &mut app,
+            owner.clone(),
+            InstantiateMsg {
+                owner: owner.to_string(),
+                initial_keys: vec![(owner.clone(), KeyType::ClaimSignerKey)],
+                no_self_attest_topics: vec![sanctions_clearance],
+                ..Default::default()
+            },
+        );
+
+        let mut add_claim =
+            |issuer: &Addr, secret_key: &SecretKey, public_key: &PublicKey, topic: Uint128| {
+                let claim = sign_claim(
+                    Claim {
+                        id: None,
+                        topic,
+                        issuer: issuer.clone(),
+                        signature: Binary::from(vec![]),
+                        data: Binary::from(vec![1, 2, 3]),
+                        data_hash: None,
+                        uri: "https://example.com".to_string(),
+                        valid_until: None,
+                        issued_at: None,
+                        encryption: None,
+                        related_identity: None,
+                        namespace: None,
+                        data_root: None,
+                        signature_scheme: SignatureScheme::Secp256k1,
+                        subject: Some(user_addr.clone()),
+                        nonce: None,
+                        signed_expiry: None,
+                    },
+                    secret_key,
+                );
+                app.execute_contract(
+                    owner.clone(),
+                    contract_addr.clone(),
+                    &ExecuteMsg::AddClaim {
+                        claim,
+                        public_key: Binary::from(public_key.serialize()),
+                        user_addr: user_addr.clone(),
+                        idempotency_key: None,
+                    },
+                    &[],
+                )
+            };
+
+        // The user can't clear themselves for sanctions
+        let err = add_claim(
+            &user_addr,
+            &user_secret_key,
+            &user_public_key,
+            sanctions_clearance,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ContractError>(),
+            Some(ContractError::SelfAttestationNotAllowed { topic }) if *topic == sanctions_clearance
+        ));
+
+        // Other topics may still be self-attested, and a third party may clear the user
+        add_claim(
+            &user_addr,
+            &user_secret_key,
+            &user_public_key,
+            WellKnownTopic::Kyc.into(),
+        )
+        .unwrap();
+        add_claim(
+            &issuer,
+            &issuer_secret_key,
+            &issuer_public_key,
+            sanctions_clearance,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn records_stored_before_newer_fields_load_with_defaults() {
+        let issuer = MockApi::default().addr_make("issuer");
+        let claim: Claim = from_json(format!(
+            r#"{{"topic":"1","issuer":"{issuer}","signature":"","data":"AQID","uri":"https://example.com"}}"#
+        ))
+        .unwrap();
+        assert_eq!(
+            claim,
+            Claim {
+                id: None,
+                topic: WellKnownTopic::Kyc.into(),
+                issuer: issuer.clone(),
+                signature: Binary::default(),
+                data: Binary::from(vec![1, 2, 3]),
+                data_hash: None,
+                uri: "https://example.com".to_string(),
+                valid_until: None,
+                issued_at: None,
+                encryption: None,
+                related_identity: None,
+                namespace: None,
+                data_root: None,
+                signature_scheme: SignatureScheme::Secp256k1,
+                subject: None,
+                nonce: None,
+                signed_expiry: None,
+            }
+        );
+
+        let key: Key = from_json(format!(
+            r#"{{"owner":"{issuer}","purposes":["ManagementKey"]}}"#
+        ))
+        .unwrap();
+        assert_eq!(
+            key,
+            Key {
+                owner: issuer,
+                purposes: vec![KeyType::ManagementKey],
+                last_used: None,
+                weight: 1,
+                active: true,
+            }
+        );
     }
 }
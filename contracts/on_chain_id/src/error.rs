@@ -1,4 +1,4 @@
-use cosmwasm_std::{StdError, Uint128};
+use cosmwasm_std::{StdError, Uint128, Uint64};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,21 +9,86 @@ pub enum ContractError {
     #[error("Unauthorized: {reason}")]
     Unauthorized { reason: String },
 
+    #[error("{query} query to {contract} failed: {reason}")]
+    CrossContractQueryFailed {
+        contract: String,
+        query: String,
+        reason: String,
+    },
+
     #[error("Invalid address: {reason}")]
     InvalidAddress { reason: String },
 
     #[error("Invalid key type: {key_type}")]
     InvalidKeyType { key_type: String },
 
+    #[error("Not a well-known claim topic: {topic}")]
+    UnknownClaimTopic { topic: Uint128 },
+
     #[error("Invalid key purpose")]
     InvalidKeyPurpose {},
 
     #[error("Invalid claim topic: {topic}")]
     InvalidClaimTopic { topic: String },
 
+    #[error("Issuer {issuer} is not allowed for claim topic {claim_topic}")]
+    IssuerNotAllowed {
+        issuer: String,
+        claim_topic: Uint128,
+    },
+
+    #[error("{holder} must hold a {key_type} to receive claims of topic {claim_topic}")]
+    MissingHolderPurpose {
+        holder: String,
+        key_type: String,
+        claim_topic: Uint128,
+    },
+
     #[error("Invalid issuer signature: {reason}")]
     InvalidIssuerSignature { reason: String },
 
+    #[error("Public key does not belong to the claim issuer")]
+    IssuerKeyMismatch {},
+
+    #[error("Claim permit has already been used")]
+    PermitAlreadyUsed {},
+
+    #[error("Claims can't be extended beyond {max_valid_until}")]
+    ExpiryBeyondMax { max_valid_until: Uint64 },
+
+    #[error("Invalid revocation: {reason}")]
+    InvalidRevocation { reason: String },
+
+    #[error("Invalid claim data hash: {reason}")]
+    InvalidDataHash { reason: String },
+
+    #[error("Invalid claim data root: {reason}")]
+    InvalidDataRoot { reason: String },
+
+    #[error("Claim URI is {size} bytes, over the limit of {max}")]
+    ClaimUriTooLong { size: usize, max: usize },
+
+    #[error("Claim URI must start with one of {schemes}")]
+    UnsupportedClaimUriScheme { schemes: String },
+
+    #[error("Claim data is {size} bytes, over the limit of {max}")]
+    ClaimDataTooLarge { size: usize, max: u32 },
+
+    #[error("Claim was issued for {subject}, not {user_addr}")]
+    ClaimSubjectMismatch { subject: String, user_addr: String },
+
+    #[error("Claim must set its {field}, so it can't be replayed")]
+    MissingReplayProtection { field: String },
+
+    #[error("Claim nonce {nonce} is not above the issuer's last nonce {last_nonce}")]
+    StaleClaimNonce { nonce: u64, last_nonce: u64 },
+
+    #[error("A user may hold at most {max} claims of topic {topic}")]
+    TopicClaimLimitExceeded { topic: Uint128, max: u32 },
+
+    #[error("Claims of topic {topic} can't be self-attested")]
+    SelfAttestationNotAllowed { topic: Uint128 },
+
     #[error("Error while serializing data: {reason}")]
     SerializationError { reason: String },
 
@@ -36,15 +101,36 @@ pub enum ContractError {
     #[error("No keys found for the given owner")]
     NoKeysFound {},
 
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
     #[error("Key already exists for type {key_type}")]
     KeyAlreadyExists { key_type: String },
 
+    #[error("Invalid management weight: {reason}")]
+    InvalidManagementWeight { reason: String },
+
+    #[error("Identity already holds the maximum of {max} keys")]
+    TotalKeysExceeded { max: u32 },
+
+    #[error("Identity already holds the maximum of {max} claims")]
+    TotalClaimsExceeded { max: u32 },
+
     #[error("Invalid claim ID: {claim_id}")]
     InvalidClaimId { claim_id: String },
 
     #[error("Claim not found with ID: {claim_topic}")]
     ClaimNotFound { claim_topic: Uint128 },
 
+    #[error("Claim not found with ID: {claim_id}")]
+    ClaimIdNotFound { claim_id: String },
+
+    #[error("Claim topic {claim_topic} is deprecated")]
+    ClaimTopicDeprecated { claim_topic: Uint128 },
+
+    #[error("Invalid claim batch: {reason}")]
+    InvalidClaimBatch { reason: String },
+
     #[error("Claim already exists with ID: {claim_topic}")]
     ClaimAlreadyExists { claim_topic: Uint128 },
 
@@ -60,6 +146,12 @@ pub enum ContractError {
     #[error("Invalid signature: {reason}")]
     InvalidSignature { reason: String },
 
+    #[error("Idempotency key {idempotency_key} was already used with a different message")]
+    IdempotencyKeyMismatch { idempotency_key: String },
+
+    #[error("Migration to {version} was not approved by the admin")]
+    MigrationNotApproved { version: String },
+
     #[error("Invalid contract version: {expected} != {actual}")]
     InvalidContract { expected: String, actual: String },
 
@@ -1,21 +1,206 @@
 use crate::error::ContractError;
-use crate::state::{Key, KeyType, KEYS, OWNER};
-use crate::utils::check_key_authorization;
-use cosmwasm_std::{DepsMut, MessageInfo, Response};
+use crate::msg::ExecuteMsg;
+use crate::state::{
+    Config, Key, KeyType, ADMIN, CONFIG, KEYS, MANAGEMENT_APPROVALS, MIGRATION_APPROVAL, OWNER,
+    PENDING_OWNER,
+};
+use crate::utils::{
+    check_admin, check_key_authorization, ensure_management_weight_remains,
+    ensure_total_keys_within, grant_key_purpose, management_weight,
+};
+use cosmwasm_std::{
+    to_json_vec, Addr, BankMsg, Binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128,
+    WasmMsg,
+};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
+/// Record `sender`'s approval of a key management operation. Returns the response to send
+/// back while the approvals don't yet meet the management threshold, and `None` when the
+/// operation should run, including when it doesn't need approvals or the sender holds no
+/// ManagementKey (the operation then rejects the sender itself).
+pub fn collect_management_approval(
+    deps: &mut DepsMut,
+    env: &Env,
+    sender: &Addr,
+    msg: &ExecuteMsg,
+) -> Result<Option<Response>, ContractError> {
+    let needs_approval = match msg {
+        ExecuteMsg::AddKey { .. }
+        | ExecuteMsg::RevokeKey { .. }
+        | ExecuteMsg::ReassignKeys { .. }
+        | ExecuteMsg::SetKeyWeight { .. }
+        | ExecuteMsg::SetKeyActive { .. }
+        | ExecuteMsg::SetManagementThreshold { .. }
+        | ExecuteMsg::ProposeNewOwner { .. } => true,
+        ExecuteMsg::Execute { msgs } => is_management_execution(deps, env, msgs)?,
+        _ => false,
+    };
+    if !needs_approval {
+        return Ok(None);
+    }
+
+    let threshold = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .management_threshold
+        .max(1);
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    let sender_weight = management_weight(&keys, Some(sender));
+    if sender_weight == 0 || sender_weight >= threshold {
+        return Ok(None);
+    }
+
+    let serialized = to_json_vec(msg).map_err(|e| ContractError::SerializationError {
+        reason: e.to_string(),
+    })?;
+    let operation = Sha256::digest(serialized);
+    let mut approvers = MANAGEMENT_APPROVALS
+        .may_load(deps.storage, &operation)
+        .map_err(|e| ContractError::LoadError {
+            entity: "management approvals".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    // Approvals of keys revoked since no longer count
+    approvers.retain(|approver| management_weight(&keys, Some(approver)) > 0);
+    if !approvers.contains(sender) {
+        approvers.push(sender.clone());
+    }
+    let approved_weight: u32 = approvers
+        .iter()
+        .map(|approver| management_weight(&keys, Some(approver)))
+        .sum();
+    if approved_weight >= threshold {
+        MANAGEMENT_APPROVALS.remove(deps.storage, &operation);
+        return Ok(None);
+    }
+
+    check_key_authorization(deps, env, sender, KeyType::ManagementKey)?;
+    MANAGEMENT_APPROVALS
+        .save(deps.storage, &operation, &approvers)
+        .map_err(|e| ContractError::SaveError {
+            entity: "management approvals".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Some(
+        Response::new()
+            .add_attribute("action", "approve_management_operation")
+            .add_attribute("operation", Binary::from(operation.as_slice()).to_base64())
+            .add_attribute("approved_weight", approved_weight.to_string())
+            .add_attribute("threshold", threshold.to_string()),
+    ))
+}
+
+/// Whether dispatching `msgs` needs a ManagementKey: when any of them calls the identity
+/// itself, or, under a `Config::max_execution_send`, when together they move more of a
+/// denom than it, counting bank sends and burns and the funds of contract calls.
+/// Messages whose value can't be told, like staking or distribution ones, always do then.
+fn is_management_execution(
+    deps: &DepsMut,
+    env: &Env,
+    msgs: &[CosmosMsg],
+) -> Result<bool, ContractError> {
+    let calls_itself = msgs.iter().any(|msg| match msg {
+        CosmosMsg::Wasm(
+            WasmMsg::Execute { contract_addr, .. }
+            | WasmMsg::Migrate { contract_addr, .. }
+            | WasmMsg::UpdateAdmin { contract_addr, .. }
+            | WasmMsg::ClearAdmin { contract_addr },
+        ) => *contract_addr == env.contract.address.as_str(),
+        _ => false,
+    });
+    if calls_itself {
+        return Ok(true);
+    }
+
+    let Some(max_send) = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .max_execution_send
+    else {
+        return Ok(false);
+    };
+    let mut sent: BTreeMap<&str, Uint128> = BTreeMap::new();
+    for msg in msgs {
+        let funds = match msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. } | BankMsg::Burn { amount }) => amount,
+            CosmosMsg::Wasm(
+                WasmMsg::Execute { funds, .. } | WasmMsg::Instantiate { funds, .. },
+            ) => funds,
+            _ => return Ok(true),
+        };
+        for coin in funds {
+            let total = sent.entry(&coin.denom).or_default();
+            *total = total.saturating_add(coin.amount);
+            if *total > max_send {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Dispatch `msgs` on behalf of the identity, as ERC-734's `execute` for ExecutionKeys.
+/// Management-level messages have already collected their approvals.
+pub fn execute_dispatch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msgs: Vec<CosmosMsg>,
+) -> Result<Response, ContractError> {
+    let management = is_management_execution(&deps, &env, &msgs)?;
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).or_else(
+        |e| {
+            if management {
+                return Err(e);
+            }
+            check_key_authorization(&mut deps, &env, &info.sender, KeyType::ExecutionKey)
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute")
+        .add_attribute("executor", info.sender)
+        .add_attribute("messages", msgs.len().to_string())
+        .add_messages(msgs))
+}
+
 pub fn execute_add_key(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     key_owner: String,
     key_type: String,
 ) -> Result<Response, ContractError> {
     // Check if the sender is authorized to add keys
-    check_key_authorization(&deps, &info.sender, KeyType::ManagementKey).map_err(|e| {
-        ContractError::Unauthorized {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
             reason: format!("Sender lacks ManagementKey: {}", e),
-        }
-    })?;
+        },
+    )?;
 
     let addr_key_owner =
         deps.api
@@ -28,11 +213,6 @@ pub fn execute_add_key(
         key_type: key_type.clone(),
     })?;
 
-    let new_key = Key {
-        key_type: key_type.clone(),
-        owner: addr_key_owner.clone(),
-    };
-
     let owner = OWNER
         .load(deps.storage)
         .map_err(|e| ContractError::LoadError {
@@ -49,15 +229,17 @@ pub fn execute_add_key(
         })?
         .unwrap_or_default();
 
-    // Check if the key already exists
-    if keys.iter().any(|k| k.key_type == key_type) {
-        return Err(ContractError::KeyAlreadyExists {
-            key_type: key_type.to_string(),
-        });
-    }
-
-    // Add the new key
-    keys.push(new_key);
+    // Add the purpose to the owner's key, within the identity's key cap
+    grant_key_purpose(&mut keys, &addr_key_owner, key_type.clone())?;
+    let max_total_keys = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .max_total_keys;
+    ensure_total_keys_within(&keys, max_total_keys)?;
 
     // Save the updated keys
     KEYS.save(deps.storage, &owner, &keys)
@@ -73,17 +255,18 @@ pub fn execute_add_key(
 }
 
 pub fn execute_remove_key(
-    deps: DepsMut,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     key_owner: String,
     key_type: String,
 ) -> Result<Response, ContractError> {
     // Check if the sender is authorized to remove keys
-    check_key_authorization(&deps, &info.sender, KeyType::ManagementKey).map_err(|e| {
-        ContractError::Unauthorized {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
             reason: format!("Sender lacks ManagementKey: {}", e),
-        }
-    })?;
+        },
+    )?;
 
     let addr_key_owner =
         deps.api
@@ -118,27 +301,526 @@ pub fn execute_remove_key(
             reason: e.to_string(),
         })?;
 
-    // Find and remove the key
-    if let Some(index) = keys
+    // Take the purpose off the key, removing the key once it holds none
+    let Some(index) = keys
         .iter()
-        .position(|k| k.key_type == key_type && k.owner == addr_key_owner)
-    {
-        keys.remove(index);
-        // Save the updated keys
-        KEYS.save(deps.storage, &owner, &keys)
-            .map_err(|e| ContractError::SaveError {
-                entity: "keys".to_string(),
-                reason: e.to_string(),
-            })?;
-    } else {
+        .position(|k| k.owner == addr_key_owner && k.has_purpose(&key_type))
+    else {
         return Err(ContractError::KeyNotFound {
             key_type: key_type.to_string(),
             owner: addr_key_owner.to_string(),
         });
+    };
+    keys[index].purposes.retain(|purpose| *purpose != key_type);
+    if keys[index].purposes.is_empty() {
+        keys.remove(index);
     }
+    ensure_management_weight_remains(&keys, management_threshold(&deps)?)?;
+
+    // Save the updated keys
+    KEYS.save(deps.storage, &owner, &keys)
+        .map_err(|e| ContractError::SaveError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
 
     Ok(Response::new()
         .add_attribute("action", "remove_key")
         .add_attribute("key_owner", addr_key_owner)
         .add_attribute("key_type", key_type.to_string()))
 }
+
+pub fn execute_reassign_keys(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from_owner: String,
+    to_owner: String,
+) -> Result<Response, ContractError> {
+    // Check if the sender is authorized to manage keys
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks ManagementKey: {}", e),
+        },
+    )?;
+
+    let addr_from_owner =
+        deps.api
+            .addr_validate(&from_owner)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid key owner address '{}': {}", from_owner, e),
+            })?;
+    let addr_to_owner =
+        deps.api
+            .addr_validate(&to_owner)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid key owner address '{}': {}", to_owner, e),
+            })?;
+
+    if addr_from_owner == addr_to_owner {
+        return Err(ContractError::InvalidAddress {
+            reason: "Cannot reassign keys to the same owner".to_string(),
+        });
+    }
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    let mut keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    // Prevent moving the owner's Management Key away from the owner
+    if addr_from_owner == owner
+        && keys
+            .iter()
+            .any(|k| k.owner == owner && k.has_purpose(&KeyType::ManagementKey))
+    {
+        return Err(ContractError::Unauthorized {
+            reason: "Cannot reassign the owner's Management Key".to_string(),
+        });
+    }
+
+    let Some(index) = keys.iter().position(|k| k.owner == addr_from_owner) else {
+        return Err(ContractError::NoKeysFound {});
+    };
+    let key = keys.remove(index);
+    let moved = key.purposes.clone();
+
+    // Merge into the new owner's key, dropping the purposes it already holds
+    if let Some(existing) = keys.iter_mut().find(|k| k.owner == addr_to_owner) {
+        if !existing.has_purpose(&KeyType::ManagementKey) {
+            existing.weight = key.weight;
+        }
+        for purpose in key.purposes {
+            if !existing.has_purpose(&purpose) {
+                existing.purposes.push(purpose);
+            }
+        }
+    } else {
+        // The new holder hasn't used the key yet
+        keys.insert(
+            index,
+            Key {
+                owner: addr_to_owner.clone(),
+                last_used: None,
+                ..key
+            },
+        );
+    }
+    ensure_management_weight_remains(&keys, management_threshold(&deps)?)?;
+
+    KEYS.save(deps.storage, &owner, &keys)
+        .map_err(|e| ContractError::SaveError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reassign_keys")
+        .add_attribute("from_owner", addr_from_owner)
+        .add_attribute("to_owner", addr_to_owner)
+        .add_attribute(
+            "key_types",
+            moved
+                .iter()
+                .map(|key_type| key_type.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ))
+}
+
+pub fn execute_set_key_weight(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    key_owner: String,
+    weight: u32,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks ManagementKey: {}", e),
+        },
+    )?;
+
+    let addr_key_owner =
+        deps.api
+            .addr_validate(&key_owner)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid key owner address '{}': {}", key_owner, e),
+            })?;
+    if weight == 0 {
+        return Err(ContractError::InvalidManagementWeight {
+            reason: "Key weight must be at least 1".to_string(),
+        });
+    }
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let mut keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    let key = keys
+        .iter_mut()
+        .find(|k| k.owner == addr_key_owner && k.has_purpose(&KeyType::ManagementKey))
+        .ok_or_else(|| ContractError::KeyNotFound {
+            key_type: KeyType::ManagementKey.to_string(),
+            owner: addr_key_owner.to_string(),
+        })?;
+    key.weight = weight;
+    ensure_management_weight_remains(&keys, management_threshold(&deps)?)?;
+
+    KEYS.save(deps.storage, &owner, &keys)
+        .map_err(|e| ContractError::SaveError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_key_weight")
+        .add_attribute("key_owner", addr_key_owner)
+        .add_attribute("weight", weight.to_string()))
+}
+
+pub fn execute_set_key_active(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    key_owner: String,
+    active: bool,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks ManagementKey: {}", e),
+        },
+    )?;
+
+    let addr_key_owner =
+        deps.api
+            .addr_validate(&key_owner)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid key owner address '{}': {}", key_owner, e),
+            })?;
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let mut keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    let key = keys
+        .iter_mut()
+        .find(|k| k.owner == addr_key_owner)
+        .ok_or(ContractError::NoKeysFound {})?;
+    key.active = active;
+    // Disabled ManagementKeys don't count towards the threshold
+    ensure_management_weight_remains(&keys, management_threshold(&deps)?)?;
+
+    KEYS.save(deps.storage, &owner, &keys)
+        .map_err(|e| ContractError::SaveError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_key_active")
+        .add_attribute("key_owner", addr_key_owner)
+        .add_attribute("active", active.to_string()))
+}
+
+pub fn execute_set_management_threshold(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    threshold: u32,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks ManagementKey: {}", e),
+        },
+    )?;
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+    // A threshold the keys can't reach would lock key management for good
+    if management_weight(&keys, None) < threshold {
+        return Err(ContractError::InvalidManagementWeight {
+            reason: format!(
+                "Threshold {} exceeds the combined Management Key weight",
+                threshold
+            ),
+        });
+    }
+
+    let mut config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    config.management_threshold = threshold;
+    CONFIG
+        .save(deps.storage, &config)
+        .map_err(|e| ContractError::SaveError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_management_threshold")
+        .add_attribute("threshold", threshold.to_string()))
+}
+
+fn management_threshold(deps: &DepsMut) -> Result<u32, ContractError> {
+    Ok(CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default()
+        .management_threshold)
+}
+
+pub fn execute_propose_new_owner(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    check_key_authorization(&mut deps, &env, &info.sender, KeyType::ManagementKey).map_err(
+        |e| ContractError::Unauthorized {
+            reason: format!("Sender lacks ManagementKey: {}", e),
+        },
+    )?;
+
+    let addr_new_owner =
+        deps.api
+            .addr_validate(&new_owner)
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid new owner address '{}': {}", new_owner, e),
+            })?;
+    PENDING_OWNER
+        .save(deps.storage, &addr_new_owner)
+        .map_err(|e| ContractError::SaveError {
+            entity: "pending owner".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_owner")
+        .add_attribute("new_owner", addr_new_owner))
+}
+
+pub fn execute_set_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    admin: Option<String>,
+) -> Result<Response, ContractError> {
+    check_admin(&deps, &info.sender)?;
+
+    let admin = match admin {
+        Some(admin) => {
+            let admin =
+                deps.api
+                    .addr_validate(&admin)
+                    .map_err(|e| ContractError::InvalidAddress {
+                        reason: format!("Invalid admin address '{}': {}", admin, e),
+                    })?;
+            ADMIN
+                .save(deps.storage, &admin)
+                .map_err(|e| ContractError::SaveError {
+                    entity: "admin".to_string(),
+                    reason: e.to_string(),
+                })?;
+            admin.to_string()
+        }
+        None => {
+            ADMIN.remove(deps.storage);
+            "owner".to_string()
+        }
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "set_admin")
+        .add_attribute("admin", admin))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    mut config: Config,
+) -> Result<Response, ContractError> {
+    check_admin(&deps, &info.sender)?;
+
+    for addr in [&config.claim_topics_registry, &config.owner_roles]
+        .into_iter()
+        .flatten()
+    {
+        deps.api
+            .addr_validate(addr.as_str())
+            .map_err(|e| ContractError::InvalidAddress {
+                reason: format!("Invalid address '{}': {}", addr, e),
+            })?;
+    }
+
+    let current = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    config.management_threshold = current.management_threshold;
+
+    // A lower key cap can't leave the identity holding more keys than it allows
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let keys = KEYS
+        .may_load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    ensure_total_keys_within(&keys, config.max_total_keys)?;
+
+    CONFIG
+        .save(deps.storage, &config)
+        .map_err(|e| ContractError::SaveError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn execute_approve_migration(
+    deps: DepsMut,
+    info: MessageInfo,
+    to_version: String,
+) -> Result<Response, ContractError> {
+    check_admin(&deps, &info.sender)?;
+
+    MIGRATION_APPROVAL
+        .save(deps.storage, &to_version)
+        .map_err(|e| ContractError::SaveError {
+            entity: "migration approval".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "approve_migration")
+        .add_attribute("to_version", to_version))
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let new_owner = PENDING_OWNER
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "pending owner".to_string(),
+            reason: e.to_string(),
+        })?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if info.sender != new_owner {
+        return Err(ContractError::Unauthorized {
+            reason: "Only the proposed owner can accept ownership".to_string(),
+        });
+    }
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let mut keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+
+    // The new owner manages the identity from now on
+    match keys.iter_mut().find(|k| k.owner == new_owner) {
+        Some(key) if key.has_purpose(&KeyType::ManagementKey) => key.active = true,
+        _ => {
+            grant_key_purpose(&mut keys, &new_owner, KeyType::ManagementKey)?;
+            let max_total_keys = CONFIG
+                .may_load(deps.storage)
+                .map_err(|e| ContractError::LoadError {
+                    entity: "config".to_string(),
+                    reason: e.to_string(),
+                })?
+                .unwrap_or_default()
+                .max_total_keys;
+            ensure_total_keys_within(&keys, max_total_keys)?;
+        }
+    }
+
+    // The previous owner's keys are revoked, so it can't act for the identity anymore
+    if owner != new_owner {
+        keys.retain(|k| k.owner != owner);
+        ensure_management_weight_remains(&keys, management_threshold(&deps)?)?;
+    }
+
+    // Keys are stored under the identity's owner
+    KEYS.remove(deps.storage, &owner);
+    KEYS.save(deps.storage, &new_owner, &keys)
+        .map_err(|e| ContractError::SaveError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+    OWNER
+        .save(deps.storage, &new_owner)
+        .map_err(|e| ContractError::SaveError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("previous_owner", owner)
+        .add_attribute("new_owner", new_owner))
+}
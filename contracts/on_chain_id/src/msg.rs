@@ -1,31 +1,210 @@
-use crate::state::{Claim, Key};
+use crate::state::{
+    Claim, ClaimVersion, Config, DuplicateClaimPolicy, EncryptionEnvelope, Key, KeyType,
+    MigrationRecord, SignatureEncoding, UnknownTopicPolicy,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary, Uint128};
+use cosmwasm_std::{Addr, Binary, CosmosMsg, Uint128, Uint64};
 
 #[cw_serde]
+#[cfg_attr(test, derive(Default))]
 pub struct InstantiateMsg {
     pub owner: String,
+    // Account for contract-level operations such as topic policies, configuration changes and
+    // migration approvals, the owner when `None`
+    pub admin: Option<String>,
+    pub default_validity_secs: Option<u64>,
+    pub signature_encoding: Option<SignatureEncoding>,
+    pub allow_issuer_removal: Option<bool>,
+    pub claim_topics_registry: Option<String>,
+    pub owner_roles_address: Option<String>,
+    pub unknown_topic_policy: Option<UnknownTopicPolicy>,
+    pub max_claim_validity_secs: Option<u64>,
+    pub duplicate_claim_policy: Option<DuplicateClaimPolicy>,
+    pub management_threshold: Option<u32>,
+    // Caps on the keys and claims the identity holds in total, to bound its storage
+    pub max_total_keys: Option<u32>,
+    pub max_total_claims: Option<u32>,
+    // Largest claim `data` accepted, in bytes, `state::DEFAULT_MAX_CLAIM_DATA_BYTES` if `None`
+    pub max_claim_data_bytes: Option<u32>,
+    // Largest amount of a denom an Execute bank send may move without ManagementKey approval
+    pub max_execution_send: Option<Uint128>,
+    // Most claims of a topic a user may hold, `state::DEFAULT_MAX_CLAIMS_PER_TOPIC` if `None`
+    pub max_claims_per_topic: Option<u32>,
+    // Topics on which the claim holder or the owner may never attest for themselves
+    #[serde(default)]
+    pub no_self_attest_topics: Vec<Uint128>,
+    // Keys granted at creation in addition to the owner's ManagementKey, with a weight of 1.
+    // As with AddKey, each key type other than ManagementKey may only be held once.
+    #[serde(default)]
+    pub initial_keys: Vec<(Addr, KeyType)>,
 }
 
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     AddKey {
         key_owner: String,
         key_type: String,
+        // Makes retries of the same message a no-op, reusing it for another message fails
+        idempotency_key: Option<String>,
     },
     RevokeKey {
         key_owner: String,
         key_type: String,
     },
+    // Moves every key held by `from_owner` to `to_owner`, keeping their purposes
+    ReassignKeys {
+        from_owner: String,
+        to_owner: String,
+    },
+    // Weight `key_owner`'s ManagementKey contributes towards the management threshold
+    SetKeyWeight {
+        key_owner: String,
+        weight: u32,
+    },
+    // Disables a key, with all its purposes, without removing it, or re-enables it
+    SetKeyActive {
+        key_owner: String,
+        active: bool,
+    },
+    // Combined ManagementKey weight AddKey, RevokeKey, ReassignKeys, SetKeyWeight,
+    // SetKeyActive, SetManagementThreshold and ProposeNewOwner require. Below it, each ManagementKey
+    // holder sends the same message and the operation runs once the approvals add up.
+    SetManagementThreshold {
+        threshold: u32,
+    },
+    // First step of an ownership transfer, replacing any earlier proposal
+    ProposeNewOwner {
+        new_owner: String,
+    },
+    // Sent by the proposed owner to become the owner, gaining an active ManagementKey.
+    // The previous owner's keys are revoked.
+    AcceptOwnership {},
+    // Sent by the admin to hand its role over. `None` makes the owner the admin again.
+    SetAdmin {
+        admin: Option<String>,
+    },
+    // Sent by the admin to replace the identity's configuration. The management threshold
+    // is left as is, since ManagementKeys set it with SetManagementThreshold.
+    UpdateConfig {
+        config: Config,
+    },
+    // Sent by the admin to allow migrating the contract to `to_version`. Required before
+    // each migration once an admin separate from the owner is set.
+    ApproveMigration {
+        to_version: String,
+    },
     AddClaim {
         claim: Claim,
         public_key: Binary,
         user_addr: Addr,
+        // Makes retries of the same message a no-op, reusing it for another message fails
+        idempotency_key: Option<String>,
+    },
+    // Adds a claim submitted by a relayer rather than the issuer. The issuer, holding a
+    // CLAIM_SIGNER_KEY, authorizes it with `issuer_permit`, its signature over
+    // `claim_permit_message_hash`. Each permit can be used once.
+    AddClaimWithPermit {
+        claim: Claim,
+        public_key: Binary,
+        user_addr: Addr,
+        issuer_permit: Binary,
+    },
+    // Adds several claims signed with the same issuer key, e.g. when onboarding a user.
+    // Either all of them are added or, if any is rejected, none.
+    AddClaimsBatch {
+        claims: Vec<Claim>,
+        public_key: Binary,
+        user_addr: Addr,
     },
     RemoveClaim {
         claim_topic: Uint128,
         user_addr: Addr,
     },
+    // Swaps a claim for a new one in a single transaction, e.g. to renew it before it
+    // expires, so the identity is never left without either
+    ReplaceClaim {
+        old_claim_id: String,
+        new_claim: Claim,
+        public_key: Binary,
+        user_addr: Addr,
+    },
+    RemoveClaimById {
+        claim_id: String,
+        user_addr: Addr,
+    },
+    // Changes when a claim expires without re-signing it. `issuer_signature` is the
+    // issuer's signature over `expiry_update_message_hash`.
+    SetClaimExpiry {
+        user_addr: Addr,
+        claim_id: String,
+        valid_until: Uint64,
+        issuer_signature: Binary,
+        public_key: Binary,
+    },
+    // Moves a claim to another identity contract, which must have approved the move
+    MigrateClaim {
+        claim_id: String,
+        user_addr: Addr,
+        to_identity: String,
+        // Issuer key the target verifies the claim signature with
+        public_key: Binary,
+    },
+    // Lets `from_identity` migrate the claim `claim_id` into this identity
+    ApproveClaimMigration {
+        from_identity: String,
+        claim_id: String,
+    },
+    // Restricts which issuers may attest to `topic`. An empty list lifts the restriction.
+    SetTopicAllowedIssuers {
+        topic: Uint128,
+        issuers: Vec<String>,
+    },
+    // Only users holding a key of `required_holder_purpose` may receive claims of `topic`.
+    // `None` lifts the restriction.
+    SetTopicHolderPurpose {
+        topic: Uint128,
+        required_holder_purpose: Option<KeyType>,
+    },
+    // Overrides how many claims of `topic` a user may hold. `None` restores the identity-wide
+    // limit.
+    SetTopicClaimLimit {
+        topic: Uint128,
+        max_claims: Option<u32>,
+    },
+    // Regulatory takedown by a ComplianceManager, regardless of the identity's keys
+    ForceRemoveClaim {
+        user_addr: Addr,
+        claim_id: String,
+    },
+    // Appends to the sender's revocation list. Entries are SHA-256 hashes of the revoked
+    // claim IDs (see `utils::claim_id_hash`), so IDs aren't disclosed before they're used.
+    PublishRevocations {
+        revoked_ids: Vec<Binary>,
+    },
+    // Revokes a claim `user_addr` holds on this identity. Sent by its issuer, holding a
+    // CLAIM_SIGNER_KEY.
+    RevokeClaimSignature {
+        claim_id: String,
+        user_addr: Addr,
+    },
+    // Delegates the sender's claim signing to `signer_contract`: claims it issues are only
+    // accepted with the key that contract currently authorizes. `None` removes the delegation.
+    SetSignerContract {
+        signer_contract: Option<String>,
+    },
+    // Sent by the source identity of an approved claim migration
+    ReceiveClaim {
+        claim: Claim,
+        public_key: Binary,
+        user_addr: Addr,
+    },
+    // Dispatches `msgs` on behalf of the identity. Needs an ExecutionKey or ManagementKey;
+    // messages calling the identity itself, or moving more than `max_execution_send` in
+    // total, are approved like key management instead.
+    Execute {
+        msgs: Vec<CosmosMsg>,
+    },
 }
 
 #[cw_serde]
@@ -34,15 +213,362 @@ pub struct MigrateMsg {}
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    // The key held by `key_owner` if it has the `key_type` purpose. `key_owner` is the key's
+    // holder (`Key::owner`), not the identity owner all keys are stored under: each holder has a
+    // single key carrying all its purposes, so the identity owner's own key is found with the
+    // owner's address and the keys it granted to others with theirs.
     #[returns(Key)]
     GetKey { key_owner: String, key_type: String },
 
+    // Keys held by `key_owner`, empty when it holds none
+    #[returns(Vec<Key>)]
+    GetKeys { key_owner: String },
+
+    // Keys of `key_type` whoever holds them, e.g. every ManagementKey during a recovery
+    #[returns(Vec<Key>)]
+    GetKeysByType { key_type: String },
+
+    // Keys that haven't authorized anything in the last `older_than_secs` seconds,
+    // including keys that were never used
+    #[returns(Vec<Key>)]
+    GetStaleKeys { older_than_secs: u64 },
+
+    // Claims held by `user_addr` whose signature is not revoked, leaving out expired ones
+    // when `exclude_expired` is set
     #[returns(Vec<Claim>)]
-    GetValidatedClaimsForUser { user_addr: Addr },
+    GetValidatedClaimsForUser {
+        user_addr: Addr,
+        exclude_expired: Option<bool>,
+    },
 
     #[returns(bool)]
-    VerifyClaim { claim_id: Uint128, user_addr: Addr },
+    VerifyClaim {
+        claim_id: Uint128,
+        user_addr: Addr,
+        // Namespace of the topic, the default one when `None`
+        namespace: Option<String>,
+        // Trusted issuers registry to vet claim issuers against for the topic; any issuer
+        // is accepted when `None`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trusted_issuers_registry: Option<String>,
+    },
+
+    // The valid claim VerifyClaim relies on for `topic`: the most recently issued one,
+    // ties broken by the lowest issuer address
+    #[returns(Option<Claim>)]
+    GetClaimForTopic {
+        user_addr: Addr,
+        topic: Uint128,
+        namespace: Option<String>,
+    },
+
+    // Checks every topic a gate requires at once, as VerifyClaim would for each, reporting
+    // the claim and issuer satisfying each topic. With `trusted_issuers_registry`, only
+    // claims of issuers it trusts for their topic count, the registry being asked about
+    // each issuer once however many topics it attests to.
+    #[returns(PreflightVerificationResponse)]
+    PreflightVerification {
+        user_addr: Addr,
+        required_topics: Vec<Uint128>,
+        trusted_issuers_registry: Option<String>,
+    },
+
+    // Fewest issuers the user must still obtain claims from to satisfy every topic of
+    // `profile`, among the issuers allowed for each topic
+    #[returns(RequiredIssuersResponse)]
+    RequiredIssuersForProfile {
+        user_addr: Addr,
+        profile: Vec<Uint128>,
+    },
+
+    // Checks many claims and their issuers' public keys at once, as AddClaim would,
+    // returning the rejected ones. Empty when all would be accepted.
+    #[returns(Vec<ClaimRejection>)]
+    WouldAcceptClaims {
+        claims_with_keys: Vec<(Claim, Binary)>,
+    },
+
+    // Whether a claim presented without being stored, e.g. one issued just in time for a
+    // transfer, proves `required_topic`: it must be signed with its issuer's key (or the key
+    // the issuer's signer contract authorizes) and be unexpired, unrevoked and from an
+    // issuer allowed for the topic
+    #[returns(bool)]
+    VerifyProvidedClaim {
+        claim: Box<Claim>,
+        public_key: Binary,
+        required_topic: Uint128,
+    },
+
+    // Claim by ID together with its current validity
+    #[returns(ClaimResponse)]
+    GetClaim { user_addr: Addr, claim_id: String },
+
+    // Claim by ID as stored, without evaluating its validity
+    #[returns(Claim)]
+    GetRawClaim { user_addr: Addr, claim_id: String },
+
+    // Claim by ID with its contents, which are only returned to holders of a ManagementKey
+    // or ExecutionKey proving it with `auth`. Anyone else gets the claim redacted.
+    #[returns(ClaimContentsResponse)]
+    GetClaimContents {
+        user_addr: Addr,
+        claim_id: String,
+        auth: Option<QueryAuth>,
+    },
+
+    // Unexpired claims whose `valid_until` is at most `seconds` away, soonest first
+    #[returns(Vec<Claim>)]
+    GetClaimsExpiringWithin { user_addr: Addr, seconds: u64 },
+
+    // A page of the claims held by `user_addr` in ascending topic order, starting after the
+    // topic `start_after`. A page never ends partway through a topic's claims.
+    #[returns(ClaimsPageResponse)]
+    GetClaimsPaginated {
+        user_addr: Addr,
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    },
+
+    // Claims held by `user_addr` currently in `status`
+    #[returns(Vec<Claim>)]
+    GetClaimsByStatus {
+        user_addr: Addr,
+        status: ClaimStatus,
+    },
+
+    // Claims held by `user_addr` that link to another identity
+    #[returns(Vec<Claim>)]
+    GetRelationshipClaims { user_addr: Addr },
+
+    // Claims held by `user_addr` that were issued by `issuer`
+    #[returns(Vec<Claim>)]
+    GetClaimsByIssuer { user_addr: Addr, issuer: Addr },
+
+    // IDs of the claims held by `user_addr` for `topic` in the default namespace
+    #[returns(Vec<String>)]
+    GetClaimIdsByTopic { user_addr: Addr, topic: Uint128 },
+
+    // Whether `leaf` is one of the attributes the claim commits to in its `data_root`, with
+    // `proof` the sibling hashes from the leaf up (see `utils::merkle_root`). False as well
+    // when the claim is not currently valid.
+    #[returns(bool)]
+    VerifyDisclosure {
+        user_addr: Addr,
+        claim_id: String,
+        leaf: Binary,
+        proof: Vec<Binary>,
+    },
+
+    // Versions of a claim, oldest first and ending with the current one unless the claim
+    // was removed. Only the current version is used for verification.
+    #[returns(Vec<ClaimVersion>)]
+    GetClaimHistory { user_addr: Addr, claim_id: String },
+
+    // Encryption header of a claim, `None` when its data is not encrypted
+    #[returns(Option<EncryptionEnvelope>)]
+    GetClaimEnvelope { user_addr: Addr, claim_id: String },
+
+    // Issuers allowed to attest to `topic`, empty when any issuer is accepted
+    #[returns(Vec<Addr>)]
+    GetTopicAllowedIssuers { topic: Uint128 },
+
+    // Key purpose required of holders of `topic` claims, if any
+    #[returns(Option<KeyType>)]
+    GetTopicHolderPurpose { topic: Uint128 },
+
+    // Most claims of `topic` a user may hold
+    #[returns(u32)]
+    GetTopicClaimLimit { topic: Uint128 },
+
+    // Address of the account holding `public_key`, to check it matches the claim's issuer
+    // before submitting the claim
+    #[returns(ExpectedSignerResponse)]
+    ExpectedSigner {
+        claim: Box<Claim>,
+        public_key: Binary,
+    },
+
+    // Contract `issuer` delegated its claim signing to, if any
+    #[returns(Option<Addr>)]
+    GetSignerContract { issuer: Addr },
+
+    // Whether `issuer` has published a revocation of `claim_id` or revoked its signature
+    #[returns(bool)]
+    IsRevoked { issuer: Addr, claim_id: String },
+
+    // Revocation list of `issuer`, oldest first. `start_after` is a position in the list.
+    #[returns(Vec<Binary>)]
+    GetRevocations {
+        issuer: Addr,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
 
     #[returns(String)]
     GetOwner {},
+
+    // Account proposed as the next owner, if an ownership transfer is pending
+    #[returns(Option<Addr>)]
+    GetPendingOwner {},
+
+    // Account allowed to perform contract-level operations
+    #[returns(Addr)]
+    GetAdmin {},
+
+    #[returns(Config)]
+    GetConfig {},
+
+    // Owner, key and claim counts, and linked registries in one response
+    #[returns(IdentitySummaryResponse)]
+    IdentitySummary {},
+
+    // Past migrations of this contract, oldest first
+    #[returns(Vec<MigrationRecord>)]
+    GetMigrationHistory {},
+
+    // Keys of the identity and claims held by `user_addr`, with their statuses, and the
+    // linked registries in one response, for backups. At most `utils::pagination::MAX_LIMIT`
+    // keys and as many claims are included, see `FullSnapshotResponse::complete`.
+    #[returns(FullSnapshotResponse)]
+    FullSnapshot { user_addr: Addr },
+
+    // Claims held by `user_addr` that were issued by `issuer` for topics
+    // `trusted_issuers_registry` no longer trusts them for, used to clean up claims
+    // after the issuer is removed from the registry. Claims on topics the issuer is
+    // still trusted for are left out.
+    #[returns(Vec<Claim>)]
+    GetClaimsByRemovedIssuer {
+        user_addr: Addr,
+        issuer: Addr,
+        trusted_issuers_registry: String,
+    },
+}
+
+// Where a stored claim currently stands. A claim failing several checks is reported
+// under the first of Expired, Revoked and UntrustedIssuer.
+#[cw_serde]
+pub enum ClaimStatus {
+    Valid,
+    Expired,
+    // Revoked by its issuer
+    Revoked,
+    // Issuer isn't allowed for the claim's topic
+    UntrustedIssuer,
+}
+
+// Proof that a query comes from the holder of `public_key`: its signature over
+// `utils::query_auth_message_hash` for this contract, the query with its arguments and
+// `expires_at`
+#[cw_serde]
+pub struct QueryAuth {
+    pub public_key: Binary,
+    pub signature: Binary,
+    // Unix seconds after which the signature is no longer accepted
+    pub expires_at: Uint64,
+}
+
+#[cw_serde]
+pub struct ClaimContentsResponse {
+    // With `data`, `uri` and `encryption` cleared when `redacted`
+    pub claim: Claim,
+    pub redacted: bool,
+}
+
+#[cw_serde]
+pub struct RequiredIssuersResponse {
+    // Sorted by address
+    pub issuers: Vec<Addr>,
+    // Unsatisfied topics no recommendation is made for: any issuer may attest to them, or
+    // they are unregistered and can never verify
+    pub uncovered_topics: Vec<Uint128>,
+}
+
+#[cw_serde]
+pub struct PreflightVerificationResponse {
+    // Whether every required topic is satisfied
+    pub verified: bool,
+    // In the order the topics were required
+    pub topics: Vec<TopicVerification>,
+}
+
+#[cw_serde]
+pub struct TopicVerification {
+    pub topic: Uint128,
+    pub satisfied: bool,
+    // Claim VerifyClaim relies on for the topic, `None` when no claim is needed or none is valid
+    pub claim_id: Option<String>,
+    pub issuer: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct ClaimsPageResponse {
+    pub claims: Vec<Claim>,
+    // Topic to pass as `start_after` for the next page, `None` when the page is empty
+    pub last_topic: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct IdentitySummaryResponse {
+    pub owner: Addr,
+    // Purposes held across all keys, as counted against `max_total_keys`
+    pub key_count: u32,
+    // Claims held across all users of this identity
+    pub claim_count: u32,
+    pub claim_topics_registry: Option<Addr>,
+    pub owner_roles: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct FullSnapshotResponse {
+    pub owner: Addr,
+    pub admin: Addr,
+    pub keys: Vec<Key>,
+    pub claims: Vec<ClaimSnapshot>,
+    pub claim_topics_registry: Option<Addr>,
+    pub owner_roles: Option<Addr>,
+    // False when keys or claims were left out, which `GetKeys` and `GetClaimsPaginated`
+    // then return in full
+    pub complete: bool,
+}
+
+#[cw_serde]
+pub struct ClaimSnapshot {
+    pub claim: Claim,
+    pub status: ClaimStatus,
+}
+
+// Queried on the signer contract an issuer delegated its claim signing to
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum SignerContractQueryMsg {
+    #[returns(SigningKeyResponse)]
+    SigningKey { issuer: Addr },
+}
+
+#[cw_serde]
+pub struct SigningKeyResponse {
+    pub public_key: Binary,
+    // Unix seconds after which the key no longer signs claims, `None` if it doesn't expire
+    pub valid_until: Option<Uint64>,
+}
+
+#[cw_serde]
+pub struct ExpectedSignerResponse {
+    pub signer: Addr,
+    pub matches_issuer: bool,
+}
+
+#[cw_serde]
+pub struct ClaimRejection {
+    // Position of the claim in the request
+    pub index: u32,
+    pub reason: String,
+}
+
+#[cw_serde]
+pub struct ClaimResponse {
+    pub claim: Claim,
+    pub valid: bool,
+    // Why the claim is not valid, if it isn't
+    pub reason: Option<String>,
 }
@@ -1,6 +1,6 @@
 use crate::error::ContractError;
-use cosmwasm_std::{Addr, Binary, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Attribute, Binary, Timestamp, Uint128, Uint64};
+use cw_storage_plus::{Deque, Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -11,23 +11,348 @@ pub const KEYS: Map<&Addr, Vec<Key>> = Map::new("keys");
 
 //Addr being the Identity owner
 pub const CLAIMS: Map<&Addr, Vec<Claim>> = Map::new("claims");
+// Number of claims held across all users, kept alongside CLAIMS
+pub const TOTAL_CLAIMS: Item<u32> = Item::new("total_claims");
 
 //Addr being the Owner of the Identity (not to be confused with the Key owner)
 pub const OWNER: Item<Addr> = Item::new("owner");
 
+// Account proposed as the next owner, which becomes the owner once it accepts
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+
+// Account for contract-level operations, such as topic policies, configuration changes and
+// migration approvals, while the owner manages keys and claims. Unset means the current
+// owner is the admin.
+pub const ADMIN: Item<Addr> = Item::new("admin");
+
+// Contract version the admin allowed the next migration to. Only required once an admin
+// separate from the owner is set, so identities without one migrate as before.
+pub const MIGRATION_APPROVAL: Item<String> = Item::new("migration_approval");
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+// (sender, idempotency key) -> attributes of the response originally produced for it
+pub const IDEMPOTENCY_KEYS: Map<(&Addr, &str), Vec<Attribute>> = Map::new("idempotency_keys");
+// (sender, idempotency key) -> SHA-256 of the message first sent with it. Keys recorded
+// before hashes were kept have no entry and replay without the check.
+pub const IDEMPOTENCY_KEY_HASHES: Map<(&Addr, &str), Binary> = Map::new("idempotency_key_hashes");
+// Insertion order of idempotency keys, used to evict the oldest once the cap is reached
+pub const IDEMPOTENCY_KEY_QUEUE: Deque<(Addr, String)> = Deque::new("idempotency_key_queue");
+pub const MAX_IDEMPOTENCY_KEYS: u32 = 1000;
+
+// Topic -> the only issuers whose claims are accepted for it. Topics without an entry accept any issuer.
+pub const TOPIC_ALLOWED_ISSUERS: Map<u128, Vec<Addr>> = Map::new("topic_allowed_issuers");
+
+// Topic -> key purpose a user must hold on this identity to receive claims of the topic
+pub const TOPIC_HOLDER_PURPOSE: Map<u128, KeyType> = Map::new("topic_holder_purpose");
+
+// Topic -> most claims of the topic a user may hold, overriding `Config::max_claims_per_topic`
+pub const TOPIC_CLAIM_LIMITS: Map<u128, u32> = Map::new("topic_claim_limits");
+
+// Issuer -> SHA-256 hashes of the IDs of claims it has revoked, oldest first.
+// Consulted whenever a stored claim's validity is evaluated.
+pub const CRL: Map<&Addr, Vec<Binary>> = Map::new("crl");
+pub const MAX_REVOCATIONS_PER_PUBLISH: usize = 100;
+pub const MAX_REVOCATIONS_PER_ISSUER: usize = 1000;
+
+// Claim ID -> revoked by its issuer through `RevokeClaimSignature`. Unlike the CRL the ID
+// is disclosed, and only claims stored on this identity can be revoked.
+pub const REVOKED_CLAIMS: Map<&str, bool> = Map::new("revoked_claims");
+
+// Issuer -> highest `Claim::nonce` among the claims it added to this identity
+pub const CLAIM_NONCES: Map<&Addr, u64> = Map::new("claim_nonces");
+
+// `claim_permit_message_hash` of permits consumed by `AddClaimWithPermit`, so a relayer
+// can't add the claim again once it has been removed
+pub const USED_CLAIM_PERMITS: Map<&[u8], bool> = Map::new("used_claim_permits");
+
+// Issuer -> contract managing the key the issuer signs claims with
+pub const SIGNER_CONTRACTS: Map<&Addr, Addr> = Map::new("signer_contracts");
+
+// Upper bound on the claims checked by a single WouldAcceptClaims query or added by an
+// AddClaimsBatch, and on the topics checked by a PreflightVerification
+pub const MAX_CLAIMS_PER_BATCH: usize = 100;
+
+// Bounds on what a claim stores
+pub const MAX_CLAIM_URI_BYTES: usize = 512;
+pub const CLAIM_URI_SCHEMES: [&str; 3] = ["http://", "https://", "ipfs://"];
+pub const DEFAULT_MAX_CLAIM_DATA_BYTES: u32 = 2048;
+pub const DEFAULT_MAX_CLAIMS_PER_TOPIC: u32 = 5;
+
+// Upper bound on the topics of a profile passed to RequiredIssuersForProfile, whose search
+// for the fewest issuers grows exponentially with them
+pub const MAX_PROFILE_TOPICS: usize = 16;
+
+// (source identity, claim ID) of claim migrations approved by a ManagementKey of this identity
+pub const CLAIM_MIGRATION_APPROVALS: Map<(&Addr, &str), bool> =
+    Map::new("claim_migration_approvals");
+
+// Hash of a pending key management operation -> ManagementKey holders that approved it so far
+pub const MANAGEMENT_APPROVALS: Map<&[u8], Vec<Addr>> = Map::new("management_approvals");
+
+// Sibling hashes accepted in a `VerifyDisclosure` proof, enough for 2^32 attributes
+pub const MAX_DISCLOSURE_PROOF_DEPTH: usize = 32;
+
+// (user, ID of the current claim) -> versions the claim replaced, oldest first. Kept when
+// the claim is removed, for audit.
+pub const CLAIM_VERSIONS: Map<(&Addr, &str), Vec<ClaimVersion>> = Map::new("claim_versions");
+// Superseded versions kept per claim, the oldest are dropped beyond it
+pub const MAX_CLAIM_VERSIONS: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimVersion {
+    // Counts from 1, including versions dropped since
+    pub version: u32,
+    pub claim: Claim,
+    // Unix seconds at which a newer version took its place, `None` for the current one
+    pub superseded_at: Option<Uint64>,
+}
+
+// Upgrade history, oldest first
+pub const MIGRATIONS: Item<Vec<MigrationRecord>> = Item::new("migrations");
+pub const MAX_MIGRATION_RECORDS: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrationRecord {
+    pub from_version: String,
+    pub to_version: String,
+    pub block_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Config {
+    // Validity period (in seconds) applied to claims added without a `valid_until`.
+    // `None` keeps such claims valid forever.
+    #[serde(default)]
+    pub default_validity_secs: Option<u64>,
+    // How issuers' claim signatures are encoded before verification.
+    #[serde(default)]
+    pub signature_encoding: SignatureEncoding,
+    // Whether a claim's issuer may remove it without holding a ClaimSignerKey on this identity.
+    #[serde(default)]
+    pub allow_issuer_removal: bool,
+    // Claim topics registry consulted before adding claims; deprecated topics are rejected.
+    #[serde(default)]
+    pub claim_topics_registry: Option<Addr>,
+    // Owner roles contract whose ComplianceManagers may force-remove claims.
+    #[serde(default)]
+    pub owner_roles: Option<Addr>,
+    // Furthest into the future, in seconds from now, SetClaimExpiry may extend a claim.
+    #[serde(default)]
+    pub max_claim_validity_secs: Option<u64>,
+    // How VerifyClaim treats topics the claim topics registry doesn't know.
+    #[serde(default)]
+    pub unknown_topic_policy: UnknownTopicPolicy,
+    // How AddClaim treats a claim from an issuer that already attested to the topic.
+    #[serde(default)]
+    pub duplicate_claim_policy: DuplicateClaimPolicy,
+    // Combined ManagementKey weight needed to manage keys. Up to 1, any ManagementKey acts alone.
+    #[serde(default)]
+    pub management_threshold: u32,
+    // Most keys the identity may hold in total, unlimited when `None`.
+    #[serde(default)]
+    pub max_total_keys: Option<u32>,
+    // Most claims the identity may hold across all users, unlimited when `None`.
+    #[serde(default)]
+    pub max_total_claims: Option<u32>,
+    // Largest claim `data` accepted, in bytes. `None` applies DEFAULT_MAX_CLAIM_DATA_BYTES.
+    #[serde(default)]
+    pub max_claim_data_bytes: Option<u32>,
+    // Largest amount of a denom an Execute may move on an ExecutionKey alone, summed over
+    // its messages. Larger amounts, and messages whose value can't be told, need
+    // ManagementKey approval. Unlimited when `None`.
+    #[serde(default)]
+    pub max_execution_send: Option<Uint128>,
+    // Most claims of a topic a user may hold, unless the topic has its own limit.
+    // `None` applies DEFAULT_MAX_CLAIMS_PER_TOPIC.
+    #[serde(default)]
+    pub max_claims_per_topic: Option<u32>,
+    // Topics whose claims must come from a third party, never the holder or the owner.
+    #[serde(default)]
+    pub no_self_attest_topics: Vec<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub enum DuplicateClaimPolicy {
+    // The new claim is refused.
+    #[default]
+    Reject,
+    // The new claim takes the place of the existing one.
+    Overwrite,
+    // The existing claim is kept and the call succeeds without changes.
+    Ignore,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub enum UnknownTopicPolicy {
+    // Claims under unregistered topics never verify.
+    #[default]
+    Reject,
+    // Unregistered topics are not required, so verifying them always succeeds.
+    Ignore,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub enum SignatureEncoding {
+    // The claim hash is signed directly with the issuer's key.
+    #[default]
+    Raw,
+    // The claim hash is wrapped in an ADR-36 sign doc, as done by wallets signing arbitrary data.
+    Adr36,
+}
+
+// Curve of the key an issuer signs claims with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub enum SignatureScheme {
+    #[default]
+    Secp256k1,
+    Ed25519,
+}
+
+// Keys and claims are stored as JSON, so every field added to Key or Claim after its first
+// release needs a serde default for records stored before it to keep loading. `purposes` is
+// left required: it's how `fold_legacy_keys` tells single-purpose keys apart.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Key {
     pub owner: Addr,
-    pub key_type: KeyType,
+    // ERC-734 purposes the key holds, each at most once
+    pub purposes: Vec<KeyType>,
+    // Unix seconds of the last execute this key authorized, `None` if it never has.
+    #[serde(default)]
+    pub last_used: Option<Uint64>,
+    // Counts towards the management threshold while the key holds the ManagementKey purpose.
+    #[serde(default = "default_key_weight")]
+    pub weight: u32,
+    // Inactive keys authorize nothing until reactivated, but remain on the identity.
+    #[serde(default = "default_key_active")]
+    pub active: bool,
+}
+
+fn default_key_weight() -> u32 {
+    1
+}
+
+fn default_key_active() -> bool {
+    true
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Claim {
+    // Identifier derived from the claim contents, set by the contract.
+    #[serde(default)]
+    pub id: Option<String>,
     pub topic: Uint128,
     pub issuer: Addr,
     pub signature: Binary,
     pub data: Binary,
+    // SHA-256 of `data`. When set, the issuer signs this hash instead of the data itself,
+    // so large payloads don't need to be part of the signed message.
+    #[serde(default)]
+    pub data_hash: Option<Binary>,
     pub uri: String,
+    // Unix seconds after which the claim is no longer valid. `None` never expires.
+    #[serde(default)]
+    pub valid_until: Option<Uint64>,
+    // Unix seconds at which the claim was stored, stamped by the contract.
+    #[serde(default)]
+    pub issued_at: Option<Uint64>,
+    // Present when `data` is encrypted for the holder of an EncryptionKey.
+    #[serde(default)]
+    pub encryption: Option<EncryptionEnvelope>,
+    // Another identity this claim links to, e.g. for "controls" relationships.
+    #[serde(default)]
+    pub related_identity: Option<Addr>,
+    // Scheme the topic number belongs to, so equal numbers from different schemes don't
+    // collide. `None` is the default namespace, which topic policies and registries cover.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    // Merkle root of the claim's attributes (see `utils::merkle_root`), so the holder can
+    // disclose them one at a time with `VerifyDisclosure` instead of publishing all of them.
+    #[serde(default)]
+    pub data_root: Option<Binary>,
+    // Curve of the issuer's key, which the public key submitted with the claim is a key of
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
+    // User the claim was issued for, so it can't be copied to another user's identity.
+    // Required on new claims.
+    #[serde(default)]
+    pub subject: Option<Addr>,
+    // Must be above the last nonce of the issuer's claims added to this identity, so the
+    // signed claim can't be submitted again. Required on new claims.
+    #[serde(default)]
+    pub nonce: Option<Uint64>,
+    // Set by the contract once it changes `valid_until` after the issuer signed (default
+    // validity period, `SetClaimExpiry`), so the signature can still be checked when moved
+    #[serde(default)]
+    pub signed_expiry: Option<SignedExpiry>,
+}
+
+// Expiry an issuer's claim signature covers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedExpiry {
+    pub valid_until: Option<Uint64>,
+}
+
+// Header of an encrypted claim. The ciphertext itself is stored in the claim's `data`
+// and is decrypted off-chain by the recipient.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EncryptionEnvelope {
+    // Encryption scheme identifier, interpreted off-chain
+    pub scheme: u8,
+    // Owner of the EncryptionKey the data is encrypted to
+    pub recipient: Addr,
+}
+
+impl Key {
+    pub fn has_purpose(&self, purpose: &KeyType) -> bool {
+        self.purposes.contains(purpose)
+    }
+}
+
+impl Claim {
+    pub fn is_in_topic(&self, topic: Uint128, namespace: Option<&str>) -> bool {
+        self.topic == topic && self.namespace.as_deref() == namespace
+    }
+
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.valid_until
+            .is_some_and(|valid_until| now.seconds() > valid_until.u64())
+    }
+}
+
+// Standard ERC-3643 claim topics. Claims and messages still take a raw `Uint128`,
+// so any other numeric topic stays valid.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+pub enum WellKnownTopic {
+    // 1: Know Your Customer
+    Kyc,
+    // 2: Anti Money Laundering
+    Aml,
+    // 3: Accredited investor status
+    Accreditation,
+}
+
+impl From<WellKnownTopic> for Uint128 {
+    fn from(topic: WellKnownTopic) -> Self {
+        match topic {
+            WellKnownTopic::Kyc => Uint128::new(1),
+            WellKnownTopic::Aml => Uint128::new(2),
+            WellKnownTopic::Accreditation => Uint128::new(3),
+        }
+    }
+}
+
+impl TryFrom<Uint128> for WellKnownTopic {
+    type Error = ContractError;
+
+    fn try_from(topic: Uint128) -> Result<Self, Self::Error> {
+        match topic.u128() {
+            1 => Ok(WellKnownTopic::Kyc),
+            2 => Ok(WellKnownTopic::Aml),
+            3 => Ok(WellKnownTopic::Accreditation),
+            _ => Err(ContractError::UnknownClaimTopic { topic }),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -40,6 +365,8 @@ pub enum KeyType {
     ClaimSignerKey,
     // 4: ENCRYPTION keys, used to encrypt data e.g. hold in claims.
     EncryptionKey,
+    // Any other ERC-734 purpose, given by its number.
+    Custom(u32),
 }
 
 impl FromStr for KeyType {
@@ -51,9 +378,17 @@ impl FromStr for KeyType {
             "ExecutionKey" => Ok(KeyType::ExecutionKey),
             "ClaimSignerKey" => Ok(KeyType::ClaimSignerKey),
             "EncryptionKey" => Ok(KeyType::EncryptionKey),
-            _ => Err(ContractError::InvalidKeyType {
-                key_type: s.to_string(),
-            }),
+            // ERC-734 purposes, the first four being the named ones
+            _ => match s.parse::<u32>() {
+                Ok(1) => Ok(KeyType::ManagementKey),
+                Ok(2) => Ok(KeyType::ExecutionKey),
+                Ok(3) => Ok(KeyType::ClaimSignerKey),
+                Ok(4) => Ok(KeyType::EncryptionKey),
+                Ok(purpose) if purpose > 0 => Ok(KeyType::Custom(purpose)),
+                _ => Err(ContractError::InvalidKeyType {
+                    key_type: s.to_string(),
+                }),
+            },
         }
     }
 }
@@ -65,6 +400,7 @@ impl fmt::Display for KeyType {
             KeyType::ExecutionKey => write!(f, "ExecutionKey"),
             KeyType::ClaimSignerKey => write!(f, "ClaimSignerKey"),
             KeyType::EncryptionKey => write!(f, "EncryptionKey"),
+            KeyType::Custom(purpose) => write!(f, "{}", purpose),
         }
     }
 }
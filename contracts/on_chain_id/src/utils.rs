@@ -1,10 +1,53 @@
 use crate::error::ContractError;
-use crate::state::{Claim, KeyType, KEYS, OWNER};
-use cosmwasm_std::{Addr, Binary, DepsMut};
+use crate::msg::{ClaimRejection, QueryAuth, QueryMsg, SignerContractQueryMsg, SigningKeyResponse};
+use crate::state::{
+    Claim, EncryptionEnvelope, Key, KeyType, SignatureEncoding, SignatureScheme, ADMIN,
+    CLAIM_NONCES, CLAIM_URI_SCHEMES, CONFIG, CRL, DEFAULT_MAX_CLAIMS_PER_TOPIC,
+    DEFAULT_MAX_CLAIM_DATA_BYTES, IDEMPOTENCY_KEYS, IDEMPOTENCY_KEY_HASHES, IDEMPOTENCY_KEY_QUEUE,
+    KEYS, MAX_CLAIMS_PER_BATCH, MAX_CLAIM_URI_BYTES, MAX_IDEMPOTENCY_KEYS, OWNER, REVOKED_CLAIMS,
+    SIGNER_CONTRACTS, TOPIC_ALLOWED_ISSUERS, TOPIC_CLAIM_LIMITS, TOPIC_HOLDER_PURPOSE,
+    TOTAL_CLAIMS,
+};
+use cosmwasm_std::{
+    Addr, Api, Binary, CanonicalAddr, Deps, DepsMut, Env, Response, StdError, StdResult, Storage,
+    Uint128, Uint64, VerificationError,
+};
+use registery::claim_topics::msg::ClaimTopicStatus;
+use registery::claim_topics::QueryMsg as ClaimTopicsQueryMsg;
+use ripemd::Ripemd160;
+use roles::owner_roles::msg::{IsOwnerResponse, OwnerRole};
+use roles::owner_roles::QueryMsg as OwnerRolesQueryMsg;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
+/// The admin, which is the owner unless one was set.
+pub fn load_admin(storage: &dyn Storage) -> StdResult<Addr> {
+    match ADMIN.may_load(storage)? {
+        Some(admin) => Ok(admin),
+        None => OWNER.load(storage),
+    }
+}
+
+/// Check that `sender` is the admin.
+pub fn check_admin(deps: &DepsMut, sender: &Addr) -> Result<(), ContractError> {
+    let admin = load_admin(deps.storage).map_err(|e| ContractError::LoadError {
+        entity: "admin".to_string(),
+        reason: e.to_string(),
+    })?;
+    if *sender != admin {
+        return Err(ContractError::Unauthorized {
+            reason: "Only the admin can perform this operation".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Check that `sender` holds a key of type `required_key`, recording the key's use.
 pub fn check_key_authorization(
-    deps: &DepsMut,
+    deps: &mut DepsMut,
+    env: &Env,
     sender: &Addr,
     required_key: KeyType,
 ) -> Result<(), ContractError> {
@@ -17,7 +60,7 @@ pub fn check_key_authorization(
         })?;
 
     // Load the Vec<Key> for the owner address
-    let keys = KEYS
+    let mut keys = KEYS
         .load(deps.storage, &owner)
         .map_err(|e| ContractError::LoadError {
             entity: "keys".to_string(),
@@ -25,17 +68,570 @@ pub fn check_key_authorization(
         })?;
 
     // Check if the sender is the owner and has the required key type
+    let Some(key) = keys
+        .iter_mut()
+        .find(|key| key.owner == *sender && key.has_purpose(&required_key))
+    else {
+        return Err(ContractError::Unauthorized {
+            reason: format!("Sender lacks required key type: {:?}", required_key),
+        });
+    };
+    if !key.active {
+        return Err(ContractError::Unauthorized {
+            reason: format!("Sender's {} is inactive", required_key),
+        });
+    }
+
+    key.last_used = Some(Uint64::new(env.block.time.seconds()));
+    KEYS.save(deps.storage, &owner, &keys)
+        .map_err(|e| ContractError::SaveError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+    Ok(())
+}
+
+/// SHA-256 of the JSON encoding of `msg`
+pub fn message_hash(msg: &impl serde::Serialize) -> Result<Binary, ContractError> {
+    let msg = cosmwasm_std::to_json_vec(msg).map_err(|e| ContractError::SerializationError {
+        reason: e.to_string(),
+    })?;
+    Ok(Binary::from(Sha256::digest(msg).as_slice()))
+}
+
+/// Run `execute` at most once per sender and idempotency key, given along with the hash of
+/// the message it came with (see `message_hash`). Repeating the key with the same message
+/// is a no-op that returns the attributes of the original response, repeating it with a
+/// different message is an error.
+pub fn with_idempotency_key(
+    mut deps: DepsMut,
+    sender: &Addr,
+    idempotency_key: Option<(String, Binary)>,
+    execute: impl FnOnce(DepsMut) -> Result<Response, ContractError>,
+) -> Result<Response, ContractError> {
+    let Some((idempotency_key, msg_hash)) = idempotency_key else {
+        return execute(deps);
+    };
+
+    let load_error = |e: cosmwasm_std::StdError| ContractError::LoadError {
+        entity: "idempotency key".to_string(),
+        reason: e.to_string(),
+    };
+    let seen_hash = IDEMPOTENCY_KEY_HASHES
+        .may_load(deps.storage, (sender, &idempotency_key))
+        .map_err(load_error)?;
+    if seen_hash.is_some_and(|seen_hash| seen_hash != msg_hash) {
+        return Err(ContractError::IdempotencyKeyMismatch { idempotency_key });
+    }
+    let seen = IDEMPOTENCY_KEYS
+        .may_load(deps.storage, (sender, &idempotency_key))
+        .map_err(load_error)?;
+    if let Some(attributes) = seen {
+        return Ok(Response::new()
+            .add_attributes(attributes)
+            .add_attribute("idempotent_replay", "true"));
+    }
+
+    let res = execute(deps.branch())?;
+
+    let save_error = |e: cosmwasm_std::StdError| ContractError::SaveError {
+        entity: "idempotency key".to_string(),
+        reason: e.to_string(),
+    };
+    IDEMPOTENCY_KEYS
+        .save(deps.storage, (sender, &idempotency_key), &res.attributes)
+        .map_err(save_error)?;
+    IDEMPOTENCY_KEY_HASHES
+        .save(deps.storage, (sender, &idempotency_key), &msg_hash)
+        .map_err(save_error)?;
+    IDEMPOTENCY_KEY_QUEUE
+        .push_back(deps.storage, &(sender.clone(), idempotency_key))
+        .map_err(save_error)?;
+    // Forget the oldest key once the cap is exceeded
+    if IDEMPOTENCY_KEY_QUEUE
+        .len(deps.storage)
+        .map_err(save_error)?
+        > MAX_IDEMPOTENCY_KEYS
+    {
+        if let Some((sender, key)) = IDEMPOTENCY_KEY_QUEUE
+            .pop_front(deps.storage)
+            .map_err(save_error)?
+        {
+            IDEMPOTENCY_KEYS.remove(deps.storage, (&sender, &key));
+            IDEMPOTENCY_KEY_HASHES.remove(deps.storage, (&sender, &key));
+        }
+    }
+
+    Ok(res)
+}
+
+/// Ensure `sender` holds the ComplianceManager role in the configured owner roles contract.
+pub fn check_compliance_manager(deps: &DepsMut, sender: &Addr) -> Result<(), ContractError> {
+    let config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    let Some(owner_roles) = config.owner_roles else {
+        return Err(ContractError::Unauthorized {
+            reason: "No owner roles contract configured".to_string(),
+        });
+    };
+
+    let res: IsOwnerResponse = deps
+        .querier
+        .query_wasm_smart(
+            &owner_roles,
+            &OwnerRolesQueryMsg::IsOwner {
+                role: OwnerRole::ComplianceManager,
+                owner: sender.clone(),
+            },
+        )
+        .map_err(|e| ContractError::CrossContractQueryFailed {
+            contract: owner_roles.to_string(),
+            query: "IsOwner".to_string(),
+            reason: e.to_string(),
+        })?;
+    if !res.is_owner {
+        return Err(ContractError::Unauthorized {
+            reason: format!("Sender lacks role: {}", OwnerRole::ComplianceManager),
+        });
+    }
+
+    Ok(())
+}
+
+/// Ensure a claim signed over the hash of its data carries the data it commits to.
+pub fn check_data_hash(claim: &Claim) -> Result<(), ContractError> {
+    let Some(data_hash) = &claim.data_hash else {
+        return Ok(());
+    };
+
+    if Sha256::digest(&claim.data).as_slice() != data_hash.as_slice() {
+        return Err(ContractError::InvalidDataHash {
+            reason: "Data hash does not match the SHA-256 of the claim data".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Ensure the claim's URI and data stay within the bounds of what a claim stores.
+pub fn check_claim_size(claim: &Claim, max_data_bytes: Option<u32>) -> Result<(), ContractError> {
+    if claim.uri.len() > MAX_CLAIM_URI_BYTES {
+        return Err(ContractError::ClaimUriTooLong {
+            size: claim.uri.len(),
+            max: MAX_CLAIM_URI_BYTES,
+        });
+    }
+    if !CLAIM_URI_SCHEMES
+        .iter()
+        .any(|scheme| claim.uri.starts_with(scheme))
+    {
+        return Err(ContractError::UnsupportedClaimUriScheme {
+            schemes: CLAIM_URI_SCHEMES.join(", "),
+        });
+    }
+    let max = max_data_bytes.unwrap_or(DEFAULT_MAX_CLAIM_DATA_BYTES);
+    if claim.data.len() > max as usize {
+        return Err(ContractError::ClaimDataTooLarge {
+            size: claim.data.len(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Ensure a Merkle root committed by the claim is a SHA-256 hash.
+pub fn check_data_root(claim: &Claim) -> Result<(), ContractError> {
+    match &claim.data_root {
+        Some(data_root) if data_root.len() != 32 => Err(ContractError::InvalidDataRoot {
+            reason: format!("Expected a 32 byte root, got {} bytes", data_root.len()),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Root of the Merkle tree containing `leaf`, given the sibling hashes on its path from the
+/// bottom up. Leaves are hashed as SHA-256(0x00 || leaf) and inner nodes as
+/// SHA-256(0x01 || lower || higher) of their sorted children, so proofs carry no positions.
+/// `None` when a sibling isn't a 32 byte hash.
+pub fn merkle_root(leaf: &[u8], proof: &[Binary]) -> Option<[u8; 32]> {
+    let mut node: [u8; 32] = Sha256::new()
+        .chain_update([0x00])
+        .chain_update(leaf)
+        .finalize()
+        .into();
+    for sibling in proof {
+        let sibling: [u8; 32] = sibling.as_slice().try_into().ok()?;
+        let (lower, higher) = if node <= sibling {
+            (node, sibling)
+        } else {
+            (sibling, node)
+        };
+        node = Sha256::new()
+            .chain_update([0x01])
+            .chain_update(lower)
+            .chain_update(higher)
+            .finalize()
+            .into();
+    }
+    Some(node)
+}
+
+/// Ensure the claim's issuer is on the topic's allowed issuers list, if the topic has one.
+pub fn check_topic_allowed_issuer(deps: &DepsMut, claim: &Claim) -> Result<(), ContractError> {
+    if claim.namespace.is_some() {
+        return Ok(());
+    }
+    let allowed_issuers = TOPIC_ALLOWED_ISSUERS
+        .may_load(deps.storage, claim.topic.u128())
+        .map_err(|e| ContractError::LoadError {
+            entity: "topic allowed issuers".to_string(),
+            reason: e.to_string(),
+        })?;
+    match allowed_issuers {
+        Some(allowed_issuers) if !allowed_issuers.contains(&claim.issuer) => {
+            Err(ContractError::IssuerNotAllowed {
+                issuer: claim.issuer.to_string(),
+                claim_topic: claim.topic,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Ensure a claim names the user it was issued for, and is added for that user
+pub fn check_claim_subject(claim: &Claim, user_addr: &Addr) -> Result<(), ContractError> {
+    match &claim.subject {
+        None => Err(ContractError::MissingReplayProtection {
+            field: "subject".to_string(),
+        }),
+        Some(subject) if subject != user_addr => Err(ContractError::ClaimSubjectMismatch {
+            subject: subject.to_string(),
+            user_addr: user_addr.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Ensure a claim on a topic requiring a third party wasn't issued by its holder or the owner
+pub fn check_self_attestation(
+    storage: &dyn Storage,
+    claim: &Claim,
+    user_addr: &Addr,
+) -> Result<(), ContractError> {
+    let config = CONFIG
+        .may_load(storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    if !config.no_self_attest_topics.contains(&claim.topic) {
+        return Ok(());
+    }
+    let owner = OWNER
+        .may_load(storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    if claim.issuer == *user_addr || owner.as_ref() == Some(&claim.issuer) {
+        return Err(ContractError::SelfAttestationNotAllowed { topic: claim.topic });
+    }
+    Ok(())
+}
+
+/// Record the claim's nonce as the issuer's last, rejecting nonces that aren't above it
+pub fn consume_claim_nonce(storage: &mut dyn Storage, claim: &Claim) -> Result<(), ContractError> {
+    let Some(nonce) = claim.nonce else {
+        return Err(ContractError::MissingReplayProtection {
+            field: "nonce".to_string(),
+        });
+    };
+    let nonce = nonce.u64();
+    if let Some(last_nonce) =
+        CLAIM_NONCES
+            .may_load(storage, &claim.issuer)
+            .map_err(|e| ContractError::LoadError {
+                entity: "claim nonces".to_string(),
+                reason: e.to_string(),
+            })?
+    {
+        if nonce <= last_nonce {
+            return Err(ContractError::StaleClaimNonce { nonce, last_nonce });
+        }
+    }
+    CLAIM_NONCES
+        .save(storage, &claim.issuer, &nonce)
+        .map_err(|e| ContractError::SaveError {
+            entity: "claim nonces".to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Ensure `user_addr` holds the key purpose the claim's topic requires of its holders, if any.
+pub fn check_holder_purpose(
+    deps: &DepsMut,
+    user_addr: &Addr,
+    claim: &Claim,
+) -> Result<(), ContractError> {
+    if claim.namespace.is_some() {
+        return Ok(());
+    }
+    let Some(required) = TOPIC_HOLDER_PURPOSE
+        .may_load(deps.storage, claim.topic.u128())
+        .map_err(|e| ContractError::LoadError {
+            entity: "topic holder purpose".to_string(),
+            reason: e.to_string(),
+        })?
+    else {
+        return Ok(());
+    };
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
+    if keys
+        .iter()
+        .any(|key| key.owner == *user_addr && key.has_purpose(&required))
+    {
+        Ok(())
+    } else {
+        Err(ContractError::MissingHolderPurpose {
+            holder: user_addr.to_string(),
+            key_type: required.to_string(),
+            claim_topic: claim.topic,
+        })
+    }
+}
+
+/// Ensure the claim's topic has not been deprecated in the configured claim topics registry.
+pub fn check_claim_topic_open(deps: &DepsMut, claim: &Claim) -> Result<(), ContractError> {
+    // The registry only knows topics of the default namespace
+    if claim.namespace.is_some() {
+        return Ok(());
+    }
+
+    let config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    let Some(registry) = config.claim_topics_registry else {
+        return Ok(());
+    };
+
+    let status: ClaimTopicStatus = deps
+        .querier
+        .query_wasm_smart(
+            &registry,
+            &ClaimTopicsQueryMsg::GetClaimTopicStatus { topic: claim.topic },
+        )
+        .map_err(|e| ContractError::CrossContractQueryFailed {
+            contract: registry.to_string(),
+            query: "GetClaimTopicStatus".to_string(),
+            reason: e.to_string(),
+        })?;
+    if status == ClaimTopicStatus::Deprecated {
+        return Err(ContractError::ClaimTopicDeprecated {
+            claim_topic: claim.topic,
+        });
+    }
+
+    Ok(())
+}
+
+/// Ensure the identity a relationship claim links to exists.
+pub fn check_related_identity(deps: &DepsMut, claim: &Claim) -> Result<(), ContractError> {
+    let Some(related_identity) = &claim.related_identity else {
+        return Ok(());
+    };
+
+    let related_identity = deps
+        .api
+        .addr_validate(related_identity.as_str())
+        .map_err(|e| ContractError::InvalidAddress {
+            reason: format!("Invalid related identity '{}': {}", related_identity, e),
+        })?;
+    // Identities are contracts, so the related identity must be deployed
+    deps.querier
+        .query_wasm_contract_info(&related_identity)
+        .map_err(|_| ContractError::IdentityNotFound {
+            owner: related_identity.to_string(),
+        })?;
+
+    Ok(())
+}
+
+/// Ensure an encrypted claim is addressed to an existing EncryptionKey.
+pub fn check_encryption_recipient(deps: &DepsMut, claim: &Claim) -> Result<(), ContractError> {
+    let Some(envelope) = &claim.encryption else {
+        return Ok(());
+    };
+
+    let owner = OWNER
+        .load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "owner".to_string(),
+            reason: e.to_string(),
+        })?;
+    let keys = KEYS
+        .load(deps.storage, &owner)
+        .map_err(|e| ContractError::LoadError {
+            entity: "keys".to_string(),
+            reason: e.to_string(),
+        })?;
 
     if keys
         .iter()
-        .any(|key| key.key_type == required_key && key.owner == *sender)
+        .any(|key| key.owner == envelope.recipient && key.has_purpose(&KeyType::EncryptionKey))
     {
         Ok(())
+    } else {
+        Err(ContractError::KeyNotFound {
+            key_type: KeyType::EncryptionKey.to_string(),
+            owner: envelope.recipient.to_string(),
+        })
+    }
+}
+
+/// Weight of the active ManagementKeys held by `holder`, or of all of them when `None`
+pub fn management_weight(keys: &[Key], holder: Option<&Addr>) -> u32 {
+    keys.iter()
+        .filter(|key| key.has_purpose(&KeyType::ManagementKey) && key.active)
+        .filter(|key| holder.is_none_or(|holder| key.owner == *holder))
+        .map(|key| key.weight)
+        .sum()
+}
+
+/// Ensure the identity is still manageable after a key change: the remaining
+/// ManagementKeys must together meet the management threshold.
+pub fn ensure_management_weight_remains(keys: &[Key], threshold: u32) -> Result<(), ContractError> {
+    let remaining = management_weight(keys, None);
+    if remaining >= threshold.max(1) {
+        Ok(())
     } else {
         Err(ContractError::Unauthorized {
-            reason: format!("Sender lacks required key type: {:?}", required_key),
+            reason: format!(
+                "Remaining Management Keys weigh {} but the management threshold is {}",
+                remaining, threshold
+            ),
+        })
+    }
+}
+
+/// Ensure the identity holds no more than `max_total_keys` keys, each purpose of a key
+/// counting as one.
+pub fn ensure_total_keys_within(
+    keys: &[Key],
+    max_total_keys: Option<u32>,
+) -> Result<(), ContractError> {
+    let total: usize = keys.iter().map(|key| key.purposes.len()).sum();
+    match max_total_keys {
+        Some(max) if total > max as usize => Err(ContractError::TotalKeysExceeded { max }),
+        _ => Ok(()),
+    }
+}
+
+/// Grant `purpose` to `holder`'s key, creating the key if `holder` has none yet. Several
+/// holders may share management, but every other purpose is held by a single key.
+pub fn grant_key_purpose(
+    keys: &mut Vec<Key>,
+    holder: &Addr,
+    purpose: KeyType,
+) -> Result<(), ContractError> {
+    if keys.iter().any(|key| {
+        key.has_purpose(&purpose) && (purpose != KeyType::ManagementKey || key.owner == *holder)
+    }) {
+        return Err(ContractError::KeyAlreadyExists {
+            key_type: purpose.to_string(),
+        });
+    }
+
+    match keys.iter_mut().find(|key| key.owner == *holder) {
+        Some(key) => key.purposes.push(purpose),
+        None => keys.push(Key {
+            owner: holder.clone(),
+            purposes: vec![purpose],
+            last_used: None,
+            weight: 1,
+            active: true,
+        }),
+    }
+    Ok(())
+}
+
+/// Update the count of claims held across all users after one was stored (`stored`) or
+/// removed. Storing one beyond the configured `max_total_claims` is refused.
+pub fn track_total_claims(storage: &mut dyn Storage, stored: bool) -> Result<(), ContractError> {
+    let total = TOTAL_CLAIMS
+        .may_load(storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "total claims".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
+    let total = if stored {
+        let config = CONFIG
+            .may_load(storage)
+            .map_err(|e| ContractError::LoadError {
+                entity: "config".to_string(),
+                reason: e.to_string(),
+            })?
+            .unwrap_or_default();
+        if let Some(max) = config.max_total_claims.filter(|max| total >= *max) {
+            return Err(ContractError::TotalClaimsExceeded { max });
+        }
+        total + 1
+    } else {
+        total.saturating_sub(1)
+    };
+    TOTAL_CLAIMS
+        .save(storage, &total)
+        .map_err(|e| ContractError::SaveError {
+            entity: "total claims".to_string(),
+            reason: e.to_string(),
         })
+}
+
+/// Most claims of `topic` a user may hold: the topic's own limit, else the identity-wide one
+pub fn topic_claim_limit(storage: &dyn Storage, topic: Uint128) -> StdResult<u32> {
+    if let Some(max) = TOPIC_CLAIM_LIMITS.may_load(storage, topic.u128())? {
+        return Ok(max);
     }
+    Ok(CONFIG
+        .may_load(storage)?
+        .unwrap_or_default()
+        .max_claims_per_topic
+        .unwrap_or(DEFAULT_MAX_CLAIMS_PER_TOPIC))
+}
+
+/// Entry identifying `claim_id` in an issuer's revocation list
+pub fn claim_id_hash(claim_id: &str) -> Binary {
+    Binary::from(Sha256::digest(claim_id.as_bytes()).as_slice())
+}
+
+pub fn is_revoked(deps: Deps, issuer: &Addr, claim_id: &str) -> StdResult<bool> {
+    if REVOKED_CLAIMS.has(deps.storage, claim_id) {
+        return Ok(true);
+    }
+    let revoked = CRL.may_load(deps.storage, issuer)?.unwrap_or_default();
+    Ok(revoked.contains(&claim_id_hash(claim_id)))
 }
 
 pub fn generate_claim_id(claim: &mut Claim) {
@@ -45,8 +641,89 @@ pub fn generate_claim_id(claim: &mut Claim) {
     hasher.update(claim.issuer.as_bytes());
     hasher.update(&claim.data);
     hasher.update(claim.uri.as_bytes());
-    // let id = hex::encode(hasher.finalize());
-    // claim.id = Some(id);
+    if let Some(namespace) = &claim.namespace {
+        hasher.update(namespace.as_bytes());
+    }
+    let id = hex::encode(hasher.finalize());
+    claim.id = Some(id);
+}
+
+/// Check many claims as `AddClaim` would, returning the rejected ones by index.
+///
+/// Gas: the config is loaded once and each topic's allowed issuers at most once, so the
+/// cost is dominated by one secp256k1 verification per claim, plus hashing proportional
+/// to each claim's signed data (see `data_hash` for keeping that small).
+/// Checks that need other contracts, like topic deprecation, are not covered.
+pub fn verify_claims_batch(
+    deps: Deps,
+    env: &Env,
+    claims_with_keys: &[(Claim, Binary)],
+) -> StdResult<Vec<ClaimRejection>> {
+    if claims_with_keys.len() > MAX_CLAIMS_PER_BATCH {
+        return Err(StdError::generic_err(format!(
+            "At most {} claims can be verified per batch",
+            MAX_CLAIMS_PER_BATCH
+        )));
+    }
+
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let mut allowed_issuers: HashMap<u128, Option<Vec<Addr>>> = HashMap::new();
+    let mut rejections = vec![];
+    for (index, (claim, public_key)) in claims_with_keys.iter().enumerate() {
+        let topic = claim.topic.u128();
+        let topic_issuers = match allowed_issuers.entry(topic) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(TOPIC_ALLOWED_ISSUERS.may_load(deps.storage, topic)?)
+            }
+        };
+
+        let reason = if let Err(e) = check_claim_size(claim, config.max_claim_data_bytes)
+            .and_then(|_| check_data_hash(claim))
+        {
+            Some(e.to_string())
+        } else if claim.namespace.is_none()
+            && topic_issuers
+                .as_ref()
+                .is_some_and(|issuers| !issuers.contains(&claim.issuer))
+        {
+            Some(
+                ContractError::IssuerNotAllowed {
+                    issuer: claim.issuer.to_string(),
+                    claim_topic: claim.topic,
+                }
+                .to_string(),
+            )
+        } else {
+            let message_hash = claim_message_hash(claim, &config.signature_encoding);
+            match verify_signature(
+                deps.api,
+                &claim.signature_scheme,
+                &message_hash,
+                &claim.signature,
+                public_key,
+            ) {
+                Ok(true) => check_issuer_key(
+                    deps,
+                    env,
+                    &claim.issuer,
+                    &claim.signature_scheme,
+                    public_key,
+                )
+                .err()
+                .map(|e| e.to_string()),
+                Ok(false) => Some("Signature verification failed".to_string()),
+                Err(e) => Some(e.to_string()),
+            }
+        };
+        if let Some(reason) = reason {
+            rejections.push(ClaimRejection {
+                index: index as u32,
+                reason,
+            });
+        }
+    }
+    Ok(rejections)
 }
 
 pub fn verify_claim_signature(
@@ -54,19 +731,112 @@ pub fn verify_claim_signature(
     claim: &Claim,
     public_key: Binary,
 ) -> Result<(), ContractError> {
-    // Hash the claim data (excluding signature)
-    let message_hash = hash_claim_without_signature(claim);
+    let config = CONFIG
+        .may_load(deps.storage)
+        .map_err(|e| ContractError::LoadError {
+            entity: "config".to_string(),
+            reason: e.to_string(),
+        })?
+        .unwrap_or_default();
 
-    // Retrieve the signature from the claim
-    let signature = claim.signature.as_slice();
+    // Hash the claim data (excluding signature) as the issuer signed it
+    let message_hash = claim_message_hash(claim, &config.signature_encoding);
 
-    // Use cosmwasm_std::secp256k1_verify for signature verification
-    let valid = deps
-        .api
-        .secp256k1_verify(message_hash.as_slice(), signature, public_key.as_slice())
-        .map_err(|e| ContractError::InvalidIssuerSignature {
+    verify_issuer_signature(
+        deps,
+        &claim.signature_scheme,
+        &message_hash,
+        &claim.signature,
+        &public_key,
+    )
+}
+
+/// Ensure `public_key` may sign for `issuer`: it must be the key the issuer's signer contract
+/// currently authorizes when the issuer delegated its claim signing, the issuer's own account
+/// key otherwise.
+pub fn check_issuer_key(
+    deps: Deps,
+    env: &Env,
+    issuer: &Addr,
+    scheme: &SignatureScheme,
+    public_key: &Binary,
+) -> Result<(), ContractError> {
+    let Some(signer_contract) = SIGNER_CONTRACTS
+        .may_load(deps.storage, issuer)
+        .map_err(|e| ContractError::LoadError {
+            entity: "signer contract".to_string(),
+            reason: e.to_string(),
+        })?
+    else {
+        return match key_address(deps, scheme, public_key) {
+            Ok(signer) if signer == *issuer => Ok(()),
+            _ => Err(ContractError::IssuerKeyMismatch {}),
+        };
+    };
+
+    let signing_key: SigningKeyResponse = deps
+        .querier
+        .query_wasm_smart(
+            &signer_contract,
+            &SignerContractQueryMsg::SigningKey {
+                issuer: issuer.clone(),
+            },
+        )
+        .map_err(|e| ContractError::CrossContractQueryFailed {
+            contract: signer_contract.to_string(),
+            query: "SigningKey".to_string(),
             reason: e.to_string(),
         })?;
+    if signing_key.public_key != *public_key {
+        return Err(ContractError::InvalidIssuerSignature {
+            reason: format!(
+                "Key is not authorized by signer contract {}",
+                signer_contract
+            ),
+        });
+    }
+    if let Some(valid_until) = signing_key.valid_until {
+        if env.block.time.seconds() > valid_until.u64() {
+            return Err(ContractError::InvalidIssuerSignature {
+                reason: format!(
+                    "Signing key authorized by {} expired at {}",
+                    signer_contract, valid_until
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify a signature over `message_hash` with a key of the given scheme.
+pub fn verify_signature(
+    api: &dyn Api,
+    scheme: &SignatureScheme,
+    message_hash: &[u8; 32],
+    signature: &[u8],
+    public_key: &[u8],
+) -> Result<bool, VerificationError> {
+    match scheme {
+        SignatureScheme::Secp256k1 => api.secp256k1_verify(message_hash, signature, public_key),
+        SignatureScheme::Ed25519 => api.ed25519_verify(message_hash, signature, public_key),
+    }
+}
+
+/// Verify a signature by a claim issuer over `message_hash`.
+pub fn verify_issuer_signature(
+    deps: &DepsMut,
+    scheme: &SignatureScheme,
+    message_hash: &[u8; 32],
+    signature: &Binary,
+    public_key: &Binary,
+) -> Result<(), ContractError> {
+    let valid =
+        verify_signature(deps.api, scheme, message_hash, signature, public_key).map_err(|e| {
+            ContractError::InvalidIssuerSignature {
+                reason: e.to_string(),
+            }
+        })?;
 
     if !valid {
         return Err(ContractError::InvalidIssuerSignature {
@@ -77,11 +847,208 @@ pub fn verify_claim_signature(
     Ok(())
 }
 
+// The fields of a claim its issuer signs. Encoded as JSON, so every field is delimited and
+// an absent one reads as null: no field can be shifted into its neighbour.
+#[derive(Serialize)]
+struct SignedClaimPayload<'a> {
+    topic: Uint128,
+    issuer: &'a Addr,
+    // Only one of the two is signed, the hash standing in for data kept out of the message
+    data: Option<&'a Binary>,
+    data_hash: Option<&'a Binary>,
+    uri: &'a str,
+    valid_until: Option<Uint64>,
+    encryption: Option<&'a EncryptionEnvelope>,
+    related_identity: Option<&'a Addr>,
+    namespace: Option<&'a str>,
+    data_root: Option<&'a Binary>,
+    signature_scheme: &'a SignatureScheme,
+    subject: Option<&'a Addr>,
+    nonce: Option<Uint64>,
+}
+
+/// SHA-256 of the JSON encoding of the claim fields its issuer signs
 pub fn hash_claim_without_signature(claim: &Claim) -> [u8; 32] {
+    let payload = SignedClaimPayload {
+        topic: claim.topic,
+        issuer: &claim.issuer,
+        data: match claim.data_hash {
+            Some(_) => None,
+            None => Some(&claim.data),
+        },
+        data_hash: claim.data_hash.as_ref(),
+        uri: &claim.uri,
+        valid_until: claim.valid_until,
+        encryption: claim.encryption.as_ref(),
+        related_identity: claim.related_identity.as_ref(),
+        namespace: claim.namespace.as_deref(),
+        data_root: claim.data_root.as_ref(),
+        signature_scheme: &claim.signature_scheme,
+        subject: claim.subject.as_ref(),
+        nonce: claim.nonce,
+    };
+    // Serializing plain addresses, strings and numbers can't fail
+    let payload = cosmwasm_std::to_json_vec(&payload).unwrap_or_default();
+    Sha256::digest(payload).into()
+}
+
+/// Account address of a compressed secp256k1 public key, derived like Cosmos SDK accounts:
+/// RIPEMD-160 of its SHA-256, encoded with the chain's address prefix.
+pub fn pubkey_to_address(deps: Deps, public_key: &Binary) -> StdResult<Addr> {
+    if public_key.len() != 33 {
+        return Err(StdError::generic_err(format!(
+            "Expected a 33 byte compressed secp256k1 public key, got {} bytes",
+            public_key.len()
+        )));
+    }
+    let hash = Ripemd160::digest(Sha256::digest(public_key.as_slice()));
+    deps.api
+        .addr_humanize(&CanonicalAddr::from(hash.as_slice()))
+}
+
+/// Account address of a public key of the given scheme. Ed25519 keys are derived like
+/// Tendermint keys: the first 20 bytes of their SHA-256.
+pub fn key_address(deps: Deps, scheme: &SignatureScheme, public_key: &Binary) -> StdResult<Addr> {
+    match scheme {
+        SignatureScheme::Secp256k1 => pubkey_to_address(deps, public_key),
+        SignatureScheme::Ed25519 => {
+            if public_key.len() != 32 {
+                return Err(StdError::generic_err(format!(
+                    "Expected a 32 byte ed25519 public key, got {} bytes",
+                    public_key.len()
+                )));
+            }
+            let hash = Sha256::digest(public_key.as_slice());
+            deps.api.addr_humanize(&CanonicalAddr::from(&hash[..20]))
+        }
+    }
+}
+
+/// Hash that the issuer is expected to have signed under the given encoding.
+pub fn claim_message_hash(claim: &Claim, encoding: &SignatureEncoding) -> [u8; 32] {
+    encode_for_signing(hash_claim_without_signature(claim), &claim.issuer, encoding)
+}
+
+/// Hash the issuer signs to change the expiry of `claim`, stored on `identity`, to
+/// `valid_until`. It commits to the current expiry so the authorization can't be replayed.
+pub fn expiry_update_message_hash(
+    identity: &Addr,
+    claim: &Claim,
+    valid_until: Uint64,
+    encoding: &SignatureEncoding,
+) -> [u8; 32] {
     let mut hasher = Sha256::new();
-    hasher.update(claim.topic.to_string().as_bytes());
-    hasher.update(claim.issuer.as_bytes());
-    hasher.update(&claim.data);
-    hasher.update(claim.uri.as_bytes());
-    hasher.finalize().into()
+    hasher.update(identity.as_bytes());
+    hasher.update(claim.id.as_deref().unwrap_or_default().as_bytes());
+    if let Some(current) = claim.valid_until {
+        hasher.update(current.to_string().as_bytes());
+    }
+    hasher.update(valid_until.to_string().as_bytes());
+    encode_for_signing(hasher.finalize().into(), &claim.issuer, encoding)
+}
+
+/// Hash the issuer signs to let anyone add `claim` for `user_addr` on `identity`
+pub fn claim_permit_message_hash(
+    identity: &Addr,
+    claim: &Claim,
+    user_addr: &Addr,
+    encoding: &SignatureEncoding,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(identity.as_bytes());
+    hasher.update(hash_claim_without_signature(claim));
+    hasher.update(user_addr.as_bytes());
+    encode_for_signing(hasher.finalize().into(), &claim.issuer, encoding)
+}
+
+// What a key holder signs to run a query, encoded as JSON like `SignedClaimPayload`
+#[derive(Serialize)]
+struct SignedQuery<'a> {
+    identity: &'a Addr,
+    query: &'a QueryMsg,
+    expires_at: Uint64,
+}
+
+/// Hash a key holder signs to run `query` on `identity` until `expires_at`. The query is
+/// signed in full, its arguments included, with its own `auth` left unset.
+pub fn query_auth_message_hash(
+    identity: &Addr,
+    query: &QueryMsg,
+    signer: &Addr,
+    expires_at: Uint64,
+    encoding: &SignatureEncoding,
+) -> [u8; 32] {
+    let signed = SignedQuery {
+        identity,
+        query,
+        expires_at,
+    };
+    // Serializing a query message can't fail
+    let signed = cosmwasm_std::to_json_vec(&signed).unwrap_or_default();
+    encode_for_signing(Sha256::digest(signed).into(), signer, encoding)
+}
+
+/// Whether `auth` proves the query comes from a holder of a key of one of `purposes`.
+/// Queries can't be authenticated by their sender, so callers sign them instead.
+pub fn is_query_authorized(
+    deps: Deps,
+    env: &Env,
+    query: &QueryMsg,
+    auth: Option<&QueryAuth>,
+    purposes: &[KeyType],
+) -> StdResult<bool> {
+    let Some(auth) = auth else {
+        return Ok(false);
+    };
+    if env.block.time.seconds() > auth.expires_at.u64() {
+        return Ok(false);
+    }
+    let Ok(signer) = pubkey_to_address(deps, &auth.public_key) else {
+        return Ok(false);
+    };
+    let config = CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let message_hash = query_auth_message_hash(
+        &env.contract.address,
+        query,
+        &signer,
+        auth.expires_at,
+        &config.signature_encoding,
+    );
+    let valid = deps
+        .api
+        .secp256k1_verify(
+            &message_hash,
+            auth.signature.as_slice(),
+            auth.public_key.as_slice(),
+        )
+        .unwrap_or(false);
+    if !valid {
+        return Ok(false);
+    }
+
+    let owner = OWNER.load(deps.storage)?;
+    Ok(KEYS
+        .may_load(deps.storage, &owner)?
+        .unwrap_or_default()
+        .iter()
+        .any(|key| {
+            key.owner == signer && key.active && purposes.iter().any(|p| key.has_purpose(p))
+        }))
+}
+
+fn encode_for_signing(hash: [u8; 32], signer: &Addr, encoding: &SignatureEncoding) -> [u8; 32] {
+    match encoding {
+        SignatureEncoding::Raw => hash,
+        SignatureEncoding::Adr36 => Sha256::digest(adr36_sign_doc(signer, &hash)).into(),
+    }
+}
+
+// Canonical (sorted, compact) amino JSON of an ADR-36 `MsgSignData` sign doc
+fn adr36_sign_doc(signer: &Addr, data: &[u8]) -> Vec<u8> {
+    format!(
+        r#"{{"account_number":"0","chain_id":"","fee":{{"amount":[],"gas":"0"}},"memo":"","msgs":[{{"type":"sign/MsgSignData","value":{{"data":"{}","signer":"{}"}}}}],"sequence":"0"}}"#,
+        Binary::from(data).to_base64(),
+        signer
+    )
+    .into_bytes()
 }
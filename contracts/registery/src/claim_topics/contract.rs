@@ -1,14 +1,19 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+};
 use cw2::set_contract_version;
+use cw_storage_plus::Map;
 use roles::owner_roles::msg::OwnerRole;
-// use cw2::set_contract_version;
 
-use crate::claim_topics::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::claim_topics::msg::{ClaimTopicInfo, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::claim_topics::ContractError;
 
-use super::state::OWNER_ROLES_ADDRESS;
+use super::state::{
+    ClaimTopic, CLAIM_TOPICS, DEFAULT_MAX_TOPICS, MAX_TOPICS, OWNER_ROLES_ADDRESS, ROLE_CACHE,
+    ROLE_CACHE_EPOCH, TOPIC_COUNT,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:claim_topics";
@@ -23,6 +28,8 @@ pub fn instantiate(
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     OWNER_ROLES_ADDRESS.save(deps.storage, &msg.owner_roles_address)?;
+    MAX_TOPICS.save(deps.storage, &msg.max_topics.unwrap_or(DEFAULT_MAX_TOPICS))?;
+    TOPIC_COUNT.save(deps.storage, &0)?;
     Ok(Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("owner", info.sender))
@@ -30,18 +37,41 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     // checking with the owner role contract to ensure only authorized personnel
     // with a role of ClaimRegistryManager are allowed to execute the functions
-    execute::check_role(deps.as_ref(), info.sender, OwnerRole::ClaimRegistryManager)?;
+    execute::check_role_cached(
+        deps.branch(),
+        &env,
+        info.sender.clone(),
+        OwnerRole::ClaimRegistryManager,
+    )?;
 
     match msg {
-        ExecuteMsg::AddClaimTopic { topic } => execute::add_claim_topic(deps, topic),
-        ExecuteMsg::RemoveClaimTopic { topic } => execute::remove_claim_topic(deps, topic),
+        ExecuteMsg::AddClaimTopic {
+            topic,
+            info: topic_info,
+        } => execute::add_claim_topic(deps, info.sender, topic, topic_info),
+        ExecuteMsg::RemoveClaimTopic { topic } => {
+            execute::remove_claim_topic(deps, info.sender, topic)
+        }
+        ExecuteMsg::AddClaimTopics { topics } => {
+            execute::add_claim_topics(deps, info.sender, topics)
+        }
+        ExecuteMsg::RemoveClaimTopics { topics } => {
+            execute::remove_claim_topics(deps, info.sender, topics)
+        }
+        ExecuteMsg::DeprecateClaimTopic { topic } => {
+            execute::deprecate_claim_topic(deps, info.sender, topic)
+        }
+        ExecuteMsg::InvalidateRoleCache {} => execute::invalidate_role_cache(deps),
+        ExecuteMsg::SetOwnerRolesAddress { address } => {
+            execute::set_owner_roles_address(deps, address)
+        }
     }
 }
 
@@ -51,14 +81,69 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::IsClaimTopicValid { topic } => {
             to_json_binary(&query::is_claim_topic_valid(deps, topic)?)
         }
+        QueryMsg::GetClaimTopicStatus { topic } => {
+            to_json_binary(&query::get_claim_topic_status(deps, topic)?)
+        }
+        QueryMsg::GetClaimTopicInfo { topic } => {
+            to_json_binary(&query::get_claim_topic_info(deps, topic)?)
+        }
+        QueryMsg::GetClaimTopics { start_after, limit } => {
+            to_json_binary(&query::get_claim_topics(deps, start_after, limit)?)
+        }
+        QueryMsg::GetClaimTopicCount {} => to_json_binary(&query::get_claim_topic_count(deps)?),
     }
 }
 
-pub mod execute {
-    use crate::claim_topics::state::CLAIM_TOPICS;
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let current_version = cw2::get_contract_version(deps.storage)?;
+    if current_version.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContract {
+            expected: CONTRACT_NAME.to_string(),
+            actual: current_version.contract,
+        });
+    }
 
+    // Topics stored before they had metadata only recorded whether they were active.
+    // Entries already in the new shape don't read as a bool and are left alone.
+    let legacy_topics: Map<u128, bool> = Map::new("claim_topics");
+    let legacy: Vec<(u128, bool)> = legacy_topics
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter_map(Result::ok)
+        .collect();
+    for (topic, active) in &legacy {
+        CLAIM_TOPICS.save(
+            deps.storage,
+            *topic,
+            &ClaimTopic {
+                info: ClaimTopicInfo::default(),
+                active: *active,
+            },
+        )?;
+    }
+
+    // Registries instantiated before topics were capped. Topics already above the cap stay,
+    // but no more can be added until enough are removed.
+    if !MAX_TOPICS.exists(deps.storage) {
+        MAX_TOPICS.save(deps.storage, &DEFAULT_MAX_TOPICS)?;
+    }
+    let topic_count = CLAIM_TOPICS
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .count() as u32;
+    TOPIC_COUNT.save(deps.storage, &topic_count)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", current_version.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("legacy_topics_migrated", legacy.len().to_string()))
+}
+
+pub mod execute {
     use super::*;
-    use cosmwasm_std::{to_json_binary, Addr, QueryRequest, Uint128, WasmQuery};
+    use cosmwasm_std::{to_json_binary, Addr, Event, QueryRequest, Storage, Uint128, WasmQuery};
     use roles::owner_roles::{msg::OwnerRole, QueryMsg};
 
     pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
@@ -69,50 +154,281 @@ pub mod execute {
             contract_addr: owner_roles.to_string(),
             msg: to_json_binary(&msg)?,
         });
-        let has_role: bool = deps.querier.query(&query)?;
+        let has_role: bool =
+            deps.querier
+                .query(&query)
+                .map_err(|e| ContractError::CrossContractQueryFailed {
+                    contract: owner_roles.to_string(),
+                    query: "IsOwner".to_string(),
+                    reason: e.to_string(),
+                })?;
         if !has_role {
             return Err(ContractError::Unauthorized {});
         }
         Ok(())
     }
 
-    pub fn add_claim_topic(deps: DepsMut, claim_topic: Uint128) -> Result<Response, ContractError> {
+    // Every change to a topic is reported in a `claim_topics/update` event, whose
+    // `operation` is "add", "remove" or "deprecate". Batches emit one event per topic.
+    fn topic_update_event(operation: &str, claim_topic: Uint128, by: &Addr) -> Event {
+        Event::new("claim_topics/update")
+            .add_attribute("operation", operation)
+            .add_attribute("topic", claim_topic.to_string())
+            .add_attribute("by", by.to_string())
+    }
+
+    // A single topic's update event, along with the same attributes on the response
+    fn topic_updated(action: &str, operation: &str, claim_topic: Uint128, by: Addr) -> Response {
+        Response::new()
+            .add_event(topic_update_event(operation, claim_topic, &by))
+            .add_attribute("action", action)
+            .add_attribute("topic", claim_topic.to_string())
+            .add_attribute("by", by)
+    }
+
+    // Counts `added` more topics as registered, refusing to go over the cap
+    fn count_added_topics(storage: &mut dyn Storage, added: u32) -> Result<(), ContractError> {
+        let count = TOPIC_COUNT.may_load(storage)?.unwrap_or_default();
+        let max_topics = MAX_TOPICS.may_load(storage)?.unwrap_or(DEFAULT_MAX_TOPICS);
+        let count = count
+            .checked_add(added)
+            .filter(|count| *count <= max_topics)
+            .ok_or(ContractError::TooManyTopics {})?;
+        TOPIC_COUNT.save(storage, &count)?;
+        Ok(())
+    }
+
+    fn count_removed_topics(storage: &mut dyn Storage, removed: u32) -> StdResult<()> {
+        let count = TOPIC_COUNT.may_load(storage)?.unwrap_or_default();
+        TOPIC_COUNT.save(storage, &count.saturating_sub(removed))
+    }
+
+    // `check_role`, skipping the query when the role was granted earlier in the same block.
+    // Grants are cached, refusals aren't.
+    pub fn check_role_cached(
+        deps: DepsMut,
+        env: &Env,
+        owner: Addr,
+        role: OwnerRole,
+    ) -> Result<(), ContractError> {
+        let epoch = ROLE_CACHE_EPOCH.may_load(deps.storage)?.unwrap_or_default();
+        let cached = (env.block.height, epoch);
+        let key = (&owner, role.to_string());
+        if ROLE_CACHE.may_load(deps.storage, key.clone())? == Some(cached) {
+            return Ok(());
+        }
+
+        check_role(deps.as_ref(), owner.clone(), role)?;
+        ROLE_CACHE.save(deps.storage, key, &cached)?;
+        Ok(())
+    }
+
+    pub fn invalidate_role_cache(deps: DepsMut) -> Result<Response, ContractError> {
+        bump_role_cache_epoch(deps.storage)?;
+        Ok(Response::new().add_attribute("action", "invalidate_role_cache"))
+    }
+
+    fn bump_role_cache_epoch(storage: &mut dyn Storage) -> StdResult<()> {
+        let epoch = ROLE_CACHE_EPOCH.may_load(storage)?.unwrap_or_default();
+        ROLE_CACHE_EPOCH.save(storage, &(epoch + 1))
+    }
+
+    pub fn add_claim_topic(
+        deps: DepsMut,
+        sender: Addr,
+        claim_topic: Uint128,
+        info: ClaimTopicInfo,
+    ) -> Result<Response, ContractError> {
         if CLAIM_TOPICS.has(deps.storage, claim_topic.into()) {
             return Err(ContractError::ClaimTopicsExists {});
         }
-        CLAIM_TOPICS.save(deps.storage, claim_topic.into(), &true)?;
+        count_added_topics(deps.storage, 1)?;
+        CLAIM_TOPICS.save(
+            deps.storage,
+            claim_topic.into(),
+            &ClaimTopic { info, active: true },
+        )?;
 
-        Ok(Response::new().add_attribute("action", "add_claim_topic"))
+        Ok(topic_updated("add_claim_topic", "add", claim_topic, sender))
     }
 
     pub fn remove_claim_topic(
         deps: DepsMut,
+        sender: Addr,
         claim_topic: Uint128,
     ) -> Result<Response, ContractError> {
         if !CLAIM_TOPICS.has(deps.storage, claim_topic.into()) {
             return Err(ContractError::ClaimTopicsNotFound {});
         }
         CLAIM_TOPICS.remove(deps.storage, claim_topic.into());
+        count_removed_topics(deps.storage, 1)?;
+
+        Ok(topic_updated(
+            "remove_claim_topic",
+            "remove",
+            claim_topic,
+            sender,
+        ))
+    }
+
+    pub fn add_claim_topics(
+        deps: DepsMut,
+        sender: Addr,
+        claim_topics: Vec<Uint128>,
+    ) -> Result<Response, ContractError> {
+        // Every topic is checked before any is saved, a topic repeated in the batch
+        // counting as already registered
+        for (i, claim_topic) in claim_topics.iter().enumerate() {
+            if CLAIM_TOPICS.has(deps.storage, claim_topic.u128())
+                || claim_topics[..i].contains(claim_topic)
+            {
+                return Err(ContractError::ClaimTopicsExists {});
+            }
+        }
+        let added =
+            u32::try_from(claim_topics.len()).map_err(|_| ContractError::TooManyTopics {})?;
+        count_added_topics(deps.storage, added)?;
+        for claim_topic in &claim_topics {
+            CLAIM_TOPICS.save(
+                deps.storage,
+                claim_topic.u128(),
+                &ClaimTopic {
+                    info: ClaimTopicInfo::default(),
+                    active: true,
+                },
+            )?;
+        }
 
-        Ok(Response::new().add_attribute("action", "remove_claim_topic"))
+        Ok(Response::new()
+            .add_events(
+                claim_topics
+                    .iter()
+                    .map(|claim_topic| topic_update_event("add", *claim_topic, &sender)),
+            )
+            .add_attribute("action", "add_claim_topics")
+            .add_attribute("count", claim_topics.len().to_string()))
+    }
+
+    pub fn remove_claim_topics(
+        deps: DepsMut,
+        sender: Addr,
+        claim_topics: Vec<Uint128>,
+    ) -> Result<Response, ContractError> {
+        // A topic repeated in the batch is already gone by its second removal
+        for (i, claim_topic) in claim_topics.iter().enumerate() {
+            if !CLAIM_TOPICS.has(deps.storage, claim_topic.u128())
+                || claim_topics[..i].contains(claim_topic)
+            {
+                return Err(ContractError::ClaimTopicsNotFound {});
+            }
+        }
+        for claim_topic in &claim_topics {
+            CLAIM_TOPICS.remove(deps.storage, claim_topic.u128());
+        }
+        count_removed_topics(deps.storage, claim_topics.len() as u32)?;
+
+        Ok(Response::new()
+            .add_events(
+                claim_topics
+                    .iter()
+                    .map(|claim_topic| topic_update_event("remove", *claim_topic, &sender)),
+            )
+            .add_attribute("action", "remove_claim_topics")
+            .add_attribute("count", claim_topics.len().to_string()))
+    }
+
+    pub fn set_owner_roles_address(
+        deps: DepsMut,
+        address: Addr,
+    ) -> Result<Response, ContractError> {
+        let address = deps.api.addr_validate(address.as_str())?;
+        OWNER_ROLES_ADDRESS.save(deps.storage, &address)?;
+        // Grants cached from the previous roles contract no longer hold
+        bump_role_cache_epoch(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_owner_roles_address")
+            .add_attribute("address", address))
+    }
+
+    pub fn deprecate_claim_topic(
+        deps: DepsMut,
+        sender: Addr,
+        claim_topic: Uint128,
+    ) -> Result<Response, ContractError> {
+        let mut topic = CLAIM_TOPICS
+            .may_load(deps.storage, claim_topic.into())?
+            .ok_or(ContractError::ClaimTopicsNotFound {})?;
+        if !topic.active {
+            return Err(ContractError::ClaimTopicDeprecated {});
+        }
+        topic.active = false;
+        CLAIM_TOPICS.save(deps.storage, claim_topic.into(), &topic)?;
+
+        Ok(topic_updated(
+            "deprecate_claim_topic",
+            "deprecate",
+            claim_topic,
+            sender,
+        ))
     }
 }
 pub mod query {
-    use cosmwasm_std::Uint128;
+    use cosmwasm_std::{StdError, Uint128};
+    use utils::pagination::{paginate, MAX_LIMIT};
 
-    use crate::claim_topics::state::CLAIM_TOPICS;
+    use crate::claim_topics::msg::ClaimTopicStatus;
 
     use super::*;
     pub fn is_claim_topic_valid(deps: Deps, topic: Uint128) -> StdResult<bool> {
         Ok(CLAIM_TOPICS.has(deps.storage, topic.into()))
     }
+
+    pub fn get_claim_topic_status(deps: Deps, topic: Uint128) -> StdResult<ClaimTopicStatus> {
+        Ok(match CLAIM_TOPICS.may_load(deps.storage, topic.into())? {
+            Some(ClaimTopic { active: true, .. }) => ClaimTopicStatus::Active,
+            Some(ClaimTopic { active: false, .. }) => ClaimTopicStatus::Deprecated,
+            None => ClaimTopicStatus::NotFound,
+        })
+    }
+
+    pub fn get_claim_topic_info(deps: Deps, topic: Uint128) -> StdResult<ClaimTopicInfo> {
+        CLAIM_TOPICS
+            .may_load(deps.storage, topic.into())?
+            .map(|claim_topic| claim_topic.info)
+            .ok_or_else(|| StdError::not_found(format!("claim topic {}", topic)))
+    }
+
+    pub fn get_claim_topics(
+        deps: Deps,
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Uint128>> {
+        Ok(paginate(
+            deps.storage,
+            &CLAIM_TOPICS,
+            start_after.map(|topic| topic.u128()),
+            Some(limit.unwrap_or(MAX_LIMIT)),
+        )?
+        .into_iter()
+        .map(|(topic, _)| Uint128::new(topic))
+        .collect())
+    }
+
+    pub fn get_claim_topic_count(deps: Deps) -> StdResult<u64> {
+        Ok(TOPIC_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::claim_topics::msg::ClaimTopicStatus;
     use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
-    use cosmwasm_std::{from_json, Addr, ContractResult, SystemResult, Uint128};
+    use cosmwasm_std::{from_json, Addr, ContractResult, Event, SystemResult, Uint128};
     use roles::owner_roles::msg::OwnerRole;
     use roles::owner_roles::QueryMsg;
 
@@ -120,6 +436,7 @@ mod tests {
         let owner_roles_address = Addr::unchecked("owner_roles_contract");
         let msg = InstantiateMsg {
             owner_roles_address: owner_roles_address.clone(),
+            max_topics: None,
         };
         let info = message_info(&Addr::unchecked("creator"), &[]);
         let _ = instantiate(deps, mock_env(), info, msg).unwrap();
@@ -153,6 +470,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -161,10 +479,25 @@ mod tests {
         let info = message_info(&Addr::unchecked("authorized_user"), &[]);
         let msg = ExecuteMsg::AddClaimTopic {
             topic: Uint128::new(1),
+            info: ClaimTopicInfo::default(),
         };
 
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(res.attributes, vec![("action", "add_claim_topic")]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "add_claim_topic"),
+                ("topic", "1"),
+                ("by", "authorized_user")
+            ]
+        );
+        assert_eq!(
+            res.events,
+            vec![Event::new("claim_topics/update")
+                .add_attribute("operation", "add")
+                .add_attribute("topic", "1")
+                .add_attribute("by", "authorized_user")]
+        );
 
         // Verify the topic was added
         let msg = crate::claim_topics::QueryMsg::IsClaimTopicValid {
@@ -192,6 +525,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -202,6 +536,7 @@ mod tests {
         // First, add a claim topic
         let msg = ExecuteMsg::AddClaimTopic {
             topic: Uint128::new(1),
+            info: ClaimTopicInfo::default(),
         };
         execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
@@ -210,7 +545,21 @@ mod tests {
             topic: Uint128::new(1),
         };
         let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(res.attributes, vec![("action", "remove_claim_topic")]);
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "remove_claim_topic"),
+                ("topic", "1"),
+                ("by", "authorized_user")
+            ]
+        );
+        assert_eq!(
+            res.events,
+            vec![Event::new("claim_topics/update")
+                .add_attribute("operation", "remove")
+                .add_attribute("topic", "1")
+                .add_attribute("by", "authorized_user")]
+        );
 
         // Verify the topic was removed
         let msg = crate::claim_topics::QueryMsg::IsClaimTopicValid {
@@ -220,4 +569,502 @@ mod tests {
         let is_valid: bool = from_json(res).unwrap();
         assert!(!is_valid);
     }
+
+    #[test]
+    fn deprecate_claim_topic() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        // Mock the owner roles contract query
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { msg, .. } => {
+                let parsed: QueryMsg = from_json(msg).unwrap();
+                match parsed {
+                    QueryMsg::IsOwner { .. } => {
+                        SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap()))
+                    }
+                    _ => panic!("Unexpected query"),
+                }
+            }
+            _ => panic!("Unexpected query type"),
+        });
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let topic = Uint128::new(1);
+        let status = |deps: Deps| -> ClaimTopicStatus {
+            let msg = crate::claim_topics::QueryMsg::GetClaimTopicStatus { topic };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // Unknown topics cannot be deprecated
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::DeprecateClaimTopic { topic },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ClaimTopicsNotFound {}));
+        assert_eq!(status(deps.as_ref()), ClaimTopicStatus::NotFound);
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::AddClaimTopic {
+                topic,
+                info: ClaimTopicInfo::default(),
+            },
+        )
+        .unwrap();
+        assert_eq!(status(deps.as_ref()), ClaimTopicStatus::Active);
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::DeprecateClaimTopic { topic },
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "deprecate_claim_topic"),
+                ("topic", "1"),
+                ("by", "authorized_user")
+            ]
+        );
+        assert_eq!(
+            res.events,
+            vec![Event::new("claim_topics/update")
+                .add_attribute("operation", "deprecate")
+                .add_attribute("topic", "1")
+                .add_attribute("by", "authorized_user")]
+        );
+        assert_eq!(status(deps.as_ref()), ClaimTopicStatus::Deprecated);
+
+        // A deprecated topic is still registered, so existing claims keep verifying
+        let msg = crate::claim_topics::QueryMsg::IsClaimTopicValid { topic };
+        let is_valid: bool = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(is_valid);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::DeprecateClaimTopic { topic },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ClaimTopicDeprecated {}));
+    }
+
+    #[test]
+    fn failing_owner_roles_query_is_attributed() {
+        let mut deps = mock_dependencies();
+        let owner_roles_address = setup_contract(deps.as_mut());
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Err("unknown variant `IsOwner`".to_string()))
+        });
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AddClaimTopic {
+                topic: Uint128::new(1),
+                info: ClaimTopicInfo::default(),
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::CrossContractQueryFailed {
+                contract, query, ..
+            } => {
+                assert_eq!(contract, owner_roles_address.to_string());
+                assert_eq!(query, "IsOwner");
+            }
+            err => panic!("Unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn batch_add_and_remove_claim_topics() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        deps.querier
+            .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let is_valid = |deps: Deps, topic: u128| -> bool {
+            let msg = crate::claim_topics::QueryMsg::IsClaimTopicValid {
+                topic: Uint128::new(topic),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        let add = |deps: DepsMut, topics: &[u128]| {
+            execute(
+                deps,
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::AddClaimTopics {
+                    topics: topics.iter().copied().map(Uint128::new).collect(),
+                },
+            )
+        };
+        let remove = |deps: DepsMut, topics: &[u128]| {
+            execute(
+                deps,
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::RemoveClaimTopics {
+                    topics: topics.iter().copied().map(Uint128::new).collect(),
+                },
+            )
+        };
+
+        let update_events = |operation: &str, topics: &[&str]| -> Vec<Event> {
+            topics
+                .iter()
+                .map(|topic| {
+                    Event::new("claim_topics/update")
+                        .add_attribute("operation", operation)
+                        .add_attribute("topic", *topic)
+                        .add_attribute("by", "authorized_user")
+                })
+                .collect()
+        };
+
+        let res = add(deps.as_mut(), &[1, 2]).unwrap();
+        assert_eq!(res.events, update_events("add", &["1", "2"]));
+        assert!(is_valid(deps.as_ref(), 1) && is_valid(deps.as_ref(), 2));
+
+        // A batch conflicting with a registered topic, or repeating one, adds nothing
+        let err = add(deps.as_mut(), &[3, 2]).unwrap_err();
+        assert!(matches!(err, ContractError::ClaimTopicsExists {}));
+        let err = add(deps.as_mut(), &[3, 3]).unwrap_err();
+        assert!(matches!(err, ContractError::ClaimTopicsExists {}));
+        assert!(!is_valid(deps.as_ref(), 3));
+
+        // Likewise a removal batch naming an unknown topic removes nothing
+        let err = remove(deps.as_mut(), &[1, 3]).unwrap_err();
+        assert!(matches!(err, ContractError::ClaimTopicsNotFound {}));
+        assert!(is_valid(deps.as_ref(), 1));
+
+        let res = remove(deps.as_mut(), &[1, 2]).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![("action", "remove_claim_topics"), ("count", "2")]
+        );
+        assert_eq!(res.events, update_events("remove", &["1", "2"]));
+        assert!(!is_valid(deps.as_ref(), 1) && !is_valid(deps.as_ref(), 2));
+    }
+
+    #[test]
+    fn list_claim_topics() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        deps.querier
+            .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for topic in (1..=35).rev() {
+            let msg = ExecuteMsg::AddClaimTopic {
+                topic: Uint128::new(topic),
+                info: ClaimTopicInfo::default(),
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::DeprecateClaimTopic {
+            topic: Uint128::new(2),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let list = |start_after: Option<u128>, limit: Option<u32>| -> Vec<Uint128> {
+            let msg = crate::claim_topics::QueryMsg::GetClaimTopics {
+                start_after: start_after.map(Uint128::new),
+                limit,
+            };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // Pages are in ascending order and hold 30 topics unless asked for fewer
+        let page = list(None, None);
+        assert_eq!(page, (1..=30).map(Uint128::new).collect::<Vec<_>>());
+        assert_eq!(
+            list(Some(30), None),
+            (31..=35).map(Uint128::new).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            list(Some(1), Some(2)),
+            vec![Uint128::new(2), Uint128::new(3)]
+        );
+        assert_eq!(list(None, Some(100)).len(), 30);
+
+        let msg = crate::claim_topics::QueryMsg::GetClaimTopicCount {};
+        let count: u64 = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(count, 35);
+    }
+
+    #[test]
+    fn claim_topic_metadata() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        deps.querier
+            .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let topic = Uint128::new(7);
+        let topic_info = ClaimTopicInfo {
+            name: "Sanctions clearance".to_string(),
+            description: "Holder was screened against sanctions lists".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AddClaimTopic {
+                topic,
+                info: topic_info.clone(),
+            },
+        )
+        .unwrap();
+
+        let msg = crate::claim_topics::QueryMsg::GetClaimTopicInfo { topic };
+        let stored: ClaimTopicInfo =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(stored, topic_info);
+        let msg = crate::claim_topics::QueryMsg::IsClaimTopicValid { topic };
+        let is_valid: bool = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(is_valid);
+
+        // Unknown topics have no metadata
+        let msg = crate::claim_topics::QueryMsg::GetClaimTopicInfo {
+            topic: Uint128::new(8),
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+
+        // A registration without metadata, as older clients send it, is accepted
+        let msg: ExecuteMsg = from_json(br#"{"add_claim_topic":{"topic":"8"}}"#).unwrap();
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = crate::claim_topics::QueryMsg::GetClaimTopicInfo {
+            topic: Uint128::new(8),
+        };
+        let stored: ClaimTopicInfo =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(stored, ClaimTopicInfo::default());
+    }
+
+    #[test]
+    fn migrate_topics_stored_as_bool() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.0.1").unwrap();
+        let legacy_topics: Map<u128, bool> = Map::new("claim_topics");
+        legacy_topics.save(deps.as_mut().storage, 1, &true).unwrap();
+        legacy_topics
+            .save(deps.as_mut().storage, 2, &false)
+            .unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res
+            .attributes
+            .contains(&cosmwasm_std::Attribute::new("legacy_topics_migrated", "2")));
+        let msg = crate::claim_topics::QueryMsg::GetClaimTopicCount {};
+        let count: u64 = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(count, 2);
+
+        let status = |deps: Deps, topic: u128| -> ClaimTopicStatus {
+            let msg = crate::claim_topics::QueryMsg::GetClaimTopicStatus {
+                topic: Uint128::new(topic),
+            };
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(status(deps.as_ref(), 1), ClaimTopicStatus::Active);
+        assert_eq!(status(deps.as_ref(), 2), ClaimTopicStatus::Deprecated);
+        let msg = crate::claim_topics::QueryMsg::GetClaimTopicInfo {
+            topic: Uint128::new(1),
+        };
+        let stored: ClaimTopicInfo =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(stored, ClaimTopicInfo::default());
+
+        // Migrating a registry of another contract is refused
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:other", "0.0.1").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidContract { .. }));
+    }
+
+    #[test]
+    fn topic_count_is_capped() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            owner_roles_address: Addr::unchecked("owner_roles_contract"),
+            max_topics: Some(3),
+        };
+        let info = message_info(&Addr::unchecked("creator"), &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        deps.querier
+            .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let add = |deps: DepsMut, topic: u128| {
+            let msg = ExecuteMsg::AddClaimTopic {
+                topic: Uint128::new(topic),
+                info: ClaimTopicInfo::default(),
+            };
+            execute(deps, mock_env(), info.clone(), msg)
+        };
+        let count = |deps: Deps| -> u64 {
+            let msg = crate::claim_topics::QueryMsg::GetClaimTopicCount {};
+            from_json(query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        add(deps.as_mut(), 1).unwrap();
+        // A batch that would go one over the cap adds nothing
+        let msg = ExecuteMsg::AddClaimTopics {
+            topics: vec![Uint128::new(2), Uint128::new(3), Uint128::new(4)],
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::TooManyTopics {}));
+        assert_eq!(count(deps.as_ref()), 1);
+
+        // Up to the cap is fine, one more is not
+        let msg = ExecuteMsg::AddClaimTopics {
+            topics: vec![Uint128::new(2), Uint128::new(3)],
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(count(deps.as_ref()), 3);
+        let err = add(deps.as_mut(), 4).unwrap_err();
+        assert!(matches!(err, ContractError::TooManyTopics {}));
+
+        // Removing a topic frees its place
+        let msg = ExecuteMsg::RemoveClaimTopic {
+            topic: Uint128::new(1),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(count(deps.as_ref()), 2);
+        add(deps.as_mut(), 4).unwrap();
+        assert_eq!(count(deps.as_ref()), 3);
+    }
+
+    #[test]
+    fn set_owner_roles_address() {
+        let mut deps = mock_dependencies();
+        let owner_roles_address = setup_contract(deps.as_mut());
+        let manager = deps.api.addr_make("manager");
+        let stranger = deps.api.addr_make("stranger");
+        let new_owner_roles = deps.api.addr_make("new_owner_roles");
+        let manager_clone = manager.clone();
+        deps.querier.update_wasm(move |query| match query {
+            cosmwasm_std::WasmQuery::Smart { msg, .. } => match from_json(msg).unwrap() {
+                QueryMsg::IsOwner { owner, .. } => SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(&(owner == manager_clone)).unwrap(),
+                )),
+                _ => panic!("Unexpected query"),
+            },
+            _ => panic!("Unexpected query type"),
+        });
+        let repoint = |address: Addr| ExecuteMsg::SetOwnerRolesAddress { address };
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&stranger, &[]),
+            repoint(new_owner_roles.clone()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        assert_eq!(
+            OWNER_ROLES_ADDRESS.load(&deps.storage).unwrap(),
+            owner_roles_address
+        );
+
+        // Malformed addresses are refused
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&manager, &[]),
+            repoint(Addr::unchecked("not-an-address")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&manager, &[]),
+            repoint(new_owner_roles.clone()),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                ("action", "set_owner_roles_address"),
+                ("address", new_owner_roles.as_str())
+            ]
+        );
+        assert_eq!(
+            OWNER_ROLES_ADDRESS.load(&deps.storage).unwrap(),
+            new_owner_roles
+        );
+    }
+
+    #[test]
+    fn role_grants_are_cached_within_a_block() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        let granted = Rc::new(Cell::new(true));
+        let role_queries = Rc::new(Cell::new(0));
+        let (querier_granted, querier_queries) = (granted.clone(), role_queries.clone());
+        deps.querier.update_wasm(move |_| {
+            querier_queries.set(querier_queries.get() + 1);
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&querier_granted.get()).unwrap(),
+            ))
+        });
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let add = |deps: DepsMut, env: Env, topic: u128| {
+            let msg = ExecuteMsg::AddClaimTopic {
+                topic: Uint128::new(topic),
+                info: ClaimTopicInfo::default(),
+            };
+            execute(deps, env, info.clone(), msg)
+        };
+        let env = mock_env();
+
+        // Only the first call of the block asks the owner roles contract
+        add(deps.as_mut(), env.clone(), 1).unwrap();
+        add(deps.as_mut(), env.clone(), 2).unwrap();
+        assert_eq!(role_queries.get(), 1);
+
+        // A revocation isn't seen within the block until the cache is invalidated
+        granted.set(false);
+        add(deps.as_mut(), env.clone(), 3).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::InvalidateRoleCache {},
+        )
+        .unwrap();
+        let err = add(deps.as_mut(), env.clone(), 4).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        assert_eq!(role_queries.get(), 2);
+
+        // Refusals aren't cached, and a new block asks again
+        granted.set(true);
+        add(deps.as_mut(), env.clone(), 4).unwrap();
+        assert_eq!(role_queries.get(), 3);
+        let mut next_block = env;
+        next_block.block.height += 1;
+        add(deps.as_mut(), next_block.clone(), 5).unwrap();
+        add(deps.as_mut(), next_block, 6).unwrap();
+        assert_eq!(role_queries.get(), 4);
+    }
 }
@@ -9,10 +9,23 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("CrossContractQueryFailed: {query} query to {contract} failed: {reason}")]
+    CrossContractQueryFailed {
+        contract: String,
+        query: String,
+        reason: String,
+    },
+
     #[error("ClaimTopicsExists")]
     ClaimTopicsExists {},
     #[error("ClaimTopicsNotFound")]
     ClaimTopicsNotFound {},
+    #[error("ClaimTopicDeprecated")]
+    ClaimTopicDeprecated {},
+    #[error("TooManyTopics")]
+    TooManyTopics {},
+    #[error("Cannot migrate from {actual}, expected {expected}")]
+    InvalidContract { expected: String, actual: String },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -5,4 +5,4 @@ pub mod msg;
 pub mod state;
 
 pub use self::error::ContractError;
-pub use self::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+pub use self::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
@@ -4,12 +4,42 @@ use cosmwasm_std::{Addr, Uint128};
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner_roles_address: Addr,
+    // Most topics registered at once, `state::DEFAULT_MAX_TOPICS` if `None`
+    #[serde(default)]
+    pub max_topics: Option<u32>,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
-    AddClaimTopic { topic: Uint128 },
-    RemoveClaimTopic { topic: Uint128 },
+    AddClaimTopic {
+        topic: Uint128,
+        #[serde(default)]
+        info: ClaimTopicInfo,
+    },
+    RemoveClaimTopic {
+        topic: Uint128,
+    },
+    // Register or remove several topics at once, without metadata; nothing changes if any
+    // of them fails
+    AddClaimTopics {
+        topics: Vec<Uint128>,
+    },
+    RemoveClaimTopics {
+        topics: Vec<Uint128>,
+    },
+    // Stops new claims under the topic while existing claims stay valid
+    DeprecateClaimTopic {
+        topic: Uint128,
+    },
+    // Forgets role grants cached earlier in the block, so a revocation applies right away
+    InvalidateRoleCache {},
+    // Repoints the registry at a redeployed owner roles contract
+    SetOwnerRolesAddress {
+        address: Addr,
+    },
 }
 
 #[cw_serde]
@@ -17,7 +47,38 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(IsClaimTopicValidResponse)]
     IsClaimTopicValid { topic: Uint128 },
+    #[returns(ClaimTopicStatus)]
+    GetClaimTopicStatus { topic: Uint128 },
+    #[returns(ClaimTopicInfo)]
+    GetClaimTopicInfo { topic: Uint128 },
+    // Registered topics in ascending order, deprecated ones included, a full page of
+    // `utils::pagination::MAX_LIMIT` when `limit` is unset
+    #[returns(Vec<Uint128>)]
+    GetClaimTopics {
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    },
+    #[returns(u64)]
+    GetClaimTopicCount {},
 }
 
 #[cw_serde]
 pub struct IsClaimTopicValidResponse(bool);
+
+// What a topic attests to, for integrators to present it. Empty for topics registered
+// without metadata.
+#[cw_serde]
+#[derive(Default)]
+pub struct ClaimTopicInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[cw_serde]
+pub enum ClaimTopicStatus {
+    // New claims may be issued under the topic
+    Active,
+    // Existing claims remain valid, but no new claims may be issued
+    Deprecated,
+    NotFound,
+}
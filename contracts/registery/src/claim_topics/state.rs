@@ -1,5 +1,26 @@
+use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Addr;
 use cw_storage_plus::{Item, Map};
 
+use crate::claim_topics::msg::ClaimTopicInfo;
+
 pub const OWNER_ROLES_ADDRESS: Item<Addr> = Item::new("owner_role_addr");
-pub const CLAIM_TOPICS: Map<u128, bool> = Map::new("claim_topics");
+// Most topics that may be registered at once, as verification goes through all of them
+pub const MAX_TOPICS: Item<u32> = Item::new("max_topics");
+pub const DEFAULT_MAX_TOPICS: u32 = 50;
+// Number of entries in CLAIM_TOPICS
+pub const TOPIC_COUNT: Item<u32> = Item::new("topic_count");
+// (sender, role) -> (block height, cache epoch) the owner roles contract last granted the
+// role at. Only trusted within that block and epoch.
+pub const ROLE_CACHE: Map<(&Addr, String), (u64, u64)> = Map::new("role_cache");
+// Bumped to drop every cached grant at once
+pub const ROLE_CACHE_EPOCH: Item<u64> = Item::new("role_cache_epoch");
+// Every registered topic. Before topics had metadata the value was only `active`.
+pub const CLAIM_TOPICS: Map<u128, ClaimTopic> = Map::new("claim_topics");
+
+#[cw_serde]
+pub struct ClaimTopic {
+    pub info: ClaimTopicInfo,
+    // Whether the topic is still open to new claims
+    pub active: bool,
+}
@@ -1,11 +1,15 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+};
 use cw2::set_contract_version;
+use cw_storage_plus::Bound;
+use utils::pagination::page_limit;
 
 use crate::identity::error::ContractError;
 use crate::identity::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::identity::state::{IDENTITIES, OWNER};
+use crate::identity::state::{CONTRACT_HOLDERS, IDENTITIES, IDENTITIES_BY_COUNTRY, OWNER};
 use crate::identity::storage_management::{
     add_identity, remove_identity, update_country, update_identity,
 };
@@ -60,10 +64,20 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetIdentity { owner } => to_json_binary(&query_identity(deps, owner)?),
         QueryMsg::GetCountry { owner } => to_json_binary(&query_country(deps, owner)?),
-        QueryMsg::GetIdentitiesByCountry { country } => {
-            to_json_binary(&query_identities_by_country(deps, country)?)
-        }
+        QueryMsg::GetIdentitiesByCountry {
+            country,
+            start_after,
+            limit,
+        } => to_json_binary(&query_identities_by_country(
+            deps,
+            country,
+            start_after,
+            limit,
+        )?),
         QueryMsg::GetOwner {} => to_json_binary(&query_owner(deps)?),
+        QueryMsg::IsContractHolder { owner } => {
+            to_json_binary(&query_is_contract_holder(deps, owner)?)
+        }
     }
 }
 
@@ -79,16 +93,40 @@ fn query_country(deps: Deps, owner: String) -> StdResult<Option<String>> {
     Ok(identity.map(|(_, country)| country))
 }
 
-fn query_identities_by_country(deps: Deps, country: String) -> StdResult<Vec<String>> {
-    let identities: StdResult<Vec<_>> = IDENTITIES
-        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .filter(|r| match r {
-            Ok((_, (_, c))) => c == &country,
-            Err(_) => false,
-        })
-        .map(|r| r.map(|(owner, _)| owner.to_string()))
-        .collect();
-    identities
+fn query_identities_by_country(
+    deps: Deps,
+    country: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<String>> {
+    let start_after = start_after
+        .map(|owner| deps.api.addr_validate(&owner))
+        .transpose()?;
+    IDENTITIES_BY_COUNTRY
+        .prefix(country.as_str())
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(page_limit(limit))
+        .map(|owner| owner.map(|owner| owner.to_string()))
+        .collect()
+}
+
+fn query_is_contract_holder(deps: Deps, owner: String) -> StdResult<Option<bool>> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    if !IDENTITIES.has(deps.storage, owner_addr.clone()) {
+        return Ok(None);
+    }
+    // Identities registered before the flag was recorded derive it from the chain
+    match CONTRACT_HOLDERS.may_load(deps.storage, owner_addr.clone())? {
+        Some(is_contract_holder) => Ok(Some(is_contract_holder)),
+        None => Ok(Some(
+            deps.querier.query_wasm_contract_info(owner_addr).is_ok(),
+        )),
+    }
 }
 
 fn query_owner(deps: Deps) -> StdResult<String> {
@@ -311,12 +349,122 @@ mod tests {
         let res: Vec<String> = app
             .wrap()
             .query_wasm_smart(
-                contract_addr,
+                contract_addr.clone(),
                 &QueryMsg::GetIdentitiesByCountry {
                     country: "Wonderland".to_string(),
+                    start_after: None,
+                    limit: None,
                 },
             )
             .unwrap();
-        assert_eq!(vec![alice.to_string(), bob.to_string()], res);
+        let mut wonderland = vec![alice.to_string(), bob.to_string()];
+        wonderland.sort();
+        assert_eq!(wonderland, res);
+
+        // Pages only count identities in the country and start after the last owner
+        let page = |start_after: Option<String>| -> Vec<String> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetIdentitiesByCountry {
+                        country: "Wonderland".to_string(),
+                        start_after,
+                        limit: Some(1),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(page(None), wonderland[..1]);
+        assert_eq!(page(Some(wonderland[0].clone())), wonderland[1..]);
+        assert!(page(Some(wonderland[1].clone())).is_empty());
+
+        // Moving or removing an identity moves or removes it from the country's list
+        let msg = ExecuteMsg::UpdateCountry {
+            owner: charlie.to_string(),
+            new_country: "Wonderland".to_string(),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let msg = ExecuteMsg::RemoveIdentity {
+            owner: alice.to_string(),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        let by_country = |country: &str| -> Vec<String> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::GetIdentitiesByCountry {
+                        country: country.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap()
+        };
+        let mut wonderland = vec![bob.to_string(), charlie.to_string()];
+        wonderland.sort();
+        assert_eq!(by_country("Wonderland"), wonderland);
+        assert!(by_country("Neverland").is_empty());
+    }
+
+    #[test]
+    fn contract_and_wallet_holders() {
+        let mut app = App::default();
+        let owner = app.api().addr_make("owner");
+        let contract_addr = instantiate_contract(&mut app, owner.clone());
+
+        // Any deployed contract can hold an identity, e.g. a DAO treasury
+        let treasury = instantiate_contract(&mut app, owner.clone());
+        let alice = app.api().addr_make("alice");
+
+        for holder in [&treasury, &alice] {
+            let msg = ExecuteMsg::AddIdentity {
+                owner: holder.to_string(),
+                identity_address: app.api().addr_make("identity").to_string(),
+                country: "Wonderland".to_string(),
+            };
+            app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+        }
+
+        let is_contract_holder = |app: &App, holder: &Addr| -> Option<bool> {
+            app.wrap()
+                .query_wasm_smart(
+                    contract_addr.clone(),
+                    &QueryMsg::IsContractHolder {
+                        owner: holder.to_string(),
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(is_contract_holder(&app, &treasury), Some(true));
+        assert_eq!(is_contract_holder(&app, &alice), Some(false));
+
+        // Removing the identity clears the flag
+        let msg = ExecuteMsg::RemoveIdentity {
+            owner: treasury.to_string(),
+        };
+        app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+            .unwrap();
+        assert_eq!(is_contract_holder(&app, &treasury), None);
+
+        // Identities registered before the flag was recorded still report how they are held
+        let bob = app.api().addr_make("bob");
+        for holder in [&treasury, &bob] {
+            let msg = ExecuteMsg::AddIdentity {
+                owner: holder.to_string(),
+                identity_address: app.api().addr_make("identity").to_string(),
+                country: "Wonderland".to_string(),
+            };
+            app.execute_contract(owner.clone(), contract_addr.clone(), &msg, &[])
+                .unwrap();
+            CONTRACT_HOLDERS.remove(
+                app.contract_storage_mut(&contract_addr).as_mut(),
+                holder.clone(),
+            );
+        }
+        assert_eq!(is_contract_holder(&app, &treasury), Some(true));
+        assert_eq!(is_contract_holder(&app, &bob), Some(false));
     }
 }
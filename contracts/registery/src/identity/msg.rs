@@ -30,8 +30,18 @@ pub enum QueryMsg {
     GetIdentity { owner: String },
     #[returns(String)]
     GetCountry { owner: String },
+    // Owners of the identities registered in `country`, in ascending order, see
+    // `utils::pagination`
     #[returns(Vec<String>)]
-    GetIdentitiesByCountry { country: String },
+    GetIdentitiesByCountry {
+        country: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     #[returns(String)]
     GetOwner {},
+    // Whether the identity is held by a smart contract, `None` when it is not registered.
+    // Identities registered before the flag was recorded are looked up on the chain.
+    #[returns(Option<bool>)]
+    IsContractHolder { owner: String },
 }
@@ -4,5 +4,12 @@ use cw_storage_plus::{Item, Map};
 // Owner, (identity address, country)
 pub const IDENTITIES: Map<Addr, (Addr, String)> = Map::new("identities");
 
+// (Country, owner) -> true, so identities can be listed per country without scanning the
+// others. Kept in sync with the country in `IDENTITIES`.
+pub const IDENTITIES_BY_COUNTRY: Map<(&str, &Addr), bool> = Map::new("identities_by_country");
+
+// Owner -> whether the owner is a smart contract account rather than a wallet
+pub const CONTRACT_HOLDERS: Map<Addr, bool> = Map::new("contract_holders");
+
 // Contract owner
 pub const OWNER: Item<Addr> = Item::new("owner");
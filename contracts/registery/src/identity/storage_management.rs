@@ -1,5 +1,5 @@
 use crate::identity::error::ContractError;
-use crate::identity::state::IDENTITIES;
+use crate::identity::state::{CONTRACT_HOLDERS, IDENTITIES, IDENTITIES_BY_COUNTRY};
 use crate::identity::utils::is_authorized;
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
 
@@ -30,12 +30,21 @@ pub fn add_identity(
         owner_addr.clone(),
         &(identity_addr, country.clone()),
     )?;
+    IDENTITIES_BY_COUNTRY.save(deps.storage, (&country, &owner_addr), &true)?;
+
+    // Record whether the holder is a contract, e.g. a DAO treasury, rather than a wallet
+    let is_contract_holder = deps
+        .querier
+        .query_wasm_contract_info(owner_addr.clone())
+        .is_ok();
+    CONTRACT_HOLDERS.save(deps.storage, owner_addr, &is_contract_holder)?;
 
     Ok(Response::new()
         .add_attribute("action", "add_identity")
         .add_attribute("owner", owner)
         .add_attribute("identity_address", identity_address)
-        .add_attribute("country", country))
+        .add_attribute("country", country)
+        .add_attribute("is_contract_holder", is_contract_holder.to_string()))
 }
 
 pub fn remove_identity(
@@ -52,12 +61,14 @@ pub fn remove_identity(
     }
 
     // Check if the identity exists
-    if !IDENTITIES.has(deps.storage, owner_addr.clone()) {
+    let Some((_, country)) = IDENTITIES.may_load(deps.storage, owner_addr.clone())? else {
         return Err(ContractError::IdentityNotFound {});
-    }
+    };
 
     // Remove the identity
     IDENTITIES.remove(deps.storage, owner_addr.clone());
+    IDENTITIES_BY_COUNTRY.remove(deps.storage, (&country, &owner_addr));
+    CONTRACT_HOLDERS.remove(deps.storage, owner_addr);
 
     Ok(Response::new()
         .add_attribute("action", "remove_identity")
@@ -110,7 +121,7 @@ pub fn update_country(
     }
 
     // Check if the identity exists
-    let (identity_addr, _) = IDENTITIES.load(deps.storage, owner_addr.clone())?;
+    let (identity_addr, country) = IDENTITIES.load(deps.storage, owner_addr.clone())?;
 
     // Update the country
     IDENTITIES.save(
@@ -118,6 +129,8 @@ pub fn update_country(
         owner_addr.clone(),
         &(identity_addr, new_country.clone()),
     )?;
+    IDENTITIES_BY_COUNTRY.remove(deps.storage, (&country, &owner_addr));
+    IDENTITIES_BY_COUNTRY.save(deps.storage, (&new_country, &owner_addr), &true)?;
 
     Ok(Response::new()
         .add_attribute("action", "update_country")
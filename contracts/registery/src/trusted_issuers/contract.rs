@@ -90,6 +90,17 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::GetIssuerClaimTopics { issuer } => {
             to_json_binary(&query::get_issuer_claim_topics(deps, issuer)?)
         }
+        QueryMsg::GetIssuersRankedForTopic {
+            topic,
+            claim_topics_registry,
+        } => to_json_binary(&query::get_issuers_ranked_for_topic(
+            deps,
+            topic,
+            claim_topics_registry,
+        )?),
+        QueryMsg::ListTrustedIssuers { start_after, limit } => {
+            to_json_binary(&query::list_trusted_issuers(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -97,7 +108,7 @@ pub mod execute {
     use crate::trusted_issuers::{msg::TrustedIssuer, state::TRUSTED_ISSUERS};
 
     use super::*;
-    use cosmwasm_std::{to_json_binary, Addr, QueryRequest, Uint128, WasmQuery};
+    use cosmwasm_std::{to_json_binary, Addr, Event, QueryRequest, Uint128, WasmQuery};
     use roles::owner_roles::{msg::OwnerRole, QueryMsg};
 
     pub fn check_role(deps: Deps, owner: Addr, role: OwnerRole) -> Result<(), ContractError> {
@@ -108,7 +119,14 @@ pub mod execute {
             contract_addr: owner_roles.to_string(),
             msg: to_json_binary(&msg)?,
         });
-        let has_role: bool = deps.querier.query(&query)?;
+        let has_role: bool =
+            deps.querier
+                .query(&query)
+                .map_err(|e| ContractError::CrossContractQueryFailed {
+                    contract: owner_roles.to_string(),
+                    query: "IsOwner".to_string(),
+                    reason: e.to_string(),
+                })?;
         if !has_role {
             return Err(ContractError::Unauthorized {});
         }
@@ -133,7 +151,7 @@ pub mod execute {
         Ok(Response::new()
             .add_attribute("action", "add_trusted_issuer")
             .add_attribute("issuer", issuer.to_string())
-            .add_attribute("claim_topics", format!("{:?}", claim_topics)))
+            .add_attribute("claim_topics", topics_attribute(&claim_topics)))
     }
 
     pub fn update_trusted_issuer(
@@ -154,27 +172,50 @@ pub mod execute {
         Ok(Response::new()
             .add_attribute("action", "updated_trusted_issuer")
             .add_attribute("issuer", issuer.to_string())
-            .add_attribute("claim_topics", format!("{:?}", claim_topics)))
+            .add_attribute("claim_topics", topics_attribute(&claim_topics)))
+    }
+
+    // Topics as a comma-separated list of numbers, e.g. "1,2"
+    fn topics_attribute(claim_topics: &[Uint128]) -> String {
+        claim_topics
+            .iter()
+            .map(|topic| topic.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
     }
 
     pub fn remove_trusted_issuer(deps: DepsMut, issuer: Addr) -> Result<Response, ContractError> {
-        if !TRUSTED_ISSUERS.has(deps.storage, issuer.clone()) {
-            return Err(ContractError::IssuerNotFound {});
-        }
+        let trusted_issuer = TRUSTED_ISSUERS
+            .may_load(deps.storage, issuer.clone())?
+            .ok_or(ContractError::IssuerNotFound {})?;
         TRUSTED_ISSUERS.remove(deps.storage, issuer.clone());
+
+        // Signal identities holding claims from this issuer that those claims are no longer trusted
+        let event = Event::new("trusted_issuer_removed")
+            .add_attribute("issuer", issuer.to_string())
+            .add_attribute(
+                "claim_topics",
+                topics_attribute(&trusted_issuer.claim_topics),
+            );
+
         Ok(Response::new()
+            .add_event(event)
             .add_attribute("action", "remove_trusted_issuer")
             .add_attribute("issuer", issuer.to_string()))
     }
 }
 
 pub mod query {
-    use cosmwasm_std::{Deps, StdError};
+    use cosmwasm_std::{Deps, Order, StdError};
 
+    use crate::claim_topics::msg::ClaimTopicStatus;
+    use crate::claim_topics::QueryMsg as ClaimTopicsQueryMsg;
     use crate::trusted_issuers::state::TRUSTED_ISSUERS;
 
     use super::*;
     use cosmwasm_std::{Addr, Uint128};
+    use std::collections::BTreeMap;
+    use utils::pagination::paginate;
 
     pub fn is_trusted_issuer(deps: Deps, issuer: Addr) -> StdResult<bool> {
         Ok(TRUSTED_ISSUERS.has(deps.storage, issuer))
@@ -186,14 +227,80 @@ pub mod query {
             .map(|trusted_issuer| trusted_issuer.claim_topics)
             .map_err(|_| StdError::generic_err("Issuer not found"))
     }
+
+    // Exempt from `utils::pagination`, see `QueryMsg::GetIssuersRankedForTopic`. The scan
+    // is bounded by the number of trusted issuers, which only the issuers registry
+    // manager role can grow.
+    pub fn get_issuers_ranked_for_topic(
+        deps: Deps,
+        topic: Uint128,
+        claim_topics_registry: Option<String>,
+    ) -> StdResult<Vec<Addr>> {
+        let claim_topics_registry = claim_topics_registry
+            .map(|registry| deps.api.addr_validate(&registry))
+            .transpose()?;
+        let issuers = TRUSTED_ISSUERS
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, trusted_issuer)) => trusted_issuer.claim_topics.contains(&topic),
+                Err(_) => true,
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        // Each topic's status is queried once, issuers usually share most of them
+        let mut required = BTreeMap::new();
+        let mut ranked = Vec::with_capacity(issuers.len());
+        for (issuer, trusted_issuer) in issuers {
+            let mut coverage = 0;
+            for claim_topic in trusted_issuer.claim_topics {
+                let is_required = match (&claim_topics_registry, required.get(&claim_topic)) {
+                    (None, _) => true,
+                    (Some(_), Some(is_required)) => *is_required,
+                    (Some(registry), None) => {
+                        let status: ClaimTopicStatus = deps.querier.query_wasm_smart(
+                            registry,
+                            &ClaimTopicsQueryMsg::GetClaimTopicStatus { topic: claim_topic },
+                        )?;
+                        let is_required = status == ClaimTopicStatus::Active;
+                        required.insert(claim_topic, is_required);
+                        is_required
+                    }
+                };
+                if is_required {
+                    coverage += 1;
+                }
+            }
+            ranked.push((issuer, coverage));
+        }
+
+        // Most comprehensive first; the stable sort keeps ties in address order
+        ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+        Ok(ranked.into_iter().map(|(issuer, _)| issuer).collect())
+    }
+
+    pub fn list_trusted_issuers(
+        deps: Deps,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(Addr, Vec<Uint128>)>> {
+        Ok(
+            paginate(deps.storage, &TRUSTED_ISSUERS, start_after, limit)?
+                .into_iter()
+                .map(|(issuer, trusted_issuer)| (issuer, trusted_issuer.claim_topics))
+                .collect(),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::claim_topics::msg::ClaimTopicStatus;
+    use crate::claim_topics::QueryMsg as ClaimTopicsQueryMsg;
+    use crate::trusted_issuers::{msg::TrustedIssuer, state::TRUSTED_ISSUERS};
     use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
-    use cosmwasm_std::{from_json, Addr, ContractResult, SystemResult, Uint128};
+    use cosmwasm_std::{from_json, Addr, ContractResult, Event, SystemResult, Uint128};
     use roles::owner_roles::msg::OwnerRole;
 
     fn setup_contract(deps: DepsMut) -> Addr {
@@ -233,6 +340,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -281,6 +389,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -329,6 +438,7 @@ mod tests {
                             panic!("Unexpected role query")
                         }
                     }
+                    _ => panic!("Unexpected query"),
                 }
             }
             _ => panic!("Unexpected query type"),
@@ -357,6 +467,12 @@ mod tests {
                 ("issuer", issuer.as_str()),
             ]
         );
+        assert_eq!(
+            res.events,
+            vec![Event::new("trusted_issuer_removed")
+                .add_attribute("issuer", issuer.as_str())
+                .add_attribute("claim_topics", "1,2")]
+        );
 
         // Verify the issuer was removed
         let msg = QueryMsg::IsTrustedIssuer { issuer };
@@ -364,4 +480,188 @@ mod tests {
         let is_trusted: bool = from_json(res).unwrap();
         assert!(!is_trusted);
     }
+
+    #[test]
+    fn issuers_ranked_for_topic() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+
+        let issuers = [
+            ("kyc_only", vec![1]),
+            ("full_service", vec![1, 2, 3]),
+            ("aml_only", vec![2]),
+            ("kyc_and_aml", vec![1, 2]),
+            ("kyc_and_legacy", vec![1, 3, 4]),
+        ];
+        for (issuer, topics) in issuers {
+            TRUSTED_ISSUERS
+                .save(
+                    deps.as_mut().storage,
+                    Addr::unchecked(issuer),
+                    &TrustedIssuer {
+                        claim_topics: topics.into_iter().map(Uint128::new).collect(),
+                    },
+                )
+                .unwrap();
+        }
+
+        let ranked = |deps: Deps, topic: u128, claim_topics_registry: Option<&str>| {
+            let msg = QueryMsg::GetIssuersRankedForTopic {
+                topic: Uint128::new(topic),
+                claim_topics_registry: claim_topics_registry.map(str::to_string),
+            };
+            let ranked: Vec<Addr> = from_json(query(deps, mock_env(), msg).unwrap()).unwrap();
+            ranked
+                .into_iter()
+                .map(|issuer| issuer.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Without a claim topics registry, every topic an issuer is trusted for counts
+        assert_eq!(
+            ranked(deps.as_ref(), 1, None),
+            vec!["full_service", "kyc_and_legacy", "kyc_and_aml", "kyc_only"]
+        );
+
+        // With one, only its active topics do: topic 3 is deprecated and 4 unknown
+        let claim_topics_registry = deps.api.addr_make("claim_topics_registry");
+        deps.querier.update_wasm(|query| match query {
+            cosmwasm_std::WasmQuery::Smart { msg, .. } => {
+                let ClaimTopicsQueryMsg::GetClaimTopicStatus { topic } = from_json(msg).unwrap()
+                else {
+                    panic!("Unexpected query")
+                };
+                let status = match topic.u128() {
+                    1 | 2 => ClaimTopicStatus::Active,
+                    3 => ClaimTopicStatus::Deprecated,
+                    _ => ClaimTopicStatus::NotFound,
+                };
+                SystemResult::Ok(ContractResult::Ok(to_json_binary(&status).unwrap()))
+            }
+            _ => panic!("Unexpected query type"),
+        });
+        assert_eq!(
+            ranked(deps.as_ref(), 1, Some(claim_topics_registry.as_str())),
+            vec!["full_service", "kyc_and_aml", "kyc_and_legacy", "kyc_only"]
+        );
+
+        // No issuer covers the topic
+        assert!(ranked(deps.as_ref(), 5, None).is_empty());
+    }
+
+    #[test]
+    fn list_trusted_issuers() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        deps.querier
+            .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for (issuer, topics) in [
+            ("issuer_a", vec![1]),
+            ("issuer_b", vec![1, 2]),
+            ("issuer_c", vec![3]),
+            ("issuer_d", vec![2, 3]),
+        ] {
+            let msg = ExecuteMsg::AddTrustedIssuer {
+                issuer: Addr::unchecked(issuer),
+                claim_topics: topics.into_iter().map(Uint128::new).collect(),
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::UpdateIssuerClaimTopics {
+            issuer: Addr::unchecked("issuer_a"),
+            claim_topics: vec![Uint128::new(1), Uint128::new(3)],
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::RemoveTrustedIssuer {
+            issuer: Addr::unchecked("issuer_c"),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let list = |start_after: Option<&str>| -> Vec<(Addr, Vec<Uint128>)> {
+            let msg = QueryMsg::ListTrustedIssuers {
+                start_after: start_after.map(Addr::unchecked),
+                limit: Some(2),
+            };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let topics = |topics: &[u128]| topics.iter().copied().map(Uint128::new).collect();
+
+        assert_eq!(
+            list(None),
+            vec![
+                (Addr::unchecked("issuer_a"), topics(&[1, 3])),
+                (Addr::unchecked("issuer_b"), topics(&[1, 2])),
+            ]
+        );
+        assert_eq!(
+            list(Some("issuer_b")),
+            vec![(Addr::unchecked("issuer_d"), topics(&[2, 3]))]
+        );
+        assert!(list(Some("issuer_d")).is_empty());
+    }
+
+    #[test]
+    fn list_trusted_issuers_clamps_limit() {
+        let mut deps = mock_dependencies();
+        setup_contract(deps.as_mut());
+        deps.querier
+            .update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_json_binary(&true).unwrap())));
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        for i in 0..35 {
+            let msg = ExecuteMsg::AddTrustedIssuer {
+                issuer: Addr::unchecked(format!("issuer_{i:02}")),
+                claim_topics: vec![Uint128::new(1)],
+            };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let list = |start_after: Option<&str>, limit: Option<u32>| -> Vec<(Addr, Vec<Uint128>)> {
+            let msg = QueryMsg::ListTrustedIssuers {
+                start_after: start_after.map(Addr::unchecked),
+                limit,
+            };
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+
+        assert_eq!(list(None, None).len(), 10);
+        let page = list(None, Some(100));
+        assert_eq!(page.len(), 30);
+        assert_eq!(page[29].0, Addr::unchecked("issuer_29"));
+        let rest = list(Some("issuer_29"), Some(100));
+        assert_eq!(rest.len(), 5);
+        assert_eq!(rest[0].0, Addr::unchecked("issuer_30"));
+    }
+
+    #[test]
+    fn failing_owner_roles_query_is_attributed() {
+        let mut deps = mock_dependencies();
+        let owner_roles_address = setup_contract(deps.as_mut());
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Err("unknown variant `IsOwner`".to_string()))
+        });
+
+        let info = message_info(&Addr::unchecked("authorized_user"), &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::AddTrustedIssuer {
+                issuer: Addr::unchecked("issuer"),
+                claim_topics: vec![Uint128::new(1)],
+            },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::CrossContractQueryFailed {
+                contract, query, ..
+            } => {
+                assert_eq!(contract, owner_roles_address.to_string());
+                assert_eq!(query, "IsOwner");
+            }
+            err => panic!("Unexpected error: {err}"),
+        }
+    }
 }
@@ -9,6 +9,13 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("CrossContractQueryFailed: {query} query to {contract} failed: {reason}")]
+    CrossContractQueryFailed {
+        contract: String,
+        query: String,
+        reason: String,
+    },
+
     #[error("IssuerAlreadyExists")]
     IssuerAlreadyExists {},
     #[error("IssuerNotFound")]
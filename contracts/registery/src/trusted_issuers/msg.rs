@@ -28,6 +28,22 @@ pub enum QueryMsg {
     IsTrustedIssuer { issuer: Addr },
     #[returns(GetIssuerClaimTopicsResponse)]
     GetIssuerClaimTopics { issuer: Addr },
+    // Issuers trusted for `topic`, those covering the most required topics first, ties in
+    // address order. Required topics are the active ones of `claim_topics_registry`;
+    // without it every topic an issuer is trusted for counts. Not paginated: callers such
+    // as on_chain_id's VerifyClaim treat the result as the complete set of trusted
+    // issuers, and ranking needs every one of them anyway.
+    #[returns(Vec<Addr>)]
+    GetIssuersRankedForTopic {
+        topic: Uint128,
+        claim_topics_registry: Option<String>,
+    },
+    // Every trusted issuer with its claim topics, in address order
+    #[returns(Vec<(Addr, Vec<Uint128>)>)]
+    ListTrustedIssuers {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -97,7 +97,7 @@ pub fn execute(
 ///
 /// * `deps` - Dependencies
 /// * `_env` - The environment info (unused)
-/// * `msg` - Query IsOwner
+/// * `msg` - Query IsOwner or BatchIsOwner
 ///
 /// # Returns
 ///
@@ -106,6 +106,9 @@ pub fn execute(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::IsOwner { role, owner } => to_json_binary(&query::is_owner(deps, role, owner)?),
+        QueryMsg::BatchIsOwner { queries } => {
+            to_json_binary(&query::batch_is_owner(deps, queries)?)
+        }
     }
 }
 
@@ -376,19 +379,33 @@ pub mod query {
     use super::*;
     use crate::owner_roles::{
         msg::{IsOwnerResponse, OwnerRole},
-        state::OWNER_ROLES,
+        state::{MAX_BATCH_IS_OWNER, OWNER_ROLES},
     };
-    use cosmwasm_std::Addr;
+    use cosmwasm_std::{Addr, StdError};
 
     pub fn is_owner(deps: Deps, role: OwnerRole, owner: Addr) -> StdResult<IsOwnerResponse> {
         let is_owner = OWNER_ROLES.has_role(deps.storage, role.to_string(), owner)?;
         Ok(IsOwnerResponse { is_owner, role })
     }
+
+    pub fn batch_is_owner(deps: Deps, queries: Vec<(OwnerRole, Addr)>) -> StdResult<Vec<bool>> {
+        if queries.len() > MAX_BATCH_IS_OWNER {
+            return Err(StdError::generic_err(format!(
+                "Too many role checks: at most {} per query",
+                MAX_BATCH_IS_OWNER
+            )));
+        }
+        queries
+            .into_iter()
+            .map(|(role, owner)| OWNER_ROLES.has_role(deps.storage, role.to_string(), owner))
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::owner_roles::msg::{IsOwnerResponse, OwnerRole};
+    use crate::owner_roles::state::MAX_BATCH_IS_OWNER;
 
     use super::*;
 
@@ -617,6 +634,63 @@ mod tests {
         assert_eq!(is_owner.role, OwnerRole::OwnerAdmin);
     }
 
+    #[test]
+    fn batch_is_owner_matches_individual_queries() {
+        let mut deps = mock_dependencies();
+        let owner = Addr::unchecked("owner");
+        let info = message_info(&owner, &[]);
+
+        let msg = InstantiateMsg {
+            owner: owner.clone(),
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        for (role, addr) in [
+            (OwnerRole::OwnerAdmin, alice.clone()),
+            (OwnerRole::ComplianceManager, bob.clone()),
+        ] {
+            let msg = ExecuteMsg::AddOwnerRole { role, owner: addr };
+            execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        }
+
+        let queries = vec![
+            (OwnerRole::OwnerAdmin, alice.clone()),
+            (OwnerRole::OwnerAdmin, bob.clone()),
+            (OwnerRole::ComplianceManager, alice.clone()),
+            (OwnerRole::ComplianceManager, bob.clone()),
+        ];
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BatchIsOwner {
+                queries: queries.clone(),
+            },
+        )
+        .unwrap();
+        let batch: Vec<bool> = from_json(res).unwrap();
+        assert_eq!(batch, vec![true, false, false, true]);
+
+        // Each entry matches the corresponding IsOwner call
+        for ((role, addr), expected) in queries.into_iter().zip(batch) {
+            let msg = QueryMsg::IsOwner { role, owner: addr };
+            let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+            let is_owner: IsOwnerResponse = from_json(res).unwrap();
+            assert_eq!(is_owner.is_owner, expected);
+        }
+
+        // Oversized batches are rejected
+        let queries = vec![(OwnerRole::OwnerAdmin, alice); MAX_BATCH_IS_OWNER + 1];
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BatchIsOwner { queries },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Too many role checks"));
+    }
+
     #[test]
     fn test_set_compliance_registry() {
         let mut deps = mock_dependencies();
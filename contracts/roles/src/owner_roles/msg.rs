@@ -51,6 +51,9 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(IsOwnerResponse)]
     IsOwner { role: OwnerRole, owner: Addr },
+    // Results are returned in the same order as the queries
+    #[returns(Vec<bool>)]
+    BatchIsOwner { queries: Vec<(OwnerRole, Addr)> },
 }
 
 #[cw_serde]
@@ -2,6 +2,9 @@ use crate::role_management::RoleManagement;
 use cosmwasm_std::Addr;
 use cw_storage_plus::Item;
 
+// Maximum number of role checks accepted by a single `BatchIsOwner` query
+pub const MAX_BATCH_IS_OWNER: usize = 50;
+
 pub const OWNER_ROLES: RoleManagement = RoleManagement::new("owner_roles");
 pub const OWNER: Item<Addr> = Item::new("owner");
 pub const COMPLIANCE_REGISTRY: Item<Addr> = Item::new("compliance");
@@ -1,5 +1,7 @@
+pub mod pagination;
+
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Uint128};
 
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -10,8 +12,78 @@ pub enum QueryMsg {
         from: Option<Addr>,
         to: Option<Addr>,
         amount: Option<Uint128>,
+        /// Decimals the compliance registry has on record for the token, which amounts are
+        /// expressed in. Omitted when none is configured.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
     },
 }
 
 #[cw_serde]
 pub struct CheckTokenComplianceResponse(bool);
+
+/// Exports a module's state for a token, so a replacement module can be seeded with it.
+/// Implemented by modules that support carrying their state over on replacement.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum ModuleStateQueryMsg {
+    #[returns(Binary)]
+    ExportState { token_address: Addr },
+}
+
+/// Evaluates a transfer as if earlier simulated transfers had already executed.
+/// `overlay` is the opaque record of those earlier effects, as returned by the previous
+/// step; it is `None` for the first step. Implemented by modules whose verdict depends on
+/// the effects of previous transfers.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum ModuleSimulationQueryMsg {
+    #[returns(SimulatedTransferResponse)]
+    SimulateTransfer {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Option<Uint128>,
+        overlay: Option<Binary>,
+        /// As in `QueryMsg::CheckTokenCompliance`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decimals: Option<u8>,
+    },
+}
+
+#[cw_serde]
+pub struct SimulatedTransferResponse {
+    pub compliant: bool,
+    /// Effects of the earlier steps plus this one, to pass on to the next step
+    pub overlay: Binary,
+}
+
+/// Seeds a module with state previously exported by the module it replaces.
+#[cw_serde]
+pub enum ModuleStateExecuteMsg {
+    ImportState { token_address: Addr, state: Binary },
+}
+
+/// Reports a movement of the sending token that went through to the compliance registry,
+/// which forwards it to the token's modules as `ModuleHookExecuteMsg::Transferred`. Mints
+/// have no `from` and burns no `to`.
+#[cw_serde]
+pub enum RegistryHookExecuteMsg {
+    Transferred {
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    },
+}
+
+/// Notifies a module of a transfer that went through, for modules whose verdicts depend on
+/// past transfers. Sent by the registry to the modules bound with the transfer hook enabled.
+#[cw_serde]
+pub enum ModuleHookExecuteMsg {
+    Transferred {
+        token_address: Addr,
+        from: Option<Addr>,
+        to: Option<Addr>,
+        amount: Uint128,
+    },
+}
@@ -0,0 +1,49 @@
+//! Shared bounds for enumeration queries, so that no query reads an unbounded number of
+//! entries. Pages start strictly after `start_after`: a client passes the last key (or
+//! position) of the previous page to get the next one.
+
+use cosmwasm_std::{StdResult, Storage};
+use cw_storage_plus::{Bound, KeyDeserialize, Map, PrimaryKey};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Page size when the query doesn't set a limit
+pub const DEFAULT_LIMIT: u32 = 10;
+/// Largest page any enumeration query returns
+pub const MAX_LIMIT: u32 = 30;
+
+/// Number of entries to return for a requested `limit`
+pub fn page_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize
+}
+
+/// A page of `map` in ascending key order
+pub fn paginate<'a, K, T>(
+    storage: &dyn Storage,
+    map: &Map<K, T>,
+    start_after: Option<K>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(K::Output, T)>>
+where
+    K: PrimaryKey<'a> + KeyDeserialize,
+    K::Output: 'static,
+    T: Serialize + DeserializeOwned,
+{
+    map.range(
+        storage,
+        start_after.map(Bound::exclusive),
+        None,
+        cosmwasm_std::Order::Ascending,
+    )
+    .take(page_limit(limit))
+    .collect()
+}
+
+/// A page of a stored list, `start_after` being a position in the list
+pub fn paginate_list<T>(items: Vec<T>, start_after: Option<u32>, limit: Option<u32>) -> Vec<T> {
+    let start = start_after.map_or(0, |position| position as usize + 1);
+    items
+        .into_iter()
+        .skip(start)
+        .take(page_limit(limit))
+        .collect()
+}